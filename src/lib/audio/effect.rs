@@ -0,0 +1,875 @@
+//! Items related to the pre-spatial effects processing applied to each sound.
+//!
+//! Each `audio::Source` may specify a chain of `Effect`s. The parameters of each effect are
+//! described as ranges so that the soundscape may choose unique values for each sound. When a
+//! sound is spawned, the chain is converted to a `Chain` of `Processor`s which are run on the
+//! sound's interleaved samples on the audio output thread, before they are mixed via DBAP.
+
+use audio::SAMPLE_RATE;
+use nannou::math::map_range;
+use nannou::rand::Rng;
+use std::f32::consts::PI;
+use utils::{db_to_gain, gain_to_db, Range};
+
+/// The maximum delay time supported by the `Delay` effect.
+pub const MAX_DELAY_MS: f32 = 2_000.0;
+
+/// The frequency at which the `Eq` low shelf is centred.
+const EQ_LOW_SHELF_HZ: f32 = 200.0;
+
+/// The frequency at which the `Eq` high shelf is centred.
+const EQ_HIGH_SHELF_HZ: f32 = 5_000.0;
+
+/// The Q used for the `Eq` mid band.
+const EQ_MID_Q: f32 = 0.7;
+
+/// A single effect within a source's effects chain.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Effect {
+    Filter(Filter),
+    Eq(Equaliser),
+    Compressor(Compressor),
+    Delay(Delay),
+    Distortion(Distortion),
+    Bitcrusher(Bitcrusher),
+    /// Sums all channels of the sound and writes the result to every channel.
+    MonoFold,
+}
+
+/// A resonant biquad filter.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub cutoff_hz: Range<f32>,
+    pub q: Range<f32>,
+}
+
+/// The response of a `Filter`.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum FilterKind {
+    LowPass,
+    HighPass,
+    BandPass,
+}
+
+/// A three band equaliser with a low shelf, a peaking mid band and a high shelf.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Equaliser {
+    pub low_db: Range<f32>,
+    pub mid_db: Range<f32>,
+    pub mid_hz: Range<f32>,
+    pub high_db: Range<f32>,
+}
+
+/// A feed-forward compressor whose detection is linked across all channels of the sound.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Compressor {
+    pub threshold_db: Range<f32>,
+    pub ratio: Range<f32>,
+    pub attack_ms: Range<f32>,
+    pub release_ms: Range<f32>,
+    pub makeup_db: Range<f32>,
+}
+
+/// A feedback delay.
+///
+/// The input to the feedback loop is scaled by `1 - feedback` and the wet signal is crossfaded with
+/// the dry signal by `mix`, so the delay never raises the level of a sound above unity.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Delay {
+    pub time_ms: Range<f32>,
+    pub feedback: Range<f32>,
+    pub mix: Range<f32>,
+}
+
+/// A `tanh` waveshaping distortion.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Distortion {
+    pub drive_db: Range<f32>,
+    pub mix: Range<f32>,
+}
+
+/// Reduces the bit depth and sample rate of the sound.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Bitcrusher {
+    pub bits: Range<f32>,
+    pub downsample: Range<f32>,
+    pub mix: Range<f32>,
+}
+
+/// The running chain of effect processors for a single sound.
+#[derive(Clone, Debug, Default)]
+pub struct Chain {
+    processors: Vec<Processor>,
+}
+
+/// The DSP state for a single effect within a sound's `Chain`.
+#[derive(Clone, Debug)]
+pub enum Processor {
+    Filter(FilterProcessor),
+    Eq(EqProcessor),
+    Compressor(CompressorProcessor),
+    Delay(DelayProcessor),
+    Distortion(DistortionProcessor),
+    Bitcrusher(BitcrusherProcessor),
+    MonoFold,
+}
+
+#[derive(Clone, Debug)]
+pub struct FilterProcessor {
+    kind: FilterKind,
+    cutoff_hz: f32,
+    q: f32,
+    biquad: Biquad,
+}
+
+#[derive(Clone, Debug)]
+pub struct EqProcessor {
+    low_db: f32,
+    mid_db: f32,
+    mid_hz: f32,
+    high_db: f32,
+    low: Biquad,
+    mid: Biquad,
+    high: Biquad,
+}
+
+#[derive(Clone, Debug)]
+pub struct CompressorProcessor {
+    threshold_db: f32,
+    ratio: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    makeup_gain: f32,
+    envelope: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct DelayProcessor {
+    delay_frames: usize,
+    feedback: f32,
+    mix: f32,
+    buffer: Vec<f32>,
+    write_frame: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct DistortionProcessor {
+    drive: f32,
+    mix: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct BitcrusherProcessor {
+    steps: f32,
+    downsample: usize,
+    mix: f32,
+    held: Vec<f32>,
+    countdown: usize,
+}
+
+/// A biquad filter with independent state for each channel.
+#[derive(Clone, Debug)]
+struct Biquad {
+    coeffs: Coeffs,
+    // `[x1, x2, y1, y2]` for each channel.
+    state: Vec<[f32; 4]>,
+}
+
+/// Normalised biquad coefficients.
+#[derive(Copy, Clone, Debug)]
+struct Coeffs {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Effect {
+    pub const VARIANT_COUNT: usize = 7;
+
+    /// The names of all parameters that may be controlled for this effect.
+    pub fn param_names(&self) -> &'static [&'static str] {
+        match *self {
+            Effect::Filter(_) => &["cutoff_hz", "q"],
+            Effect::Eq(_) => &["low_db", "mid_db", "mid_hz", "high_db"],
+            Effect::Compressor(_) => {
+                &["threshold_db", "ratio", "attack_ms", "release_ms", "makeup_db"]
+            },
+            Effect::Delay(_) => &["time_ms", "feedback", "mix"],
+            Effect::Distortion(_) => &["drive_db", "mix"],
+            Effect::Bitcrusher(_) => &["bits", "downsample", "mix"],
+            Effect::MonoFold => &[],
+        }
+    }
+
+    /// The range of the parameter with the given name.
+    pub fn param(&self, name: &str) -> Option<Range<f32>> {
+        let param = match *self {
+            Effect::Filter(ref f) => match name {
+                "cutoff_hz" => f.cutoff_hz,
+                "q" => f.q,
+                _ => return None,
+            },
+            Effect::Eq(ref eq) => match name {
+                "low_db" => eq.low_db,
+                "mid_db" => eq.mid_db,
+                "mid_hz" => eq.mid_hz,
+                "high_db" => eq.high_db,
+                _ => return None,
+            },
+            Effect::Compressor(ref c) => match name {
+                "threshold_db" => c.threshold_db,
+                "ratio" => c.ratio,
+                "attack_ms" => c.attack_ms,
+                "release_ms" => c.release_ms,
+                "makeup_db" => c.makeup_db,
+                _ => return None,
+            },
+            Effect::Delay(ref d) => match name {
+                "time_ms" => d.time_ms,
+                "feedback" => d.feedback,
+                "mix" => d.mix,
+                _ => return None,
+            },
+            Effect::Distortion(ref d) => match name {
+                "drive_db" => d.drive_db,
+                "mix" => d.mix,
+                _ => return None,
+            },
+            Effect::Bitcrusher(ref b) => match name {
+                "bits" => b.bits,
+                "downsample" => b.downsample,
+                "mix" => b.mix,
+                _ => return None,
+            },
+            Effect::MonoFold => return None,
+        };
+        Some(param)
+    }
+
+    /// Mutable access to the range of the parameter with the given name.
+    pub fn param_mut(&mut self, name: &str) -> Option<&mut Range<f32>> {
+        let param = match *self {
+            Effect::Filter(ref mut f) => match name {
+                "cutoff_hz" => &mut f.cutoff_hz,
+                "q" => &mut f.q,
+                _ => return None,
+            },
+            Effect::Eq(ref mut eq) => match name {
+                "low_db" => &mut eq.low_db,
+                "mid_db" => &mut eq.mid_db,
+                "mid_hz" => &mut eq.mid_hz,
+                "high_db" => &mut eq.high_db,
+                _ => return None,
+            },
+            Effect::Compressor(ref mut c) => match name {
+                "threshold_db" => &mut c.threshold_db,
+                "ratio" => &mut c.ratio,
+                "attack_ms" => &mut c.attack_ms,
+                "release_ms" => &mut c.release_ms,
+                "makeup_db" => &mut c.makeup_db,
+                _ => return None,
+            },
+            Effect::Delay(ref mut d) => match name {
+                "time_ms" => &mut d.time_ms,
+                "feedback" => &mut d.feedback,
+                "mix" => &mut d.mix,
+                _ => return None,
+            },
+            Effect::Distortion(ref mut d) => match name {
+                "drive_db" => &mut d.drive_db,
+                "mix" => &mut d.mix,
+                _ => return None,
+            },
+            Effect::Bitcrusher(ref mut b) => match name {
+                "bits" => &mut b.bits,
+                "downsample" => &mut b.downsample,
+                "mix" => &mut b.mix,
+                _ => return None,
+            },
+            Effect::MonoFold => return None,
+        };
+        Some(param)
+    }
+
+    /// The absolute limits of the parameter with the given name, used for GUI sliders.
+    pub fn param_limits(name: &str) -> Range<f32> {
+        let (min, max) = match name {
+            "cutoff_hz" | "mid_hz" => (20.0, 20_000.0),
+            "q" => (0.1, 20.0),
+            "low_db" | "mid_db" | "high_db" => (-24.0, 24.0),
+            "threshold_db" => (-60.0, 0.0),
+            "ratio" => (1.0, 20.0),
+            "attack_ms" => (0.1, 200.0),
+            "release_ms" => (1.0, 2_000.0),
+            "makeup_db" => (0.0, 24.0),
+            "time_ms" => (1.0, MAX_DELAY_MS),
+            "feedback" => (0.0, 0.95),
+            "drive_db" => (0.0, 48.0),
+            "bits" => (1.0, 16.0),
+            "downsample" => (1.0, 32.0),
+            _ => (0.0, 1.0),
+        };
+        Range { min, max }
+    }
+
+    /// Produce the index of the Effect value variant.
+    pub fn to_index(&self) -> usize {
+        match *self {
+            Effect::Filter(_) => 0,
+            Effect::Eq(_) => 1,
+            Effect::Compressor(_) => 2,
+            Effect::Delay(_) => 3,
+            Effect::Distortion(_) => 4,
+            Effect::Bitcrusher(_) => 5,
+            Effect::MonoFold => 6,
+        }
+    }
+
+    /// Produce a label for the variant at the given index.
+    pub fn label_from_index(i: usize) -> &'static str {
+        match i {
+            0 => "Filter",
+            1 => "EQ",
+            2 => "Compressor",
+            3 => "Delay",
+            4 => "Distortion",
+            5 => "Bitcrusher",
+            6 => "Mono Fold",
+            _ => "",
+        }
+    }
+
+    /// Produce a default Effect variant for the given variant index.
+    pub fn from_index(i: usize) -> Option<Self> {
+        match i {
+            0 => Some(Effect::Filter(default::FILTER)),
+            1 => Some(Effect::Eq(default::EQ)),
+            2 => Some(Effect::Compressor(default::COMPRESSOR)),
+            3 => Some(Effect::Delay(default::DELAY)),
+            4 => Some(Effect::Distortion(default::DISTORTION)),
+            5 => Some(Effect::Bitcrusher(default::BITCRUSHER)),
+            6 => Some(Effect::MonoFold),
+            _ => None,
+        }
+    }
+}
+
+impl Chain {
+    /// Create a chain for a sound with the given number of channels, choosing a random value
+    /// within each parameter's range.
+    ///
+    /// This is used by the soundscape so that each sound is processed slightly differently.
+    pub fn random<R>(effects: &[Effect], channels: usize, mut rng: R) -> Self
+    where
+        R: Rng,
+    {
+        Chain::from_effects(effects, channels, |range| {
+            if range.min == range.max {
+                range.min
+            } else {
+                map_range(rng.gen::<f32>(), 0.0, 1.0, range.min, range.max)
+            }
+        })
+    }
+
+    /// Create a chain for a sound with the given number of channels, using the middle of each
+    /// parameter's range.
+    pub fn middle(effects: &[Effect], channels: usize) -> Self {
+        Chain::from_effects(effects, channels, |range| (range.min + range.max) * 0.5)
+    }
+
+    /// Create a chain from the given effects, using `pick` to select a value for each parameter.
+    pub fn from_effects<F>(effects: &[Effect], channels: usize, mut pick: F) -> Self
+    where
+        F: FnMut(Range<f32>) -> f32,
+    {
+        let processors = effects
+            .iter()
+            .map(|effect| Processor::new(effect, channels, &mut pick))
+            .collect();
+        Chain { processors }
+    }
+
+    /// Whether or not there are any effects within the chain.
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Set the parameter with the given name for the effect at the given index.
+    ///
+    /// Returns `false` if there is no effect at the index or the effect has no such parameter.
+    pub fn set_param(&mut self, index: usize, name: &str, value: f32) -> bool {
+        match self.processors.get_mut(index) {
+            None => false,
+            Some(processor) => processor.set_param(name, value),
+        }
+    }
+
+    /// Process the given interleaved samples in place.
+    pub fn process(&mut self, samples: &mut [f32], channels: usize) {
+        if channels == 0 {
+            return;
+        }
+        for processor in &mut self.processors {
+            processor.process(samples, channels);
+        }
+    }
+}
+
+impl Processor {
+    /// Create the processor for the given effect, using `pick` to choose each parameter value.
+    fn new<F>(effect: &Effect, channels: usize, pick: &mut F) -> Self
+    where
+        F: FnMut(Range<f32>) -> f32,
+    {
+        let mut processor = match *effect {
+            Effect::Filter(ref f) => Processor::Filter(FilterProcessor {
+                kind: f.kind,
+                cutoff_hz: 1_000.0,
+                q: 0.7,
+                biquad: Biquad::new(channels),
+            }),
+            Effect::Eq(_) => Processor::Eq(EqProcessor {
+                low_db: 0.0,
+                mid_db: 0.0,
+                mid_hz: 1_000.0,
+                high_db: 0.0,
+                low: Biquad::new(channels),
+                mid: Biquad::new(channels),
+                high: Biquad::new(channels),
+            }),
+            Effect::Compressor(_) => Processor::Compressor(CompressorProcessor {
+                threshold_db: 0.0,
+                ratio: 1.0,
+                attack_coeff: 0.0,
+                release_coeff: 0.0,
+                makeup_gain: 1.0,
+                envelope: 0.0,
+            }),
+            Effect::Delay(_) => {
+                let max_frames = ms_to_frames(MAX_DELAY_MS) + 1;
+                Processor::Delay(DelayProcessor {
+                    delay_frames: 1,
+                    feedback: 0.0,
+                    mix: 0.0,
+                    buffer: vec![0.0; max_frames * channels],
+                    write_frame: 0,
+                })
+            },
+            Effect::Distortion(_) => Processor::Distortion(DistortionProcessor {
+                drive: 1.0,
+                mix: 0.0,
+            }),
+            Effect::Bitcrusher(_) => Processor::Bitcrusher(BitcrusherProcessor {
+                steps: 1.0,
+                downsample: 1,
+                mix: 0.0,
+                held: vec![0.0; channels],
+                countdown: 0,
+            }),
+            Effect::MonoFold => Processor::MonoFold,
+        };
+        for &name in effect.param_names() {
+            if let Some(range) = effect.param(name) {
+                processor.set_param(name, pick(range));
+            }
+        }
+        processor
+    }
+
+    /// Set the parameter with the given name.
+    ///
+    /// Returns `false` if the processor has no parameter with the given name.
+    pub fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match *self {
+            Processor::Filter(ref mut f) => {
+                match name {
+                    "cutoff_hz" => f.cutoff_hz = value,
+                    "q" => f.q = value,
+                    _ => return false,
+                }
+                f.biquad.coeffs = match f.kind {
+                    FilterKind::LowPass => Coeffs::low_pass(f.cutoff_hz, f.q),
+                    FilterKind::HighPass => Coeffs::high_pass(f.cutoff_hz, f.q),
+                    FilterKind::BandPass => Coeffs::band_pass(f.cutoff_hz, f.q),
+                };
+            },
+            Processor::Eq(ref mut eq) => {
+                match name {
+                    "low_db" => eq.low_db = value,
+                    "mid_db" => eq.mid_db = value,
+                    "mid_hz" => eq.mid_hz = value,
+                    "high_db" => eq.high_db = value,
+                    _ => return false,
+                }
+                eq.low.coeffs = Coeffs::low_shelf(EQ_LOW_SHELF_HZ, eq.low_db);
+                eq.mid.coeffs = Coeffs::peaking(eq.mid_hz, EQ_MID_Q, eq.mid_db);
+                eq.high.coeffs = Coeffs::high_shelf(EQ_HIGH_SHELF_HZ, eq.high_db);
+            },
+            Processor::Compressor(ref mut c) => match name {
+                "threshold_db" => c.threshold_db = value,
+                "ratio" => c.ratio = value.max(1.0),
+                "attack_ms" => c.attack_coeff = smoothing_coeff(value),
+                "release_ms" => c.release_coeff = smoothing_coeff(value),
+                "makeup_db" => c.makeup_gain = db_to_gain(value),
+                _ => return false,
+            },
+            Processor::Delay(ref mut d) => match name {
+                "time_ms" => {
+                    let max_frames = ms_to_frames(MAX_DELAY_MS);
+                    d.delay_frames = ms_to_frames(value).max(1).min(max_frames);
+                },
+                "feedback" => d.feedback = value.max(0.0).min(0.99),
+                "mix" => d.mix = value.max(0.0).min(1.0),
+                _ => return false,
+            },
+            Processor::Distortion(ref mut d) => match name {
+                "drive_db" => d.drive = db_to_gain(value),
+                "mix" => d.mix = value.max(0.0).min(1.0),
+                _ => return false,
+            },
+            Processor::Bitcrusher(ref mut b) => match name {
+                "bits" => b.steps = 2.0f32.powf(value.max(1.0) - 1.0),
+                "downsample" => b.downsample = (value.round() as usize).max(1),
+                "mix" => b.mix = value.max(0.0).min(1.0),
+                _ => return false,
+            },
+            Processor::MonoFold => return false,
+        }
+        true
+    }
+
+    /// Process the given interleaved samples in place.
+    fn process(&mut self, samples: &mut [f32], channels: usize) {
+        match *self {
+            Processor::Filter(ref mut f) => f.biquad.process(samples, channels),
+
+            Processor::Eq(ref mut eq) => {
+                eq.low.process(samples, channels);
+                eq.mid.process(samples, channels);
+                eq.high.process(samples, channels);
+            },
+
+            Processor::Compressor(ref mut c) => {
+                for frame in samples.chunks_mut(channels) {
+                    let peak = frame.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+                    let coeff = if peak > c.envelope { c.attack_coeff } else { c.release_coeff };
+                    c.envelope = peak + coeff * (c.envelope - peak);
+                    let envelope_db = gain_to_db(c.envelope);
+                    let over_db = envelope_db - c.threshold_db;
+                    let gain_db = if over_db > 0.0 {
+                        -over_db * (1.0 - 1.0 / c.ratio)
+                    } else {
+                        0.0
+                    };
+                    let gain = db_to_gain(gain_db) * c.makeup_gain;
+                    for sample in frame.iter_mut() {
+                        *sample *= gain;
+                    }
+                }
+            },
+
+            Processor::Delay(ref mut d) => {
+                let buffer_frames = d.buffer.len() / channels;
+                if buffer_frames == 0 {
+                    return;
+                }
+                for frame in samples.chunks_mut(channels) {
+                    let read_frame =
+                        (d.write_frame + buffer_frames - d.delay_frames) % buffer_frames;
+                    for (ch, sample) in frame.iter_mut().enumerate() {
+                        let delayed = d.buffer[read_frame * channels + ch];
+                        let input = *sample * (1.0 - d.feedback);
+                        d.buffer[d.write_frame * channels + ch] = input + delayed * d.feedback;
+                        *sample = *sample * (1.0 - d.mix) + delayed * d.mix;
+                    }
+                    d.write_frame = (d.write_frame + 1) % buffer_frames;
+                }
+            },
+
+            Processor::Distortion(ref mut d) => {
+                let normalise = d.drive.tanh();
+                for sample in samples.iter_mut() {
+                    let shaped = (*sample * d.drive).tanh() / normalise;
+                    *sample += (shaped - *sample) * d.mix;
+                }
+            },
+
+            Processor::Bitcrusher(ref mut b) => {
+                for frame in samples.chunks_mut(channels) {
+                    if b.countdown == 0 {
+                        for (held, &sample) in b.held.iter_mut().zip(frame.iter()) {
+                            *held = (sample * b.steps).round() / b.steps;
+                        }
+                        b.countdown = b.downsample;
+                    }
+                    b.countdown -= 1;
+                    for (sample, &held) in frame.iter_mut().zip(b.held.iter()) {
+                        *sample += (held - *sample) * b.mix;
+                    }
+                }
+            },
+
+            Processor::MonoFold => {
+                let scale = 1.0 / channels as f32;
+                for frame in samples.chunks_mut(channels) {
+                    let mono = frame.iter().fold(0.0, |sum, &s| sum + s) * scale;
+                    for sample in frame.iter_mut() {
+                        *sample = mono;
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl Biquad {
+    fn new(channels: usize) -> Self {
+        let coeffs = Coeffs::identity();
+        let state = vec![[0.0; 4]; channels];
+        Biquad { coeffs, state }
+    }
+
+    fn process(&mut self, samples: &mut [f32], channels: usize) {
+        let Coeffs { b0, b1, b2, a1, a2 } = self.coeffs;
+        for frame in samples.chunks_mut(channels) {
+            for (sample, state) in frame.iter_mut().zip(self.state.iter_mut()) {
+                let x = *sample;
+                let [x1, x2, y1, y2] = *state;
+                let y = b0 * x + b1 * x1 + b2 * x2 - a1 * y1 - a2 * y2;
+                *state = [x, x1, y, y1];
+                *sample = y;
+            }
+        }
+    }
+}
+
+// Biquad coefficient formulae from Robert Bristow-Johnson's "Audio EQ Cookbook".
+impl Coeffs {
+    fn identity() -> Self {
+        Coeffs { b0: 1.0, b1: 0.0, b2: 0.0, a1: 0.0, a2: 0.0 }
+    }
+
+    fn normalise(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Coeffs { b0: b0 / a0, b1: b1 / a0, b2: b2 / a0, a1: a1 / a0, a2: a2 / a0 }
+    }
+
+    // The angular frequency and its sine and cosine for the given frequency.
+    fn omega(hz: f32) -> (f32, f32) {
+        let nyquist = SAMPLE_RATE as f32 * 0.5;
+        let hz = hz.max(1.0).min(nyquist * 0.99);
+        let w0 = 2.0 * PI * hz / SAMPLE_RATE as f32;
+        (w0.sin(), w0.cos())
+    }
+
+    fn low_pass(hz: f32, q: f32) -> Self {
+        let (sin, cos) = Coeffs::omega(hz);
+        let alpha = sin / (2.0 * q.max(0.01));
+        let b1 = 1.0 - cos;
+        Coeffs::normalise(b1 * 0.5, b1, b1 * 0.5, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    fn high_pass(hz: f32, q: f32) -> Self {
+        let (sin, cos) = Coeffs::omega(hz);
+        let alpha = sin / (2.0 * q.max(0.01));
+        let b1 = -(1.0 + cos);
+        Coeffs::normalise(-b1 * 0.5, b1, -b1 * 0.5, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    fn band_pass(hz: f32, q: f32) -> Self {
+        let (sin, cos) = Coeffs::omega(hz);
+        let alpha = sin / (2.0 * q.max(0.01));
+        Coeffs::normalise(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    fn peaking(hz: f32, q: f32, gain_db: f32) -> Self {
+        let (sin, cos) = Coeffs::omega(hz);
+        let a = 10.0f32.powf(gain_db / 40.0);
+        let alpha = sin / (2.0 * q.max(0.01));
+        Coeffs::normalise(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    fn low_shelf(hz: f32, gain_db: f32) -> Self {
+        let (sin, cos) = Coeffs::omega(hz);
+        let a = 10.0f32.powf(gain_db / 40.0);
+        // Shelf slope of 1.
+        let alpha = sin / 2.0 * 2.0f32.sqrt();
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+        Coeffs::normalise(
+            a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha),
+            (a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha,
+        )
+    }
+
+    fn high_shelf(hz: f32, gain_db: f32) -> Self {
+        let (sin, cos) = Coeffs::omega(hz);
+        let a = 10.0f32.powf(gain_db / 40.0);
+        // Shelf slope of 1.
+        let alpha = sin / 2.0 * 2.0f32.sqrt();
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+        Coeffs::normalise(
+            a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha),
+            (a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha,
+        )
+    }
+}
+
+fn ms_to_frames(ms: f32) -> usize {
+    (ms.max(0.0) * SAMPLE_RATE as f32 / 1_000.0) as usize
+}
+
+// The one-pole smoothing coefficient for the given time constant.
+fn smoothing_coeff(ms: f32) -> f32 {
+    let frames = ms.max(0.01) * SAMPLE_RATE as f32 / 1_000.0;
+    (-1.0 / frames).exp()
+}
+
+pub mod default {
+    use super::{Bitcrusher, Compressor, Delay, Distortion, Equaliser, Filter, FilterKind};
+    use utils::Range;
+
+    pub const FILTER: Filter = Filter {
+        kind: FilterKind::LowPass,
+        cutoff_hz: Range { min: 2_000.0, max: 8_000.0 },
+        q: Range { min: 0.7, max: 0.7 },
+    };
+    pub const EQ: Equaliser = Equaliser {
+        low_db: Range { min: 0.0, max: 0.0 },
+        mid_db: Range { min: 0.0, max: 0.0 },
+        mid_hz: Range { min: 1_000.0, max: 1_000.0 },
+        high_db: Range { min: 0.0, max: 0.0 },
+    };
+    pub const COMPRESSOR: Compressor = Compressor {
+        threshold_db: Range { min: -18.0, max: -18.0 },
+        ratio: Range { min: 4.0, max: 4.0 },
+        attack_ms: Range { min: 10.0, max: 10.0 },
+        release_ms: Range { min: 200.0, max: 200.0 },
+        makeup_db: Range { min: 0.0, max: 0.0 },
+    };
+    pub const DELAY: Delay = Delay {
+        time_ms: Range { min: 250.0, max: 500.0 },
+        feedback: Range { min: 0.3, max: 0.3 },
+        mix: Range { min: 0.3, max: 0.3 },
+    };
+    pub const DISTORTION: Distortion = Distortion {
+        drive_db: Range { min: 12.0, max: 12.0 },
+        mix: Range { min: 0.5, max: 0.5 },
+    };
+    pub const BITCRUSHER: Bitcrusher = Bitcrusher {
+        bits: Range { min: 8.0, max: 8.0 },
+        downsample: Range { min: 1.0, max: 4.0 },
+        mix: Range { min: 1.0, max: 1.0 },
+    };
+}
+
+#[test]
+fn test_filter_and_eq_response() {
+    // The peak amplitude of a mono sine at the given frequency once processed by the effect.
+    //
+    // Only the second half of the output is measured so that the effect has time to settle.
+    fn sine_response(effect: Effect, hz: f32) -> f32 {
+        let frames = SAMPLE_RATE as usize / 2;
+        let mut samples: Vec<f32> = (0..frames)
+            .map(|i| (2.0 * PI * hz * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        Chain::middle(&[effect], 1).process(&mut samples, 1);
+        samples[frames / 2..].iter().fold(0.0, |max, s| max.max(s.abs()))
+    }
+    let fixed = |value| Range { min: value, max: value };
+
+    let low_pass = |hz| {
+        let kind = FilterKind::LowPass;
+        sine_response(Effect::Filter(Filter { kind, cutoff_hz: fixed(1_000.0), q: fixed(0.7) }), hz)
+    };
+    assert!((low_pass(100.0) - 1.0).abs() < 0.05);
+    assert!(low_pass(10_000.0) < 0.05);
+
+    let high_pass = |hz| {
+        let kind = FilterKind::HighPass;
+        sine_response(Effect::Filter(Filter { kind, cutoff_hz: fixed(1_000.0), q: fixed(0.7) }), hz)
+    };
+    assert!(high_pass(100.0) < 0.05);
+    assert!((high_pass(10_000.0) - 1.0).abs() < 0.05);
+
+    // A flat EQ passes the signal through unchanged while a boosted mid band amplifies it.
+    let eq = |mid_db| Effect::Eq(Equaliser { mid_db: fixed(mid_db), ..default::EQ });
+    assert!((sine_response(eq(0.0), 1_000.0) - 1.0).abs() < 0.01);
+    assert!((sine_response(eq(12.0), 1_000.0) - db_to_gain(12.0)).abs() < 0.1);
+    assert!((sine_response(eq(12.0), 50.0) - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn test_compressor() {
+    let fixed = |value| Range { min: value, max: value };
+    let compressor = Effect::Compressor(Compressor {
+        threshold_db: fixed(-20.0),
+        ratio: fixed(4.0),
+        attack_ms: fixed(1.0),
+        release_ms: fixed(50.0),
+        makeup_db: fixed(0.0),
+    });
+    let level = |input: f32| {
+        let mut samples = vec![input; SAMPLE_RATE as usize / 10];
+        Chain::middle(&[compressor.clone()], 1).process(&mut samples, 1);
+        *samples.last().unwrap()
+    };
+
+    // Signals below the threshold are untouched.
+    assert!((level(0.05) - 0.05).abs() < 1e-4);
+
+    // 20dB over the threshold at a ratio of 4:1 is reduced by 15dB.
+    assert!((level(1.0) - db_to_gain(-15.0)).abs() < 1e-3);
+}
+
+#[test]
+fn test_delay() {
+    let fixed = |value| Range { min: value, max: value };
+    let delay = |feedback, mix| {
+        Effect::Delay(Delay { time_ms: fixed(10.0), feedback: fixed(feedback), mix: fixed(mix) })
+    };
+    let delay_frames = ms_to_frames(10.0);
+    let mut samples = vec![0.0; delay_frames * 3 + 1];
+    samples[0] = 1.0;
+    Chain::middle(&[delay(0.5, 0.5)], 1).process(&mut samples, 1);
+
+    // Half of the dry impulse followed by each echo, scaled by the feedback.
+    assert_eq!(samples[0], 0.5);
+    assert_eq!(samples[delay_frames], 0.25);
+    assert_eq!(samples[delay_frames * 2], 0.125);
+    assert_eq!(samples[delay_frames * 3], 0.0625);
+    let echoes = [0, delay_frames, delay_frames * 2, delay_frames * 3];
+    for (i, &sample) in samples.iter().enumerate() {
+        if !echoes.contains(&i) {
+            assert_eq!(sample, 0.0);
+        }
+    }
+
+    // A full scale signal never rises above unity, even when fully wet with heavy feedback.
+    for &(feedback, mix) in &[(0.95, 1.0), (0.95, 0.5), (0.5, 1.0), (0.0, 1.0)] {
+        let mut samples: Vec<f32> = (0..SAMPLE_RATE as usize)
+            .map(|i| if (i / 7) % 2 == 0 { 1.0 } else { -1.0 })
+            .chain((0..SAMPLE_RATE as usize).map(|_| 1.0))
+            .collect();
+        Chain::middle(&[delay(feedback, mix)], 1).process(&mut samples, 1);
+        let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!(peak <= 1.0 + 1e-4, "peak {} for feedback {} and mix {}", peak, feedback, mix);
+    }
+}
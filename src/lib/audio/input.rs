@@ -1,6 +1,9 @@
 //! The capture function implementation for the audio server's input stream.
 //!
 //! The input stream has a number of `Source`s that read from one or more of the stream's channels.
+//!
+//! `Network` sources are also read here so that their jitter buffers are drained at the rate of
+//! the input device.
 
 use audio::source;
 use fxhash::FxHashMap;
@@ -17,6 +20,8 @@ pub type Stream = nannou::audio::Stream<Model>;
 pub struct Model {
     // All sources that currently exist.
     pub sources: FxHashMap<source::Id, source::Realtime>,
+    // The receivers for all network sources that currently exist.
+    pub network_sources: FxHashMap<source::Id, source::network::Receiver>,
     // The currently active sounds using the realtime source with the given source ID.
     pub active_sounds: FxHashMap<source::Id, Vec<ActiveSound>>,
 }
@@ -54,9 +59,11 @@ impl Model {
     /// avoid unexpected dynamic allocation within on the audio thread.
     pub fn new() -> Self {
         let sources = Default::default();
+        let network_sources = Default::default();
         let active_sounds = Default::default();
         Model {
             sources,
+            network_sources,
             active_sounds,
         }
     }
@@ -66,6 +73,7 @@ impl Model {
     /// This is called when we switch between projects within the GUI.
     pub fn clear_project_specific_data(&mut self) {
         self.sources.clear();
+        self.network_sources.clear();
         self.active_sounds.clear();
     }
 }
@@ -75,9 +83,17 @@ pub fn capture(mut model: Model, buffer: &Buffer) -> Model {
     {
         let Model {
             ref sources,
+            ref mut network_sources,
             ref mut active_sounds,
         } = model;
 
+        // Read the next block from each network source's jitter buffer.
+        //
+        // This is done even when there are no active sounds so that the buffer does not grow.
+        for receiver in network_sources.values_mut() {
+            receiver.read_frames(buffer.len_frames());
+        }
+
         // Remove any sounds that have been closed.
         for sounds in active_sounds.values_mut() {
             sounds.retain(|s| !s.is_closed.load(atomic::Ordering::Relaxed));
@@ -85,10 +101,13 @@ pub fn capture(mut model: Model, buffer: &Buffer) -> Model {
 
         // Send every sample buffered in chronological order to the active sounds.
         for (source_id, sounds) in active_sounds.iter() {
-            // Retrieve the realtime data for this source.
-            let realtime = match sources.get(source_id) {
-                None => continue,
-                Some(rt) => rt,
+            // Retrieve the realtime or network data for this source.
+            let realtime = sources.get(source_id);
+            let network = network_sources.get(source_id);
+            let n_channels = match (realtime, network) {
+                (Some(rt), _) => rt.channels.len(),
+                (None, Some(net)) => net.channels(),
+                (None, None) => continue,
             };
 
             for sound in sounds {
@@ -111,7 +130,7 @@ pub fn capture(mut model: Model, buffer: &Buffer) -> Model {
                 let mut samples = match sound.buffer_rx.try_pop() {
                     // This branch should never be hit but is here just in case.
                     None => {
-                        let samples_len = frames_to_take * realtime.channels.len();
+                        let samples_len = frames_to_take * n_channels;
                         Vec::with_capacity(samples_len)
                     },
                     // There should always be a buffer waiting in this channel.
@@ -121,13 +140,34 @@ pub fn capture(mut model: Model, buffer: &Buffer) -> Model {
                     },
                 };
 
-                // Get the channel range and ensure it is no greater than the buffer len.
-                let start = cmp::min(realtime.channels.start, buffer.channels());
-                let end = cmp::min(realtime.channels.end, buffer.channels());
-
-                // Read the necessary samples from the buffer.
-                for frame in buffer.frames().take(frames_to_take) {
-                    samples.extend(frame[start..end].iter().cloned());
+                match (realtime, network) {
+                    (Some(realtime), _) => {
+                        // Read the sample for each of the source's inputs from each frame.
+                        //
+                        // Inputs beyond the number of device channels are silent.
+                        for frame in buffer.frames().take(frames_to_take) {
+                            for input in &realtime.channels {
+                                samples.push(frame.get(input.channel).cloned().unwrap_or(0.0));
+                            }
+                        }
+
+                        // Apply the trim for each input.
+                        for (i, input) in realtime.channels.iter().enumerate() {
+                            let gain = input.trim_gain();
+                            if gain != 1.0 {
+                                for sample in samples.iter_mut().skip(i).step_by(n_channels) {
+                                    *sample *= gain;
+                                }
+                            }
+                        }
+                    },
+                    (None, Some(network)) => {
+                        // Read the necessary samples from the network source's current block.
+                        let block = network.block();
+                        let len = cmp::min(frames_to_take * n_channels, block.len());
+                        samples.extend(block[..len].iter().cloned());
+                    },
+                    (None, None) => (),
                 }
 
                 // Send the buffer to the realtime signal.
@@ -0,0 +1,408 @@
+//! Loudness analysis of WAV sources following EBU R128 / ITU-R BS.1770.
+//!
+//! Each WAV is analysed on a dedicated background thread, producing its integrated loudness in
+//! LUFS and its true peak in dBTP. These are used to optionally normalise each source toward a
+//! project-wide loudness target.
+
+use audio::source::{self, wav};
+use hound;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use utils::db_to_gain;
+
+/// The default project-wide loudness target.
+pub const DEFAULT_TARGET_LUFS: f32 = -23.0;
+
+/// The lowest loudness reported. Blocks below this are ignored by the absolute gate.
+pub const MIN_LUFS: f32 = -70.0;
+
+/// The lowest true peak reported, e.g. for silent WAVs.
+pub const MIN_DBTP: f32 = -120.0;
+
+/// Normalisation will never raise a source's true peak above this.
+pub const TRUE_PEAK_CEILING_DBTP: f32 = -1.0;
+
+/// The maximum gain that normalisation may apply in either direction.
+pub const MAX_GAIN_DB: f32 = 24.0;
+
+// Loudness is gated over 400ms blocks, each overlapping the last by 75%, built from 100ms steps.
+const STEP_MS: f64 = 100.0;
+const STEPS_PER_BLOCK: usize = 4;
+
+// Blocks more than this many LU below the absolute-gated loudness are ignored.
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+// The true peak is measured by oversampling by this factor.
+const OVERSAMPLING: usize = 4;
+// The number of taps of the interpolation filter used for each oversampled phase.
+const TAPS_PER_PHASE: usize = 12;
+
+/// The result of analysing a single WAV.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Analysis {
+    /// The gated integrated loudness in LUFS.
+    pub integrated_lufs: f32,
+    /// The maximum true peak across all channels in dBTP.
+    pub true_peak_dbtp: f32,
+}
+
+/// Measures the loudness and true peak of a stream of interleaved samples.
+pub struct Meter {
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    // The number of frames within each step.
+    step_frames: usize,
+    // The number of frames accumulated into the current step.
+    frames: usize,
+    // The sum of squared K-weighted samples per channel for the current step.
+    step_sums: Vec<f64>,
+    // The mean square per channel for each completed step.
+    steps: Vec<Vec<f64>>,
+    // The interpolation filter for each oversampled phase.
+    phases: Vec<[f64; TAPS_PER_PHASE]>,
+    // The most recent samples per channel, most recent first.
+    histories: Vec<[f64; TAPS_PER_PHASE]>,
+    peak: f64,
+}
+
+/// A second order IIR filter.
+#[derive(Copy, Clone, Debug)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+/// A request for the analysis thread.
+struct Request {
+    id: source::Id,
+    path: PathBuf,
+}
+
+/// The result of an analysis, sent back from the analysis thread.
+pub struct Report {
+    /// The source whose WAV was analysed.
+    pub id: source::Id,
+    /// The path of the analysed WAV.
+    pub path: PathBuf,
+    /// The analysis, or a description of why it failed.
+    pub result: Result<Analysis, String>,
+}
+
+/// A handle to the loudness analysis thread.
+pub struct Analyser {
+    request_tx: mpsc::Sender<Request>,
+    report_rx: mpsc::Receiver<Report>,
+}
+
+impl Analysis {
+    /// The gain that brings the analysed WAV to the given target loudness.
+    ///
+    /// The gain is limited so that the resulting true peak does not exceed the
+    /// `TRUE_PEAK_CEILING_DBTP` and so that it never exceeds `MAX_GAIN_DB` in either direction.
+    pub fn normalisation_gain(&self, target_lufs: f32) -> f32 {
+        let gain_db = (target_lufs - self.integrated_lufs)
+            .min(TRUE_PEAK_CEILING_DBTP - self.true_peak_dbtp)
+            .max(-MAX_GAIN_DB)
+            .min(MAX_GAIN_DB);
+        db_to_gain(gain_db)
+    }
+}
+
+impl Meter {
+    /// Create a meter for a stream with the given number of channels and sample rate.
+    pub fn new(channels: usize, sample_hz: f64) -> Self {
+        let filter = k_weighting(sample_hz);
+        let step_frames = (sample_hz * STEP_MS / 1_000.0).round() as usize;
+        Meter {
+            channels,
+            filters: vec![filter; channels],
+            step_frames,
+            frames: 0,
+            step_sums: vec![0.0; channels],
+            steps: vec![],
+            phases: interpolation_phases(),
+            histories: vec![[0.0; TAPS_PER_PHASE]; channels],
+            peak: 0.0,
+        }
+    }
+
+    /// Feed the meter the given interleaved samples.
+    ///
+    /// `samples` should contain a whole number of frames.
+    pub fn process(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels) {
+            for (ch, &sample) in frame.iter().enumerate() {
+                let sample = sample as f64;
+
+                // Loudness.
+                let [ref mut shelf, ref mut high_pass] = self.filters[ch];
+                let weighted = high_pass.process(shelf.process(sample));
+                self.step_sums[ch] += weighted * weighted;
+
+                // True peak.
+                let history = &mut self.histories[ch];
+                for i in (1..TAPS_PER_PHASE).rev() {
+                    history[i] = history[i - 1];
+                }
+                history[0] = sample;
+                for phase in &self.phases {
+                    let interpolated: f64 =
+                        phase.iter().zip(history.iter()).map(|(h, x)| h * x).sum();
+                    self.peak = self.peak.max(interpolated.abs());
+                }
+                self.peak = self.peak.max(sample.abs());
+            }
+
+            self.frames += 1;
+            if self.frames == self.step_frames {
+                self.end_step();
+            }
+        }
+    }
+
+    // Store the mean square of the current step and begin the next.
+    fn end_step(&mut self) {
+        let frames = self.frames as f64;
+        let means = self.step_sums.iter().map(|sum| sum / frames).collect();
+        self.steps.push(means);
+        self.frames = 0;
+        for sum in &mut self.step_sums {
+            *sum = 0.0;
+        }
+    }
+
+    /// Produce the analysis of all samples processed so far.
+    ///
+    /// Streams shorter than a single gating block (e.g. short clicks) are measured as a single
+    /// block spanning their full length.
+    pub fn analysis(&self) -> Analysis {
+        // Collect the mean square per channel of each gating block.
+        let blocks: Vec<Vec<f64>> = if self.steps.len() >= STEPS_PER_BLOCK {
+            self.steps
+                .windows(STEPS_PER_BLOCK)
+                .map(|steps| {
+                    (0..self.channels)
+                        .map(|ch| steps.iter().map(|s| s[ch]).sum::<f64>() / steps.len() as f64)
+                        .collect()
+                })
+                .collect()
+        } else {
+            let total_frames = self.steps.len() * self.step_frames + self.frames;
+            if total_frames == 0 {
+                vec![]
+            } else {
+                let block = (0..self.channels)
+                    .map(|ch| {
+                        let completed: f64 =
+                            self.steps.iter().map(|s| s[ch] * self.step_frames as f64).sum();
+                        (completed + self.step_sums[ch]) / total_frames as f64
+                    })
+                    .collect();
+                vec![block]
+            }
+        };
+
+        // Apply the absolute gate, then the relative gate.
+        let loudness = |block: &Vec<f64>| -> f64 { lufs(block.iter().sum()) };
+        let absolute_gated: Vec<&Vec<f64>> = blocks
+            .iter()
+            .filter(|b| loudness(b) > MIN_LUFS as f64)
+            .collect();
+        let integrated_lufs = match mean_loudness(&absolute_gated, self.channels) {
+            None => MIN_LUFS,
+            Some(absolute_lufs) => {
+                let relative_gate = absolute_lufs - RELATIVE_GATE_LU;
+                let relative_gated: Vec<&Vec<f64>> = absolute_gated
+                    .iter()
+                    .cloned()
+                    .filter(|b| loudness(b) > relative_gate)
+                    .collect();
+                mean_loudness(&relative_gated, self.channels)
+                    .map(|lufs| (lufs as f32).max(MIN_LUFS))
+                    .unwrap_or(MIN_LUFS)
+            },
+        };
+
+        let true_peak_dbtp = if self.peak > 0.0 {
+            (20.0 * self.peak.log10()) as f32
+        } else {
+            MIN_DBTP
+        };
+        let true_peak_dbtp = true_peak_dbtp.max(MIN_DBTP);
+
+        Analysis { integrated_lufs, true_peak_dbtp }
+    }
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+impl Analyser {
+    /// Request that the WAV for the given source be analysed.
+    ///
+    /// The result may be collected via `try_recv`.
+    pub fn request(&self, id: source::Id, path: PathBuf) {
+        self.request_tx.send(Request { id, path }).ok();
+    }
+
+    /// Collect the next finished analysis, if any.
+    pub fn try_recv(&self) -> Option<Report> {
+        self.report_rx.try_recv().ok()
+    }
+}
+
+/// Analyse the WAV at the given path.
+pub fn analyse_wav(path: &Path) -> Result<Analysis, hound::Error> {
+    let mut reader = wav::reader::WavReader::open(path)?;
+    let spec = reader.spec();
+    let channels = spec.channels as usize;
+    let mut meter = Meter::new(channels, spec.sample_rate as f64);
+    let mut frame = Vec::with_capacity(channels);
+    wav::samples::for_each(&mut reader, |sample| {
+        frame.push(sample);
+        if frame.len() == channels {
+            meter.process(&frame);
+            frame.clear();
+        }
+    })?;
+    Ok(meter.analysis())
+}
+
+/// Spawn the loudness analysis thread.
+///
+/// WAVs are analysed one at a time in the order in which they are requested.
+pub fn spawn() -> Analyser {
+    let (request_tx, request_rx) = mpsc::channel::<Request>();
+    let (report_tx, report_rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("loudness_analysis".into())
+        .spawn(move || {
+            for Request { id, path } in request_rx {
+                let result = analyse_wav(&path).map_err(|err| format!("{}", err));
+                if report_tx.send(Report { id, path, result }).is_err() {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+    Analyser { request_tx, report_rx }
+}
+
+// The loudness in LUFS of the given sum of channel mean squares.
+//
+// All channels are weighted equally as the channel layout of a source is arbitrary.
+fn lufs(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+// The loudness of the mean of the given blocks.
+fn mean_loudness(blocks: &[&Vec<f64>], channels: usize) -> Option<f64> {
+    if blocks.is_empty() {
+        return None;
+    }
+    let sum: f64 = (0..channels)
+        .map(|ch| blocks.iter().map(|b| b[ch]).sum::<f64>() / blocks.len() as f64)
+        .sum();
+    Some(lufs(sum))
+}
+
+// The two stage K-weighting filter from BS.1770 for the given sample rate.
+//
+// The pre-filter is a high shelf modelling the acoustic effect of the head, followed by the
+// "RLB" high pass filter.
+fn k_weighting(sample_hz: f64) -> [Biquad; 2] {
+    // Stage 1: high shelf.
+    let f0 = 1681.974450955533;
+    let g = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / sample_hz).tan();
+    let vh = 10.0f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        z1: 0.0,
+        z2: 0.0,
+    };
+
+    // Stage 2: high pass.
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / sample_hz).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        z1: 0.0,
+        z2: 0.0,
+    };
+
+    [shelf, high_pass]
+}
+
+// A Hann-windowed sinc interpolation filter split into its oversampled phases.
+//
+// Each phase is normalised to unity gain at DC.
+fn interpolation_phases() -> Vec<[f64; TAPS_PER_PHASE]> {
+    let len = OVERSAMPLING * TAPS_PER_PHASE;
+    let centre = (len - 1) as f64 / 2.0;
+    let taps: Vec<f64> = (0..len)
+        .map(|n| {
+            let x = (n as f64 - centre) / OVERSAMPLING as f64;
+            let sinc = if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+            let window = 0.5 - 0.5 * (2.0 * PI * n as f64 / (len - 1) as f64).cos();
+            sinc * window
+        })
+        .collect();
+    (0..OVERSAMPLING)
+        .map(|phase| {
+            let mut coeffs = [0.0; TAPS_PER_PHASE];
+            for (i, c) in coeffs.iter_mut().enumerate() {
+                *c = taps[phase + i * OVERSAMPLING];
+            }
+            let sum: f64 = coeffs.iter().sum();
+            for c in coeffs.iter_mut() {
+                *c /= sum;
+            }
+            coeffs
+        })
+        .collect()
+}
+
+#[test]
+fn test_stereo_sine_loudness() {
+    // EBU Tech 3341 case 1: a stereo 1 kHz sine at -23 dBFS should measure -23 LUFS.
+    let sample_hz = 48_000.0;
+    let amp = db_to_gain(-23.0);
+    let samples: Vec<f32> = (0..sample_hz as usize * 20)
+        .flat_map(|i| {
+            let s = amp * (2.0 * PI * 1_000.0 * i as f64 / sample_hz).sin() as f32;
+            vec![s, s]
+        })
+        .collect();
+    let mut meter = Meter::new(2, sample_hz);
+    meter.process(&samples);
+    let analysis = meter.analysis();
+    assert!((analysis.integrated_lufs - -23.0).abs() < 0.1, "{:?}", analysis);
+    assert!((analysis.true_peak_dbtp - -23.0).abs() < 0.2, "{:?}", analysis);
+}
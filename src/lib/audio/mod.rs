@@ -9,12 +9,15 @@ pub use self::speaker::Speaker;
 pub mod dbap;
 pub mod detection;
 pub mod detector;
+pub mod effect;
 pub mod fft;
 pub mod input;
+pub mod loudness;
 pub mod output;
 pub mod sound;
 pub mod source;
 pub mod speaker;
+pub mod waveform;
 
 /// Sounds should only be output to speakers that are nearest to avoid the need to render each
 /// sound to every speaker on the map.
@@ -159,6 +159,18 @@ pub struct Model {
     pub dbap_rolloff_db: f64,
     /// the set of sources that are currently soloed. if not empty, only these sounds should play.
     pub soloed: FxHashSet<source::Id>,
+    /// the loudness normalisation gain for each source. sources without an entry have unity gain.
+    source_gains: FxHashMap<source::Id, f32>,
+    /// sources that are currently muted by the soundscape schedule.
+    scheduled_mutes: FxHashSet<source::Id>,
+    /// the product of the volumes of each source's soundscape groups. sources without an entry
+    /// have unity gain.
+    group_gains: FxHashMap<source::Id, f32>,
+    /// sources that are currently silenced by the mute or solo of soundscape groups.
+    group_mutes: FxHashSet<source::Id>,
+    /// the gain applied to the speakers of each installation in response to its occupancy.
+    /// installations without an entry have unity gain.
+    installation_gains: FxHashMap<installation::Id, f32>,
     /// a map from audio sound ids to the audio sounds themselves.
     sounds: FxHashMap<sound::Id, ActiveSound>,
     /// a map from speaker ids to the speakers themselves.
@@ -232,6 +244,19 @@ impl Model {
         // The currently soloed sources (none by default).
         let soloed = Default::default();
 
+        // No sources are normalised by default.
+        let source_gains = Default::default();
+
+        // No sources are muted by the schedule until it is first evaluated.
+        let scheduled_mutes = Default::default();
+
+        // Group levels are unity until they are first received from the soundscape thread.
+        let group_gains = Default::default();
+        let group_mutes = Default::default();
+
+        // Installation levels are unity until occupancy is reported to the soundscape thread.
+        let installation_gains = Default::default();
+
         // A map from audio sound IDs to the audio sounds themselves.
         let sounds = Default::default();
 
@@ -289,6 +314,11 @@ impl Model {
             master_volume,
             dbap_rolloff_db,
             soloed,
+            source_gains,
+            scheduled_mutes,
+            group_gains,
+            group_mutes,
+            installation_gains,
             sounds,
             sounds_ordered,
             sound_channels,
@@ -446,6 +476,45 @@ impl Model {
         count
     }
 
+    /// Begin the release of all sounds that were spawned via the source with the given `Id`.
+    ///
+    /// Each sound will fade out using its own release curve before being removed.
+    ///
+    /// Returns the number of sounds that were released.
+    pub fn release_sounds_with_source(&mut self, id: &source::Id) -> usize {
+        self.update_sounds_with_source(id, |_, sound| sound.signal.release())
+    }
+
+    /// Set the loudness normalisation gain applied to all sounds of the source with the given `Id`.
+    pub fn set_source_gain(&mut self, id: source::Id, gain: f32) {
+        if gain == 1.0 {
+            self.source_gains.remove(&id);
+        } else {
+            self.source_gains.insert(id, gain);
+        }
+    }
+
+    /// Set the sources that are muted by the soundscape schedule, replacing any previous set.
+    pub fn set_scheduled_mutes(&mut self, mutes: FxHashSet<source::Id>) {
+        self.scheduled_mutes = mutes;
+    }
+
+    /// Set the gains and mutes applied to sources via their soundscape groups, replacing any
+    /// previous levels.
+    pub fn set_group_levels(
+        &mut self,
+        gains: FxHashMap<source::Id, f32>,
+        mutes: FxHashSet<source::Id>,
+    ) {
+        self.group_gains = gains;
+        self.group_mutes = mutes;
+    }
+
+    /// Set the gains applied to the speakers of each installation, replacing any previous gains.
+    pub fn set_installation_gains(&mut self, gains: FxHashMap<installation::Id, f32>) {
+        self.installation_gains = gains;
+    }
+
     /// Removes the sound and sends an `End` active sound message to the GUI.
     ///
     /// Also removes the sound from DBAP tracking.
@@ -477,6 +546,11 @@ impl Model {
         self.channels.detection.clear_project_specific_data();
         self.frame_count.store(0, atomic::Ordering::Relaxed);
         self.soloed.clear();
+        self.source_gains.clear();
+        self.scheduled_mutes.clear();
+        self.group_gains.clear();
+        self.group_mutes.clear();
+        self.installation_gains.clear();
         self.speakers.clear();
 
         let Model { ref mut sounds, ref channels, .. } = *self;
@@ -508,14 +582,6 @@ impl Channels {
     }
 }
 
-/// A simple linear interpolation function.
-///
-/// This is used to interpolate between previous and current DBAP speaker gains over the duration
-/// of the buffer.
-fn lerp(a: f32, b: f32, lerp: f32) -> f32 {
-    a + (b - a) * lerp
-}
-
 /// The function given to nannou to use for rendering.
 pub fn render(mut model: Model, mut buffer: Buffer) -> (Model, Buffer) {
     {
@@ -524,6 +590,11 @@ pub fn render(mut model: Model, mut buffer: Buffer) -> (Model, Buffer) {
             cpu_saving_enabled,
             dbap_rolloff_db,
             ref soloed,
+            ref source_gains,
+            ref scheduled_mutes,
+            ref group_gains,
+            ref group_mutes,
+            ref installation_gains,
             ref mut frame_count,
             ref mut sounds,
             ref mut sounds_ordered,
@@ -615,28 +686,46 @@ pub fn render(mut model: Model, mut buffer: Buffer) -> (Model, Buffer) {
             //
             // - There are no speakers.
             // - The source is muted.
+            // - The source is muted by the soundscape schedule.
+            // - The source is muted or not soloed via its soundscape groups.
             // - Some other source(s) is/are soloed.
             let play_condition = speakers.is_empty()
                 || sound.muted
+                || scheduled_mutes.contains(&sound.source_id())
+                || group_mutes.contains(&sound.source_id())
                 || (!soloed.is_empty() && !soloed.contains(&sound.source_id()));
-            if play_condition {
-                // Pull samples from the signal but do not render them.
-                let samples_yielded = sound.signal.samples().take(num_samples).count();
-                if samples_yielded < num_samples {
-                    exhausted_sounds.push(sound_id);
-                }
-                continue;
-            }
 
             // Collect the samples from the `Sound`'s `Signal`.
             {
                 let mut samples_written = 0;
                 for sample in sound.signal.samples().take(num_samples) {
-                    let sample = sample * sound.volume;
                     ordered_sound.unmixed_samples.push(sample);
                     samples_written += 1;
                 }
 
+                // Apply the pre-spatial effects.
+                //
+                // The effects are processed even while the sound is muted so that their state,
+                // e.g. a delay's tail, stays current for when the sound is unmuted.
+                sound.effects.process(&mut ordered_sound.unmixed_samples, n_channels);
+
+                // Discard the processed samples of muted sounds rather than rendering them.
+                if play_condition {
+                    ordered_sound.unmixed_samples.clear();
+                    if samples_written < num_samples {
+                        exhausted_sounds.push(sound_id);
+                    }
+                    continue;
+                }
+
+                // Apply the user's source and group volumes and loudness normalisation.
+                let source_gain = source_gains.get(&sound.source_id()).cloned().unwrap_or(1.0);
+                let group_gain = group_gains.get(&sound.source_id()).cloned().unwrap_or(1.0);
+                let gain = sound.volume * source_gain * group_gain;
+                for sample in ordered_sound.unmixed_samples.iter_mut() {
+                    *sample *= gain;
+                }
+
                 // If CPU saving is not enabled, send the samples to the detector for analysis.
                 if !cpu_saving_enabled {
                     let mut detection_buffer = channels.detection.pop_sound_buffer();
@@ -753,10 +842,15 @@ pub fn render(mut model: Model, mut buffer: Buffer) -> (Model, Buffer) {
                     continue;
                 }
 
-                // Update the speaker gains.
+                // Update the speaker gains, applying the gain of each speaker's installations.
                 let current_gains = dbap::SpeakerGains::new(&dbap_speakers, dbap_rolloff_db);
                 for (info_i, current_gain) in speaker_infos_range.clone().zip(current_gains) {
-                    dbap_speaker_infos[info_i].current_gain = current_gain as _;
+                    let info = &mut dbap_speaker_infos[info_i];
+                    let speaker_id = channels_to_speakers[&info.output_channel];
+                    let installations = &speakers[&speaker_id].speaker.installations;
+                    let installation_gain =
+                        speaker::installation_gain(installations, installation_gains);
+                    info.current_gain = current_gain as f32 * installation_gain;
                 }
 
                 // Create the `SoundChannel` ready for mixing.
@@ -810,7 +904,7 @@ pub fn render(mut model: Model, mut buffer: Buffer) -> (Model, Buffer) {
                         output_channel,
                     } = *speaker_info;
 
-                    let speaker_gain = lerp(previous_gain, current_gain, lerp_amt);
+                    let speaker_gain = utils::lerp(previous_gain, current_gain, lerp_amt);
                     frame[output_channel] += channel_sample * speaker_gain;
                 }
             }
@@ -1,4 +1,4 @@
-use audio::{input, output, source, Source, SAMPLE_RATE};
+use audio::{effect, input, output, source, Source, SAMPLE_RATE};
 use crossbeam::sync::SegQueue;
 use fxhash::FxHashSet;
 use installation;
@@ -12,7 +12,7 @@ use time_calc::{Ms, Samples};
 /// `Sound`s can be thought of as a stack of three primary components:
 ///
 /// 1. **Source**: for generating audio data (via oscillator, wave, audio input, etc).
-/// 2. **Pre-spatial effects processing**: E.g. fades.
+/// 2. **Pre-spatial effects processing**: E.g. fades, filters, delays.
 /// 3. **Spatial Output**: maps the sound from a position in space to the output channels.
 pub struct Sound {
     // State shared with the handles.
@@ -34,6 +34,8 @@ pub struct Sound {
     // TODO: This could potentially just be an actual type? `sound::Signal` that matched on
     // the source kind, stored its own stack of effects, etc?
     pub signal: source::Signal,
+    // The chain of effects applied to the signal's samples before they are spatialised.
+    pub effects: effect::Chain,
     // The location and orientation of the sound within the space.
     pub position: Position,
     // A constant radians offset for the channels, provided by the sound's `Source`.
@@ -102,6 +104,18 @@ impl Shared {
 }
 
 impl Handle {
+    /// A handle to a sound that is never sent to the audio output thread, e.g. for simulating the
+    /// soundscape.
+    pub fn simulated(id: Id, source_id: source::Id) -> Self {
+        let shared = Arc::new(Shared {
+            is_playing: AtomicBool::new(true),
+            source_id,
+            id,
+            source: SourceHandle::Wav,
+        });
+        Handle { shared }
+    }
+
     /// Whether or not the soundscape is currently playing.
     pub fn is_playing(&self) -> bool {
         self.shared.is_playing()
@@ -139,7 +153,13 @@ impl Handle {
 
 /// Creates a sound from the given `Source` and send it to the output stream.
 ///
-/// If the sound is a realtime source, send the source end to the input stream.
+/// If the sound is a realtime or network source, send the source end to the input stream.
+///
+/// The `playback_rate` is only applied to WAV sources. Realtime sources always play back at their
+/// natural rate.
+///
+/// The `effects` chain should have been created from the source's `effects` with the source's
+/// number of channels.
 pub fn spawn_from_source(
     id: Id,
     source_id: source::Id,
@@ -147,6 +167,8 @@ pub fn spawn_from_source(
     position: Position,
     attack_duration_frames: Samples,
     release_duration_frames: Samples,
+    playback_rate: f64,
+    effects: effect::Chain,
     continuous_preview: bool,
     max_duration_frames: Option<Samples>,
     frame_count: u64,
@@ -157,6 +179,15 @@ pub fn spawn_from_source(
 ) -> Handle
 {
     let installations = source.role.clone().into();
+
+    // Soundscape sources may specify the shape of their fades.
+    let (attack_curve, release_curve) = match source.role {
+        Some(source::Role::Soundscape(ref soundscape)) => {
+            (soundscape.attack_curve, soundscape.release_curve)
+        },
+        _ => (source::default::CURVE, source::default::CURVE),
+    };
+
     match source.kind {
         source::Kind::Wav(ref wav) => {
             spawn_from_wav(
@@ -171,6 +202,10 @@ pub fn spawn_from_source(
                 installations,
                 attack_duration_frames,
                 release_duration_frames,
+                attack_curve,
+                release_curve,
+                playback_rate,
+                effects,
                 continuous_preview,
                 max_duration_frames,
                 frame_count,
@@ -183,7 +218,33 @@ pub fn spawn_from_source(
             spawn_from_realtime(
                 id,
                 source_id,
-                realtime,
+                realtime.duration,
+                realtime.channels.len(),
+                source.spread,
+                source.volume,
+                source.muted,
+                position,
+                source.channel_radians,
+                installations,
+                attack_duration_frames,
+                release_duration_frames,
+                attack_curve,
+                release_curve,
+                effects,
+                continuous_preview,
+                max_duration_frames,
+                input_stream,
+                output_stream,
+                latency,
+            )
+        },
+
+        source::Kind::Network(ref network) => {
+            spawn_from_realtime(
+                id,
+                source_id,
+                network.duration,
+                network.channels,
                 source.spread,
                 source.volume,
                 source.muted,
@@ -192,6 +253,9 @@ pub fn spawn_from_source(
                 installations,
                 attack_duration_frames,
                 release_duration_frames,
+                attack_curve,
+                release_curve,
+                effects,
                 continuous_preview,
                 max_duration_frames,
                 input_stream,
@@ -215,6 +279,10 @@ pub fn spawn_from_wav(
     installations: Installations,
     attack_duration_frames: Samples,
     release_duration_frames: Samples,
+    attack_curve: source::Curve,
+    release_curve: source::Curve,
+    playback_rate: f64,
+    effects: effect::Chain,
     continuous_preview: bool,
     max_duration_frames: Option<Samples>,
     frame_count: u64,
@@ -223,16 +291,20 @@ pub fn spawn_from_wav(
 ) -> Handle
 {
     // The wave samples iterator.
-    let samples = wav_reader.play(id, &wav.path, frame_count, wav.should_loop || continuous_preview)
+    let samples = wav_reader.play(id, wav, frame_count, wav.should_loop || continuous_preview)
         .unwrap_or_else(|err| {
             panic!("failed to send new wav \"{}\"to wav_reader thread: {:?}: {}",
                    wav.path.display(), err, err);
         });
 
+    // Interpolate the samples if the sound is to be played back at a different rate.
+    let samples = source::wav::rate::RateStream::new(samples, playback_rate);
+
     // The source signal.
     let playback = wav.playback.clone();
     let kind = source::SignalKind::Wav { samples, playback };
-    let mut signal = source::Signal::new(kind, attack_duration_frames, release_duration_frames);
+    let mut signal = source::Signal::new(kind, attack_duration_frames, release_duration_frames)
+        .with_curves(attack_curve, release_curve);
     if let Some(duration) = max_duration_frames {
         signal = signal.with_duration_frames(duration);
     }
@@ -255,6 +327,7 @@ pub fn spawn_from_wav(
         volume,
         muted,
         signal,
+        effects,
         position: initial_position,
         channel_radians,
         spread,
@@ -279,13 +352,18 @@ pub fn spawn_from_wav(
     handle
 }
 
-/// Creates a sound from the given `source::Realtime` and send it to the output audio stream.
+/// Creates a sound from a `source::Realtime` or `source::Network` and send it to the output audio
+/// stream.
 ///
 /// Also spawns the `input::ActiveSound` on the input audio stream.
+///
+/// `input_duration` is the duration for which the source should be captured and `n_channels` is
+/// the number of channels captured by the source.
 pub fn spawn_from_realtime(
     id: Id,
     source_id: source::Id,
-    realtime: &source::Realtime,
+    input_duration: Ms,
+    n_channels: usize,
     spread: Metres,
     volume: f32,
     muted: bool,
@@ -294,6 +372,9 @@ pub fn spawn_from_realtime(
     installations: Installations,
     attack_duration_frames: Samples,
     release_duration_frames: Samples,
+    attack_curve: source::Curve,
+    release_curve: source::Curve,
+    effects: effect::Chain,
     continuous_preview: bool,
     max_duration_frames: Option<Samples>,
     audio_input: &input::Stream,
@@ -304,12 +385,11 @@ pub fn spawn_from_realtime(
     let duration = if continuous_preview {
         input::Duration::Infinite
     } else {
-        let frames = realtime.duration.samples(SAMPLE_RATE as _);
+        let frames = input_duration.samples(SAMPLE_RATE as _);
         input::Duration::Frames(frames as _)
     };
 
     // Add some latency in case input and output streams aren't synced.
-    let n_channels = realtime.channels.len();
     let delay_frames = latency.samples(SAMPLE_RATE as _);
     let delay_samples = delay_frames as usize * n_channels;
 
@@ -350,7 +430,8 @@ pub fn spawn_from_realtime(
     };
 
     let kind = source::SignalKind::Realtime { samples };
-    let mut signal = source::Signal::new(kind, attack_duration_frames, release_duration_frames);
+    let mut signal = source::Signal::new(kind, attack_duration_frames, release_duration_frames)
+        .with_curves(attack_curve, release_curve);
     if let Some(duration) = max_duration_frames {
         signal = signal.with_duration_frames(duration);
     }
@@ -388,6 +469,7 @@ pub fn spawn_from_realtime(
         volume,
         muted,
         signal,
+        effects,
         position: initial_position,
         channel_radians,
         spread,
@@ -498,6 +580,11 @@ pub struct Id(u64);
 
 impl Id {
     pub const INITIAL: Self = Id(0);
+
+    /// The integer representation of the `Id`.
+    pub fn to_u64(&self) -> u64 {
+        self.0
+    }
 }
 
 /// A threadsafe unique `Id` generator for sharing between the `Composer` and `GUI` threads.
@@ -0,0 +1,120 @@
+//! Items related to the shape of the attack and release envelopes applied to a source's signal.
+
+use std::f32::consts::PI;
+
+/// The steepness of the `Exponential` curve.
+const EXPONENTIAL_STEEPNESS: f32 = 5.0;
+
+/// The shape of the ramp used for a fade-in or fade-out.
+///
+/// All curves map a normalised position in the ramp (`0.0` silent, `1.0` full volume) to a gain.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Curve {
+    /// A straight line between silence and full volume.
+    Linear,
+    /// Maintains constant perceived power throughout the ramp. Useful for crossfades.
+    EqualPower,
+    /// Slow to start and quick to finish. Sounds even in loudness for long fades.
+    Exponential,
+    /// Eases in and out of the ramp.
+    SCurve,
+    /// A cubic bezier with user-specified control points.
+    Bezier(Bezier),
+}
+
+/// The control points of a cubic bezier curve ramping from `0.0` to `1.0`.
+///
+/// Only the gain (y) component of each control point is specified. They are spaced evenly along
+/// the ramp.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Bezier {
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Curve {
+    pub const VARIANT_COUNT: usize = 5;
+
+    /// Produce the gain for the given normalised position within the ramp.
+    pub fn gain(&self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+        match *self {
+            Curve::Linear => t,
+            Curve::EqualPower => (t * PI * 0.5).sin(),
+            Curve::Exponential => {
+                let k = EXPONENTIAL_STEEPNESS;
+                ((k * t).exp() - 1.0) / (k.exp() - 1.0)
+            },
+            Curve::SCurve => (1.0 - (t * PI).cos()) * 0.5,
+            Curve::Bezier(ref bezier) => bezier.gain(t),
+        }
+    }
+
+    /// Produce the index of the Curve value variant.
+    pub fn to_index(&self) -> usize {
+        match *self {
+            Curve::Linear => 0,
+            Curve::EqualPower => 1,
+            Curve::Exponential => 2,
+            Curve::SCurve => 3,
+            Curve::Bezier(_) => 4,
+        }
+    }
+
+    /// Produce a label for the variant at the given index.
+    pub fn label_from_index(i: usize) -> &'static str {
+        match i {
+            0 => "Linear",
+            1 => "Equal Power",
+            2 => "Exponential",
+            3 => "S-Curve",
+            4 => "Bezier",
+            _ => "",
+        }
+    }
+
+    /// Produce a default Curve variant for the given variant index.
+    pub fn from_index(i: usize) -> Option<Self> {
+        match i {
+            0 => Some(Curve::Linear),
+            1 => Some(Curve::EqualPower),
+            2 => Some(Curve::Exponential),
+            3 => Some(Curve::SCurve),
+            4 => Some(Curve::Bezier(super::default::BEZIER)),
+            _ => None,
+        }
+    }
+}
+
+impl Bezier {
+    /// The gain at the given normalised position within the ramp.
+    pub fn gain(&self, t: f32) -> f32 {
+        let u = 1.0 - t;
+        3.0 * u * u * t * self.a + 3.0 * u * t * t * self.b + t * t * t
+    }
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        super::default::CURVE
+    }
+}
+
+#[test]
+fn test_curves() {
+    let curves = (0..Curve::VARIANT_COUNT).map(|i| Curve::from_index(i).unwrap());
+    for curve in curves {
+        // Each curve begins silent and ends at full volume.
+        assert!(curve.gain(0.0).abs() < 1e-6, "{:?}", curve);
+        assert!((curve.gain(1.0) - 1.0).abs() < 1e-6, "{:?}", curve);
+
+        // Positions beyond the ramp are clamped.
+        assert_eq!(curve.gain(-1.0), curve.gain(0.0));
+        assert_eq!(curve.gain(2.0), curve.gain(1.0));
+
+        // The gain never decreases throughout the ramp.
+        let steps = 1_000;
+        let gains: Vec<f32> = (0..steps + 1).map(|i| curve.gain(i as f32 / steps as f32)).collect();
+        assert!(gains.windows(2).all(|w| w[1] >= w[0]), "{:?}", curve);
+    }
+}
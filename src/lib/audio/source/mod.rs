@@ -1,18 +1,25 @@
+use audio::effect;
 use fxhash::FxHashSet;
 use installation;
 use metres::Metres;
 use nannou::math::map_range;
 use nannou::rand::Rng;
+use pitch_calc::Step;
 use soundscape;
 use std::ops;
 use time_calc::{Ms, Samples};
 use utils::{self, Range};
 
+pub use self::envelope::Curve;
 pub use self::movement::Movement;
+pub use self::network::Network;
 pub use self::realtime::Realtime;
 pub use self::wav::Wav;
 
+pub mod envelope;
+pub mod network;
 pub mod realtime;
+pub mod tag;
 pub mod wav;
 
 pub const MAX_PLAYBACK_DURATION: Ms = Ms(utils::DAY_MS);
@@ -21,6 +28,12 @@ pub const MAX_ATTACK_DURATION: Ms = Ms(utils::MIN_MS);
 
 pub const MAX_RELEASE_DURATION: Ms = Ms(utils::MIN_MS);
 
+/// The maximum playback rate offset in semitones in either direction.
+pub const MAX_PLAYBACK_RATE_SEMITONES: f32 = 12.0;
+
+/// The maximum weight with which a source may be chosen by the soundscape.
+pub const MAX_WEIGHT: f64 = 10.0;
+
 /// Items related to audio sources.
 ///
 /// Audio sources come in two kinds:
@@ -53,6 +66,14 @@ pub struct Source {
     /// Whether or not the source has been muted.
     #[serde(default)]
     pub muted: bool,
+    /// The chain of effects applied to each of the source's sounds prior to spatialisation.
+    #[serde(default)]
+    pub effects: Vec<effect::Effect>,
+    /// Free-form tags used to filter sources and to select them via soundscape group queries.
+    ///
+    /// Tags are stored in their normalised form (see `tag::normalise`).
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// A **Signal** yielding interleaved samples.
@@ -73,7 +94,7 @@ pub struct Signal {
 /// Indicates whether the signal is sourced from a `Wav` or `Realtime` source.
 pub enum SignalKind {
     Wav {
-        samples: wav::reader::SamplesStream,
+        samples: wav::rate::RateStream,
         playback: wav::Playback,
     },
     Realtime {
@@ -93,6 +114,9 @@ pub struct Duration {
 pub struct Attack {
     duration_frames: Samples,
     current_frame: Samples,
+    curve: Curve,
+    // Whether the attack is held at its current gain, e.g. due to an early release.
+    held: bool,
 }
 
 /// An iterator producing the volume modifier for a release envelope.
@@ -100,6 +124,7 @@ pub struct Attack {
 pub struct Release {
     duration_frames: Samples,
     frame_countdown: Samples,
+    curve: Curve,
 }
 
 /// The samples produced by a source signal with attack and release applied.
@@ -142,20 +167,38 @@ pub struct Soundscape {
     pub occurrence_rate: Range<Ms>,
     #[serde(default = "default::simultaneous_sounds")]
     pub simultaneous_sounds: Range<usize>,
+    /// The likelihood of choosing this source relative to other equally suitable sources.
+    ///
+    /// E.g. a source with a weight of `2.0` is chosen twice as often as one with `1.0`.
+    #[serde(default = "default::weight")]
+    pub weight: f64,
     #[serde(default = "default::playback_duration")]
     pub playback_duration: Range<Ms>,
     #[serde(default = "default::attack_duration")]
     pub attack_duration: Range<Ms>,
     #[serde(default = "default::release_duration")]
     pub release_duration: Range<Ms>,
+    /// The range of playback rates in semitones relative to the original rate.
+    ///
+    /// A rate is chosen at random within this range for each sound spawned by the soundscape.
+    #[serde(default = "default::playback_rate")]
+    pub playback_rate: Range<f32>,
+    /// The shape of the fade-in applied to each sound.
+    #[serde(default = "default::curve")]
+    pub attack_curve: Curve,
+    /// The shape of the fade-out applied to each sound, including when stopped early.
+    #[serde(default = "default::curve")]
+    pub release_curve: Curve,
     #[serde(default = "default::movement")]
     pub movement: Movement,
 }
 
 /// Items related to the movement of a source's associated sounds within a soundscape.
 pub mod movement {
+    use metres::Metres;
     use nannou::math::{Point2, Vector2};
     use nannou::prelude::PI_F64;
+    use time_calc::Ms;
     use utils::Range;
 
     /// The absolute maximum speed of an agent.
@@ -167,6 +210,12 @@ pub mod movement {
     /// The absolute maximum force that may be applied to an agent's movement.
     pub const MAX_ROTATION: f64 = 100.0 * PI_F64;
 
+    /// The maximum distance in metres at which an agent may perceive its flock neighbours.
+    pub const MAX_FLOCK_RADIUS: f64 = 20.0;
+
+    /// The maximum weight of each of the flocking forces applied to an agent.
+    pub const MAX_FLOCK_WEIGHT: f64 = 4.0;
+
     /// The maximum number of vertices in an Ngon.
     pub const MAX_VERTICES: usize = 50;
 
@@ -188,6 +237,33 @@ pub mod movement {
     /// The skew applied to the perception of Ngon "nth" vertices.
     pub const NTH_SKEW: f32 = 0.75;
 
+    /// The maximum duration of a single traversal of a path.
+    pub const MAX_PATH_DURATION: Ms = Ms(10.0 * 60.0 * 1_000.0);
+
+    /// The skew applied to the perception of the path traversal duration.
+    pub const PATH_DURATION_SKEW: f32 = 0.5;
+
+    /// The maximum frequency of either axis of a Lissajous figure relative to the base rate.
+    pub const MAX_LISSAJOUS_FREQUENCY: usize = 12;
+
+    /// The maximum rate at which a Lissajous figure is traced in cycles per second.
+    pub const MAX_LISSAJOUS_RATE: f64 = 1.0;
+
+    /// The skew applied to the perception of the Lissajous rate.
+    pub const LISSAJOUS_RATE_SKEW: f32 = 0.5;
+
+    /// The absolute maximum turbulence of a wandering sound in radians per second.
+    pub const MAX_TURBULENCE: f64 = 4.0 * PI_F64;
+
+    /// The skew applied to the perception of the wander turbulence.
+    pub const TURBULENCE_SKEW: f32 = 0.5;
+
+    /// The maximum time taken for a tracked sound to catch up with its tracked point.
+    pub const MAX_TRACKED_SMOOTHING: Ms = Ms(5.0 * 1_000.0);
+
+    /// The maximum duration for which a tracked sound may lose tracking before it is released.
+    pub const MAX_TRACKED_TIMEOUT: Ms = Ms(60.0 * 1_000.0);
+
     /// Whether a source may assigned to normalised fixed position or generative movement.
     #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
     pub enum Movement {
@@ -201,6 +277,11 @@ pub mod movement {
     pub enum Generative {
         Agent(Agent),
         Ngon(Ngon),
+        Path(Path),
+        Orbit(Orbit),
+        Lissajous(Lissajous),
+        Wander(Wander),
+        Tracked(Tracked),
     }
 
     /// A generative movement kind modelling an automonomous agent.
@@ -219,6 +300,43 @@ pub mod movement {
         /// Whether or not the agent's orientation should be applied to channel rotation.
         #[serde(default = "super::default::directional")]
         pub directional: bool,
+        /// Boids-style forces applied between neighbouring agents.
+        #[serde(default = "super::default::flocking")]
+        pub flocking: Flocking,
+    }
+
+    /// Boids-style separation, alignment and cohesion between neighbouring agents.
+    ///
+    /// All weights default to `0.0`, in which case each agent steers independently.
+    #[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Flocking {
+        /// Which other agents are considered neighbours.
+        #[serde(default = "super::default::flock_neighbours")]
+        pub neighbours: FlockNeighbours,
+        /// The distance in metres within which neighbours are perceived.
+        #[serde(default = "super::default::flock_radius")]
+        pub radius: f64,
+        /// The distance in metres within which the agent steers away from neighbours.
+        #[serde(default = "super::default::separation_distance")]
+        pub separation_distance: f64,
+        /// The weight of the force steering away from crowding neighbours.
+        #[serde(default)]
+        pub separation: f64,
+        /// The weight of the force steering toward the average heading of neighbours.
+        #[serde(default)]
+        pub alignment: f64,
+        /// The weight of the force steering toward the average location of neighbours.
+        #[serde(default)]
+        pub cohesion: f64,
+    }
+
+    /// Describes which other agents are considered neighbours within a flock.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+    pub enum FlockNeighbours {
+        /// Agents spawned from the same source.
+        Source,
+        /// Agents spawned from any source that shares a soundscape group.
+        Group,
     }
 
     /// A generative movement kind that traces the vertices of an n-sided polygon.
@@ -242,6 +360,148 @@ pub mod movement {
         pub speed: Range<f64>,
     }
 
+    /// A generative movement kind that follows a smooth path authored over the floorplan.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Path {
+        /// The control points through which the Catmull-Rom spline passes, in exhibition space.
+        #[serde(default)]
+        pub points: Vec<Point2<Metres>>,
+        /// The rate at which the path is travelled.
+        #[serde(default = "super::default::path_timing")]
+        pub timing: PathTiming,
+        /// What happens upon reaching the end of the path.
+        #[serde(default = "super::default::path_mode")]
+        pub mode: PathMode,
+        /// Whether or not each sound should begin from a random offset along the path.
+        ///
+        /// Otherwise, every sound begins from the first point.
+        #[serde(default)]
+        pub random_offset: bool,
+    }
+
+    /// A generative movement kind that circles a centre point within the installation.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Orbit {
+        /// The centre of the orbit normalised to the bounds of the installation area.
+        #[serde(default = "super::default::orbit_centre")]
+        pub centre: Point2<f64>,
+        /// The radius of the orbit using a normalised value.
+        ///
+        /// `1.0` means the orbit will reach the nearest bound of the installation area.
+        #[serde(default = "super::default::orbit_radius")]
+        pub radius: Range<f64>,
+        /// The rate at which the orbit is travelled in metres per second.
+        #[serde(default = "super::default::speed")]
+        pub speed: Range<f64>,
+        /// The direction in which the centre is circled.
+        #[serde(default = "super::default::orbit_direction")]
+        pub direction: OrbitDirection,
+        /// Whether or not the direction of travel should be applied to channel rotation.
+        #[serde(default = "super::default::directional")]
+        pub directional: bool,
+    }
+
+    /// The direction in which an orbit circles its centre.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+    pub enum OrbitDirection {
+        Clockwise,
+        Anticlockwise,
+        /// Either direction is chosen at random for each sound.
+        Either,
+    }
+
+    /// A generative movement kind that traces a Lissajous figure over the installation.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Lissajous {
+        /// The frequency of the horizontal oscillation relative to the rate.
+        #[serde(default = "super::default::lissajous_frequency_x")]
+        pub frequency_x: Range<usize>,
+        /// The frequency of the vertical oscillation relative to the rate.
+        #[serde(default = "super::default::lissajous_frequency_y")]
+        pub frequency_y: Range<usize>,
+        /// The phase offset in radians of the horizontal oscillation.
+        #[serde(default = "super::default::lissajous_phase")]
+        pub phase: Range<f64>,
+        /// The rate in cycles per second at which the figure is traced.
+        #[serde(default = "super::default::lissajous_rate")]
+        pub rate: Range<f64>,
+        /// Describes the dimensions of the figure using a normalised value.
+        ///
+        /// `1.0` means the figure will extend to the bounds of the installation area.
+        #[serde(default = "super::default::normalised_dimensions")]
+        pub normalised_dimensions: Vector2<f64>,
+        /// Whether or not the direction of travel should be applied to channel rotation.
+        #[serde(default = "super::default::directional")]
+        pub directional: bool,
+    }
+
+    /// A generative movement kind that wanders organically through the installation area.
+    ///
+    /// The heading of the sound is steered by coherent noise that is seeded via the project seed.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Wander {
+        /// The speed of the sound in metres per second.
+        #[serde(default = "super::default::wander_speed")]
+        pub speed: Range<f64>,
+        /// The maximum rate in radians per second at which the noise may turn the heading.
+        #[serde(default = "super::default::turbulence")]
+        pub turbulence: Range<f64>,
+        /// The strength with which the sound is steered away from the bounds of the area.
+        ///
+        /// `0.0` means the sound will slide along the bounds, while `1.0` turns sharply away.
+        #[serde(default = "super::default::edge_repulsion")]
+        pub edge_repulsion: f64,
+        /// Whether or not the sound's heading should be applied to channel rotation.
+        #[serde(default = "super::default::directional")]
+        pub directional: bool,
+    }
+
+    /// A generative movement kind that follows a point tracked by an external system over OSC.
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub struct Tracked {
+        /// The tracked point that the sound should follow.
+        #[serde(default = "super::default::tracked_target")]
+        pub target: TrackedTarget,
+        /// Roughly the time taken for the sound to catch up with its tracked point.
+        #[serde(default = "super::default::tracked_smoothing")]
+        pub smoothing: Ms,
+        /// How long the sound may go without a tracked point before it is released.
+        #[serde(default = "super::default::tracked_timeout")]
+        pub timeout: Ms,
+        /// Whether or not the sound's direction of travel should be applied to channel rotation.
+        #[serde(default = "super::default::directional")]
+        pub directional: bool,
+    }
+
+    /// Describes which tracked point a sound should follow.
+    #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+    pub enum TrackedTarget {
+        /// The tracked point nearest to the sound.
+        ///
+        /// If the point is lost, the sound moves on to the next nearest point.
+        Nearest,
+        /// The tracked point with the given name.
+        Named(String),
+    }
+
+    /// Describes the rate at which a path is travelled.
+    #[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+    pub enum PathTiming {
+        /// The speed of travel in metres per second.
+        Speed(Range<f64>),
+        /// The duration of a single traversal from the first point to the last.
+        Duration(Range<Ms>),
+    }
+
+    /// What happens upon reaching the end of a path.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+    pub enum PathMode {
+        /// The path is closed from the last point back to the first and travelled endlessly.
+        Loop,
+        /// The path is travelled back and forth between the first and last points.
+        PingPong,
+    }
+
     impl Movement {
         pub const VARIANT_COUNT: usize = 2;
 
@@ -273,13 +533,18 @@ pub mod movement {
     }
 
     impl Generative {
-        pub const VARIANT_COUNT: usize = 2;
+        pub const VARIANT_COUNT: usize = 7;
 
         /// Produce the index of the Movement value variant.
         pub fn to_index(&self) -> usize {
             match *self {
                 Generative::Agent(_) => 0,
                 Generative::Ngon(_) => 1,
+                Generative::Path(_) => 2,
+                Generative::Orbit(_) => 3,
+                Generative::Lissajous(_) => 4,
+                Generative::Wander(_) => 5,
+                Generative::Tracked(_) => 6,
             }
         }
 
@@ -288,6 +553,11 @@ pub mod movement {
             match i {
                 0 => "AGENT",
                 1 => "NGON",
+                2 => "PATH",
+                3 => "ORBIT",
+                4 => "LISSAJOUS",
+                5 => "WANDER",
+                6 => "TRACKED",
                 _ => "",
             }
         }
@@ -297,6 +567,171 @@ pub mod movement {
             match i {
                 0 => Some(Generative::Agent(super::default::AGENT)),
                 1 => Some(Generative::Ngon(super::default::NGON)),
+                2 => Some(Generative::Path(super::default::path())),
+                3 => Some(Generative::Orbit(super::default::ORBIT)),
+                4 => Some(Generative::Lissajous(super::default::LISSAJOUS)),
+                5 => Some(Generative::Wander(super::default::WANDER)),
+                6 => Some(Generative::Tracked(super::default::TRACKED)),
+                _ => None,
+            }
+        }
+    }
+
+    impl OrbitDirection {
+        pub const VARIANT_COUNT: usize = 3;
+
+        /// Produce the index of the OrbitDirection value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                OrbitDirection::Clockwise => 0,
+                OrbitDirection::Anticlockwise => 1,
+                OrbitDirection::Either => 2,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "CW",
+                1 => "ACW",
+                2 => "EITHER",
+                _ => "",
+            }
+        }
+
+        /// Produce the OrbitDirection variant for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(OrbitDirection::Clockwise),
+                1 => Some(OrbitDirection::Anticlockwise),
+                2 => Some(OrbitDirection::Either),
+                _ => None,
+            }
+        }
+    }
+
+    impl PathTiming {
+        pub const VARIANT_COUNT: usize = 2;
+
+        /// Produce the index of the PathTiming value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                PathTiming::Speed(_) => 0,
+                PathTiming::Duration(_) => 1,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "SPEED",
+                1 => "DURATION",
+                _ => "",
+            }
+        }
+
+        /// Produce a default PathTiming variant for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(PathTiming::Speed(super::default::SPEED)),
+                1 => Some(PathTiming::Duration(super::default::PATH_DURATION)),
+                _ => None,
+            }
+        }
+    }
+
+    impl Flocking {
+        /// Whether or not any of the flocking forces are applied.
+        pub fn is_enabled(&self) -> bool {
+            self.separation > 0.0 || self.alignment > 0.0 || self.cohesion > 0.0
+        }
+    }
+
+    impl FlockNeighbours {
+        pub const VARIANT_COUNT: usize = 2;
+
+        /// Produce the index of the FlockNeighbours value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                FlockNeighbours::Source => 0,
+                FlockNeighbours::Group => 1,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "SOURCE",
+                1 => "GROUP",
+                _ => "",
+            }
+        }
+
+        /// Produce the FlockNeighbours variant for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(FlockNeighbours::Source),
+                1 => Some(FlockNeighbours::Group),
+                _ => None,
+            }
+        }
+    }
+
+    impl TrackedTarget {
+        pub const VARIANT_COUNT: usize = 2;
+
+        /// Produce the index of the TrackedTarget value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                TrackedTarget::Nearest => 0,
+                TrackedTarget::Named(_) => 1,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "NEAREST",
+                1 => "NAMED",
+                _ => "",
+            }
+        }
+
+        /// Produce a default TrackedTarget variant for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(TrackedTarget::Nearest),
+                1 => Some(TrackedTarget::Named(String::new())),
+                _ => None,
+            }
+        }
+    }
+
+    impl PathMode {
+        pub const VARIANT_COUNT: usize = 2;
+
+        /// Produce the index of the PathMode value variant.
+        pub fn to_index(&self) -> usize {
+            match *self {
+                PathMode::Loop => 0,
+                PathMode::PingPong => 1,
+            }
+        }
+
+        /// Produce a label for the variant at the given index.
+        pub fn label_from_index(i: usize) -> &'static str {
+            match i {
+                0 => "LOOP",
+                1 => "PING PONG",
+                _ => "",
+            }
+        }
+
+        /// Produce the PathMode variant for the given variant index.
+        pub fn from_index(i: usize) -> Option<Self> {
+            match i {
+                0 => Some(PathMode::Loop),
+                1 => Some(PathMode::PingPong),
                 _ => None,
             }
         }
@@ -307,6 +742,7 @@ pub mod movement {
 pub enum Kind {
     Wav(Wav),
     Realtime(Realtime),
+    Network(Network),
 }
 
 impl Kind {
@@ -315,7 +751,7 @@ impl Kind {
     /// This is dependent upon whether or not the source is potentially infinite.
     pub fn playback_duration_skew(&self) -> f32 {
         match *self {
-            Kind::Realtime(_) => skew::PLAYBACK_DURATION_MAX,
+            Kind::Realtime(_) | Kind::Network(_) => skew::PLAYBACK_DURATION_MAX,
             Kind::Wav(ref wav) => match wav.should_loop {
                 true => skew::PLAYBACK_DURATION_MAX,
                 false => playback_duration_skew(wav.duration.to_ms(super::SAMPLE_RATE)),
@@ -344,6 +780,29 @@ pub fn playback_duration_skew(duration: Ms) -> f32 {
     )
 }
 
+/// Convert the given offset in semitones to a playback rate multiplier.
+///
+/// E.g. `12.0` produces a rate of `2.0` while `-12.0` produces a rate of `0.5`.
+pub fn playback_rate_from_semitones(semitones: f32) -> f64 {
+    const BASE_STEP: f32 = 60.0;
+    let base_hz = Step(BASE_STEP).hz() as f64;
+    let hz = Step(BASE_STEP + semitones).hz() as f64;
+    hz / base_hz
+}
+
+/// Generate a random playback rate multiplier within the given range of semitones.
+pub fn random_playback_rate<R>(mut rng: R, range: Range<f32>) -> f64
+where
+    R: Rng,
+{
+    let semitones = if range.min == range.max {
+        range.min
+    } else {
+        map_range(rng.gen::<f32>(), 0.0, 1.0, range.min, range.max)
+    };
+    playback_rate_from_semitones(semitones)
+}
+
 /// Generate a random playback duration within the given range.
 pub fn random_playback_duration<R>(mut rng: R, range: Range<Ms>) -> Ms
 where
@@ -360,15 +819,23 @@ impl Source {
         match self.kind {
             Kind::Wav(ref wav) => wav.channels,
             Kind::Realtime(ref rt) => rt.channels.len(),
+            Kind::Network(ref net) => net.channels,
         }
     }
+
+    /// Whether or not the source has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
 }
 
 impl Attack {
     /// Construct an `Attack` from its duration in frames.
     pub fn from_duration_frames(duration_frames: Samples) -> Self {
         let current_frame = Samples(0);
-        Attack { duration_frames, current_frame }
+        let curve = default::CURVE;
+        let held = false;
+        Attack { duration_frames, current_frame, curve, held }
     }
 }
 
@@ -376,7 +843,8 @@ impl Release {
     /// Construct a `Release` from its duration in frames.
     pub fn from_duration_frames(duration_frames: Samples) -> Self {
         let frame_countdown = duration_frames;
-        Release { duration_frames, frame_countdown }
+        let curve = default::CURVE;
+        Release { duration_frames, frame_countdown, curve }
     }
 }
 
@@ -429,6 +897,33 @@ impl Signal {
         self
     }
 
+    /// Specify the shape of the attack and release envelopes.
+    pub fn with_curves(mut self, attack: Curve, release: Curve) -> Self {
+        self.attack.curve = attack;
+        self.release.curve = release;
+        self
+    }
+
+    /// Begin the release now, ending the signal once the release completes.
+    ///
+    /// This is used to stop a sound early without a click. Does nothing if the signal was already
+    /// going to end before the release could complete. If the attack is still in progress, it is
+    /// held at its current gain so that the release ramps down from there.
+    pub fn release(&mut self) {
+        let release_frames = self.release.duration_frames;
+        if let Some(remaining) = self.remaining_frames() {
+            if remaining <= release_frames {
+                return;
+            }
+        }
+        let elapsed = self.duration.as_ref().map(|d| d.current_frame).unwrap_or(Samples(0));
+        self.duration = Some(Duration {
+            duration_frames: elapsed + release_frames,
+            current_frame: elapsed,
+        });
+        self.attack.held = true;
+    }
+
     /// The minimum number of frames between `self.remaining_frames` and
     /// `self.kind.remaining_frames()` if any.
     ///
@@ -512,8 +1007,10 @@ impl Attack {
         if self.current_frame < self.duration_frames {
             let current = self.current_frame.samples() as f32;
             let duration = self.duration_frames.samples() as f32;
-            self.current_frame += Samples(1);
-            current / duration
+            if !self.held {
+                self.current_frame += Samples(1);
+            }
+            self.curve.gain(current / duration)
         } else {
             1.0
         }
@@ -526,7 +1023,7 @@ impl Release {
             let current = self.frame_countdown.samples() as f32;
             let duration = self.duration_frames.samples() as f32;
             self.frame_countdown -= Samples(1);
-            current / duration
+            self.curve.gain(current / duration)
         } else {
             1.0
         }
@@ -608,7 +1105,7 @@ pub mod skew {
 pub mod default {
     use metres::Metres;
     use nannou::math::{Point2, Vector2};
-    use super::{movement, Movement};
+    use super::{envelope, movement, Curve, Movement};
     use time_calc::Ms;
     use utils::{HR_MS, Range};
 
@@ -618,6 +1115,7 @@ pub mod default {
     pub const VOLUME: f32 = 0.6;
     pub const OCCURRENCE_RATE: Range<Ms> = Range { min: Ms(500.0), max: Ms(HR_MS as _) };
     pub const SIMULTANEOUS_SOUNDS: Range<usize> = Range { min: 0, max: 1 };
+    pub const WEIGHT: f64 = 1.0;
     // Assume that the user wants to play back the sound endlessly at first.
     pub const PLAYBACK_DURATION: Range<Ms> = Range {
         min: super::MAX_PLAYBACK_DURATION,
@@ -625,6 +1123,10 @@ pub mod default {
     };
     pub const ATTACK_DURATION: Range<Ms> = Range { min: Ms(0.0), max: Ms(0.0) };
     pub const RELEASE_DURATION: Range<Ms> = Range { min: Ms(0.0), max: Ms(0.0) };
+    pub const PLAYBACK_RATE: Range<f32> = Range { min: 0.0, max: 0.0 };
+    pub const CURVE: Curve = Curve::Linear;
+    pub const BEZIER: envelope::Bezier = envelope::Bezier { a: 0.1, b: 0.9 };
+    pub const JITTER_BUFFER: Ms = Ms(40.0);
     pub const FIXED: Point2<f64> = Point2 { x: 0.5, y: 0.5 };
     pub const MAX_SPEED: Range<f64> = Range { min: 1.0, max: 5.0 };
     pub const MAX_FORCE: Range<f64> = Range { min: 0.04, max: 0.06 };
@@ -633,11 +1135,23 @@ pub mod default {
         max: super::movement::MAX_ROTATION,
     };
     pub const DIRECTIONAL: bool = true;
+    pub const FLOCK_NEIGHBOURS: movement::FlockNeighbours = movement::FlockNeighbours::Source;
+    pub const FLOCK_RADIUS: f64 = 5.0;
+    pub const SEPARATION_DISTANCE: f64 = 1.5;
+    pub const FLOCKING: movement::Flocking = movement::Flocking {
+        neighbours: FLOCK_NEIGHBOURS,
+        radius: FLOCK_RADIUS,
+        separation_distance: SEPARATION_DISTANCE,
+        separation: 0.0,
+        alignment: 0.0,
+        cohesion: 0.0,
+    };
     pub const AGENT: movement::Agent = movement::Agent {
         max_speed: MAX_SPEED,
         max_force: MAX_FORCE,
         max_rotation: MAX_ROTATION,
         directional: DIRECTIONAL,
+        flocking: FLOCKING,
     };
     pub const VERTICES: Range<usize> = Range { min: 3, max: 8 };
     pub const NTH: Range<usize> = Range { min: 1, max: 3 };
@@ -659,6 +1173,52 @@ pub mod default {
         radians_offset: RADIANS_OFFSET,
         speed: SPEED,
     };
+    pub const PATH_DURATION: Range<Ms> = Range { min: Ms(20_000.0), max: Ms(20_000.0) };
+    pub const PATH_TIMING: movement::PathTiming = movement::PathTiming::Speed(SPEED);
+    pub const PATH_MODE: movement::PathMode = movement::PathMode::Loop;
+    pub const ORBIT_CENTRE: Point2<f64> = Point2 { x: 0.5, y: 0.5 };
+    pub const ORBIT_RADIUS: Range<f64> = Range { min: 0.5, max: 1.0 };
+    pub const ORBIT_DIRECTION: movement::OrbitDirection = movement::OrbitDirection::Either;
+    pub const ORBIT: movement::Orbit = movement::Orbit {
+        centre: ORBIT_CENTRE,
+        radius: ORBIT_RADIUS,
+        speed: SPEED,
+        direction: ORBIT_DIRECTION,
+        directional: DIRECTIONAL,
+    };
+    pub const LISSAJOUS_FREQUENCY_X: Range<usize> = Range { min: 1, max: 3 };
+    pub const LISSAJOUS_FREQUENCY_Y: Range<usize> = Range { min: 2, max: 4 };
+    pub const LISSAJOUS_PHASE: Range<f64> = Range {
+        min: 0.0,
+        max: ::std::f64::consts::PI * 0.5,
+    };
+    pub const LISSAJOUS_RATE: Range<f64> = Range { min: 0.02, max: 0.05 };
+    pub const LISSAJOUS: movement::Lissajous = movement::Lissajous {
+        frequency_x: LISSAJOUS_FREQUENCY_X,
+        frequency_y: LISSAJOUS_FREQUENCY_Y,
+        phase: LISSAJOUS_PHASE,
+        rate: LISSAJOUS_RATE,
+        normalised_dimensions: NORMALISED_DIMENSIONS,
+        directional: DIRECTIONAL,
+    };
+    pub const TRACKED_TARGET: movement::TrackedTarget = movement::TrackedTarget::Nearest;
+    pub const TRACKED_SMOOTHING: Ms = Ms(500.0);
+    pub const TRACKED_TIMEOUT: Ms = Ms(3_000.0);
+    pub const TRACKED: movement::Tracked = movement::Tracked {
+        target: TRACKED_TARGET,
+        smoothing: TRACKED_SMOOTHING,
+        timeout: TRACKED_TIMEOUT,
+        directional: DIRECTIONAL,
+    };
+    pub const WANDER_SPEED: Range<f64> = Range { min: 0.5, max: 2.0 };
+    pub const TURBULENCE: Range<f64> = Range { min: 1.0, max: 3.0 };
+    pub const EDGE_REPULSION: f64 = 0.5;
+    pub const WANDER: movement::Wander = movement::Wander {
+        speed: WANDER_SPEED,
+        turbulence: TURBULENCE,
+        edge_repulsion: EDGE_REPULSION,
+        directional: DIRECTIONAL,
+    };
     pub const GENERATIVE: movement::Generative = movement::Generative::Agent(AGENT);
     pub const MOVEMENT: Movement = Movement::Fixed(FIXED);
 
@@ -682,6 +1242,10 @@ pub mod default {
         SIMULTANEOUS_SOUNDS
     }
 
+    pub fn weight() -> f64 {
+        WEIGHT
+    }
+
     pub fn playback_duration() -> Range<Ms> {
         PLAYBACK_DURATION
     }
@@ -694,6 +1258,18 @@ pub mod default {
         RELEASE_DURATION
     }
 
+    pub fn playback_rate() -> Range<f32> {
+        PLAYBACK_RATE
+    }
+
+    pub fn curve() -> Curve {
+        CURVE
+    }
+
+    pub fn jitter_buffer() -> Ms {
+        JITTER_BUFFER
+    }
+
     pub fn movement() -> Movement {
         MOVEMENT
     }
@@ -717,6 +1293,99 @@ pub mod default {
     pub fn directional() -> bool {
         DIRECTIONAL
     }
+
+    pub fn flocking() -> movement::Flocking {
+        FLOCKING
+    }
+
+    pub fn flock_neighbours() -> movement::FlockNeighbours {
+        FLOCK_NEIGHBOURS
+    }
+
+    pub fn flock_radius() -> f64 {
+        FLOCK_RADIUS
+    }
+
+    pub fn separation_distance() -> f64 {
+        SEPARATION_DISTANCE
+    }
+
+    pub fn path() -> movement::Path {
+        movement::Path {
+            points: vec![],
+            timing: PATH_TIMING,
+            mode: PATH_MODE,
+            random_offset: false,
+        }
+    }
+
+    pub fn path_timing() -> movement::PathTiming {
+        PATH_TIMING
+    }
+
+    pub fn path_mode() -> movement::PathMode {
+        PATH_MODE
+    }
+
+    pub fn speed() -> Range<f64> {
+        SPEED
+    }
+
+    pub fn normalised_dimensions() -> Vector2<f64> {
+        NORMALISED_DIMENSIONS
+    }
+
+    pub fn orbit_centre() -> Point2<f64> {
+        ORBIT_CENTRE
+    }
+
+    pub fn orbit_radius() -> Range<f64> {
+        ORBIT_RADIUS
+    }
+
+    pub fn orbit_direction() -> movement::OrbitDirection {
+        ORBIT_DIRECTION
+    }
+
+    pub fn lissajous_frequency_x() -> Range<usize> {
+        LISSAJOUS_FREQUENCY_X
+    }
+
+    pub fn lissajous_frequency_y() -> Range<usize> {
+        LISSAJOUS_FREQUENCY_Y
+    }
+
+    pub fn lissajous_phase() -> Range<f64> {
+        LISSAJOUS_PHASE
+    }
+
+    pub fn lissajous_rate() -> Range<f64> {
+        LISSAJOUS_RATE
+    }
+
+    pub fn wander_speed() -> Range<f64> {
+        WANDER_SPEED
+    }
+
+    pub fn turbulence() -> Range<f64> {
+        TURBULENCE
+    }
+
+    pub fn edge_repulsion() -> f64 {
+        EDGE_REPULSION
+    }
+
+    pub fn tracked_target() -> movement::TrackedTarget {
+        TRACKED_TARGET
+    }
+
+    pub fn tracked_smoothing() -> Ms {
+        TRACKED_SMOOTHING
+    }
+
+    pub fn tracked_timeout() -> Ms {
+        TRACKED_TIMEOUT
+    }
 }
 
 impl Default for Soundscape {
@@ -725,19 +1394,58 @@ impl Default for Soundscape {
         let groups = Default::default();
         let occurrence_rate = default::OCCURRENCE_RATE;
         let simultaneous_sounds = default::SIMULTANEOUS_SOUNDS;
+        let weight = default::WEIGHT;
         let playback_duration = default::PLAYBACK_DURATION;
         let attack_duration = default::ATTACK_DURATION;
         let release_duration = default::RELEASE_DURATION;
+        let playback_rate = default::PLAYBACK_RATE;
+        let attack_curve = default::CURVE;
+        let release_curve = default::CURVE;
         let movement = default::MOVEMENT;
         Soundscape {
             installations,
             groups,
             occurrence_rate,
             simultaneous_sounds,
+            weight,
             playback_duration,
             attack_duration,
             release_duration,
+            playback_rate,
+            attack_curve,
+            release_curve,
             movement,
         }
     }
 }
+
+#[test]
+fn test_release_during_attack() {
+    use hound;
+    use std::sync::Arc;
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44_100,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let samples: Arc<[f32]> = vec![1.0; 1_000].into();
+    let cached = wav::cache::Cached { samples, spec };
+    let stream = wav::reader::SamplesStream::from_cached(cached, 0, false);
+    let samples = wav::rate::RateStream::new(stream, 1.0);
+    let kind = SignalKind::Wav { samples, playback: wav::Playback::Retrigger };
+    let mut signal = Signal::new(kind, Samples(100), Samples(100));
+
+    // Release a fifth of the way through the attack.
+    let attack: Vec<f32> = signal.samples().take(20).collect();
+    signal.release();
+    let release: Vec<f32> = signal.samples().collect();
+    assert_eq!(release.len(), 100);
+
+    // The release begins from the gain reached by the attack rather than from full volume.
+    let step = 0.01;
+    assert!(release[0] >= attack[19] && release[0] <= attack[19] + step + 1e-6);
+    assert!(release.windows(2).all(|w| w[1] <= w[0]));
+    assert!(*release.last().unwrap() < step);
+}
@@ -0,0 +1,602 @@
+//! Items related to the network audio input sound source kind.
+//!
+//! A `Network` source receives interleaved multichannel audio over UDP, either as raw big-endian
+//! PCM or as an RTP stream with an L16 or L24 payload. Packets are received on a dedicated thread
+//! and forwarded to the audio input thread where they are re-ordered within a `JitterBuffer`.
+//! Samples are then read from the jitter buffer at the rate of the audio input device and fed to
+//! the `realtime::Signal` of each active sound in the same manner as a `Realtime` source.
+
+use audio::{FRAMES_PER_BUFFER, SAMPLE_RATE};
+use crossbeam::sync::SegQueue;
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{atomic, Arc};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::thread;
+use std::time;
+use time_calc::Ms;
+
+/// The largest UDP datagram that may be received.
+pub const MAX_PACKET_BYTES: usize = 65_507;
+
+/// The maximum number of packets that may be held while waiting for a missing packet.
+///
+/// Once exceeded, the missing packet is considered lost and skipped.
+const MAX_PENDING_PACKETS: usize = 8;
+
+/// The number of consecutive late packets after which the sender is assumed to have restarted.
+///
+/// The jitter buffer then resynchronises with the new sequence rather than dropping its packets.
+const MAX_LATE_PACKETS: usize = 8;
+
+/// The interval at which the receiving thread checks whether or not it has been closed.
+const READ_TIMEOUT_MS: u64 = 100;
+
+/// The size of a fixed RTP header in bytes.
+const RTP_HEADER_BYTES: usize = 12;
+
+/// Packets of decoded samples sent from the receiving thread to the audio input thread.
+pub type PacketQueue = Arc<SegQueue<Packet>>;
+
+/// Used sample buffers returned to the receiving thread for re-use.
+pub type BufferQueue = Arc<SegQueue<Vec<f32>>>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Network {
+    // Duration for which the network input is played.
+    pub duration: Ms,
+    // The number of interleaved channels within the received stream.
+    pub channels: usize,
+    // The UDP port on which the stream is received.
+    pub port: u16,
+    // The encoding of the received packets.
+    pub format: Format,
+    // The amount of audio buffered before playback to absorb network jitter.
+    #[serde(default = "super::default::jitter_buffer")]
+    pub jitter_buffer: Ms,
+}
+
+/// The encoding of the packets received by a `Network` source.
+///
+/// All samples are signed, big-endian ("network order") integers.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Format {
+    /// Raw 16-bit PCM with no header.
+    L16,
+    /// Raw 24-bit PCM with no header.
+    L24,
+    /// RTP packets with a 16-bit PCM payload.
+    RtpL16,
+    /// RTP packets with a 24-bit PCM payload.
+    RtpL24,
+}
+
+/// A packet of decoded, interleaved samples.
+#[derive(Debug)]
+pub struct Packet {
+    /// The RTP sequence number if there is one.
+    pub sequence: Option<u16>,
+    /// The number of channels with which the packet was decoded.
+    pub channels: usize,
+    pub samples: Vec<f32>,
+}
+
+/// The decoding configuration shared with the receiving thread.
+///
+/// Stored as atomics so that the format and channel count may be changed without rebinding the
+/// socket.
+#[derive(Debug)]
+struct Config {
+    format: AtomicUsize,
+    channels: AtomicUsize,
+}
+
+/// Re-orders received packets and buffers their samples ahead of playback.
+///
+/// Playback only begins once `target_frames` have been buffered. If the buffer runs dry, silence
+/// is yielded until it has refilled.
+#[derive(Debug)]
+pub struct JitterBuffer {
+    channels: usize,
+    target_frames: usize,
+    // The sequence number of the next expected RTP packet.
+    next_sequence: Option<u16>,
+    // Packets that arrived ahead of the next expected packet, ordered by sequence number.
+    pending: Vec<Packet>,
+    // The number of consecutive packets that arrived after their turn had passed.
+    late_packets: usize,
+    // Samples ready for playback.
+    samples: VecDeque<f32>,
+    // Whether or not the buffer is waiting to reach its target depth.
+    is_buffering: bool,
+}
+
+/// The audio input thread end of a `Network` source.
+///
+/// Owns the jitter buffer and the most recently read block of samples. Dropping the `Receiver`
+/// closes the receiving thread.
+pub struct Receiver {
+    channels: usize,
+    jitter_buffer_frames: usize,
+    local_addr: SocketAddr,
+    config: Arc<Config>,
+    packet_rx: PacketQueue,
+    buffer_tx: BufferQueue,
+    jitter_buffer: JitterBuffer,
+    // The block of samples read for the current input buffer.
+    block: Vec<f32>,
+    is_closed: Arc<AtomicBool>,
+}
+
+impl Network {
+    /// The number of frames that should be buffered before playback.
+    pub fn jitter_buffer_frames(&self) -> usize {
+        self.jitter_buffer.samples(SAMPLE_RATE) as usize
+    }
+}
+
+impl Format {
+    pub const VARIANT_COUNT: usize = 4;
+
+    /// The number of bytes used to encode a single sample.
+    pub fn bytes_per_sample(&self) -> usize {
+        match *self {
+            Format::L16 | Format::RtpL16 => 2,
+            Format::L24 | Format::RtpL24 => 3,
+        }
+    }
+
+    /// Whether or not packets are preceded by an RTP header.
+    pub fn is_rtp(&self) -> bool {
+        match *self {
+            Format::RtpL16 | Format::RtpL24 => true,
+            Format::L16 | Format::L24 => false,
+        }
+    }
+
+    /// Produce the index of the Format value variant.
+    pub fn to_index(&self) -> usize {
+        match *self {
+            Format::L16 => 0,
+            Format::L24 => 1,
+            Format::RtpL16 => 2,
+            Format::RtpL24 => 3,
+        }
+    }
+
+    /// Produce a label for the variant at the given index.
+    pub fn label_from_index(i: usize) -> &'static str {
+        match i {
+            0 => "PCM L16",
+            1 => "PCM L24",
+            2 => "RTP L16",
+            3 => "RTP L24",
+            _ => "",
+        }
+    }
+
+    /// Produce the Format variant for the given variant index.
+    pub fn from_index(i: usize) -> Option<Self> {
+        match i {
+            0 => Some(Format::L16),
+            1 => Some(Format::L24),
+            2 => Some(Format::RtpL16),
+            3 => Some(Format::RtpL24),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the given datagram into `samples`.
+///
+/// Returns the RTP sequence number if the format is RTP. Returns `Err` if the packet is not a
+/// valid RTP packet, in which case `samples` is left empty.
+///
+/// Any trailing partial frame is discarded.
+pub fn decode(
+    format: Format,
+    channels: usize,
+    bytes: &[u8],
+    samples: &mut Vec<f32>,
+) -> Result<Option<u16>, ()> {
+    samples.clear();
+    let (sequence, payload) = if format.is_rtp() {
+        let (sequence, payload) = rtp_payload(bytes)?;
+        (Some(sequence), payload)
+    } else {
+        (None, bytes)
+    };
+
+    match format.bytes_per_sample() {
+        2 => for b in payload.chunks(2).filter(|b| b.len() == 2) {
+            let sample = ((b[0] as u16) << 8 | b[1] as u16) as i16;
+            samples.push(sample as f32 / 32_768.0);
+        },
+        _ => for b in payload.chunks(3).filter(|b| b.len() == 3) {
+            // Shift into the top of an `i32` so that the sign is extended on the way back down.
+            let sample = ((b[0] as i32) << 24 | (b[1] as i32) << 16 | (b[2] as i32) << 8) >> 8;
+            samples.push(sample as f32 / 8_388_608.0);
+        },
+    }
+
+    let whole_frames_len = (samples.len() / channels.max(1)) * channels;
+    samples.truncate(whole_frames_len);
+    Ok(sequence)
+}
+
+// Returns the sequence number and payload of the given RTP packet.
+fn rtp_payload(bytes: &[u8]) -> Result<(u16, &[u8]), ()> {
+    if bytes.len() < RTP_HEADER_BYTES || bytes[0] >> 6 != 2 {
+        return Err(());
+    }
+    let has_padding = bytes[0] & 0x20 != 0;
+    let has_extension = bytes[0] & 0x10 != 0;
+    let csrc_count = (bytes[0] & 0x0f) as usize;
+    let sequence = (bytes[2] as u16) << 8 | bytes[3] as u16;
+
+    let mut start = RTP_HEADER_BYTES + csrc_count * 4;
+    if has_extension {
+        if bytes.len() < start + 4 {
+            return Err(());
+        }
+        let words = ((bytes[start + 2] as usize) << 8) | bytes[start + 3] as usize;
+        start += 4 + words * 4;
+    }
+
+    let mut end = bytes.len();
+    if has_padding {
+        end = end.saturating_sub(bytes[end - 1] as usize);
+    }
+    if start > end {
+        return Err(());
+    }
+    Ok((sequence, &bytes[start..end]))
+}
+
+impl JitterBuffer {
+    /// Create a jitter buffer that begins playback once `target_frames` have been buffered.
+    pub fn new(channels: usize, target_frames: usize) -> Self {
+        JitterBuffer {
+            channels,
+            target_frames,
+            next_sequence: None,
+            pending: Vec::with_capacity(MAX_PENDING_PACKETS + 1),
+            late_packets: 0,
+            samples: VecDeque::with_capacity(target_frames * channels * 4),
+            is_buffering: true,
+        }
+    }
+
+    /// The number of frames currently buffered for playback.
+    pub fn len_frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1)
+    }
+
+    /// Insert the given packet.
+    ///
+    /// Packets that are no longer needed are passed to `recycle` so that their buffers may be
+    /// re-used.
+    pub fn insert<F>(&mut self, packet: Packet, mut recycle: F)
+    where
+        F: FnMut(Vec<f32>),
+    {
+        // Drop packets decoded before a change in the number of channels.
+        if packet.channels != self.channels {
+            recycle(packet.samples);
+            return;
+        }
+
+        let sequence = match packet.sequence {
+            None => {
+                self.samples.extend(packet.samples.iter().cloned());
+                recycle(packet.samples);
+                return;
+            },
+            Some(sequence) => sequence,
+        };
+
+        let next = *self.next_sequence.get_or_insert(sequence);
+
+        // Drop packets that arrive after their turn has passed. If too many arrive in a row, the
+        // sender has most likely restarted with a new sequence, so begin again from this packet.
+        if (sequence.wrapping_sub(next) as i16) < 0 {
+            self.late_packets += 1;
+            if self.late_packets <= MAX_LATE_PACKETS {
+                recycle(packet.samples);
+                return;
+            }
+            for packet in self.pending.drain(..) {
+                recycle(packet.samples);
+            }
+            self.next_sequence = Some(sequence);
+        }
+        self.late_packets = 0;
+
+        // Drop packets that have been duplicated.
+        if self.pending.iter().any(|p| p.sequence == Some(sequence)) {
+            recycle(packet.samples);
+            return;
+        }
+
+        let index = self
+            .pending
+            .iter()
+            .position(|p| (sequence.wrapping_sub(p.sequence.unwrap()) as i16) < 0)
+            .unwrap_or(self.pending.len());
+        self.pending.insert(index, packet);
+
+        // If we have waited too long for a missing packet, skip ahead to the earliest we have.
+        if self.pending.len() > MAX_PENDING_PACKETS {
+            self.next_sequence = self.pending[0].sequence;
+        }
+
+        // Move all consecutive packets to the playback buffer.
+        while !self.pending.is_empty() && self.pending[0].sequence == self.next_sequence {
+            let packet = self.pending.remove(0);
+            self.samples.extend(packet.samples.iter().cloned());
+            recycle(packet.samples);
+            self.next_sequence = self.next_sequence.map(|s| s.wrapping_add(1));
+        }
+    }
+
+    /// Fill `out` with interleaved samples, or silence if the buffer is not ready for playback.
+    pub fn read(&mut self, out: &mut [f32]) {
+        // Drop the oldest frames if the buffer has drifted too far beyond its target.
+        let max_samples = (self.target_frames * 2) * self.channels + out.len();
+        if self.samples.len() > max_samples {
+            let excess = self.samples.len() - self.target_frames * self.channels;
+            let excess = excess - excess % self.channels.max(1);
+            self.samples.drain(..excess);
+        }
+
+        if self.is_buffering {
+            if self.len_frames() < self.target_frames {
+                for sample in out.iter_mut() {
+                    *sample = 0.0;
+                }
+                return;
+            }
+            self.is_buffering = false;
+        }
+
+        for sample in out.iter_mut() {
+            *sample = match self.samples.pop_front() {
+                Some(s) => s,
+                None => {
+                    self.is_buffering = true;
+                    0.0
+                },
+            };
+        }
+    }
+}
+
+impl Receiver {
+    /// Bind to the source's UDP port and spawn a thread for receiving its packets.
+    pub fn spawn(network: &Network) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", network.port))?;
+        socket.set_read_timeout(Some(time::Duration::from_millis(READ_TIMEOUT_MS)))?;
+        let local_addr = socket.local_addr()?;
+
+        let packet_queue = Arc::new(SegQueue::new());
+        let buffer_queue = Arc::new(SegQueue::new());
+        let is_closed = Arc::new(AtomicBool::new(false));
+
+        let channels = network.channels;
+        let config = Arc::new(Config {
+            format: AtomicUsize::new(network.format.to_index()),
+            channels: AtomicUsize::new(channels),
+        });
+        let thread_config = config.clone();
+        let packet_tx = packet_queue.clone();
+        let buffer_rx = buffer_queue.clone();
+        let thread_is_closed = is_closed.clone();
+        thread::Builder::new()
+            .name(format!("network_source_{}", local_addr.port()))
+            .spawn(move || run(socket, thread_config, packet_tx, buffer_rx, thread_is_closed))?;
+
+        let jitter_buffer_frames = network.jitter_buffer_frames();
+        let jitter_buffer = JitterBuffer::new(channels, jitter_buffer_frames);
+        let block = Vec::with_capacity(FRAMES_PER_BUFFER * channels);
+        Ok(Receiver {
+            channels,
+            jitter_buffer_frames,
+            local_addr,
+            config,
+            packet_rx: packet_queue,
+            buffer_tx: buffer_queue,
+            jitter_buffer,
+            block,
+            is_closed,
+        })
+    }
+
+    /// The number of channels in the source.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// The address to which the receiver is bound.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Update the decoding and buffering of the receiver for the given source.
+    ///
+    /// The `port` is ignored. A new `Receiver` must be spawned to change ports.
+    pub fn update(&mut self, network: &Network) {
+        self.config.format.store(network.format.to_index(), atomic::Ordering::Relaxed);
+        self.config.channels.store(network.channels, atomic::Ordering::Relaxed);
+        let jitter_buffer_frames = network.jitter_buffer_frames();
+        if network.channels != self.channels || jitter_buffer_frames != self.jitter_buffer_frames {
+            self.channels = network.channels;
+            self.jitter_buffer_frames = jitter_buffer_frames;
+            self.jitter_buffer = JitterBuffer::new(self.channels, jitter_buffer_frames);
+        }
+    }
+
+    /// Read the next `frames` from the jitter buffer into the receiver's current block.
+    ///
+    /// This is called once per input buffer on the audio input thread.
+    pub fn read_frames(&mut self, frames: usize) -> &[f32] {
+        let Receiver {
+            channels,
+            ref packet_rx,
+            ref buffer_tx,
+            ref mut jitter_buffer,
+            ref mut block,
+            ..
+        } = *self;
+        while let Some(packet) = packet_rx.try_pop() {
+            jitter_buffer.insert(packet, |buffer| buffer_tx.push(buffer));
+        }
+        block.resize(frames * channels, 0.0);
+        jitter_buffer.read(block);
+        block
+    }
+
+    /// The block of samples most recently read via `read_frames`.
+    pub fn block(&self) -> &[f32] {
+        &self.block
+    }
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        self.is_closed.store(true, atomic::Ordering::Relaxed);
+    }
+}
+
+// The function that is run on each network source's receiving thread.
+fn run(
+    socket: UdpSocket,
+    config: Arc<Config>,
+    packet_tx: PacketQueue,
+    buffer_rx: BufferQueue,
+    is_closed: Arc<AtomicBool>,
+) {
+    let mut bytes = vec![0u8; MAX_PACKET_BYTES];
+    while !is_closed.load(atomic::Ordering::Relaxed) {
+        let len = match socket.recv_from(&mut bytes) {
+            Ok((len, _addr)) => len,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock
+                || e.kind() == io::ErrorKind::TimedOut => continue,
+            Err(e) => {
+                eprintln!("Error while receiving network audio: {}", e);
+                break;
+            },
+        };
+
+        let format = Format::from_index(config.format.load(atomic::Ordering::Relaxed))
+            .expect("no format for index");
+        let channels = config.channels.load(atomic::Ordering::Relaxed);
+        let mut samples = buffer_rx.try_pop().unwrap_or_else(Vec::new);
+        let sequence = match decode(format, channels, &bytes[..len], &mut samples) {
+            Ok(sequence) => sequence,
+            Err(()) => continue,
+        };
+        if samples.is_empty() {
+            continue;
+        }
+        packet_tx.push(Packet { sequence, channels, samples });
+    }
+}
+
+#[test]
+fn test_jitter_buffer_reordering() {
+    let channels = 2;
+    let mut jitter_buffer = JitterBuffer::new(channels, 3);
+
+    // Decode a single-frame RTP packet whose samples are derived from its sequence number.
+    let packet = |sequence: u16| {
+        let mut bytes = vec![0x80, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        bytes[2] = (sequence >> 8) as u8;
+        bytes[3] = sequence as u8;
+        let sample = (sequence as i16).wrapping_add(1).wrapping_mul(1_024);
+        for _ in 0..channels {
+            bytes.push((sample >> 8) as u8);
+            bytes.push(sample as u8);
+        }
+        let mut samples = vec![];
+        let sequence = decode(Format::RtpL16, channels, &bytes, &mut samples).unwrap();
+        Packet { sequence, channels, samples }
+    };
+
+    // Insert the packets out of order, along with a duplicate and a packet that arrives late.
+    let mut recycled = 0;
+    for &sequence in &[0u16, 2, 2, 1, 0] {
+        jitter_buffer.insert(packet(sequence), |_| recycled += 1);
+    }
+    assert_eq!(jitter_buffer.len_frames(), 3);
+
+    // Playback yields the packets in sequence order.
+    let mut block = vec![0.0; 3 * channels];
+    jitter_buffer.read(&mut block);
+    let expected = [1.0, 1.0, 2.0, 2.0, 3.0, 3.0];
+    for (sample, expected) in block.iter().zip(expected.iter()) {
+        assert_eq!(*sample, expected * 1_024.0 / 32_768.0);
+    }
+    assert_eq!(recycled, 5);
+
+    // Once dry, silence is yielded until the target depth is buffered again.
+    jitter_buffer.read(&mut block);
+    jitter_buffer.insert(packet(3), |_| ());
+    jitter_buffer.read(&mut block);
+    assert!(block.iter().all(|&s| s == 0.0));
+
+    // If the sender restarts behind the old sequence, its packets are only dropped until the
+    // buffer resynchronises with the new sequence.
+    let restart = 40_000u16;
+    for i in 0..MAX_LATE_PACKETS as u16 + 4 {
+        jitter_buffer.insert(packet(restart.wrapping_add(i)), |_| ());
+    }
+    assert_eq!(jitter_buffer.len_frames(), 1 + 4);
+    jitter_buffer.read(&mut block[..2 * channels]);
+    let first = packet(restart + MAX_LATE_PACKETS as u16).samples[0];
+    assert_eq!(block[channels], first);
+}
+
+#[test]
+fn test_loopback_rtp_reordering() {
+    let network = Network {
+        duration: Ms(1_000.0),
+        channels: 2,
+        port: 0,
+        format: Format::RtpL16,
+        // Three frames, so that playback begins once all three packets have arrived.
+        jitter_buffer: Ms(3_000.0 / SAMPLE_RATE),
+    };
+    let mut receiver = Receiver::spawn(&network).unwrap();
+    let addr: SocketAddr = ([127, 0, 0, 1], receiver.local_addr().port()).into();
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    // Send three single-frame packets with the last two out of order.
+    for &sequence in &[0u16, 2, 1] {
+        let mut packet = vec![0x80, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        packet[2] = (sequence >> 8) as u8;
+        packet[3] = sequence as u8;
+        let sample = (sequence as i16 + 1) * 1_024;
+        for _ in 0..network.channels {
+            packet.push((sample >> 8) as u8);
+            packet.push(sample as u8);
+        }
+        sender.send_to(&packet, addr).unwrap();
+    }
+
+    // Poll the receiver until the buffered packets are played back.
+    let deadline = time::Instant::now() + time::Duration::from_secs(5);
+    let block = loop {
+        let block = receiver.read_frames(3).to_vec();
+        if block.iter().any(|&s| s != 0.0) {
+            break block;
+        }
+        assert!(time::Instant::now() < deadline, "timed out waiting for network packets");
+        thread::sleep(time::Duration::from_millis(5));
+    };
+    let expected = [1.0, 1.0, 2.0, 2.0, 3.0, 3.0];
+    assert_eq!(block.len(), expected.len());
+    for (sample, expected) in block.iter().zip(expected.iter()) {
+        assert_eq!(*sample, expected * 1_024.0 / 32_768.0);
+    }
+}
@@ -1,11 +1,13 @@
 //! Items related to the realtime audio input sound source kind.
 
 use crossbeam::sync::SegQueue;
+use serde::{Deserialize, Deserializer};
 use std::mem;
 use std::ops;
 use std::sync::{atomic, Arc};
 use std::sync::atomic::AtomicBool;
 use time_calc::{Ms, Samples};
+use utils;
 
 pub type BufferTx = Arc<SegQueue<Vec<f32>>>;
 pub type BufferRx = Arc<SegQueue<Vec<f32>>>;
@@ -14,8 +16,22 @@ pub type BufferRx = Arc<SegQueue<Vec<f32>>>;
 pub struct Realtime {
     // Duration for which the realtime input is played.
     pub duration: Ms,
-    // The range of channels occuppied by the source.
-    pub channels: ops::Range<usize>,
+    // The input channel read for each channel of the source.
+    //
+    // The same input may be listed more than once, e.g. to spread a mono input across many
+    // channels.
+    #[serde(deserialize_with = "deserialize_inputs")]
+    pub channels: Vec<Input>,
+}
+
+/// A single channel of a `Realtime` source.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Input {
+    // The index of the channel on the input device.
+    pub channel: usize,
+    // A gain adjustment applied to the input in decibels.
+    #[serde(default)]
+    pub trim_db: f32,
 }
 
 /// The signal end of a `Realtime` audio source.
@@ -36,6 +52,37 @@ pub struct Signal {
     pub is_closed: Arc<AtomicBool>,
 }
 
+impl Input {
+    /// An input reading from the given device channel with no trim.
+    pub fn new(channel: usize) -> Self {
+        Input { channel, trim_db: 0.0 }
+    }
+
+    /// The trim as an amplitude multiplier.
+    pub fn trim_gain(&self) -> f32 {
+        utils::db_to_gain(self.trim_db)
+    }
+}
+
+// Realtime sources used to be described by a contiguous range of channels. This allows projects
+// saved in the old format to be loaded.
+fn deserialize_inputs<'de, D>(deserializer: D) -> Result<Vec<Input>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Inputs {
+        Range(ops::Range<usize>),
+        List(Vec<Input>),
+    }
+    let inputs = match Inputs::deserialize(deserializer)? {
+        Inputs::Range(range) => range.map(Input::new).collect(),
+        Inputs::List(list) => list,
+    };
+    Ok(inputs)
+}
+
 impl Signal {
     /// The number of channels in the source.
     pub fn channels(&self) -> usize {
@@ -86,3 +133,29 @@ impl Drop for Signal {
         self.is_closed.store(true, atomic::Ordering::Relaxed);
     }
 }
+
+#[test]
+fn test_deserialize_inputs() {
+    use serde_json;
+
+    // Projects saved before per-channel inputs describe a contiguous range of channels.
+    let legacy = r#"{ "duration": 3000.0, "channels": { "start": 1, "end": 3 } }"#;
+    let realtime: Realtime = serde_json::from_str(legacy).unwrap();
+    assert_eq!(realtime.duration, Ms(3000.0));
+    assert_eq!(realtime.channels, vec![Input::new(1), Input::new(2)]);
+
+    // Inputs may repeat channels and omit the trim.
+    let list = r#"{
+        "duration": 1000.0,
+        "channels": [{ "channel": 4, "trim_db": -6.0 }, { "channel": 4 }]
+    }"#;
+    let realtime: Realtime = serde_json::from_str(list).unwrap();
+    let trimmed = Input { channel: 4, trim_db: -6.0 };
+    assert_eq!(realtime.channels, vec![trimmed, Input::new(4)]);
+
+    // The new format survives a round trip.
+    let json = serde_json::to_string(&realtime).unwrap();
+    let round_trip: Realtime = serde_json::from_str(&json).unwrap();
+    assert_eq!(round_trip.duration, realtime.duration);
+    assert_eq!(round_trip.channels, realtime.channels);
+}
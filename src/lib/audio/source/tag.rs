@@ -0,0 +1,306 @@
+//! Free-form tags for describing sources and queries for selecting sources by their tags.
+//!
+//! Queries are written as tags combined with `AND`, `OR` and `NOT` where `NOT` binds tightest,
+//! followed by `AND` and then `OR`. Parentheses may be used for grouping, e.g.
+//!
+//! - `birds`
+//! - `birds AND NOT night`
+//! - `(wind OR water) AND NOT loud`
+//!
+//! Tags are case-insensitive and may not contain whitespace or parentheses.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed tag query.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// Matches sources with the given tag.
+    Tag(String),
+    /// Matches sources that do not match the inner query.
+    Not(Box<Query>),
+    /// Matches sources that match both queries.
+    And(Box<Query>, Box<Query>),
+    /// Matches sources that match either query.
+    Or(Box<Query>, Box<Query>),
+}
+
+/// Errors that might occur while parsing a `Query`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// The query contained no tags.
+    Empty,
+    /// The query ended where a tag or `(` was expected.
+    UnexpectedEnd,
+    /// Some token appeared where it was not expected.
+    UnexpectedToken(String),
+    /// A `(` was never closed.
+    UnclosedParen,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Tag(String),
+}
+
+/// Normalise a tag for storage and comparison.
+///
+/// Returns `None` if the tag is empty or contains characters that may not be used in a query.
+pub fn normalise(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() || tag.chars().any(|c| c.is_whitespace() || c == '(' || c == ')') {
+        return None;
+    }
+    match &tag[..] {
+        "and" | "or" | "not" => None,
+        _ => Some(tag),
+    }
+}
+
+/// Parse a list of tags separated by commas and/or whitespace, e.g. as entered via the GUI.
+///
+/// Invalid tags are ignored and duplicates are removed, preserving the order of the first
+/// occurrence.
+pub fn parse_list(s: &str) -> Vec<String> {
+    let mut tags = vec![];
+    for tag in s.split(|c: char| c == ',' || c.is_whitespace()).filter_map(normalise) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Format a list of tags in a form that may be parsed via `parse_list`.
+pub fn format_list(tags: &[String]) -> String {
+    tags.join(", ")
+}
+
+impl Query {
+    /// Parse a query from the given string.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(s);
+        if tokens.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let mut parser = Parser { tokens: &tokens, index: 0 };
+        let query = parser.or()?;
+        match parser.next() {
+            None => Ok(query),
+            Some(token) => Err(ParseError::UnexpectedToken(token.to_string())),
+        }
+    }
+
+    /// Whether or not a source with the given tags matches the query.
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match *self {
+            Query::Tag(ref tag) => tags.iter().any(|t| t == tag),
+            Query::Not(ref q) => !q.matches(tags),
+            Query::And(ref a, ref b) => a.matches(tags) && b.matches(tags),
+            Query::Or(ref a, ref b) => a.matches(tags) || b.matches(tags),
+        }
+    }
+}
+
+// A recursive descent parser over the query tokens.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.index);
+        self.index += 1;
+        token
+    }
+
+    // or := and ("OR" and)*
+    fn or(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.and()?;
+        while let Some(&Token::Or) = self.peek() {
+            self.index += 1;
+            let rhs = self.and()?;
+            query = Query::Or(Box::new(query), Box::new(rhs));
+        }
+        Ok(query)
+    }
+
+    // and := not ("AND" not)*
+    fn and(&mut self) -> Result<Query, ParseError> {
+        let mut query = self.not()?;
+        while let Some(&Token::And) = self.peek() {
+            self.index += 1;
+            let rhs = self.not()?;
+            query = Query::And(Box::new(query), Box::new(rhs));
+        }
+        Ok(query)
+    }
+
+    // not := "NOT" not | tag | "(" or ")"
+    fn not(&mut self) -> Result<Query, ParseError> {
+        match self.next() {
+            None => Err(ParseError::UnexpectedEnd),
+            Some(&Token::Not) => Ok(Query::Not(Box::new(self.not()?))),
+            Some(&Token::Tag(ref tag)) => Ok(Query::Tag(tag.clone())),
+            Some(&Token::Open) => {
+                let query = self.or()?;
+                match self.next() {
+                    Some(&Token::Close) => Ok(query),
+                    None => Err(ParseError::UnclosedParen),
+                    Some(token) => Err(ParseError::UnexpectedToken(token.to_string())),
+                }
+            }
+            Some(token) => Err(ParseError::UnexpectedToken(token.to_string())),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut word = String::new();
+    fn push_word(word: &mut String, tokens: &mut Vec<Token>) {
+        if word.is_empty() {
+            return;
+        }
+        let token = match &word.to_lowercase()[..] {
+            "and" => Token::And,
+            "or" => Token::Or,
+            "not" => Token::Not,
+            tag => Token::Tag(tag.to_string()),
+        };
+        tokens.push(token);
+        word.clear();
+    }
+    for c in s.chars() {
+        match c {
+            '(' | ')' => {
+                push_word(&mut word, &mut tokens);
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            c if c.is_whitespace() => push_word(&mut word, &mut tokens),
+            c => word.push(c),
+        }
+    }
+    push_word(&mut word, &mut tokens);
+    tokens
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "NOT"),
+            Token::Tag(ref tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Query::Tag(ref tag) => write!(f, "{}", tag),
+            Query::Not(ref q) => match **q {
+                Query::And(..) | Query::Or(..) => write!(f, "NOT ({})", q),
+                _ => write!(f, "NOT {}", q),
+            },
+            Query::And(ref a, ref b) => {
+                for (i, q) in [a, b].iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    match ***q {
+                        Query::Or(..) => write!(f, "({})", q)?,
+                        _ => write!(f, "{}", q)?,
+                    }
+                }
+                Ok(())
+            }
+            Query::Or(ref a, ref b) => write!(f, "{} OR {}", a, b),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Empty => write!(f, "the query is empty"),
+            ParseError::UnexpectedEnd => write!(f, "expected a tag at the end of the query"),
+            ParseError::UnexpectedToken(ref t) => write!(f, "unexpected \"{}\"", t),
+            ParseError::UnclosedParen => write!(f, "missing \")\""),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        match *self {
+            ParseError::Empty => "the query is empty",
+            ParseError::UnexpectedEnd => "unexpected end of query",
+            ParseError::UnexpectedToken(_) => "unexpected token",
+            ParseError::UnclosedParen => "unclosed parenthesis",
+        }
+    }
+}
+
+// Queries are stored in their written form so that they remain readable within `state.json`.
+
+impl Serialize for Query {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Query {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Query::parse(&s).map_err(de::Error::custom)
+    }
+}
+
+#[test]
+fn test_query_parse_and_match() {
+    let query = Query::parse("birds AND NOT night").unwrap();
+    let tags = |s: &str| parse_list(s);
+    assert!(query.matches(&tags("birds, day")));
+    assert!(!query.matches(&tags("birds night")));
+    assert!(!query.matches(&tags("wind")));
+
+    let query = Query::parse("(Wind OR water) and not loud").unwrap();
+    assert_eq!(query.to_string(), "(wind OR water) AND NOT loud");
+    assert_eq!(Query::parse(&query.to_string()).unwrap(), query);
+    assert!(query.matches(&tags("water")));
+    assert!(!query.matches(&tags("water loud")));
+
+    assert_eq!(Query::parse("  "), Err(ParseError::Empty));
+    assert_eq!(Query::parse("birds AND"), Err(ParseError::UnexpectedEnd));
+    assert_eq!(Query::parse("(birds"), Err(ParseError::UnclosedParen));
+    assert_eq!(Query::parse("birds night"), Err(ParseError::UnexpectedToken("night".into())));
+}
@@ -0,0 +1,319 @@
+//! An in-memory cache of fully decoded WAV files.
+//!
+//! Short WAVs (e.g. clicks and one-shots) are decoded once into a shared `Arc<[f32]>` and played
+//! directly on the audio thread, avoiding the `wav::reader` thread round-trip and disk I/O
+//! entirely. The cache holds at most `budget_bytes` of decoded samples, evicting the least
+//! recently played WAVs when full.
+//!
+//! The cache is looked up from the threads that spawn sounds (the GUI and soundscape threads) using
+//! the WAV metadata already known by the project. A WAV that is not yet cached is streamed as usual
+//! while it is decoded in the background by the `wav::reader` thread. The cache is never accessed
+//! from the audio thread.
+//!
+//! Sounds on the audio thread may still be playing a WAV when it is evicted or removed. The cache
+//! holds on to the samples of such WAVs until those sounds have ended, freeing them on the next
+//! lookup or load so that a large allocation is never freed within the audio callback.
+
+use fxhash::{FxHashMap, FxHashSet};
+use hound;
+use std::{cmp, mem};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use super::reader::WavReader;
+
+/// A handle to the shared WAV cache.
+#[derive(Clone)]
+pub struct Cache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+/// A WAV that has been fully decoded into memory.
+#[derive(Clone)]
+pub struct Cached {
+    /// The interleaved samples of the entire WAV.
+    pub samples: Arc<[f32]>,
+    /// The spec of the WAV from which the samples were decoded.
+    pub spec: hound::WavSpec,
+}
+
+/// Statistics describing the current state of the cache, primarily for display within the GUI.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The number of WAVs currently stored in the cache.
+    pub entries: usize,
+    /// The number of bytes occupied by decoded samples.
+    pub bytes: usize,
+    /// The maximum number of bytes that may be occupied by decoded samples.
+    pub budget_bytes: usize,
+    /// The number of times a sound was played from the cache.
+    pub hits: u64,
+    /// The number of times a sound had to be streamed from disk, whether because the WAV was not
+    /// yet cached, was still being decoded or is too large to be cached.
+    pub misses: u64,
+    /// The number of WAVs evicted to make room for others.
+    pub evictions: u64,
+}
+
+/// The result of looking up a WAV within the cache.
+pub enum Lookup {
+    /// The WAV is cached and may be played directly from memory.
+    Hit(Cached),
+    /// The WAV is cacheable but not yet cached. It should be streamed and loaded via `Cache::load`
+    /// in the background.
+    Load,
+    /// The WAV is too large to be cached or is already being loaded. It should be streamed.
+    Stream,
+}
+
+struct Inner {
+    entries: FxHashMap<PathBuf, Entry>,
+    /// WAVs that are currently being decoded in the background.
+    loading: FxHashSet<PathBuf>,
+    /// WAVs whose decoded size exceeds this are never cached.
+    max_file_bytes: usize,
+    budget_bytes: usize,
+    bytes: usize,
+    /// The samples of evicted or removed WAVs that may still be held by sounds.
+    ///
+    /// These are freed by `release_evicted` once no sounds hold them.
+    evicted: Vec<Arc<[f32]>>,
+    /// Incremented on every access for tracking recency.
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+struct Entry {
+    cached: Cached,
+    last_used: u64,
+}
+
+impl Cache {
+    /// Create a new, empty cache.
+    ///
+    /// Only WAVs whose decoded size in bytes is no greater than `max_file_bytes` will be cached.
+    /// A `max_file_bytes` of `0` disables the cache.
+    pub fn new(max_file_bytes: usize, budget_bytes: usize) -> Self {
+        let inner = Inner {
+            entries: FxHashMap::default(),
+            loading: FxHashSet::default(),
+            max_file_bytes,
+            budget_bytes,
+            bytes: 0,
+            evicted: Vec::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        };
+        Cache { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// Look up the WAV at the given path whose decoded size is `bytes`.
+    ///
+    /// The size is given by the caller (e.g. from the project's `Wav` metadata) so that the file
+    /// does not have to be opened. Returns `Lookup::Load` at most once per WAV until it is loaded.
+    pub fn lookup(&self, path: &Path, bytes: usize) -> Lookup {
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_evicted();
+        inner.clock += 1;
+        let clock = inner.clock;
+        if let Some(entry) = inner.entries.get_mut(path) {
+            entry.last_used = clock;
+            let cached = entry.cached.clone();
+            inner.hits += 1;
+            return Lookup::Hit(cached);
+        }
+        inner.misses += 1;
+        if !inner.fits(bytes) || inner.loading.contains(path) {
+            return Lookup::Stream;
+        }
+        inner.loading.insert(path.to_path_buf());
+        Lookup::Load
+    }
+
+    /// Decode the WAV at the given path and insert it into the cache.
+    ///
+    /// This is called by the `wav::reader` thread after a `Lookup::Load`. Nothing is inserted if
+    /// the WAV was removed from the cache while decoding, or if it turns out to be too large.
+    pub fn load(&self, path: &Path) -> Result<(), hound::Error> {
+        let result = WavReader::open(path).and_then(|mut reader| {
+            let spec = reader.spec();
+            decode(&mut reader).map(|samples| (samples, spec))
+        });
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.release_evicted();
+        if !inner.loading.remove(path) {
+            return Ok(());
+        }
+        let (samples, spec) = result?;
+        let bytes = samples.len() * mem::size_of::<f32>();
+        if !inner.fits(bytes) {
+            return Ok(());
+        }
+        let samples: Arc<[f32]> = samples.into();
+        let cached = Cached { samples, spec };
+        let last_used = inner.clock;
+        inner.evict_until_fits(bytes);
+        inner.bytes += bytes;
+        let entry = Entry { cached, last_used };
+        inner.entries.insert(path.to_path_buf(), entry);
+        Ok(())
+    }
+
+    /// Remove the WAV at the given path from the cache, e.g. if the file has changed on disk.
+    ///
+    /// Sounds that are currently playing the WAV will continue to do so.
+    pub fn remove(&self, path: &Path) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.loading.remove(path);
+        if let Some(entry) = inner.entries.remove(path) {
+            inner.bytes -= entry.cached.bytes();
+            inner.evicted.push(entry.cached.samples);
+        }
+    }
+
+    /// Remove all WAVs from the cache.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let Inner { ref mut entries, ref mut loading, ref mut evicted, ref mut bytes, .. } = *inner;
+        evicted.extend(entries.drain().map(|(_, entry)| entry.cached.samples));
+        loading.clear();
+        *bytes = 0;
+    }
+
+    /// The current cache statistics.
+    pub fn stats(&self) -> Stats {
+        let inner = self.inner.lock().unwrap();
+        Stats {
+            entries: inner.entries.len(),
+            bytes: inner.bytes,
+            budget_bytes: inner.budget_bytes,
+            hits: inner.hits,
+            misses: inner.misses,
+            evictions: inner.evictions,
+        }
+    }
+}
+
+impl Cached {
+    /// The number of bytes occupied by the decoded samples.
+    pub fn bytes(&self) -> usize {
+        self.samples.len() * mem::size_of::<f32>()
+    }
+}
+
+impl Stats {
+    /// The fraction of all plays that were served from memory.
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f32 / total as f32
+        }
+    }
+}
+
+impl Inner {
+    // Whether or not a WAV of the given decoded size may be cached.
+    fn fits(&self, bytes: usize) -> bool {
+        bytes <= cmp::min(self.max_file_bytes, self.budget_bytes)
+    }
+
+    // Evict the least recently used entries until `bytes` more will fit within the budget.
+    //
+    // Note that evicted samples are only freed once all sounds playing them have ended. See
+    // `release_evicted`.
+    fn evict_until_fits(&mut self, bytes: usize) {
+        while self.bytes + bytes > self.budget_bytes {
+            let lru = self.entries
+                .iter()
+                .min_by_key(|&(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone());
+            let path = match lru {
+                None => break,
+                Some(path) => path,
+            };
+            if let Some(entry) = self.entries.remove(&path) {
+                self.bytes -= entry.cached.bytes();
+                self.evictions += 1;
+                self.evicted.push(entry.cached.samples);
+            }
+        }
+    }
+
+    // Free the samples of evicted WAVs that are no longer held by any sounds.
+    //
+    // As the cache holds the last reference, sounds ending on the audio thread never free the
+    // samples themselves.
+    fn release_evicted(&mut self) {
+        self.evicted.retain(|samples| Arc::strong_count(samples) > 1);
+    }
+}
+
+// Read all remaining samples from the reader.
+fn decode(reader: &mut WavReader) -> Result<Vec<f32>, hound::Error> {
+    let mut samples = Vec::with_capacity(reader.len() as usize);
+    super::samples::for_each(reader, |sample| samples.push(sample))?;
+    Ok(samples)
+}
+
+#[test]
+fn test_lookup_stats() {
+    let cache = Cache::new(1_024, 4_096);
+    let path = Path::new("short.wav");
+
+    // The first lookup starts loading, while lookups during the load are streamed.
+    match cache.lookup(path, 512) {
+        Lookup::Load => (),
+        _ => panic!("expected the first lookup to load the WAV"),
+    }
+    match cache.lookup(path, 512) {
+        Lookup::Stream => (),
+        _ => panic!("expected a lookup during the load to stream the WAV"),
+    }
+
+    // WAVs too large to be cached are always streamed.
+    match cache.lookup(Path::new("long.wav"), 2_048) {
+        Lookup::Stream => (),
+        _ => panic!("expected a lookup of a large WAV to stream the WAV"),
+    }
+
+    // Every lookup that was not served from memory counts as a miss.
+    let stats = cache.stats();
+    assert_eq!(stats.hits, 0);
+    assert_eq!(stats.misses, 3);
+    assert_eq!(stats.hit_rate(), 0.0);
+}
+
+#[test]
+fn test_evicted_samples_freed_off_audio_thread() {
+    let cache = Cache::new(1_024, 1_024);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44_100,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let samples: Arc<[f32]> = vec![0.0; 128].into();
+    let mut inner = cache.inner.lock().unwrap();
+    let cached = Cached { samples: samples.clone(), spec };
+    inner.bytes += cached.bytes();
+    inner.entries.insert(PathBuf::from("short.wav"), Entry { cached, last_used: 0 });
+
+    // A sound is still playing the WAV when it is evicted, so the cache keeps its samples.
+    let playing = samples;
+    inner.evict_until_fits(1_024);
+    assert!(inner.entries.is_empty());
+    inner.release_evicted();
+    assert_eq!(inner.evicted.len(), 1);
+
+    // Once the sound ends, the samples are freed by the cache rather than by the sound.
+    mem::drop(playing);
+    assert_eq!(Arc::strong_count(&inner.evicted[0]), 1);
+    inner.release_evicted();
+    assert!(inner.evicted.is_empty());
+}
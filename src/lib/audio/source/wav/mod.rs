@@ -1,10 +1,14 @@
-use audio;
+use audio::{self, loudness};
 use hound;
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 use time_calc::{Ms, SampleHz, Samples};
 
+pub mod cache;
+pub mod rate;
 pub mod reader;
 pub mod samples;
+pub mod watcher;
 
 /// The WAV file audio source type.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -17,6 +21,12 @@ pub struct Wav {
     pub should_loop: bool,
     #[serde(default = "default_playback")]
     pub playback: Playback,
+    /// The loudness analysis of the WAV, if it has been analysed.
+    #[serde(default)]
+    pub loudness: Option<loudness::Analysis>,
+    /// Whether or not the WAV should be normalised toward the project's loudness target.
+    #[serde(default)]
+    pub normalise: bool,
 }
 
 /// The playback mode of the WAV file.
@@ -57,6 +67,8 @@ impl Wav {
         let duration = Samples(reader.duration() as _);
         let playback = default_playback();
         let should_loop = default_should_loop();
+        let loudness = None;
+        let normalise = false;
         Ok(Wav {
             path,
             channels,
@@ -64,11 +76,51 @@ impl Wav {
             sample_hz,
             playback,
             should_loop,
+            loudness,
+            normalise,
         })
     }
 
+    /// Re-load the WAV header from the given path, e.g. after the file has changed.
+    ///
+    /// User-specified parameters are retained. The loudness analysis is only retained if the
+    /// number of channels and the duration are unchanged.
+    pub fn reload(&mut self, path: PathBuf) -> Result<(), hound::Error> {
+        let mut new_wav = Wav::from_path(path)?;
+        new_wav.should_loop = self.should_loop;
+        new_wav.playback = self.playback;
+        new_wav.normalise = self.normalise;
+        if new_wav.channels == self.channels && new_wav.duration == self.duration {
+            new_wav.loudness = self.loudness;
+        }
+        *self = new_wav;
+        Ok(())
+    }
+
     /// The duration of the `Wav` in milliseconds.
     pub fn duration_ms(&self) -> Ms {
         self.duration.to_ms(self.sample_hz)
     }
+
+    /// The gain applied to the WAV's sounds in order to reach the given loudness target.
+    ///
+    /// This is `1.0` if normalisation is disabled or the WAV has not yet been analysed.
+    pub fn normalisation_gain(&self, target_lufs: f32) -> f32 {
+        match self.loudness {
+            Some(ref analysis) if self.normalise => analysis.normalisation_gain(target_lufs),
+            _ => 1.0,
+        }
+    }
+}
+
+/// Whether or not the given path has a WAV file extension.
+pub fn is_wav_path(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    match ext.as_ref().map(|e| &e[..]) {
+        Some("wav") | Some("wave") => true,
+        _ => false,
+    }
 }
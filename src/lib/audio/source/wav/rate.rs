@@ -0,0 +1,209 @@
+//! Items related to playing back a WAV `SamplesStream` at a rate other than its natural rate.
+
+use super::reader::SamplesStream;
+use time_calc::Samples;
+
+/// A WAV `SamplesStream` played back at some given rate.
+///
+/// When the rate is `1.0` samples are yielded directly from the inner stream. Otherwise, each
+/// output frame is linearly interpolated between the two nearest frames of the inner stream.
+pub struct RateStream {
+    samples: SamplesStream,
+    /// The rate at which the inner stream is stepped through per output frame.
+    rate: f64,
+    /// The interpolation state. `None` if the rate is `1.0`.
+    interpolation: Option<Interpolation>,
+}
+
+/// State required for linearly interpolating between frames of the inner stream.
+///
+/// The frames are loaded lazily as the playhead reaches them. A frame may be left partially
+/// loaded if the inner stream is waiting on the `wav::reader` thread for its next buffer, in which
+/// case loading resumes on the next call to `next`.
+struct Interpolation {
+    /// The fractional position between the `left` and `right` frames.
+    phase: f64,
+    /// The frame at or before the current playhead. Empty once the stream is exhausted.
+    left: Vec<f32>,
+    /// The frame after the current playhead. Empty if the inner stream has no more frames.
+    right: Vec<f32>,
+    /// The interpolated frame currently being yielded.
+    frame: Vec<f32>,
+    /// The index of the next sample to yield within `frame`.
+    frame_index: usize,
+}
+
+impl RateStream {
+    /// Wrap the given stream so that it is played back at the given rate.
+    ///
+    /// A `rate` of `2.0` plays the stream back twice as fast (an octave higher), `0.5` plays it
+    /// back at half speed (an octave lower).
+    ///
+    /// No samples are read from the inner stream until the first call to `next`, as the
+    /// `wav::reader` thread may not have queued any buffers yet.
+    pub fn new(samples: SamplesStream, rate: f64) -> Self {
+        let rate = if rate > 0.0 { rate } else { 1.0 };
+        let interpolation = if rate == 1.0 {
+            None
+        } else {
+            let channels = samples.channels();
+            Some(Interpolation {
+                phase: 0.0,
+                left: Vec::with_capacity(channels),
+                right: Vec::with_capacity(channels),
+                frame: Vec::with_capacity(channels),
+                frame_index: 0,
+            })
+        };
+        RateStream { samples, rate, interpolation }
+    }
+
+    /// The rate at which the inner stream is played back.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// The number of channels in the source audio.
+    pub fn channels(&self) -> usize {
+        self.samples.channels()
+    }
+
+    /// The number of output frames remaining in the stream.
+    ///
+    /// This is the number of inner frames remaining beyond the playhead (including any frames
+    /// loaded for interpolation) scaled by the rate, plus any samples of the current interpolated
+    /// frame yet to be yielded.
+    pub fn remaining_frames(&self) -> Option<Samples> {
+        let interp = match self.interpolation {
+            None => return self.samples.remaining_frames(),
+            Some(ref interp) => interp,
+        };
+        self.samples.remaining_frames().map(|frames| {
+            let channels = self.channels();
+            let pending = (interp.frame.len() - interp.frame_index) / channels;
+            let loaded = ((interp.left.len() + interp.right.len()) / channels) as i64;
+            let inner = (frames.samples() + loaded) as f64 - interp.phase;
+            // A frame is yielded for each step of the playhead that lands before the end of the
+            // last frame. The epsilon avoids counting an extra frame due to accumulated phase
+            // error.
+            let interpolated = (inner / self.rate - 1e-9).ceil().max(0.0) as i64;
+            Samples(pending as i64 + interpolated)
+        })
+    }
+}
+
+// Load the remainder of the given `frame` from the stream.
+//
+// Returns `Some(true)` if the frame is fully loaded and `Some(false)` if the stream is exhausted,
+// in which case `frame` is left empty. Returns `None` if the stream is still waiting on its next
+// buffer, in which case the samples read so far are kept so that loading may resume later.
+fn load_frame(samples: &mut SamplesStream, frame: &mut Vec<f32>) -> Option<bool> {
+    while frame.len() < samples.channels() {
+        match samples.next_sample() {
+            Some(sample) => frame.push(sample),
+            None => {
+                if !samples.is_exhausted() {
+                    return None;
+                }
+                frame.clear();
+                return Some(false);
+            },
+        }
+    }
+    Some(true)
+}
+
+impl Iterator for RateStream {
+    type Item = f32;
+    fn next(&mut self) -> Option<Self::Item> {
+        let RateStream { ref mut samples, rate, ref mut interpolation } = *self;
+        let interp = match *interpolation {
+            None => return samples.next_sample(),
+            Some(ref mut interp) => interp,
+        };
+
+        loop {
+            if let Some(&sample) = interp.frame.get(interp.frame_index) {
+                interp.frame_index += 1;
+                return Some(sample);
+            }
+
+            // Load the frames either side of the playhead, stepping over any whole frames.
+            loop {
+                match load_frame(samples, &mut interp.left) {
+                    // Still waiting on the reader thread.
+                    None => return None,
+                    // The playhead has moved beyond the end of the stream.
+                    Some(false) => return None,
+                    Some(true) => (),
+                }
+                if load_frame(samples, &mut interp.right).is_none() {
+                    return None;
+                }
+                if interp.phase < 1.0 {
+                    break;
+                }
+                interp.phase -= 1.0;
+                ::std::mem::swap(&mut interp.left, &mut interp.right);
+                interp.right.clear();
+            }
+
+            // Interpolate the next frame between `left` and `right`, holding the last frame.
+            let phase = interp.phase as f32;
+            interp.frame.clear();
+            interp.frame_index = 0;
+            for (i, &l) in interp.left.iter().enumerate() {
+                let r = interp.right.get(i).cloned().unwrap_or(l);
+                interp.frame.push(l + (r - l) * phase);
+            }
+
+            // Step the playhead forward.
+            interp.phase += rate;
+        }
+    }
+}
+
+#[test]
+fn test_rate_stream() {
+    use super::cache::Cached;
+    use hound;
+    use std::sync::Arc;
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44_100,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let stream = |frames: usize, rate: f64| {
+        let samples: Arc<[f32]> = (0..frames).map(|i| i as f32).collect::<Vec<_>>().into();
+        let cached = Cached { samples, spec };
+        RateStream::new(SamplesStream::from_cached(cached, 0, false), rate)
+    };
+
+    // Half speed interpolates a frame between each pair, holding the last frame.
+    let mut half = stream(4, 0.5);
+    assert_eq!(half.remaining_frames(), Some(Samples(8)));
+    assert_eq!(half.next(), Some(0.0));
+    assert_eq!(half.remaining_frames(), Some(Samples(7)));
+    let rest: Vec<f32> = half.by_ref().collect();
+    assert_eq!(rest, vec![0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.0]);
+    assert_eq!(half.remaining_frames(), Some(Samples(0)));
+
+    // Double speed skips every second frame.
+    let mut double = stream(5, 2.0);
+    assert_eq!(double.remaining_frames(), Some(Samples(3)));
+    assert_eq!(double.next(), Some(0.0));
+    assert_eq!(double.remaining_frames(), Some(Samples(2)));
+    assert_eq!(double.next(), Some(2.0));
+    assert_eq!(double.next(), Some(4.0));
+    assert_eq!(double.remaining_frames(), Some(Samples(0)));
+    assert_eq!(double.next(), None);
+
+    // The remaining frames always match the number of frames actually yielded.
+    for &rate in &[0.3, 0.75, 1.0, 1.5, 3.7] {
+        let s = stream(50, rate);
+        let remaining = s.remaining_frames().unwrap();
+        assert_eq!(remaining, Samples(s.count() as i64));
+    }
+}
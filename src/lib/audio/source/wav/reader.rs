@@ -2,6 +2,8 @@
 //! audio thread.
 
 use audio::{self, sound};
+use super::Wav;
+use super::cache::{Cache, Cached, Lookup};
 use crossbeam::sync::{MsQueue, SegQueue};
 use fxhash::FxHashMap;
 use hound::{self, SampleFormat};
@@ -12,7 +14,7 @@ use std::io::BufReader;
 use std::fs::File;
 use std::mem;
 use std::ops;
-use std::path::Path;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use time_calc::Samples;
@@ -48,6 +50,7 @@ struct ChildId(usize);
 #[derive(Clone)]
 pub struct Handle {
     tx: Tx,
+    cache: Cache,
     thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
 }
 
@@ -107,6 +110,8 @@ enum ChildMessage {
     Play(sound::Id, Play),
     /// Process the next buffer and send the result back to the parent thread.
     NextBuffer(sound::Id, Sound, Vec<f32>),
+    /// Decode the WAV at the given path into the `Cache`.
+    Load(PathBuf),
 }
 
 /// Messages received by the wav reader thread.
@@ -121,6 +126,8 @@ pub enum Message {
     NextBuffer(sound::Id, Vec<f32>),
     /// Received when one of the child threads has finished processing a `NextBuffer` command.
     NextBufferComplete(sound::Id, Sound),
+    /// Decode the WAV at the given path into the `Cache`.
+    Load(PathBuf),
     /// Indicates that the sound associated with the given Id has ended.
     End(sound::Id),
     /// Break from the loop as the application is closing.
@@ -160,29 +167,70 @@ pub struct Play {
 
 /// A handle to a WAV that receives the buffered samples for use on the audio thread.
 pub struct SamplesStream {
-    buffer_rx: BufferRx,
-    buffer: RefCell<Option<Buffer>>,
-    buffer_index: usize,
+    source: StreamSource,
     wav_spec: hound::WavSpec,
     wav_len_samples: usize,
     // Whether or not the WAV is looped.
     wav_looped: bool,
 }
 
+/// Where the `SamplesStream` retrieves its samples from.
+enum StreamSource {
+    /// Buffers sent from the `wav::reader` thread.
+    Reader {
+        buffer_rx: BufferRx,
+        buffer: RefCell<Option<Buffer>>,
+        buffer_index: usize,
+        /// Whether or not the buffer containing the last sample of the WAV has been consumed.
+        ///
+        /// An empty queue alone does not indicate the end of the stream, as the reader thread may
+        /// simply not have prepared the next buffer yet.
+        exhausted: bool,
+    },
+    /// A WAV decoded into memory by the `Cache`.
+    Cached {
+        samples: Arc<[f32]>,
+        index: usize,
+    },
+}
+
 impl Handle {
     /// Play the given sound.
     ///
-    /// When called, the reader thread will add an entry for this sound into the map and prepare
-    /// the first `NUM_BUFFERS` buffers by reading samples from the given `WavReader`.
+    /// If the WAV is cached, it is played directly from memory. Otherwise the reader thread will
+    /// add an entry for this sound into the map and prepare the first `NUM_BUFFERS` buffers by
+    /// reading samples from the WAV. If the WAV is small enough to be cached, the reader thread
+    /// also decodes it into the cache in the background so that later plays are served from
+    /// memory.
     pub fn play(
         &self,
         sound_id: sound::Id,
-        wav_path: &Path,
+        wav: &Wav,
         start_frame: u64,
         looped: bool,
     ) -> Result<SamplesStream, hound::Error>
     {
-        let reader = WavReader::open(wav_path)?;
+        // Determine whether or not the WAV may be cached from its metadata alone.
+        let wav_len_samples = wav.duration.samples() as usize * wav.channels;
+        let bytes = wav_len_samples * mem::size_of::<f32>();
+        let load = match self.cache.lookup(&wav.path, bytes) {
+            Lookup::Hit(cached) => {
+                return Ok(SamplesStream::from_cached(cached, start_frame, looped));
+            },
+            Lookup::Load => true,
+            Lookup::Stream => false,
+        };
+
+        let reader = match WavReader::open(&wav.path) {
+            Ok(reader) => reader,
+            Err(err) => {
+                // Allow a later play to attempt loading the WAV again.
+                if load {
+                    self.cache.remove(&wav.path);
+                }
+                return Err(err);
+            },
+        };
         let wav_len_samples = reader.len() as _;
         let buffer_queue = Arc::new(SegQueue::new());
         let buffer_tx = buffer_queue.clone();
@@ -190,11 +238,18 @@ impl Handle {
         let spec = reader.spec();
         let play = Play { reader, buffer_tx, start_frame, looped };
         let samples_stream = SamplesStream::new(buffer_rx, spec, wav_len_samples, looped);
-        let msg = Message::Play(sound_id, play);
-        self.tx.push(msg);
+        self.tx.push(Message::Play(sound_id, play));
+        if load {
+            self.tx.push(Message::Load(wav.path.clone()));
+        }
         Ok(samples_stream)
     }
 
+    /// The cache of WAVs decoded into memory.
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
     /// Stop reading the wav for the sound with the given `Id`.
     pub fn end(&self, sound_id: sound::Id) {
         let msg = Message::End(sound_id);
@@ -233,36 +288,85 @@ impl SamplesStream {
         wav_len_samples: usize,
         wav_looped: bool,
     ) -> Self {
-        SamplesStream {
+        let source = StreamSource::Reader {
             buffer_rx,
             buffer: RefCell::new(None),
             buffer_index: 0,
+            exhausted: false,
+        };
+        SamplesStream {
+            source,
             wav_spec,
             wav_len_samples,
             wav_looped,
         }
     }
 
+    /// Play a WAV decoded into memory, starting from the given frame.
+    ///
+    /// As with `play_sound`, a `start_frame` beyond the end of the WAV wraps around to the
+    /// beginning.
+    pub fn from_cached(cached: Cached, start_frame: u64, wav_looped: bool) -> Self {
+        let Cached { samples, spec } = cached;
+        let channels = spec.channels as usize;
+        let wav_len_samples = samples.len();
+        let duration_frames = (wav_len_samples / channels) as u64;
+        let index = match duration_frames {
+            0 => 0,
+            _ => (start_frame % duration_frames) as usize * channels,
+        };
+        let source = StreamSource::Cached { samples, index };
+        SamplesStream {
+            source,
+            wav_spec: spec,
+            wav_len_samples,
+            wav_looped,
+        }
+    }
+
     /// The number of channels in the source audio.
     pub fn channels(&self) -> usize {
         self.wav_spec.channels as _
     }
 
+    /// Whether or not every sample in the stream has been yielded.
+    ///
+    /// Always `false` for looped WAVs.
+    pub fn is_exhausted(&self) -> bool {
+        match self.source {
+            StreamSource::Reader { exhausted, .. } => exhausted,
+            StreamSource::Cached { ref samples, index } => {
+                !self.wav_looped && index >= samples.len()
+            },
+        }
+    }
+
     /// The number of frames remaining in the stream.
     pub fn remaining_frames(&self) -> Option<Samples> {
         if self.wav_looped {
             return None;
         }
+        let channels = self.wav_spec.channels as usize;
+        let (buffer_rx, buffer, buffer_index) = match self.source {
+            StreamSource::Reader { exhausted: true, .. } => return Some(Samples(0)),
+            StreamSource::Reader { ref buffer_rx, ref buffer, buffer_index, .. } => {
+                (buffer_rx, buffer, buffer_index)
+            },
+            StreamSource::Cached { index, .. } => {
+                let remaining_frames = ((self.wav_len_samples - index) / channels) as _;
+                return Some(Samples(remaining_frames));
+            },
+        };
         loop {
-            if let Some(ref buffer) = *self.buffer.borrow() {
+            if let Some(ref buffer) = *buffer.borrow() {
                 let remaining_samples =
-                    self.wav_len_samples - (buffer.info.samples_range.start + self.buffer_index);
-                let remaining_frames = (remaining_samples / self.wav_spec.channels as usize) as _;
+                    self.wav_len_samples - (buffer.info.samples_range.start + buffer_index);
+                let remaining_frames = (remaining_samples / channels) as _;
                 return Some(Samples(remaining_frames));
             }
 
-            let mut buffer_mut = self.buffer.borrow_mut();
-            *buffer_mut = match self.buffer_rx.try_pop() {
+            let mut buffer_mut = buffer.borrow_mut();
+            *buffer_mut = match buffer_rx.try_pop() {
                 None => return Some(Samples(self.wav_len_samples as _)),
                 Some(buffer) => Some(buffer),
             };
@@ -271,12 +375,29 @@ impl SamplesStream {
 
     /// The next sample in the stream.
     pub fn next_sample(&mut self) -> Option<f32> {
-        let SamplesStream {
-            ref buffer,
-            ref buffer_rx,
-            ref mut buffer_index,
-            ..
-        } = *self;
+        let wav_looped = self.wav_looped;
+        let wav_len_samples = self.wav_len_samples;
+        let (buffer, buffer_rx, buffer_index, exhausted) = match self.source {
+            StreamSource::Reader { exhausted: true, .. } => return None,
+            StreamSource::Reader {
+                ref buffer,
+                ref buffer_rx,
+                ref mut buffer_index,
+                ref mut exhausted,
+            } => (buffer, buffer_rx, buffer_index, exhausted),
+            // Cached samples are read directly, cycling back to the start if looped.
+            StreamSource::Cached { ref samples, ref mut index } => {
+                if *index >= samples.len() {
+                    if !wav_looped || samples.is_empty() {
+                        return None;
+                    }
+                    *index = 0;
+                }
+                let sample = samples[*index];
+                *index += 1;
+                return Some(sample);
+            },
+        };
 
         loop {
             // If there is a sample in the current buffer, return it.
@@ -292,11 +413,17 @@ impl SamplesStream {
             // This triggers the wav reader thread to re-use the buffer and enqueue it with more
             // samples.
             let mut buffer_mut = buffer.borrow_mut();
-            mem::drop(buffer_mut.take());
+            if let Some(consumed) = buffer_mut.take() {
+                *buffer_index = 0;
+                if !wav_looped && consumed.info.samples_range.end >= wav_len_samples {
+                    *exhausted = true;
+                    return None;
+                }
+            }
 
             // Receive the next buffer.
             *buffer_mut = match buffer_rx.try_pop() {
-                // If there are no more buffers, there must be no more samples so we're done.
+                // The reader thread has not yet prepared the next buffer.
                 None => return None,
                 // Otherwise reset
                 Some(buffer) => {
@@ -486,23 +613,26 @@ fn read_next_sample(
 
 /// Runs the wav reader thread and returns a handle to it that may be used to play or seek sounds
 /// via their unique `Id`.
-pub fn spawn() -> Handle {
+///
+/// WAVs small enough to be stored within the given `cache` bypass the reader thread entirely.
+pub fn spawn(cache: Cache) -> Handle {
     let queue = Arc::new(MsQueue::new());
     let tx = queue.clone();
     let rx = queue;
     let tx2 = tx.clone();
+    let cache2 = cache.clone();
     let thread = thread::Builder::new()
         .name("wav_reader".into())
-        .spawn(move || run(tx2, rx))
+        .spawn(move || run(tx2, rx, cache2))
         .unwrap();
     let thread = Arc::new(Mutex::new(Some(thread)));
-    Handle { tx, thread }
+    Handle { tx, cache, thread }
 }
 
 /// Run the parent wav reader loop.
 ///
 /// The parent maintains all state while the children perform all significant processing.
-fn run(tx: Tx, rx: Rx) {
+fn run(tx: Tx, rx: Rx, cache: Cache) {
     // Create a threadpool for processing `Play` messages.
     let children = num_cpus::get();
     let threadpool = ThreadPool::with_name("wav_reader_children".into(), children);
@@ -512,7 +642,8 @@ fn run(tx: Tx, rx: Rx) {
     for _ in 0..children {
         let queue = child_message_queue.clone();
         let parent_tx = tx.clone();
-        threadpool.execute(move || run_child(queue, parent_tx));
+        let cache = cache.clone();
+        threadpool.execute(move || run_child(queue, parent_tx, cache));
     }
 
     // Block on receiving messages.
@@ -575,6 +706,11 @@ fn run(tx: Tx, rx: Rx) {
                 *state = SoundState::Waiting(sound);
             },
 
+            // Enqueue the WAV for one of the child threads to decode.
+            Message::Load(path) => {
+                child_message_queue.push(ChildMessage::Load(path));
+            },
+
             // End the given sound by removing it from the map, dropping the reader and in turn
             // closing the underlying WAV file handle.
             Message::End(sound_id) => {
@@ -591,7 +727,7 @@ fn run(tx: Tx, rx: Rx) {
 
 /// Run the child thread, receiving child messages as quickly as possible and sending them back to
 /// the parent thread in their processed form.
-fn run_child(child_msg_queue: Arc<ChildMessageQueue>, parent_tx: Tx) {
+fn run_child(child_msg_queue: Arc<ChildMessageQueue>, parent_tx: Tx, cache: Cache) {
     loop {
         let msg = child_msg_queue.pop();
         match msg {
@@ -609,6 +745,54 @@ fn run_child(child_msg_queue: Arc<ChildMessageQueue>, parent_tx: Tx) {
                 let msg = Message::NextBufferComplete(sound_id, sound);
                 parent_tx.push(msg);
             },
+
+            // Decode the WAV into the cache. On failure the WAV is simply streamed as usual.
+            ChildMessage::Load(path) => {
+                if let Err(err) = cache.load(&path) {
+                    eprintln!("failed to cache WAV \"{}\": {}", path.display(), err);
+                }
+            },
         }
     }
 }
+
+#[test]
+fn test_rate_stream_from_reader() {
+    use super::rate::RateStream;
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 44_100,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let reader_tx: Tx = Arc::new(MsQueue::new());
+    let buffer_queue: BufferRx = Arc::new(SegQueue::new());
+    let buffer = |samples: Vec<f32>, samples_range: ops::Range<usize>| Buffer {
+        samples,
+        sound_id: sound::Id::INITIAL,
+        reader_tx: reader_tx.clone(),
+        info: BufferInfo { samples_range },
+    };
+    let stream = SamplesStream::new(buffer_queue.clone(), spec, 6, false);
+    let mut half = RateStream::new(stream, 0.5);
+
+    // Nothing is yielded until the reader thread queues the first buffer.
+    assert_eq!(half.next(), None);
+    assert!(!half.remaining_frames().map(|f| f == Samples(0)).unwrap_or(false));
+
+    // Waiting on the next buffer pauses the stream rather than ending it.
+    buffer_queue.push(buffer(vec![0.0, 1.0, 2.0], 0..3));
+    let first: Vec<f32> = half.by_ref().collect();
+    assert_eq!(first, vec![0.0, 0.5, 1.0, 1.5]);
+
+    // The stream resumes where it left off, holding the last frame.
+    buffer_queue.push(buffer(vec![3.0, 4.0, 5.0], 3..6));
+    let rest: Vec<f32> = half.by_ref().collect();
+    assert_eq!(rest, vec![2.0, 2.5, 3.0, 3.5, 4.0, 4.5, 5.0, 5.0]);
+    assert_eq!(half.remaining_frames(), Some(Samples(0)));
+
+    // Further buffers sent after the end of the WAV are ignored.
+    buffer_queue.push(buffer(vec![], 6..6));
+    assert_eq!(half.next(), None);
+}
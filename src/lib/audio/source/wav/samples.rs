@@ -15,6 +15,33 @@ where
         .map(|r| r.map(Sample::to_sample))
 }
 
+/// Read all remaining samples from the reader, calling `f` with each sample converted to `f32`.
+pub fn for_each<R, F>(reader: &mut hound::WavReader<R>, mut f: F) -> Result<(), hound::Error>
+where
+    R: io::Read,
+    F: FnMut(f32),
+{
+    // A macro to simplify reading all samples of the given type.
+    macro_rules! read_all {
+        ($T:ty) => {{
+            let mut wav_samples = reader.samples::<$T>();
+            while let Some(sample) = next(&mut wav_samples) {
+                f(sample?);
+            }
+        }};
+    }
+
+    let spec = reader.spec();
+    match (spec.sample_format, spec.bits_per_sample) {
+        (hound::SampleFormat::Float, 32) => read_all!(f32),
+        (hound::SampleFormat::Int, 8) => read_all!(i8),
+        (hound::SampleFormat::Int, 16) => read_all!(i16),
+        (hound::SampleFormat::Int, 32) => read_all!(i32),
+        _ => return Err(hound::Error::Unsupported),
+    }
+    Ok(())
+}
+
 /// The number of remaining samples in the reader from its current position.
 pub fn remaining<R>(reader: &mut hound::WavReader<R>) -> usize
 where
@@ -0,0 +1,197 @@
+//! A thread that watches the audio directory for WAVs that are added, changed or removed while the
+//! server is running.
+//!
+//! The directory is polled rather than relying on OS-specific notifications so that it behaves
+//! the same on all platforms and on network shares. A file is only reported once its size and
+//! modification time have remained unchanged for a full poll interval, so that WAVs are not
+//! loaded while they are still being copied into the directory.
+
+use fxhash::FxHashMap;
+use nannou;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// The interval at which the audio directory is scanned.
+const POLL_INTERVAL_MS: u64 = 1_000;
+
+/// A change to a WAV within the audio directory.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A new WAV was added.
+    Created(PathBuf),
+    /// The contents of an existing WAV changed.
+    Modified(PathBuf),
+    /// A WAV was removed.
+    Removed(PathBuf),
+}
+
+/// A handle to the watcher thread, used to receive `Event`s.
+pub struct Watcher {
+    event_rx: mpsc::Receiver<Event>,
+    exit_tx: mpsc::Sender<()>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// The state of a file at the time it was scanned.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct FileState {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Watcher {
+    /// Collect the next event, if any.
+    pub fn try_recv(&self) -> Option<Event> {
+        self.event_rx.try_recv().ok()
+    }
+
+    /// Stops the watcher thread and returns the raw handle to its thread.
+    pub fn exit(&mut self) -> Option<thread::JoinHandle<()>> {
+        // The thread may have already exited.
+        self.exit_tx.send(()).ok();
+        self.thread.take()
+    }
+}
+
+/// Spawn a thread that watches the given audio directory.
+///
+/// WAVs that already exist when the watcher is spawned are not reported as `Created`. The app is
+/// woken up each time changes are found so that the GUI may process them.
+pub fn spawn(audio_path: PathBuf, app_proxy: nannou::app::Proxy) -> Watcher {
+    let (event_tx, event_rx) = mpsc::channel();
+    let (exit_tx, exit_rx) = mpsc::channel();
+    let thread = thread::Builder::new()
+        .name("wav_watcher".into())
+        .spawn(move || run(audio_path, event_tx, exit_rx, app_proxy))
+        .unwrap();
+    let thread = Some(thread);
+    Watcher { event_rx, exit_tx, thread }
+}
+
+// Scan the directory at the poll interval, reporting changes until the `Watcher` exits or is
+// dropped.
+fn run(
+    audio_path: PathBuf,
+    event_tx: mpsc::Sender<Event>,
+    exit_rx: mpsc::Receiver<()>,
+    app_proxy: nannou::app::Proxy,
+) {
+    // The last reported state of each file.
+    let mut known = scan(&audio_path);
+    // Files that have changed since they were last reported but are not yet stable.
+    let mut pending: FxHashMap<PathBuf, FileState> = FxHashMap::default();
+
+    loop {
+        match exit_rx.recv_timeout(Duration::from_millis(POLL_INTERVAL_MS)) {
+            Err(mpsc::RecvTimeoutError::Timeout) => (),
+            _ => return,
+        }
+        let current = scan(&audio_path);
+        let events = changes(&mut known, &mut pending, &current);
+        if events.is_empty() {
+            continue;
+        }
+        for event in events {
+            if event_tx.send(event).is_err() {
+                return;
+            }
+        }
+        if app_proxy.wakeup().is_err() {
+            eprintln!("wav_watcher proxy could not wakeup app");
+        }
+    }
+}
+
+// Compare the `current` state of the directory with the `known` state, producing an event for
+// each file that has been removed or that has remained unchanged since it was last `pending`.
+fn changes(
+    known: &mut FxHashMap<PathBuf, FileState>,
+    pending: &mut FxHashMap<PathBuf, FileState>,
+    current: &FxHashMap<PathBuf, FileState>,
+) -> Vec<Event> {
+    let mut events = vec![];
+
+    for (path, state) in current.iter() {
+        if known.get(path) == Some(state) {
+            pending.remove(path);
+            continue;
+        }
+        // Only report the change once the file has stopped changing.
+        if pending.get(path) == Some(state) {
+            pending.remove(path);
+            let event = match known.insert(path.clone(), *state) {
+                None => Event::Created(path.clone()),
+                Some(_) => Event::Modified(path.clone()),
+            };
+            events.push(event);
+        } else {
+            pending.insert(path.clone(), *state);
+        }
+    }
+
+    // Any files that we know of that no longer exist have been removed.
+    let removed: Vec<PathBuf> = known
+        .keys()
+        .filter(|path| !current.contains_key(*path))
+        .cloned()
+        .collect();
+    for path in removed {
+        known.remove(&path);
+        events.push(Event::Removed(path));
+    }
+    pending.retain(|path, _| current.contains_key(path));
+
+    events
+}
+
+// The state of every WAV within the directory.
+//
+// Returns an empty map if the directory does not exist.
+fn scan(audio_path: &Path) -> FxHashMap<PathBuf, FileState> {
+    WalkDir::new(audio_path)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && super::is_wav_path(e.path()))
+        .filter_map(|e| {
+            let metadata = fs::metadata(e.path()).ok()?;
+            let len = metadata.len();
+            let modified = metadata.modified().ok();
+            Some((e.path().to_path_buf(), FileState { len, modified }))
+        })
+        .collect()
+}
+
+#[test]
+fn test_changes() {
+    let state = |len| FileState { len, modified: None };
+    let path = |name: &str| PathBuf::from(name);
+    let mut known = FxHashMap::default();
+    known.insert(path("a.wav"), state(1));
+    known.insert(path("b.wav"), state(1));
+    let mut pending = FxHashMap::default();
+
+    // New and changed files are only reported once they are unchanged for a full poll.
+    let mut current = known.clone();
+    current.insert(path("a.wav"), state(2));
+    current.insert(path("c.wav"), state(1));
+    assert!(changes(&mut known, &mut pending, &current).is_empty());
+
+    // A file that is still being written continues to wait.
+    current.insert(path("c.wav"), state(2));
+    let events = changes(&mut known, &mut pending, &current);
+    assert_eq!(events, vec![Event::Modified(path("a.wav"))]);
+    let events = changes(&mut known, &mut pending, &current);
+    assert_eq!(events, vec![Event::Created(path("c.wav"))]);
+    assert!(changes(&mut known, &mut pending, &current).is_empty());
+
+    // Removed files are reported immediately.
+    current.remove(&path("b.wav"));
+    let events = changes(&mut known, &mut pending, &current);
+    assert_eq!(events, vec![Event::Removed(path("b.wav"))]);
+    assert!(pending.is_empty());
+}
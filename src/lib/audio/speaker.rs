@@ -1,5 +1,5 @@
 use audio;
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use installation;
 use metres::Metres;
 use nannou::math::Point2;
@@ -37,3 +37,19 @@ pub fn dbap_weight(
         },
     }
 }
+
+/// The gain applied to a speaker due to the occupancy of its assigned installations.
+///
+/// Speakers shared between installations follow the loudest of them. Speakers without any
+/// installations, and installations without a gain, have unity gain.
+pub fn installation_gain(
+    speaker_installations: &FxHashSet<installation::Id>,
+    installation_gains: &FxHashMap<installation::Id, f32>,
+) -> f32
+{
+    speaker_installations
+        .iter()
+        .map(|id| installation_gains.get(id).cloned().unwrap_or(1.0))
+        .fold(None, |max: Option<f32>, gain| Some(max.map(|m| m.max(gain)).unwrap_or(gain)))
+        .unwrap_or(1.0)
+}
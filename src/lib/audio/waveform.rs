@@ -0,0 +1,396 @@
+//! Waveform overviews of WAV sources for display within the GUI.
+//!
+//! Each WAV is summarised as a series of min/max peaks at several zoom levels so that the
+//! waveform may be drawn at any width without reading the WAV itself. Summaries are generated on
+//! a dedicated background thread and cached on disk within the project directory, so that each
+//! WAV need only be read once.
+
+use audio::source::{self, wav};
+use fxhash::FxHasher;
+use hound;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+/// The number of frames summarised by each peak at the finest zoom level.
+pub const FINEST_FRAMES_PER_PEAK: usize = 256;
+
+/// Each zoom level summarises this many times more frames per peak than the last.
+pub const LEVEL_SCALE: usize = 4;
+
+/// The number of zoom levels generated for each WAV.
+pub const NUM_LEVELS: usize = 5;
+
+/// The name of the directory within the project directory in which peaks are cached.
+const CACHE_DIRECTORY_STEM: &'static str = "waveforms";
+const CACHE_EXTENSION: &'static str = "peaks";
+
+// Identifies peak cache files and their format version.
+const MAGIC: &'static [u8; 4] = b"BPWF";
+const VERSION: u32 = 1;
+
+/// The minimum and maximum sample values across all channels of a range of frames.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Peak {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// The peaks of an entire WAV at a single zoom level.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    /// The number of frames summarised by each peak.
+    pub frames_per_peak: usize,
+    pub peaks: Vec<Peak>,
+}
+
+/// The peaks of an entire WAV at each zoom level, finest first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Peaks {
+    /// The total number of frames within the WAV.
+    pub frames: usize,
+    pub levels: Vec<Level>,
+}
+
+// The size and modification time of a WAV, used to invalidate cached peaks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+/// A request for the waveform thread.
+struct Request {
+    id: source::Id,
+    path: PathBuf,
+    cache_path: PathBuf,
+}
+
+/// The peaks of a WAV, sent back from the waveform thread.
+pub struct Report {
+    /// The source whose WAV was summarised.
+    pub id: source::Id,
+    /// The path of the summarised WAV.
+    pub path: PathBuf,
+    /// The peaks, or a description of why they could not be generated.
+    pub result: Result<Peaks, String>,
+}
+
+/// A handle to the waveform thread.
+pub struct Generator {
+    request_tx: mpsc::Sender<Request>,
+    report_rx: mpsc::Receiver<Report>,
+}
+
+impl Peak {
+    /// A peak that contains no samples.
+    pub const EMPTY: Peak = Peak { min: 0.0, max: 0.0 };
+
+    /// The smallest peak containing both `self` and `other`.
+    pub fn merge(self, other: Peak) -> Peak {
+        Peak {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+impl Peaks {
+    /// Read the WAV at the given path and summarise it at each zoom level.
+    pub fn from_wav(path: &Path) -> Result<Self, hound::Error> {
+        let mut reader = wav::reader::WavReader::open(path)?;
+        let channels = reader.spec().channels as usize;
+        let samples_per_peak = FINEST_FRAMES_PER_PEAK * channels;
+        let mut finest = Vec::with_capacity(reader.len() as usize / samples_per_peak + 1);
+        let mut current: Option<Peak> = None;
+        let mut count = 0;
+        let mut samples = 0;
+        wav::samples::for_each(&mut reader, |sample| {
+            let peak = Peak { min: sample, max: sample };
+            current = Some(current.map_or(peak, |p| p.merge(peak)));
+            count += 1;
+            samples += 1;
+            if count == samples_per_peak {
+                finest.extend(current.take());
+                count = 0;
+            }
+        })?;
+        finest.extend(current);
+        let frames = if channels == 0 { 0 } else { samples / channels };
+        Ok(Self::from_finest(frames, finest))
+    }
+
+    // Build the coarser levels from the finest level of peaks.
+    fn from_finest(frames: usize, finest: Vec<Peak>) -> Self {
+        let mut levels = vec![Level { frames_per_peak: FINEST_FRAMES_PER_PEAK, peaks: finest }];
+        while levels.len() < NUM_LEVELS {
+            let level = {
+                let last = levels.last().unwrap();
+                let peaks = last.peaks
+                    .chunks(LEVEL_SCALE)
+                    .map(|chunk| chunk.iter().fold(chunk[0], |a, &b| a.merge(b)))
+                    .collect();
+                let frames_per_peak = last.frames_per_peak * LEVEL_SCALE;
+                Level { frames_per_peak, peaks }
+            };
+            levels.push(level);
+        }
+        Peaks { frames, levels }
+    }
+
+    /// The coarsest level that still provides at least one peak per column when the given number
+    /// of frames is drawn across each column.
+    pub fn level(&self, frames_per_column: f64) -> &Level {
+        self.levels
+            .iter()
+            .rev()
+            .find(|level| level.frames_per_peak as f64 <= frames_per_column)
+            .unwrap_or(&self.levels[0])
+    }
+
+    /// Summarise the given range of frames into the given number of columns for drawing.
+    ///
+    /// Columns that lie beyond the end of the WAV are `Peak::EMPTY`.
+    pub fn columns(&self, start_frame: usize, end_frame: usize, num_columns: usize) -> Vec<Peak> {
+        if num_columns == 0 || end_frame <= start_frame {
+            return vec![];
+        }
+        let frames_per_column = (end_frame - start_frame) as f64 / num_columns as f64;
+        let level = self.level(frames_per_column);
+        let fpp = level.frames_per_peak as f64;
+        (0..num_columns)
+            .map(|i| {
+                let start = start_frame as f64 + i as f64 * frames_per_column;
+                let end = start + frames_per_column;
+                let start_peak = (start / fpp) as usize;
+                let end_peak = ((end / fpp).ceil() as usize).max(start_peak + 1);
+                let end_peak = end_peak.min(level.peaks.len());
+                if start_peak >= end_peak {
+                    return Peak::EMPTY;
+                }
+                let peaks = &level.peaks[start_peak..end_peak];
+                peaks.iter().fold(peaks[0], |a, &b| a.merge(b))
+            })
+            .collect()
+    }
+}
+
+impl Generator {
+    /// Request the peaks for the WAV of the given source.
+    ///
+    /// Peaks are loaded from the cache within the given project directory if they are up to date
+    /// with the WAV, otherwise they are generated and written to the cache. The result may be
+    /// collected via `try_recv`.
+    pub fn request(&self, id: source::Id, path: PathBuf, project_directory: &Path) {
+        let cache_path = cache_path(project_directory, &path);
+        self.request_tx.send(Request { id, path, cache_path }).ok();
+    }
+
+    /// Collect the next finished set of peaks, if any.
+    pub fn try_recv(&self) -> Option<Report> {
+        self.report_rx.try_recv().ok()
+    }
+}
+
+/// Spawn the waveform thread.
+///
+/// WAVs are summarised one at a time in the order in which they are requested.
+pub fn spawn() -> Generator {
+    let (request_tx, request_rx) = mpsc::channel::<Request>();
+    let (report_tx, report_rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("waveform_peaks".into())
+        .spawn(move || {
+            for Request { id, path, cache_path } in request_rx {
+                let result = load_or_generate(&path, &cache_path);
+                if report_tx.send(Report { id, path, result }).is_err() {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+    Generator { request_tx, report_rx }
+}
+
+/// The path at which the peaks for the given WAV are cached within the project directory.
+///
+/// The file name includes a hash of the full WAV path so that WAVs with the same name in
+/// different directories do not collide.
+pub fn cache_path(project_directory: &Path, wav_path: &Path) -> PathBuf {
+    let mut hasher = FxHasher::default();
+    wav_path.hash(&mut hasher);
+    let stem = wav_path.file_stem().and_then(|s| s.to_str()).unwrap_or("wav");
+    let file_name = format!("{}-{:016x}", stem, hasher.finish());
+    project_directory
+        .join(CACHE_DIRECTORY_STEM)
+        .join(file_name)
+        .with_extension(CACHE_EXTENSION)
+}
+
+// Load the cached peaks if they are up to date, otherwise generate and cache them.
+fn load_or_generate(path: &Path, cache_path: &Path) -> Result<Peaks, String> {
+    let stamp = file_stamp(path).map_err(|err| format!("{}", err))?;
+    if let Ok(peaks) = load(cache_path, &stamp) {
+        return Ok(peaks);
+    }
+    let peaks = Peaks::from_wav(path).map_err(|err| format!("{}", err))?;
+    if let Err(err) = save(cache_path, &stamp, &peaks) {
+        eprintln!("failed to cache waveform peaks at \"{}\": {}", cache_path.display(), err);
+    }
+    Ok(peaks)
+}
+
+fn file_stamp(path: &Path) -> io::Result<FileStamp> {
+    let metadata = fs::metadata(path)?;
+    let len = metadata.len();
+    let (modified_secs, modified_nanos) = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| (d.as_secs(), d.subsec_nanos()))
+        .unwrap_or((0, 0));
+    Ok(FileStamp { len, modified_secs, modified_nanos })
+}
+
+// Peaks are stored as a small header followed by each level's peaks quantised to `i16`, all
+// little-endian.
+
+fn save(cache_path: &Path, stamp: &FileStamp, peaks: &Peaks) -> io::Result<()> {
+    if let Some(dir) = cache_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut w = BufWriter::new(File::create(cache_path)?);
+    w.write_all(MAGIC)?;
+    write_u32(&mut w, VERSION)?;
+    write_u64(&mut w, stamp.len)?;
+    write_u64(&mut w, stamp.modified_secs)?;
+    write_u32(&mut w, stamp.modified_nanos)?;
+    write_u64(&mut w, peaks.frames as u64)?;
+    write_u32(&mut w, peaks.levels.len() as u32)?;
+    for level in &peaks.levels {
+        write_u32(&mut w, level.frames_per_peak as u32)?;
+        write_u32(&mut w, level.peaks.len() as u32)?;
+        for peak in &level.peaks {
+            write_u16(&mut w, quantise(peak.min) as u16)?;
+            write_u16(&mut w, quantise(peak.max) as u16)?;
+        }
+    }
+    w.flush()
+}
+
+fn load(cache_path: &Path, stamp: &FileStamp) -> io::Result<Peaks> {
+    fn invalid(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+    let mut r = BufReader::new(File::open(cache_path)?);
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC || read_u32(&mut r)? != VERSION {
+        return Err(invalid("not a peaks file of the current version"));
+    }
+    let cached_stamp = FileStamp {
+        len: read_u64(&mut r)?,
+        modified_secs: read_u64(&mut r)?,
+        modified_nanos: read_u32(&mut r)?,
+    };
+    if &cached_stamp != stamp {
+        return Err(invalid("the WAV has changed since the peaks were cached"));
+    }
+    let frames = read_u64(&mut r)? as usize;
+    let num_levels = read_u32(&mut r)? as usize;
+    let mut levels = Vec::with_capacity(num_levels);
+    for _ in 0..num_levels {
+        let frames_per_peak = read_u32(&mut r)? as usize;
+        let num_peaks = read_u32(&mut r)? as usize;
+        let mut peaks = Vec::with_capacity(num_peaks);
+        for _ in 0..num_peaks {
+            let min = dequantise(read_u16(&mut r)? as i16);
+            let max = dequantise(read_u16(&mut r)? as i16);
+            peaks.push(Peak { min, max });
+        }
+        levels.push(Level { frames_per_peak, peaks });
+    }
+    if levels.is_empty() {
+        return Err(invalid("no levels"));
+    }
+    Ok(Peaks { frames, levels })
+}
+
+fn quantise(sample: f32) -> i16 {
+    (sample.max(-1.0).min(1.0) * ::std::i16::MAX as f32).round() as i16
+}
+
+fn dequantise(sample: i16) -> f32 {
+    sample as f32 / ::std::i16::MAX as f32
+}
+
+fn write_u16<W: Write>(w: &mut W, n: u16) -> io::Result<()> {
+    w.write_all(&[n as u8, (n >> 8) as u8])
+}
+
+fn write_u32<W: Write>(w: &mut W, n: u32) -> io::Result<()> {
+    write_u16(w, n as u16)?;
+    write_u16(w, (n >> 16) as u16)
+}
+
+fn write_u64<W: Write>(w: &mut W, n: u64) -> io::Result<()> {
+    write_u32(w, n as u32)?;
+    write_u32(w, (n >> 32) as u32)
+}
+
+fn read_u16<R: Read>(r: &mut R) -> io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let lo = read_u16(r)? as u32;
+    let hi = read_u16(r)? as u32;
+    Ok(lo | hi << 16)
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let lo = read_u32(r)? as u64;
+    let hi = read_u32(r)? as u64;
+    Ok(lo | hi << 32)
+}
+
+#[test]
+fn test_peaks_columns_and_cache_round_trip() {
+    // A ramp from -1.0 to 1.0 over 8192 frames.
+    let frames = 8192;
+    let finest = (0..frames / FINEST_FRAMES_PER_PEAK)
+        .map(|i| {
+            let f = |frame: usize| frame as f32 / frames as f32 * 2.0 - 1.0;
+            let start = i * FINEST_FRAMES_PER_PEAK;
+            Peak { min: f(start), max: f(start + FINEST_FRAMES_PER_PEAK - 1) }
+        })
+        .collect();
+    let peaks = Peaks::from_finest(frames, finest);
+    assert_eq!(peaks.levels.len(), NUM_LEVELS);
+    assert_eq!(peaks.levels[1].peaks.len(), 8);
+    assert_eq!(peaks.level(2048.0).frames_per_peak, 1024);
+
+    let columns = peaks.columns(0, frames, 2);
+    assert_eq!(columns[0].min, -1.0);
+    assert!(columns[0].max < 0.0 && columns[1].min >= 0.0);
+    assert!(columns[1].max > 0.99);
+
+    let dir = ::std::env::temp_dir().join("audio_server_waveform_test");
+    let cache_path = cache_path(&dir, Path::new("ramp.wav"));
+    let stamp = FileStamp { len: 1, modified_secs: 2, modified_nanos: 3 };
+    save(&cache_path, &stamp, &peaks).unwrap();
+    let loaded = load(&cache_path, &stamp).unwrap();
+    assert_eq!(loaded.frames, frames);
+    assert_eq!(loaded.levels.len(), NUM_LEVELS);
+    assert!((loaded.levels[0].peaks[0].min - peaks.levels[0].peaks[0].min).abs() < 1e-4);
+    let changed = FileStamp { len: 2, ..stamp };
+    assert!(load(&cache_path, &changed).is_err());
+    fs::remove_dir_all(&dir).ok();
+}
@@ -13,6 +13,14 @@ pub struct Config {
     /// Whether or not CPU saving mode is enabled upon opening the server.
     #[serde(default = "default::cpu_saving_mode")]
     pub cpu_saving_mode: bool,
+    /// WAVs whose decoded size in bytes is no greater than this are played from memory.
+    ///
+    /// Set to `0` to stream all WAVs from disk.
+    #[serde(default = "default::wav_cache_max_file_bytes")]
+    pub wav_cache_max_file_bytes: usize,
+    /// The maximum number of bytes of decoded samples held by the WAV cache.
+    #[serde(default = "default::wav_cache_budget_bytes")]
+    pub wav_cache_budget_bytes: usize,
 }
 
 impl Default for Config {
@@ -20,7 +28,15 @@ impl Default for Config {
         let project_default = Default::default();
         let selected_project_slug = default::project_slug();
         let cpu_saving_mode = Default::default();
-        Config { project_default, selected_project_slug, cpu_saving_mode }
+        let wav_cache_max_file_bytes = default::wav_cache_max_file_bytes();
+        let wav_cache_budget_bytes = default::wav_cache_budget_bytes();
+        Config {
+            project_default,
+            selected_project_slug,
+            cpu_saving_mode,
+            wav_cache_max_file_bytes,
+            wav_cache_budget_bytes,
+        }
     }
 }
 
@@ -41,4 +57,13 @@ mod default {
     pub fn cpu_saving_mode() -> bool {
         false
     }
+
+    pub fn wav_cache_max_file_bytes() -> usize {
+        // Roughly 5 seconds of stereo audio.
+        2 * 1024 * 1024
+    }
+
+    pub fn wav_cache_budget_bytes() -> usize {
+        256 * 1024 * 1024
+    }
 }
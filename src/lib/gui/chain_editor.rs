@@ -0,0 +1,651 @@
+//! A `Chains` panel displaying:
+//!
+//! - A list for adding and removing chains of sources.
+//! - The name and start source of the selected chain along with a button for triggering it.
+//! - The links of the selected chain, each with its trigger, probability, delay and position.
+
+use audio;
+use gui::{collapsible_area, Channels, Gui, ProjectState, State};
+use gui::{DARK_A, ITEM_HEIGHT, SMALL_FONT_SIZE};
+use project::{self, Project};
+use nannou::ui;
+use nannou::ui::prelude::*;
+use soundscape::chains::{self, Chain, Chains, Link, Trigger};
+use time_calc::Ms;
+use utils::SEC_MS;
+
+/// GUI state related to the chain editor area.
+#[derive(Default)]
+pub struct ChainEditor {
+    pub selected: Option<Selected>,
+}
+
+/// The currently selected chain.
+pub struct Selected {
+    index: usize,
+    name: String,
+}
+
+// The moments within a sound at which a link may be evaluated.
+const TRIGGER_KINDS: &'static [&'static str] = &["On End", "At Progress"];
+
+// The maximum delay selectable via the delay slider of each link.
+const MAX_DELAY_SECS: f64 = 60.0;
+
+// The width of the button for triggering the selected chain.
+const TRIGGER_BUTTON_W: Scalar = 80.0;
+
+impl Selected {
+    fn new(index: usize, chain: &Chain) -> Self {
+        let name = chain.name.clone();
+        Selected { index, name }
+    }
+}
+
+/// Sets all widgets in the chain area and returns the `Id` of the last area.
+pub fn set(
+    last_area_id: widget::Id,
+    gui: &mut Gui,
+    project: &mut Project,
+    project_state: &mut ProjectState,
+) -> widget::Id {
+    let Gui {
+        ref mut ui,
+        ref mut ids,
+        channels,
+        state: &mut State {
+            ref mut is_open,
+            ..
+        },
+        ..
+    } = *gui;
+
+    let Project {
+        state: project::State {
+            ref mut chains,
+            ref sources,
+            ..
+        },
+        ..
+    } = *project;
+
+    let ProjectState {
+        ref mut chain_editor,
+        ..
+    } = *project_state;
+
+    // Constants to use as widget heights.
+    const PAD: Scalar = 6.0;
+    const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
+    const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const PLUS_CHAIN_H: Scalar = ITEM_HEIGHT;
+    const CHAIN_LIST_MAX_H: Scalar = ITEM_HEIGHT * 4.0;
+    const CHAIN_CANVAS_H: Scalar =
+        PAD + TITLE_H + PAD * 2.0 + PLUS_CHAIN_H + CHAIN_LIST_MAX_H + PAD;
+    const SELECTED_CANVAS_BASE_H: Scalar = PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD
+        + ITEM_HEIGHT + PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD;
+    const LINK_ROW_H: Scalar = TITLE_H + PAD
+        + ITEM_HEIGHT + PAD + ITEM_HEIGHT + PAD + ITEM_HEIGHT + PAD
+        + SLIDER_H + PAD + SLIDER_H + PAD;
+    const LINK_PROGRESS_H: Scalar = SLIDER_H + PAD;
+
+    // Unselect the chain if it no longer exists.
+    if chain_editor.selected.as_ref().map(|s| s.index >= chains.len()) == Some(true) {
+        chain_editor.selected = None;
+    }
+
+    // The height of the selected chain canvas depends on its number of links.
+    let selected_canvas_h = match chain_editor.selected {
+        None => 0.0,
+        Some(ref selected) => {
+            let links = &chains[selected.index].links;
+            let num_progresses = links.iter().filter(|l| is_progress(&l.trigger)).count();
+            SELECTED_CANVAS_BASE_H
+                + LINK_ROW_H * links.len() as Scalar
+                + LINK_PROGRESS_H * num_progresses as Scalar
+                + PAD
+        },
+    };
+    let chain_editor_canvas_h = PAD + CHAIN_CANVAS_H + PAD + selected_canvas_h;
+
+    // The collapsible area.
+    let (area, event) = collapsible_area(is_open.chain_editor, "Chain Editor", ids.side_menu)
+        .align_middle_x_of(ids.side_menu)
+        .down_from(last_area_id, 0.0)
+        .set(ids.chain_editor, ui);
+    if let Some(event) = event {
+        is_open.chain_editor = event.is_open();
+    }
+
+    // If the area is open, get the area.
+    let area = match area {
+        Some(area) => area,
+        None => return ids.chain_editor,
+    };
+
+    // The canvas on which the chain editor will be placed.
+    let canvas = widget::Canvas::new()
+        .pad(PAD)
+        .h(chain_editor_canvas_h);
+    area.set(canvas, ui);
+
+    ////////////
+    // CHAINS //
+    ////////////
+
+    // A canvas on which chain selection takes place.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(CHAIN_CANVAS_H)
+        .mid_top_of(area.id)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.chain_editor_list_canvas, ui);
+
+    widget::Text::new("Chains")
+        .top_left_of(ids.chain_editor_list_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.chain_editor_list_text, ui);
+
+    // A button for adding new chains.
+    for _click in widget::Button::new()
+        .label("+")
+        .kid_area_w_of(ids.chain_editor_list_canvas)
+        .h(PLUS_CHAIN_H)
+        .align_middle_x_of(ids.chain_editor_list_canvas)
+        .down(PAD * 2.0)
+        .set(ids.chain_editor_add, ui)
+    {
+        let chain = Chain {
+            name: "<unnamed>".to_string(),
+            ..Default::default()
+        };
+        let index = chains.len();
+        chain_editor.selected = Some(Selected::new(index, &chain));
+        chains.push(chain);
+        send_chains(channels, chains);
+    }
+
+    // If there are no chains, display some text for adding a chain.
+    if chains.is_empty() {
+        widget::Text::new("Add a chain with the \"+\" button above!")
+            .font_size(SMALL_FONT_SIZE)
+            .align_middle_x_of(ids.chain_editor_list_canvas)
+            .down(PAD + ITEM_HEIGHT)
+            .set(ids.chain_editor_none, ui);
+        return area.id;
+    }
+
+    // A list of all chains.
+    let num_chains = chains.len();
+    let (mut events, scrollbar) = widget::ListSelect::single(num_chains)
+        .down(0.0)
+        .flow_down()
+        .item_size(ITEM_HEIGHT)
+        .h(CHAIN_LIST_MAX_H)
+        .kid_area_w_of(ids.chain_editor_list_canvas)
+        .scrollbar_next_to()
+        .set(ids.chain_editor_list, ui);
+
+    // Track whether or not an item was removed.
+    let mut maybe_remove_index = None;
+    let selected_index = chain_editor.selected.as_ref().map(|s| s.index);
+    while let Some(event) = events.next(ui, |i| Some(i) == selected_index) {
+        use self::ui::widget::list_select::Event;
+        match event {
+            // Instantiate the widget for this item.
+            Event::Item(item) => {
+                let is_selected = selected_index == Some(item.i);
+
+                // Blue if selected, gray otherwise.
+                let color = if is_selected {
+                    color::BLUE
+                } else {
+                    color::DARK_CHARCOAL
+                };
+
+                let chain = &chains[item.i];
+                let label = format!("{} ({} links)", chain.name, chain.links.len());
+
+                // Use a button widget for each item.
+                let label_x = position::Relative::Place(position::Place::Start(Some(10.0)));
+                let button = widget::Button::new()
+                    .label(&label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .label_x(label_x)
+                    .color(color);
+                item.set(button, ui);
+
+                // If the button or any of its children are capturing the mouse, display
+                // the `remove` button.
+                let show_remove_button = ui.global_input()
+                    .current
+                    .widget_capturing_mouse
+                    .map(|id| {
+                        id == item.widget_id
+                            || ui.widget_graph()
+                                .does_recursive_depth_edge_exist(item.widget_id, id)
+                    })
+                    .unwrap_or(false);
+
+                if !show_remove_button {
+                    continue;
+                }
+
+                if widget::Button::new()
+                    .label("X")
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(color::DARK_RED.alpha(0.5))
+                    .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+                    .align_right_of(item.widget_id)
+                    .align_middle_y_of(item.widget_id)
+                    .parent(item.widget_id)
+                    .set(ids.chain_editor_remove, ui)
+                    .was_clicked()
+                {
+                    maybe_remove_index = Some(item.i);
+                }
+            },
+
+            // Update the selected chain.
+            Event::Selection(idx) => {
+                chain_editor.selected = Some(Selected::new(idx, &chains[idx]));
+            },
+
+            _ => (),
+        }
+    }
+
+    // The scrollbar for the list.
+    if let Some(s) = scrollbar {
+        s.set(ui);
+    }
+
+    // Remove a chain if necessary, shifting the selection to follow the selected chain.
+    if let Some(i) = maybe_remove_index {
+        chains.remove(i);
+        let selected_index = chain_editor.selected.as_ref().map(|s| s.index);
+        match selected_index {
+            Some(index) if index == i => chain_editor.selected = None,
+            Some(index) if index > i => {
+                chain_editor.selected.as_mut().unwrap().index -= 1;
+            },
+            _ => (),
+        }
+        send_chains(channels, chains);
+    }
+
+    ////////////////////
+    // SELECTED CHAIN //
+    ////////////////////
+
+    // Only continue if there is some selected chain.
+    let ChainEditor { ref mut selected } = *chain_editor;
+    let selected = match selected.as_mut() {
+        Some(selected) => selected,
+        None => return area.id,
+    };
+    let index = selected.index;
+
+    // A canvas for parameters specific to the selected chain.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(selected_canvas_h - PAD)
+        .align_middle_x_of(area.id)
+        .down_from(ids.chain_editor_list_canvas, PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.chain_editor_selected_canvas, ui);
+    let selected_canvas_kid_area = ui.kid_area_of(ids.chain_editor_selected_canvas).unwrap();
+
+    widget::Text::new("Selected Chain")
+        .top_left_of(ids.chain_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.chain_editor_selected_text, ui);
+
+    // Display a textbox for editing the name.
+    for event in widget::TextBox::new(&selected.name)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.chain_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::BLACK)
+        .set(ids.chain_editor_selected_name, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp selected name.
+            Event::Update(new_name) => {
+                selected.name = new_name;
+            },
+            // Only when enter is pressed do we update the actual name.
+            Event::Enter => {
+                chains[index].name = selected.name.clone();
+                send_chains(channels, chains);
+            },
+        }
+    }
+
+    // Collect the soundscape sources available to links, sorted by name.
+    let mut soundscape_sources: Vec<(audio::source::Id, String)> = sources
+        .iter()
+        .filter(|&(_, source)| match source.role {
+            Some(audio::source::Role::Soundscape(_)) => true,
+            _ => false,
+        })
+        .map(|(&id, source)| (id, source.name.clone()))
+        .collect();
+    soundscape_sources.sort_by(|a, b| a.1.cmp(&b.1));
+    let (source_ids, labels): (Vec<_>, Vec<_>) = soundscape_sources.into_iter().unzip();
+
+    // The source played when the chain is triggered.
+    let start_w = selected_canvas_kid_area.w() - TRIGGER_BUTTON_W - PAD;
+    let selected_start = chains[index]
+        .start
+        .and_then(|start| source_ids.iter().position(|&id| id == start));
+    for source_index in widget::DropDownList::new(&labels, selected_start)
+        .align_left()
+        .down(PAD)
+        .w_h(start_w, ITEM_HEIGHT)
+        .label("START SOURCE")
+        .label_font_size(SMALL_FONT_SIZE)
+        .scrollbar_on_top()
+        .max_visible_items(5)
+        .color(DARK_A)
+        .border_color(color::LIGHT_CHARCOAL)
+        .set(ids.chain_editor_selected_start, ui)
+    {
+        chains[index].start = Some(source_ids[source_index]);
+        send_chains(channels, chains);
+    }
+
+    // A button for triggering the chain from its start source.
+    for _click in widget::Button::new()
+        .label("Trigger")
+        .label_font_size(SMALL_FONT_SIZE)
+        .w_h(TRIGGER_BUTTON_W, ITEM_HEIGHT)
+        .right(PAD)
+        .color(color::DARK_GREEN)
+        .set(ids.chain_editor_selected_trigger, ui)
+    {
+        let name = chains[index].name.clone();
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.trigger_chain(&name);
+            })
+            .expect("failed to send chain trigger to soundscape thread");
+    }
+
+    ///////////
+    // LINKS //
+    ///////////
+
+    widget::Text::new("Links")
+        .align_left_of(ids.chain_editor_selected_start)
+        .down_from(ids.chain_editor_selected_start, PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.chain_editor_selected_links_text, ui);
+
+    // A button for adding new links, from the start source by default.
+    for _click in widget::Button::new()
+        .label("ADD LINK")
+        .label_font_size(SMALL_FONT_SIZE)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.chain_editor_selected_canvas)
+        .color(DARK_A)
+        .set(ids.chain_editor_selected_add_link, ui)
+    {
+        let link = match new_link(&chains[index], &source_ids) {
+            Some(link) => link,
+            None => continue,
+        };
+        chains[index].links.push(link);
+        send_chains(channels, chains);
+    }
+
+    // A row for each link displaying its sources, its trigger, its probability and its delay.
+    let num_links = chains[index].links.len();
+    if ids.chain_editor_link_texts.len() < num_links {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.chain_editor_link_texts.resize(num_links, id_gen);
+        ids.chain_editor_link_froms.resize(num_links, id_gen);
+        ids.chain_editor_link_removes.resize(num_links, id_gen);
+        ids.chain_editor_link_tos.resize(num_links, id_gen);
+        ids.chain_editor_link_triggers.resize(num_links, id_gen);
+        ids.chain_editor_link_same_positions.resize(num_links, id_gen);
+        ids.chain_editor_link_progresses.resize(num_links, id_gen);
+        ids.chain_editor_link_probabilities.resize(num_links, id_gen);
+        ids.chain_editor_link_delays.resize(num_links, id_gen);
+    }
+    let kid_w = selected_canvas_kid_area.w();
+    let source_w = kid_w - ITEM_HEIGHT - PAD;
+    let half_w = (kid_w - PAD) / 2.0;
+    let mut last_row_id = ids.chain_editor_selected_add_link;
+    let mut maybe_remove_index = None;
+    for i in 0..num_links {
+        let mut link = chains[index].links[i].clone();
+
+        let description = link_description(&link, sources);
+        widget::Text::new(&description)
+            .align_left_of(ids.chain_editor_selected_add_link)
+            .down_from(last_row_id, PAD)
+            .font_size(SMALL_FONT_SIZE)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_texts[i], ui);
+
+        // The source whose sounds trigger the link.
+        let selected_from = source_ids.iter().position(|&id| id == link.from);
+        for source_index in widget::DropDownList::new(&labels, selected_from)
+            .align_left()
+            .down(PAD)
+            .w_h(source_w, ITEM_HEIGHT)
+            .label_font_size(SMALL_FONT_SIZE)
+            .scrollbar_on_top()
+            .max_visible_items(5)
+            .color(DARK_A)
+            .border_color(color::LIGHT_CHARCOAL)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_froms[i], ui)
+        {
+            link.from = source_ids[source_index];
+        }
+
+        // A button for removing the link.
+        if widget::Button::new()
+            .label("X")
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::DARK_RED.alpha(0.5))
+            .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+            .right(PAD)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_removes[i], ui)
+            .was_clicked()
+        {
+            maybe_remove_index = Some(i);
+        }
+
+        // The source from which a sound is spawned when the link is followed.
+        let selected_to = source_ids.iter().position(|&id| id == link.to);
+        for source_index in widget::DropDownList::new(&labels, selected_to)
+            .align_left_of(ids.chain_editor_link_froms[i])
+            .down_from(ids.chain_editor_link_froms[i], PAD)
+            .w_h(source_w, ITEM_HEIGHT)
+            .label_font_size(SMALL_FONT_SIZE)
+            .scrollbar_on_top()
+            .max_visible_items(5)
+            .color(DARK_A)
+            .border_color(color::LIGHT_CHARCOAL)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_tos[i], ui)
+        {
+            link.to = source_ids[source_index];
+        }
+
+        // The moment within the `from` sound at which the link is evaluated.
+        let selected_trigger = match link.trigger {
+            Trigger::End => 0,
+            Trigger::Progress(_) => 1,
+        };
+        for kind in widget::DropDownList::new(TRIGGER_KINDS, Some(selected_trigger))
+            .align_left_of(ids.chain_editor_link_tos[i])
+            .down_from(ids.chain_editor_link_tos[i], PAD)
+            .w_h(half_w, ITEM_HEIGHT)
+            .label_font_size(SMALL_FONT_SIZE)
+            .scrollbar_on_top()
+            .color(DARK_A)
+            .border_color(color::LIGHT_CHARCOAL)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_triggers[i], ui)
+        {
+            link.trigger = match (kind, link.trigger) {
+                (0, _) => Trigger::End,
+                (_, Trigger::Progress(progress)) => Trigger::Progress(progress),
+                _ => Trigger::Progress(chains::default::PROGRESS),
+            };
+        }
+
+        // Whether or not the new sound is fixed at the position of the triggering sound.
+        for new_same_position in widget::Toggle::new(link.same_position)
+            .w_h(half_w, ITEM_HEIGHT)
+            .right(PAD)
+            .label("Same Position")
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::BLUE)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_same_positions[i], ui)
+        {
+            link.same_position = new_same_position;
+        }
+        last_row_id = ids.chain_editor_link_triggers[i];
+
+        let slider = |value, min, max| {
+            widget::Slider::new(value, min, max)
+                .align_left_of(ids.chain_editor_link_tos[i])
+                .w_h(kid_w, SLIDER_H)
+                .label_font_size(SMALL_FONT_SIZE)
+                .color(ui::color::LIGHT_CHARCOAL)
+                .parent(ids.chain_editor_selected_canvas)
+        };
+
+        // A slider for the progress at which the link is evaluated, if any.
+        if let Trigger::Progress(ref mut progress) = link.trigger {
+            let label = format!("At {:.0}% Progress", *progress * 100.0);
+            for new_progress in slider(*progress, 0.0, 0.99)
+                .down_from(last_row_id, PAD)
+                .label(&label)
+                .set(ids.chain_editor_link_progresses[i], ui)
+            {
+                *progress = (new_progress * 100.0).round() / 100.0;
+            }
+            last_row_id = ids.chain_editor_link_progresses[i];
+        }
+
+        // A slider for the probability with which the link is followed.
+        let label = format!("Probability: {:.0}%", link.probability * 100.0);
+        for new_probability in slider(link.probability, 0.0, 1.0)
+            .down_from(last_row_id, PAD)
+            .label(&label)
+            .set(ids.chain_editor_link_probabilities[i], ui)
+        {
+            link.probability = (new_probability * 100.0).round() / 100.0;
+        }
+        last_row_id = ids.chain_editor_link_probabilities[i];
+
+        // A slider for the range of delays before the new sound is spawned.
+        let min_secs = link.delay.min.ms() / SEC_MS;
+        let max_secs = link.delay.max.ms() / SEC_MS;
+        let label = format!("Delay: {:.1} to {:.1} secs", min_secs, max_secs);
+        for (edge, value) in widget::RangeSlider::new(min_secs, max_secs, 0.0, MAX_DELAY_SECS)
+            .align_left_of(last_row_id)
+            .down_from(last_row_id, PAD)
+            .w_h(kid_w, SLIDER_H)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(ui::color::LIGHT_CHARCOAL)
+            .parent(ids.chain_editor_selected_canvas)
+            .set(ids.chain_editor_link_delays[i], ui)
+        {
+            let ms = Ms((value * 10.0).round() / 10.0 * SEC_MS);
+            match edge {
+                widget::range_slider::Edge::Start => link.delay.min = ms,
+                widget::range_slider::Edge::End => link.delay.max = ms,
+            }
+        }
+        last_row_id = ids.chain_editor_link_delays[i];
+
+        // Update the local copy and the soundscape if the link was edited.
+        if link != chains[index].links[i] {
+            chains[index].links[i] = link;
+            send_chains(channels, chains);
+        }
+    }
+
+    // Remove a link if necessary.
+    if let Some(i) = maybe_remove_index {
+        chains[index].links.remove(i);
+        send_chains(channels, chains);
+    }
+
+    area.id
+}
+
+// Send a copy of the updated chains to the soundscape thread.
+fn send_chains(channels: &Channels, chains: &Chains) {
+    let clone = chains.clone();
+    channels
+        .soundscape
+        .send(move |soundscape| soundscape.set_chains(clone))
+        .expect("failed to send updated chains to soundscape thread");
+}
+
+// Whether or not the link is evaluated at some progress through the sound.
+fn is_progress(trigger: &Trigger) -> bool {
+    match *trigger {
+        Trigger::Progress(_) => true,
+        Trigger::End => false,
+    }
+}
+
+// Create a new link from the chain's start source, or the first source if there is none.
+//
+// Returns `None` if there are no sources to link.
+fn new_link(chain: &Chain, source_ids: &[audio::source::Id]) -> Option<Link> {
+    let first = *source_ids.first()?;
+    let from = chain.start.unwrap_or(first);
+    Some(Link {
+        from,
+        to: first,
+        trigger: Trigger::End,
+        probability: chains::default::PROBABILITY,
+        delay: chains::default::DELAY,
+        same_position: false,
+    })
+}
+
+// The name of the source or a placeholder if it has been removed.
+fn source_name(id: &audio::source::Id, sources: &project::Sources) -> String {
+    sources
+        .get(id)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| "<removed>".to_string())
+}
+
+// A human-readable sentence describing the link.
+fn link_description(link: &Link, sources: &project::Sources) -> String {
+    let from = source_name(&link.from, sources);
+    let to = source_name(&link.to, sources);
+    match link.trigger {
+        Trigger::End => format!("When {} ends, play {}", from, to),
+        Trigger::Progress(progress) => {
+            format!("When {} reaches {:.0}%, play {}", from, progress * 100.0, to)
+        },
+    }
+}
@@ -13,11 +13,13 @@ use std::sync::Arc;
 #[derive(Default)]
 pub struct InstallationEditor {
     pub selected: Option<Selected>,
+    /// Whether or not the area of the selected installation is being drawn over the floorplan.
+    pub editing_polygon: bool,
 }
 
 /// The currently selected installation.
 pub struct Selected {
-    id: installation::Id,
+    pub id: installation::Id,
     name: String,
     selected_computer: Option<SelectedComputer>,
 }
@@ -56,6 +58,7 @@ pub fn set(
         installation_editor:
             InstallationEditor {
                 ref mut selected,
+                ref mut editing_polygon,
             },
         ..
     } = *project_state;
@@ -76,12 +79,14 @@ pub fn set(
     // - Music Data OSC Output (Text and TextBox)
     let osc_canvas_h = PAD + ITEM_HEIGHT * 3.0 + PAD;
     let computer_canvas_h = ITEM_HEIGHT + PAD + ITEM_HEIGHT + PAD + COMPUTER_LIST_HEIGHT;
-    let soundscape_canvas_h = PAD + PAD * 3.0 + PAD + SLIDER_H + PAD;
+    let soundscape_canvas_h = PAD + PAD * 3.0 + PAD + SLIDER_H + PAD + ITEM_HEIGHT + PAD;
+    let occupancy_canvas_h = PAD + PAD * 3.0 + (PAD + SLIDER_H) * 5.0 + PAD;
     let selected_canvas_h = PAD
         + NAME_H + PAD
         + computer_canvas_h + PAD
         + osc_canvas_h + PAD
-        + soundscape_canvas_h + PAD;
+        + soundscape_canvas_h + PAD
+        + occupancy_canvas_h + PAD;
 
     // The total height of the installation editor as a sum of the previous heights plus necessary
     // padding.
@@ -438,6 +443,171 @@ pub fn set(
             .expect("failed to send installation update to soundscape thread");
     }
 
+    //////////
+    // AREA //
+    //////////
+
+    // Toggle drawing of the installation's area over the floorplan.
+    let kid_w = selected_canvas_kid_area.w() - PAD * 2.0;
+    let button_w = (kid_w - PAD) / 2.0;
+    let label = match *editing_polygon {
+        true => "Editing Area On Floorplan",
+        false => "Edit Area On Floorplan",
+    };
+    for new_editing in widget::Toggle::new(*editing_polygon)
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .align_left()
+        .down(PAD)
+        .h(ITEM_HEIGHT)
+        .w(button_w)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .set(ids.installation_editor_soundscape_polygon_edit, ui)
+    {
+        *editing_polygon = new_editing;
+    }
+
+    // Remove the area's polygon, falling back to the bounding box of the speakers.
+    for _click in widget::Button::new()
+        .label("Clear Area")
+        .label_font_size(SMALL_FONT_SIZE)
+        .right(PAD)
+        .h(ITEM_HEIGHT)
+        .w(button_w)
+        .color(color::DARK_RED.alpha(0.5))
+        .set(ids.installation_editor_soundscape_polygon_clear, ui)
+    {
+        installations.get_mut(&id).unwrap().polygon.clear();
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.set_installation_polygon(id, vec![]);
+            })
+            .expect("failed to send installation polygon to soundscape thread");
+    }
+
+    ///////////////
+    // OCCUPANCY //
+    ///////////////
+
+    // The canvas for displaying the response to the installation's occupancy.
+    widget::Canvas::new()
+        .middle_of(ids.installation_editor_selected_canvas)
+        .down_from(ids.installation_editor_soundscape_canvas, PAD)
+        .color(color::CHARCOAL)
+        .w(selected_canvas_kid_area.w())
+        .h(occupancy_canvas_h)
+        .pad(PAD)
+        .set(ids.installation_editor_occupancy_canvas, ui);
+
+    // The header along with the occupancy last reported via OSC.
+    let header = match channels.soundscape.occupancy(&id) {
+        None => "Occupancy - Not Reported".to_string(),
+        Some(occupancy) => format!(
+            "Occupancy - {} visitors ({:.0}%)",
+            occupancy.visitors,
+            occupancy.level * 100.0,
+        ),
+    };
+    widget::Text::new(&header)
+        .font_size(SMALL_FONT_SIZE)
+        .top_left_of(ids.installation_editor_occupancy_canvas)
+        .set(ids.installation_editor_occupancy_text, ui);
+
+    let half_w = (kid_w - PAD) / 2.0;
+    let occupancy = installations[&id].soundscape.occupancy;
+
+    // The number of visitors at which the installation is full.
+    let label = format!("Capacity: {} visitors", occupancy.capacity);
+    for value in widget::Slider::new(occupancy.capacity, 1.0, 500.0)
+        .skew(0.5)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .w(kid_w)
+        .h(SLIDER_H)
+        .align_left()
+        .down(PAD * 2.0)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .set(ids.installation_editor_occupancy_capacity_slider, ui)
+    {
+        let mut occupancy = occupancy;
+        occupancy.capacity = value.round();
+        send_occupancy(installations, channels, id, occupancy);
+    }
+
+    // The density and volume responses, each with the value for an empty and a full installation
+    // followed by the curve between them.
+    let responses = [
+        (
+            "Density",
+            occupancy.density,
+            ids.installation_editor_occupancy_density_empty_slider,
+            ids.installation_editor_occupancy_density_full_slider,
+            ids.installation_editor_occupancy_density_curve_slider,
+        ),
+        (
+            "Volume",
+            occupancy.volume,
+            ids.installation_editor_occupancy_volume_empty_slider,
+            ids.installation_editor_occupancy_volume_full_slider,
+            ids.installation_editor_occupancy_volume_curve_slider,
+        ),
+    ];
+    for (i, &(name, response, empty_id, full_id, curve_id)) in responses.iter().enumerate() {
+        let mut new_response = None;
+
+        let label = format!("{} Empty: {:.0}%", name, response.empty * 100.0);
+        for value in widget::Slider::new(response.empty, 0.0, 1.0)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(half_w)
+            .h(SLIDER_H)
+            .align_left_of(ids.installation_editor_occupancy_capacity_slider)
+            .down(PAD)
+            .color(ui::color::LIGHT_CHARCOAL)
+            .set(empty_id, ui)
+        {
+            new_response = Some(installation::Response { empty: value, ..response });
+        }
+
+        let label = format!("{} Full: {:.0}%", name, response.full * 100.0);
+        for value in widget::Slider::new(response.full, 0.0, 1.0)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(half_w)
+            .h(SLIDER_H)
+            .right(PAD)
+            .color(ui::color::LIGHT_CHARCOAL)
+            .set(full_id, ui)
+        {
+            new_response = Some(installation::Response { full: value, ..response });
+        }
+
+        let label = format!("{} Curve: {:.2}", name, response.curve);
+        for value in widget::Slider::new(response.curve, 0.1, 4.0)
+            .skew(0.5)
+            .label(&label)
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(kid_w)
+            .h(SLIDER_H)
+            .align_left_of(ids.installation_editor_occupancy_capacity_slider)
+            .down_from(empty_id, PAD)
+            .color(ui::color::LIGHT_CHARCOAL)
+            .set(curve_id, ui)
+        {
+            new_response = Some(installation::Response { curve: value, ..response });
+        }
+
+        if let Some(response) = new_response {
+            let mut occupancy = occupancy;
+            match i {
+                0 => occupancy.density = response,
+                _ => occupancy.volume = response,
+            }
+            send_occupancy(installations, channels, id, occupancy);
+        }
+    }
+
     ///////////////
     // COMPUTERS //
     ///////////////
@@ -445,7 +615,7 @@ pub fn set(
     // The canvas for displaying the computer selection / editor.
     widget::Canvas::new()
         .middle_of(ids.installation_editor_selected_canvas)
-        .down_from(ids.installation_editor_soundscape_canvas, PAD)
+        .down_from(ids.installation_editor_occupancy_canvas, PAD)
         .color(color::CHARCOAL)
         .w(selected_canvas_kid_area.w())
         .h(computer_canvas_h)
@@ -717,3 +887,21 @@ pub fn set(
 
     area.id
 }
+
+// Update the local and soundscape copies of the given installation's occupancy response.
+fn send_occupancy(
+    installations: &mut project::Installations,
+    channels: &Channels,
+    id: installation::Id,
+    occupancy: installation::Occupancy,
+) {
+    installations.get_mut(&id).unwrap().soundscape.occupancy = occupancy;
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            soundscape.update_installation(&id, |installation| {
+                installation.occupancy = occupancy;
+            });
+        })
+        .expect("failed to send installation occupancy to soundscape thread");
+}
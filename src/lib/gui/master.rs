@@ -1,6 +1,10 @@
-//! A "Master" side-bar widget providing control over master volume and input latency.
+//! A "Master" side-bar widget providing control over master volume, input latency and loudness
+//! normalisation.
+//!
+//! Also displays the state of the in-memory WAV cache.
 
 use gui::{collapsible_area, Gui};
+use audio;
 use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
 use project::{self, Project};
 use nannou::ui;
@@ -19,6 +23,7 @@ pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> wi
     let Project {
         state: project::State {
             ref mut master,
+            ref sources,
             ..
         },
         ..
@@ -29,7 +34,10 @@ pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> wi
     const MASTER_VOLUME_H: Scalar = ITEM_HEIGHT;
     const LATENCY_H: Scalar = ITEM_HEIGHT;
     const DECIBEL_H: Scalar = ITEM_HEIGHT;
-    const MASTER_H: Scalar = PAD + MASTER_VOLUME_H + PAD + LATENCY_H + PAD + DECIBEL_H + PAD;
+    const LOUDNESS_H: Scalar = ITEM_HEIGHT;
+    const WAV_CACHE_H: Scalar = ITEM_HEIGHT * 2.0;
+    const MASTER_H: Scalar = PAD + MASTER_VOLUME_H + PAD + LATENCY_H + PAD + DECIBEL_H + PAD
+        + LOUDNESS_H + PAD + WAV_CACHE_H + PAD;
 
     // The collapsible area widget.
     let is_open = state.is_open.master;
@@ -154,5 +162,51 @@ pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> wi
             .expect("failed to send updated DBAP rolloff to audio output thread");
     }
 
+    // The loudness target toward which normalised sources are adjusted.
+    let label = format!("Loudness Target: {:.1} LUFS", master.loudness_target_lufs);
+    for new_target in widget::Slider::new(master.loudness_target_lufs, -40.0, -10.0)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .h(LOUDNESS_H)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .set(ids.master_loudness_target, ui)
+    {
+        // Update the local target.
+        master.loudness_target_lufs = new_target;
+
+        // Update the audio output thread's normalisation gain for each WAV source.
+        for (&id, source) in sources.iter() {
+            if let audio::source::Kind::Wav(ref wav) = source.kind {
+                let gain = wav.normalisation_gain(new_target);
+                channels
+                    .audio_output
+                    .send(move |audio| audio.set_source_gain(id, gain))
+                    .expect("failed to send source gain to audio output thread");
+            }
+        }
+    }
+
+    // The WAV cache statistics.
+    let stats = channels.wav_reader.cache().stats();
+    let mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    let text = format!(
+        "WAV Cache: {} files, {:.1} / {:.1} MB\nHits: {}  Misses: {}  Evictions: {}  ({:.0}% hit rate)",
+        stats.entries,
+        mb(stats.bytes),
+        mb(stats.budget_bytes),
+        stats.hits,
+        stats.misses,
+        stats.evictions,
+        stats.hit_rate() * 100.0,
+    );
+    widget::Text::new(&text)
+        .font_size(SMALL_FONT_SIZE)
+        .kid_area_w_of(area.id)
+        .align_middle_x_of(area.id)
+        .down(PAD * 2.0)
+        .set(ids.master_wav_cache_text, ui);
+
     area.id
 }
@@ -12,22 +12,28 @@ use osc;
 use osc::input::Log as OscInputLog;
 use osc::output::Log as OscOutputLog;
 use project::{self, Project};
-use soundscape::Soundscape;
+use scene;
+use soundscape::{self, Soundscape};
 use slug::slugify;
 use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
 use std::ops::{Deref, DerefMut};
 use std::sync::{mpsc, Arc};
 use std::sync::atomic::AtomicUsize;
+use std::thread;
 use time_calc::Ms;
 use utils::{self, HumanReadableTime, SEC_MS, MIN_MS, HR_MS};
 
+use self::chain_editor::ChainEditor;
 use self::installation_editor::InstallationEditor;
 use self::project_editor::ProjectEditor;
+use self::scene_editor::SceneEditor;
+use self::schedule_editor::ScheduleEditor;
 use self::soundscape_editor::SoundscapeEditor;
 use self::source_editor::{SourceEditor, SourcePreviewMode};
 use self::speaker_editor::SpeakerEditor;
 
+pub mod chain_editor;
 mod custom_widget;
 pub mod installation_editor;
 pub mod control_log;
@@ -36,6 +42,9 @@ pub mod monitor;
 pub mod osc_in_log;
 pub mod osc_out_log;
 pub mod project_editor;
+pub mod rule_editor;
+pub mod scene_editor;
+pub mod schedule_editor;
 pub mod source_editor;
 pub mod soundscape_editor;
 pub mod speaker_editor;
@@ -93,6 +102,14 @@ pub struct ProjectState {
     speaker_editor: SpeakerEditor,
     /// Runtime state related to the source editor GUI panel.
     source_editor: SourceEditor,
+    /// Runtime state related to the schedule editor GUI panel.
+    schedule_editor: ScheduleEditor,
+    /// Runtime state related to the scene editor GUI panel.
+    scene_editor: SceneEditor,
+    /// Runtime state related to the chain editor GUI panel.
+    chain_editor: ChainEditor,
+    /// The scene recalled by the schedule windows that were active as of the last update.
+    scheduled_scene: Option<scene::Id>,
 }
 
 /// State available to the GUI during widget instantiation.
@@ -117,6 +134,10 @@ struct IsOpen {
     master: bool,
     installation_editor: bool,
     soundscape_editor: bool,
+    schedule_editor: bool,
+    rule_editor: bool,
+    chain_editor: bool,
+    scene_editor: bool,
     speaker_editor: bool,
     source_editor: bool,
     side_menu: bool,
@@ -140,6 +161,9 @@ pub struct Channels {
     pub control_rx: mpsc::Receiver<osc::input::Control>,
     pub soundscape: Soundscape,
     pub wav_reader: audio::source::wav::reader::Handle,
+    pub loudness: audio::loudness::Analyser,
+    pub wav_watcher: audio::source::wav::watcher::Watcher,
+    pub waveform: audio::waveform::Generator,
     pub audio_input: audio::input::Stream,
     pub audio_output: audio::output::Stream,
     pub audio_monitor_msg_rx: monitor::Receiver,
@@ -258,6 +282,10 @@ impl Default for IsOpen {
             master: false,
             installation_editor: false,
             soundscape_editor: false,
+            schedule_editor: false,
+            rule_editor: false,
+            chain_editor: false,
+            scene_editor: false,
             speaker_editor: false,
             source_editor: false,
             osc_in_log: false,
@@ -441,6 +469,302 @@ impl Model {
                         .expect("failed to send updated source volume to audio output thread");
                 }
 
+                &osc::input::Control::SourceEffect(ref source_effect) => {
+                    let osc::input::SourceEffect { ref name, effect, ref param, value } =
+                        *source_effect;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref proj, _)) => proj,
+                    };
+
+                    // Live control only applies to the sounds currently playing the source. The
+                    // authored range is left untouched so that new sounds are still randomised.
+                    let id = match project
+                        .state
+                        .sources
+                        .iter()
+                        .find(|&(_, ref s)| &s.name[..] == name)
+                    {
+                        None => continue,
+                        Some((&id, _)) => id,
+                    };
+
+                    // Update the audio output copies.
+                    let param = param.clone();
+                    channels
+                        .audio_output
+                        .send(move |audio| {
+                            audio.update_sounds_with_source(&id, move |_, sound| {
+                                sound.effects.set_param(effect, &param, value);
+                            });
+                        })
+                        .expect("failed to send updated source effect to audio output thread");
+                }
+
+                &osc::input::Control::TagVolume(ref tag_volume) => {
+                    let osc::input::TagVolume { ref tag, volume } = *tag_volume;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref mut proj, _)) => proj,
+                    };
+
+                    // Update local copies.
+                    let source_ids: Vec<_> = project
+                        .state
+                        .sources
+                        .iter_mut()
+                        .filter(|&(_, ref s)| s.has_tag(tag))
+                        .map(|(&id, source)| {
+                            source.volume = volume;
+                            id
+                        })
+                        .collect();
+                    if source_ids.is_empty() {
+                        continue;
+                    }
+
+                    // Update the soundscape copies.
+                    let soundscape_ids = source_ids.clone();
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            for id in &soundscape_ids {
+                                soundscape.update_source(id, |source| source.volume = volume);
+                            }
+                        })
+                        .expect("failed to send updated tag volume to soundscape thread");
+
+                    // Update the audio output copies.
+                    channels
+                        .audio_output
+                        .send(move |audio| {
+                            for id in &source_ids {
+                                audio.update_sounds_with_source(id, move |_, sound| {
+                                    sound.volume = volume;
+                                });
+                            }
+                        })
+                        .expect("failed to send updated tag volume to audio output thread");
+                }
+
+                &osc::input::Control::TagMute(ref tag_mute) => {
+                    let osc::input::TagMute { ref tag, muted } = *tag_mute;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref mut proj, _)) => proj,
+                    };
+
+                    // Update local copies.
+                    let source_ids: Vec<_> = project
+                        .state
+                        .sources
+                        .iter_mut()
+                        .filter(|&(_, ref s)| s.has_tag(tag))
+                        .map(|(&id, source)| {
+                            source.muted = muted;
+                            id
+                        })
+                        .collect();
+                    if source_ids.is_empty() {
+                        continue;
+                    }
+
+                    // Update the soundscape copies.
+                    let soundscape_ids = source_ids.clone();
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            for id in &soundscape_ids {
+                                soundscape.update_source(id, |source| source.muted = muted);
+                            }
+                        })
+                        .expect("failed to send muted tag update to soundscape thread");
+
+                    // Update the audio output copies.
+                    channels
+                        .audio_output
+                        .send(move |audio| {
+                            for id in &source_ids {
+                                audio.update_sounds_with_source(id, move |_, sound| {
+                                    sound.muted = muted;
+                                });
+                            }
+                        })
+                        .expect("failed to send muted tag update to audio output thread");
+                }
+
+                &osc::input::Control::GroupVolume(ref group_volume) => {
+                    let osc::input::GroupVolume { ref slug, volume } = *group_volume;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref mut proj, _)) => proj,
+                    };
+
+                    // Update local copy.
+                    let id = match project
+                        .soundscape_groups
+                        .iter_mut()
+                        .find(|&(_, ref g)| &slugify(&g.name) == slug)
+                    {
+                        None => continue,
+                        Some((&id, group)) => {
+                            group.volume = volume;
+                            id
+                        },
+                    };
+
+                    // Update the soundscape copy, which in turn updates the audio output thread.
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            soundscape.update_group(&id, |group| group.volume = volume);
+                        })
+                        .expect("failed to send updated group volume to soundscape thread");
+                }
+
+                &osc::input::Control::GroupMute(ref group_mute) => {
+                    let osc::input::GroupMute { ref slug, muted } = *group_mute;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref mut proj, _)) => proj,
+                    };
+
+                    // Update local copy.
+                    let id = match project
+                        .soundscape_groups
+                        .iter_mut()
+                        .find(|&(_, ref g)| &slugify(&g.name) == slug)
+                    {
+                        None => continue,
+                        Some((&id, group)) => {
+                            group.muted = muted;
+                            id
+                        },
+                    };
+
+                    // Update the soundscape copy, which in turn updates the audio output thread.
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            soundscape.update_group(&id, |group| group.muted = muted);
+                        })
+                        .expect("failed to send updated group mute to soundscape thread");
+                }
+
+                &osc::input::Control::GroupSolo(ref group_solo) => {
+                    let osc::input::GroupSolo { ref slug, soloed } = *group_solo;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref mut proj, _)) => proj,
+                    };
+
+                    // Update local copy.
+                    let id = match project
+                        .soundscape_groups
+                        .iter_mut()
+                        .find(|&(_, ref g)| &slugify(&g.name) == slug)
+                    {
+                        None => continue,
+                        Some((&id, group)) => {
+                            group.soloed = soloed;
+                            id
+                        },
+                    };
+
+                    // Update the soundscape copy, which in turn updates the audio output thread.
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            soundscape.update_group(&id, |group| group.soloed = soloed);
+                        })
+                        .expect("failed to send updated group solo to soundscape thread");
+                }
+
+                &osc::input::Control::RecallScene(ref recall_scene) => {
+                    let osc::input::RecallScene { ref slug, fade_secs } = *recall_scene;
+
+                    let project = match *project {
+                        None => continue,
+                        Some((ref mut proj, _)) => proj,
+                    };
+
+                    let (id, fade) = match project
+                        .scenes
+                        .iter()
+                        .find(|&(_, ref scene)| &slugify(&scene.name) == slug)
+                    {
+                        None => continue,
+                        Some((&id, scene)) => (id, scene.fade),
+                    };
+                    let fade = fade_secs.map(|secs| Ms(secs as f64 * SEC_MS)).unwrap_or(fade);
+                    project.recall_scene(&id, fade, channels);
+                }
+
+                &osc::input::Control::TrackedPoint(ref tracked_point) => {
+                    let name = tracked_point.name.clone();
+                    let point = tracked_point.point;
+                    channels
+                        .soundscape
+                        .send(move |soundscape| match point {
+                            Some(point) => soundscape.update_tracked_point(name, point),
+                            None => {
+                                soundscape.remove_tracked_point(&name);
+                            },
+                        })
+                        .expect("failed to send tracked point to soundscape thread");
+                }
+
+                &osc::input::Control::TriggerChain(ref trigger_chain) => {
+                    let project = match *project {
+                        None => continue,
+                        Some((ref proj, _)) => proj,
+                    };
+
+                    let name = match project
+                        .chains
+                        .iter()
+                        .find(|chain| slugify(&chain.name) == trigger_chain.slug)
+                    {
+                        None => continue,
+                        Some(chain) => chain.name.clone(),
+                    };
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            soundscape.trigger_chain(&name);
+                        })
+                        .expect("failed to send chain trigger to soundscape thread");
+                }
+
+                &osc::input::Control::Occupancy(ref occupancy) => {
+                    let project = match *project {
+                        None => continue,
+                        Some((ref proj, _)) => proj,
+                    };
+
+                    let id = match project
+                        .installations
+                        .iter()
+                        .find(|&(_, installation)| slugify(&installation.name) == occupancy.slug)
+                    {
+                        None => continue,
+                        Some((&id, _)) => id,
+                    };
+                    let visitors = occupancy.visitors as f64;
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            soundscape.set_occupancy(id, visitors);
+                        })
+                        .expect("failed to send occupancy to soundscape thread");
+                }
+
                 &osc::input::Control::PlaySoundscape => {
                     channels
                         .soundscape
@@ -460,6 +784,168 @@ impl Model {
             state.control_log.push_msg(control);
         }
 
+        // Collect finished loudness analyses.
+        while let Some(report) = channels.loudness.try_recv() {
+            let audio::loudness::Report { id, path, result } = report;
+            let analysis = match result {
+                Ok(analysis) => analysis,
+                Err(err) => {
+                    eprintln!("failed to analyse loudness of \"{}\": {}", path.display(), err);
+                    continue;
+                },
+            };
+
+            // Update the local copy, ensuring the source still refers to the analysed WAV.
+            let project = match *project {
+                None => continue,
+                Some((ref mut proj, _)) => proj,
+            };
+            let target_lufs = project.master.loudness_target_lufs;
+            let gain = match project.state.sources.get_mut(&id) {
+                Some(source) => match source.audio.kind {
+                    audio::source::Kind::Wav(ref mut wav) if wav.path == path => {
+                        wav.loudness = Some(analysis);
+                        wav.normalisation_gain(target_lufs)
+                    },
+                    _ => continue,
+                },
+                None => continue,
+            };
+
+            // Update the audio output copy.
+            channels
+                .audio_output
+                .send(move |audio| audio.set_source_gain(id, gain))
+                .expect("failed to send source gain to audio output thread");
+        }
+
+        // Collect finished waveform overviews for the source editor.
+        while let Some(report) = channels.waveform.try_recv() {
+            let audio::waveform::Report { id, path, result } = report;
+            let peaks = match result {
+                Ok(peaks) => peaks,
+                Err(err) => {
+                    eprintln!("failed to generate waveform of \"{}\": {}", path.display(), err);
+                    continue;
+                },
+            };
+
+            // Only keep the peaks if the source still refers to the summarised WAV.
+            let (project, project_state) = match *project {
+                None => continue,
+                Some((ref proj, ref mut proj_state)) => (proj, proj_state),
+            };
+            match project.state.sources.get(&id).map(|s| &s.audio.kind) {
+                Some(&audio::source::Kind::Wav(ref wav)) if wav.path == path => {
+                    project_state.source_editor.waveforms.insert(id, Some(peaks));
+                },
+                _ => (),
+            }
+        }
+
+        // Apply changes made to the WAVs within the audio directory.
+        while let Some(event) = channels.wav_watcher.try_recv() {
+            let (project, project_state) = match *project {
+                None => continue,
+                Some((ref mut proj, ref mut proj_state)) => (proj, proj_state),
+            };
+
+            match event {
+                // Add a source for the new WAV.
+                audio::source::wav::watcher::Event::Created(path) => {
+                    if project.sources.wav_id(&path).is_some() {
+                        continue;
+                    }
+                    let source = match project::Source::from_wav_path(path.clone()) {
+                        None => continue,
+                        Some(source) => source,
+                    };
+                    let id = project.sources.next_id();
+                    project.sources.insert(id, source);
+                    channels.loudness.request(id, path);
+                },
+
+                // Refresh the WAV's metadata, retaining all other source parameters.
+                audio::source::wav::watcher::Event::Modified(path) => {
+                    let id = match project.sources.wav_id(&path) {
+                        None => continue,
+                        Some(id) => id,
+                    };
+                    let wav = match project.sources.get_mut(&id).map(|s| &mut s.audio.kind) {
+                        Some(&mut audio::source::Kind::Wav(ref mut wav)) => wav,
+                        _ => continue,
+                    };
+                    if let Err(err) = wav.reload(path.clone()) {
+                        eprintln!("failed to reload wav \"{}\": {}", path.display(), err);
+                        continue;
+                    }
+
+                    // The contents may have changed even if the duration did not.
+                    wav.loudness = None;
+                    channels.loudness.request(id, path.clone());
+                    channels.wav_reader.cache().remove(&path);
+                    project_state.source_editor.waveforms.remove(&id);
+
+                    // Update the soundscape copy.
+                    let clone = wav.clone();
+                    channels
+                        .soundscape
+                        .send(move |soundscape| {
+                            soundscape.update_source(&id, |source| {
+                                source.kind = audio::source::Kind::Wav(clone);
+                            });
+                        })
+                        .expect("failed to send reloaded wav to soundscape thread");
+
+                    // Release sounds playing the old contents and reset the normalisation gain
+                    // until the new contents have been analysed.
+                    channels
+                        .audio_output
+                        .send(move |audio| {
+                            audio.release_sounds_with_source(&id);
+                            audio.set_source_gain(id, 1.0);
+                        })
+                        .expect("failed to send reloaded wav to audio output thread");
+                },
+
+                // Remove the source for the WAV, stopping any of its sounds.
+                audio::source::wav::watcher::Event::Removed(path) => {
+                    let id = match project.sources.wav_id(&path) {
+                        None => continue,
+                        Some(id) => id,
+                    };
+                    channels.wav_reader.cache().remove(&path);
+                    project_state.source_editor.waveforms.remove(&id);
+                    if project_state.source_editor.selected == Some(id) {
+                        project_state.source_editor.selected = None;
+                    }
+                    project.sources.soloed.remove(&id);
+                    source_editor::remove_source(channels, audio_monitor, &mut project.sources, id);
+                    channels
+                        .audio_output
+                        .send(move |audio| {
+                            audio.soloed.remove(&id);
+                            audio.set_source_gain(id, 1.0);
+                        })
+                        .expect("failed to send removed wav to audio output thread");
+                },
+            }
+        }
+
+        // Recall a scene as soon as a schedule window that recalls it begins.
+        if let Some((ref mut project, ref mut project_state)) = *project {
+            let now = project.schedule.now();
+            let scene = project.schedule.overrides(&now).scene;
+            if scene != project_state.scheduled_scene {
+                project_state.scheduled_scene = scene;
+                if let Some(id) = scene {
+                    if let Some(fade) = project.scenes.get(&id).map(|scene| scene.fade) {
+                        project.recall_scene(&id, fade, channels);
+                    }
+                }
+            }
+        }
+
         // Update the map of active sounds.
         loop {
             let msg = match channels.audio_monitor_msg_rx.try_pop() {
@@ -614,6 +1100,11 @@ impl Model {
     pub fn selected_project_slug(&self) -> Option<String> {
         self.project.as_ref().map(|&(ref project, _)| slugify(&project.name))
     }
+
+    /// Stops the thread watching the audio directory and returns the raw handle to its thread.
+    pub fn exit_wav_watcher(&mut self) -> Option<thread::JoinHandle<()>> {
+        self.channels.wav_watcher.exit()
+    }
 }
 
 impl State {
@@ -646,6 +1137,9 @@ impl Channels {
         control_rx: mpsc::Receiver<osc::input::Control>,
         soundscape: Soundscape,
         wav_reader: audio::source::wav::reader::Handle,
+        loudness: audio::loudness::Analyser,
+        wav_watcher: audio::source::wav::watcher::Watcher,
+        waveform: audio::waveform::Generator,
         audio_input: audio::input::Stream,
         audio_output: audio::output::Stream,
         audio_monitor_msg_rx: monitor::Receiver,
@@ -658,6 +1152,9 @@ impl Channels {
             control_rx,
             soundscape,
             wav_reader,
+            loudness,
+            wav_watcher,
+            waveform,
             audio_input,
             audio_output,
             audio_monitor_msg_rx,
@@ -877,6 +1374,8 @@ widget_ids! {
         master_volume,
         master_realtime_source_latency,
         master_dbap_rolloff,
+        master_loudness_target,
+        master_wav_cache_text,
         // OSC input log.
         osc_in_log,
         osc_in_log_text,
@@ -911,6 +1410,17 @@ widget_ids! {
         installation_editor_soundscape_canvas,
         installation_editor_soundscape_text,
         installation_editor_soundscape_simultaneous_sounds_slider,
+        installation_editor_soundscape_polygon_edit,
+        installation_editor_soundscape_polygon_clear,
+        installation_editor_occupancy_canvas,
+        installation_editor_occupancy_text,
+        installation_editor_occupancy_capacity_slider,
+        installation_editor_occupancy_density_empty_slider,
+        installation_editor_occupancy_density_full_slider,
+        installation_editor_occupancy_density_curve_slider,
+        installation_editor_occupancy_volume_empty_slider,
+        installation_editor_occupancy_volume_full_slider,
+        installation_editor_occupancy_volume_curve_slider,
         // Speaker Editor.
         speaker_editor,
         speaker_editor_no_speakers,
@@ -930,6 +1440,11 @@ widget_ids! {
         // Audio Sources.
         soundscape_editor,
         soundscape_editor_is_playing,
+        soundscape_editor_simulation_canvas,
+        soundscape_editor_simulation_text,
+        soundscape_editor_simulation_hours_slider,
+        soundscape_editor_simulation_button,
+        soundscape_editor_simulation_status,
         soundscape_editor_group_canvas,
         soundscape_editor_group_text,
         soundscape_editor_group_add,
@@ -939,20 +1454,114 @@ widget_ids! {
         soundscape_editor_selected_canvas,
         soundscape_editor_selected_text,
         soundscape_editor_selected_name,
+        soundscape_editor_volume_text,
+        soundscape_editor_volume_slider,
+        soundscape_editor_solo,
+        soundscape_editor_mute,
         soundscape_editor_occurrence_rate_text,
         soundscape_editor_occurrence_rate_slider,
         soundscape_editor_simultaneous_sounds_text,
         soundscape_editor_simultaneous_sounds_slider,
+        soundscape_editor_tag_query_text,
+        soundscape_editor_tag_query,
+        soundscape_editor_tag_query_status,
+        soundscape_editor_share_text,
+        soundscape_editor_share_list,
+        // Schedule.
+        schedule_editor,
+        schedule_editor_timeline_canvas,
+        schedule_editor_timeline_text,
+        schedule_editor_timeline_days[],
+        schedule_editor_timeline_rows[],
+        schedule_editor_timeline_windows[],
+        schedule_editor_timeline_now,
+        schedule_editor_utc_offset_os_timezone,
+        schedule_editor_utc_offset,
+        schedule_editor_window_canvas,
+        schedule_editor_window_text,
+        schedule_editor_window_add,
+        schedule_editor_window_none,
+        schedule_editor_window_list,
+        schedule_editor_window_remove,
+        schedule_editor_selected_canvas,
+        schedule_editor_selected_text,
+        schedule_editor_selected_name,
+        schedule_editor_selected_days[],
+        schedule_editor_selected_date_text,
+        schedule_editor_selected_date,
+        schedule_editor_selected_start,
+        schedule_editor_selected_end,
+        schedule_editor_selected_actions_text,
+        schedule_editor_selected_add_action,
+        schedule_editor_action_texts[],
+        schedule_editor_action_targets[],
+        schedule_editor_action_removes[],
+        schedule_editor_action_presets[],
+        // Rules.
+        rule_editor,
+        rule_editor_rules_canvas,
+        rule_editor_rules_text,
+        rule_editor_add,
+        rule_editor_rule_texts[],
+        rule_editor_rule_targets[],
+        rule_editor_rule_removes[],
+        rule_editor_rule_others[],
+        rule_editor_rule_withins[],
+        rule_editor_violations_canvas,
+        rule_editor_violations_text,
+        rule_editor_violations_none,
+        rule_editor_violation_texts[],
+        // Chains.
+        chain_editor,
+        chain_editor_list_canvas,
+        chain_editor_list_text,
+        chain_editor_add,
+        chain_editor_none,
+        chain_editor_list,
+        chain_editor_remove,
+        chain_editor_selected_canvas,
+        chain_editor_selected_text,
+        chain_editor_selected_name,
+        chain_editor_selected_start,
+        chain_editor_selected_trigger,
+        chain_editor_selected_links_text,
+        chain_editor_selected_add_link,
+        chain_editor_link_texts[],
+        chain_editor_link_froms[],
+        chain_editor_link_removes[],
+        chain_editor_link_tos[],
+        chain_editor_link_triggers[],
+        chain_editor_link_same_positions[],
+        chain_editor_link_progresses[],
+        chain_editor_link_probabilities[],
+        chain_editor_link_delays[],
+        // Scenes.
+        scene_editor,
+        scene_editor_list_canvas,
+        scene_editor_list_text,
+        scene_editor_add,
+        scene_editor_none,
+        scene_editor_list,
+        scene_editor_remove,
+        scene_editor_selected_canvas,
+        scene_editor_selected_text,
+        scene_editor_selected_name,
+        scene_editor_selected_fade,
+        scene_editor_selected_update,
+        scene_editor_selected_recall,
         // Audio Sources.
         source_editor,
         source_editor_no_sources,
+        source_editor_tag_filter,
         source_editor_list,
         source_editor_add_wav,
         source_editor_add_realtime,
+        source_editor_add_network,
         source_editor_remove,
         source_editor_selected_canvas,
         source_editor_selected_none,
         source_editor_selected_name,
+        source_editor_selected_tags,
         source_editor_selected_role_list,
         source_editor_selected_installations_canvas,
         source_editor_selected_installations_text,
@@ -963,6 +1572,8 @@ widget_ids! {
         source_editor_selected_soundscape_title,
         source_editor_selected_soundscape_occurrence_rate_text,
         source_editor_selected_soundscape_occurrence_rate_slider,
+        source_editor_selected_soundscape_weight_text,
+        source_editor_selected_soundscape_weight_slider,
         source_editor_selected_soundscape_simultaneous_sounds_text,
         source_editor_selected_soundscape_simultaneous_sounds_slider,
         source_editor_selected_soundscape_playback_duration_text,
@@ -971,6 +1582,18 @@ widget_ids! {
         source_editor_selected_soundscape_attack_duration_slider,
         source_editor_selected_soundscape_release_duration_text,
         source_editor_selected_soundscape_release_duration_slider,
+        source_editor_selected_soundscape_playback_rate_text,
+        source_editor_selected_soundscape_playback_rate_slider,
+        source_editor_selected_soundscape_curve_text,
+        source_editor_selected_soundscape_attack_curve_ddl,
+        source_editor_selected_soundscape_release_curve_ddl,
+        source_editor_selected_soundscape_attack_curve_bezier_a,
+        source_editor_selected_soundscape_attack_curve_bezier_b,
+        source_editor_selected_soundscape_release_curve_bezier_a,
+        source_editor_selected_soundscape_release_curve_bezier_b,
+        source_editor_selected_soundscape_curve_preview_canvas,
+        source_editor_selected_soundscape_attack_curve_plot,
+        source_editor_selected_soundscape_release_curve_plot,
         source_editor_selected_soundscape_groups_text,
         source_editor_selected_soundscape_groups_list,
         source_editor_selected_soundscape_movement_text,
@@ -983,6 +1606,13 @@ widget_ids! {
         source_editor_selected_soundscape_movement_agent_max_force_slider,
         source_editor_selected_soundscape_movement_agent_max_rotation_text,
         source_editor_selected_soundscape_movement_agent_max_rotation_slider,
+        source_editor_selected_soundscape_movement_agent_flocking_text,
+        source_editor_selected_soundscape_movement_agent_flock_neighbours,
+        source_editor_selected_soundscape_movement_agent_flock_radius,
+        source_editor_selected_soundscape_movement_agent_flock_separation_distance,
+        source_editor_selected_soundscape_movement_agent_flock_separation,
+        source_editor_selected_soundscape_movement_agent_flock_alignment,
+        source_editor_selected_soundscape_movement_agent_flock_cohesion,
         source_editor_selected_soundscape_movement_agent_directional,
         source_editor_selected_soundscape_movement_ngon_speed_text,
         source_editor_selected_soundscape_movement_ngon_speed_slider,
@@ -995,17 +1625,77 @@ widget_ids! {
         source_editor_selected_soundscape_movement_ngon_height_slider,
         source_editor_selected_soundscape_movement_ngon_radians_text,
         source_editor_selected_soundscape_movement_ngon_radians_slider,
+        source_editor_selected_soundscape_movement_path_mode_text,
+        source_editor_selected_soundscape_movement_path_mode_list,
+        source_editor_selected_soundscape_movement_path_timing_text,
+        source_editor_selected_soundscape_movement_path_timing_list,
+        source_editor_selected_soundscape_movement_path_timing_slider,
+        source_editor_selected_soundscape_movement_path_random_offset,
+        source_editor_selected_soundscape_movement_path_points_text,
+        source_editor_selected_soundscape_movement_path_edit,
+        source_editor_selected_soundscape_movement_path_clear,
+        source_editor_selected_soundscape_movement_orbit_centre_text,
+        source_editor_selected_soundscape_movement_orbit_centre_x_slider,
+        source_editor_selected_soundscape_movement_orbit_centre_y_slider,
+        source_editor_selected_soundscape_movement_orbit_radius_text,
+        source_editor_selected_soundscape_movement_orbit_radius_slider,
+        source_editor_selected_soundscape_movement_orbit_speed_text,
+        source_editor_selected_soundscape_movement_orbit_speed_slider,
+        source_editor_selected_soundscape_movement_orbit_direction_text,
+        source_editor_selected_soundscape_movement_orbit_direction_list,
+        source_editor_selected_soundscape_movement_orbit_directional,
+        source_editor_selected_soundscape_movement_lissajous_frequency_text,
+        source_editor_selected_soundscape_movement_lissajous_frequency_x_slider,
+        source_editor_selected_soundscape_movement_lissajous_frequency_y_slider,
+        source_editor_selected_soundscape_movement_lissajous_phase_text,
+        source_editor_selected_soundscape_movement_lissajous_phase_slider,
+        source_editor_selected_soundscape_movement_lissajous_rate_text,
+        source_editor_selected_soundscape_movement_lissajous_rate_slider,
+        source_editor_selected_soundscape_movement_lissajous_dimensions_text,
+        source_editor_selected_soundscape_movement_lissajous_width_slider,
+        source_editor_selected_soundscape_movement_lissajous_height_slider,
+        source_editor_selected_soundscape_movement_lissajous_directional,
+        source_editor_selected_soundscape_movement_wander_speed_text,
+        source_editor_selected_soundscape_movement_wander_speed_slider,
+        source_editor_selected_soundscape_movement_wander_turbulence_text,
+        source_editor_selected_soundscape_movement_wander_turbulence_slider,
+        source_editor_selected_soundscape_movement_wander_edge_repulsion_text,
+        source_editor_selected_soundscape_movement_wander_edge_repulsion_slider,
+        source_editor_selected_soundscape_movement_wander_directional,
+        source_editor_selected_soundscape_movement_tracked_target_text,
+        source_editor_selected_soundscape_movement_tracked_target_list,
+        source_editor_selected_soundscape_movement_tracked_name,
+        source_editor_selected_soundscape_movement_tracked_smoothing_text,
+        source_editor_selected_soundscape_movement_tracked_smoothing_slider,
+        source_editor_selected_soundscape_movement_tracked_timeout_text,
+        source_editor_selected_soundscape_movement_tracked_timeout_slider,
+        source_editor_selected_soundscape_movement_tracked_directional,
         source_editor_selected_wav_canvas,
         source_editor_selected_wav_text,
         source_editor_selected_wav_data,
+        source_editor_selected_wav_waveform_canvas,
+        source_editor_selected_wav_waveform_text,
+        source_editor_selected_wav_waveform_region,
+        source_editor_selected_wav_waveform_path,
+        source_editor_selected_wav_playheads[],
         source_editor_selected_wav_loop_toggle,
+        source_editor_selected_wav_normalise_toggle,
         source_editor_selected_wav_playback_text,
         source_editor_selected_wav_playback_list,
         source_editor_selected_realtime_canvas,
         source_editor_selected_realtime_text,
         source_editor_selected_realtime_duration,
-        source_editor_selected_realtime_start_channel,
-        source_editor_selected_realtime_end_channel,
+        source_editor_selected_realtime_inputs[],
+        source_editor_selected_realtime_input_trims[],
+        source_editor_selected_realtime_input_removes[],
+        source_editor_selected_realtime_add_input,
+        source_editor_selected_network_canvas,
+        source_editor_selected_network_text,
+        source_editor_selected_network_duration,
+        source_editor_selected_network_port,
+        source_editor_selected_network_format,
+        source_editor_selected_network_channels,
+        source_editor_selected_network_jitter_buffer,
         source_editor_selected_common_canvas,
         source_editor_selected_volume_text,
         source_editor_selected_volume_slider,
@@ -1018,6 +1708,12 @@ widget_ids! {
         source_editor_selected_channel_layout_spread_circle,
         source_editor_selected_channel_layout_channels[],
         source_editor_selected_channel_layout_channel_labels[],
+        source_editor_selected_effects_canvas,
+        source_editor_selected_effects_text,
+        source_editor_selected_effects_add,
+        source_editor_selected_effects_labels[],
+        source_editor_selected_effects_removes[],
+        source_editor_selected_effects_params[],
         source_editor_preview_canvas,
         source_editor_preview_text,
         source_editor_preview_one_shot,
@@ -1031,6 +1727,10 @@ widget_ids! {
         floorplan_speaker_labels[],
         floorplan_sounds[],
         floorplan_channel_to_speaker_lines[],
+        floorplan_path,
+        floorplan_path_points[],
+        floorplan_installation_polygon,
+        floorplan_installation_polygon_points[],
 
         // Text drawn in the CPU-saving mode.
         cpu_saving_mode,
@@ -1134,6 +1834,18 @@ fn set_side_menu_widgets(
         // Soundscape Editor - for playing/pausing and adding, editing and removing groups.
         last_area_id = soundscape_editor::set(last_area_id, gui, project, project_state);
 
+        // Schedule Editor - for playing, pausing, muting and constraining the soundscape by time.
+        last_area_id = schedule_editor::set(last_area_id, gui, project, project_state);
+
+        // Rule Editor - for constraining when sources may play in relation to one another.
+        last_area_id = rule_editor::set(last_area_id, gui, project);
+
+        // Chains.- for sequencing sources into narrative chains.
+        last_area_id = chain_editor::set(last_area_id, gui, project, project_state);
+
+        // Scene Editor - for capturing and recalling snapshots of soundscape parameters.
+        last_area_id = scene_editor::set(last_area_id, gui, project, project_state);
+
         // For adding, changing and removing audio sources.
         last_area_id = source_editor::set(last_area_id, gui, project, project_state);
 
@@ -1540,6 +2252,237 @@ fn set_widgets(
         }
     }
 
+    // Draw the path of the selected source over the floorplan while it is being edited.
+    //
+    // Clicking the floorplan appends a point to the path. Points may be dragged to move them or
+    // right-clicked to remove them.
+    let is_editing_path =
+        gui.state.is_open.source_editor && project_state.source_editor.editing_path;
+    let editing_path = match project_state.source_editor.selected {
+        Some(id) if is_editing_path => {
+            let role = project.sources.get(&id).and_then(|s| s.audio.role.as_ref());
+            match role {
+                Some(&audio::source::Role::Soundscape(audio::source::Soundscape {
+                    movement: audio::source::Movement::Generative(
+                        audio::source::movement::Generative::Path(ref path),
+                    ),
+                    ..
+                })) => Some((id, path.clone())),
+                _ => None,
+            }
+        },
+        _ => None,
+    };
+    if let Some((source_id, mut path)) = editing_path {
+        let Gui {
+            ref mut ids,
+            ref mut ui,
+            ref channels,
+            ..
+        } = *gui;
+
+        let Project {
+            state: project::State {
+                ref camera,
+                ref mut sources,
+                ..
+            },
+            ..
+        } = *project;
+        let mut changed = false;
+
+        // Append a point wherever the floorplan was clicked.
+        for click in ui.widget_input(ids.floorplan).clicks().left() {
+            let x = camera.position.x + camera.scalar_to_metres(click.xy[0]);
+            let y = camera.position.y + camera.scalar_to_metres(click.xy[1]);
+            path.points.push(Point2 { x, y });
+            changed = true;
+        }
+
+        // Ensure there are enough IDs available.
+        let num_points = path.points.len();
+        if ids.floorplan_path_points.len() < num_points {
+            let id_gen = &mut ui.widget_id_generator();
+            ids.floorplan_path_points.resize(num_points, id_gen);
+        }
+
+        // Draw the spline through the points.
+        let closed = path.mode == audio::source::movement::PathMode::Loop;
+        let polyline = soundscape::movement::path::Polyline::spline(&path.points, closed);
+        if polyline.points().len() > 1 {
+            let points = polyline
+                .points()
+                .iter()
+                .map(|&p| position_metres_to_gui(utils::pt2::to_metres(p), camera))
+                .map(|(x, y)| [x, y]);
+            widget::PointPath::abs(points)
+                .color(color::DARK_ORANGE)
+                .thickness(2.0)
+                .parent(ids.floorplan)
+                .graphics_for(ids.floorplan)
+                .set(ids.floorplan_path, ui);
+        }
+
+        // Draw a handle for each point.
+        const POINT_RADIUS: Scalar = 6.0;
+        let mut remove_index = None;
+        for i in 0..num_points {
+            let widget_id = ids.floorplan_path_points[i];
+            let (dragged_x, dragged_y) = ui.widget_input(widget_id)
+                .drags()
+                .left()
+                .fold((0.0, 0.0), |(x, y), drag| {
+                    (x + drag.delta_xy[0], y + drag.delta_xy[1])
+                });
+            if dragged_x != 0.0 || dragged_y != 0.0 {
+                path.points[i].x += camera.scalar_to_metres(dragged_x);
+                path.points[i].y += camera.scalar_to_metres(dragged_y);
+                changed = true;
+            }
+            if ui.widget_input(widget_id).clicks().right().next().is_some() {
+                remove_index = Some(i);
+            }
+
+            let color = match ui.widget_input(widget_id).mouse() {
+                Some(mouse) => if mouse.buttons.left().is_down() {
+                    color::ORANGE.clicked()
+                } else {
+                    color::ORANGE.highlighted()
+                },
+                None => color::ORANGE,
+            };
+            let (x, y) = position_metres_to_gui(path.points[i], camera);
+            widget::Circle::fill(POINT_RADIUS)
+                .x_y(x, y)
+                .parent(ids.floorplan)
+                .color(color)
+                .set(widget_id, ui);
+        }
+        if let Some(i) = remove_index {
+            path.points.remove(i);
+            changed = true;
+        }
+
+        if changed {
+            let generative = audio::source::movement::Generative::Path(path);
+            source_editor::update_generative_movement(channels, sources, source_id, generative);
+        }
+    }
+
+    // Draw the area of the selected installation over the floorplan while it is being edited.
+    //
+    // Editing works the same as for source paths, which take precedence if both are being edited.
+    let is_editing_polygon = !is_editing_path
+        && gui.state.is_open.installation_editor
+        && project_state.installation_editor.editing_polygon;
+    let editing_polygon = match project_state.installation_editor.selected {
+        Some(ref selected) if is_editing_polygon => project
+            .installations
+            .get(&selected.id)
+            .map(|installation| (selected.id, installation.polygon.clone())),
+        _ => None,
+    };
+    if let Some((installation_id, mut polygon)) = editing_polygon {
+        let Gui {
+            ref mut ids,
+            ref mut ui,
+            ref channels,
+            ..
+        } = *gui;
+
+        let Project {
+            state: project::State {
+                ref camera,
+                ref mut installations,
+                ..
+            },
+            ..
+        } = *project;
+        let mut changed = false;
+
+        // Append a vertex wherever the floorplan was clicked.
+        for click in ui.widget_input(ids.floorplan).clicks().left() {
+            let x = camera.position.x + camera.scalar_to_metres(click.xy[0]);
+            let y = camera.position.y + camera.scalar_to_metres(click.xy[1]);
+            polygon.push(Point2 { x, y });
+            changed = true;
+        }
+
+        // Ensure there are enough IDs available.
+        let num_points = polygon.len();
+        if ids.floorplan_installation_polygon_points.len() < num_points {
+            let id_gen = &mut ui.widget_id_generator();
+            ids.floorplan_installation_polygon_points.resize(num_points, id_gen);
+        }
+
+        // Draw the closed outline of the polygon.
+        if num_points > 1 {
+            let points = polygon
+                .iter()
+                .chain(polygon.first())
+                .map(|&p| position_metres_to_gui(p, camera))
+                .map(|(x, y)| [x, y]);
+            widget::PointPath::abs(points)
+                .color(color::DARK_GREEN)
+                .thickness(2.0)
+                .parent(ids.floorplan)
+                .graphics_for(ids.floorplan)
+                .set(ids.floorplan_installation_polygon, ui);
+        }
+
+        // Draw a handle for each vertex.
+        const POINT_RADIUS: Scalar = 6.0;
+        let mut remove_index = None;
+        for i in 0..num_points {
+            let widget_id = ids.floorplan_installation_polygon_points[i];
+            let (dragged_x, dragged_y) = ui.widget_input(widget_id)
+                .drags()
+                .left()
+                .fold((0.0, 0.0), |(x, y), drag| {
+                    (x + drag.delta_xy[0], y + drag.delta_xy[1])
+                });
+            if dragged_x != 0.0 || dragged_y != 0.0 {
+                polygon[i].x += camera.scalar_to_metres(dragged_x);
+                polygon[i].y += camera.scalar_to_metres(dragged_y);
+                changed = true;
+            }
+            if ui.widget_input(widget_id).clicks().right().next().is_some() {
+                remove_index = Some(i);
+            }
+
+            let color = match ui.widget_input(widget_id).mouse() {
+                Some(mouse) => if mouse.buttons.left().is_down() {
+                    color::GREEN.clicked()
+                } else {
+                    color::GREEN.highlighted()
+                },
+                None => color::GREEN,
+            };
+            let (x, y) = position_metres_to_gui(polygon[i], camera);
+            widget::Circle::fill(POINT_RADIUS)
+                .x_y(x, y)
+                .parent(ids.floorplan)
+                .color(color)
+                .set(widget_id, ui);
+        }
+        if let Some(i) = remove_index {
+            polygon.remove(i);
+            changed = true;
+        }
+
+        if changed {
+            if let Some(installation) = installations.get_mut(&installation_id) {
+                installation.polygon = polygon.clone();
+            }
+            channels
+                .soundscape
+                .send(move |soundscape| {
+                    soundscape.set_installation_polygon(installation_id, polygon);
+                })
+                .expect("failed to send installation polygon to soundscape thread");
+        }
+    }
+
     // Draw the currently active sounds over the floorplan.
     let mut speakers_in_proximity = vec![]; // TODO: Move this to where it can be re-used.
     {
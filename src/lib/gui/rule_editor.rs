@@ -0,0 +1,420 @@
+//! A `Rules` panel displaying:
+//!
+//! - A list for adding, editing and removing rules describing when sources may be played in
+//!   relation to one another.
+//! - The sources currently withheld by the rules along with the reason for each.
+
+use audio;
+use gui::{collapsible_area, Channels, Gui, State};
+use gui::{DARK_A, ITEM_HEIGHT, SMALL_FONT_SIZE};
+use project::{self, Project};
+use nannou::ui;
+use nannou::ui::prelude::*;
+use soundscape::group;
+use soundscape::rules::{self, Reason, Rule, Rules, Target};
+use time_calc::Ms;
+use utils::SEC_MS;
+
+// The kinds of rules that may be added via the "ADD RULE" list.
+const RULE_KINDS: &'static [&'static str] = &["Not While", "Only After"];
+
+// The maximum duration selectable via the "within" slider of `Only After` rules.
+const MAX_WITHIN_SECS: f64 = 60.0;
+
+// The targets available to rules, each sorted by name.
+struct Targets {
+    groups: Vec<(group::Id, String)>,
+    sources: Vec<(audio::source::Id, String)>,
+}
+
+/// Sets all widgets in the rule area and returns the `Id` of the last area.
+pub fn set(last_area_id: widget::Id, gui: &mut Gui, project: &mut Project) -> widget::Id {
+    let Gui {
+        ref mut ui,
+        ref mut ids,
+        channels,
+        state: &mut State {
+            ref mut is_open,
+            ..
+        },
+        ..
+    } = *gui;
+
+    let Project {
+        state: project::State {
+            ref mut rules,
+            ref soundscape_groups,
+            ref sources,
+            ..
+        },
+        ..
+    } = *project;
+
+    // The sources currently withheld by the rules.
+    let violations = channels.soundscape.rule_violations();
+
+    // Constants to use as widget heights.
+    const PAD: Scalar = 6.0;
+    const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
+    const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const RULES_CANVAS_BASE_H: Scalar = PAD + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD;
+    const RULE_ROW_H: Scalar = TITLE_H + PAD + ITEM_HEIGHT + PAD + ITEM_HEIGHT + PAD;
+    const RULE_WITHIN_H: Scalar = SLIDER_H + PAD;
+    const VIOLATION_H: Scalar = TITLE_H + PAD;
+    const VIOLATIONS_CANVAS_BASE_H: Scalar = PAD + TITLE_H + PAD * 2.0 + PAD;
+
+    let num_withins = rules.iter().filter(|r| is_only_after(r)).count();
+    let rules_canvas_h = RULES_CANVAS_BASE_H
+        + RULE_ROW_H * rules.len() as Scalar
+        + RULE_WITHIN_H * num_withins as Scalar
+        + PAD;
+    let violations_canvas_h =
+        VIOLATIONS_CANVAS_BASE_H + VIOLATION_H * violations.len().max(1) as Scalar;
+    let rule_editor_canvas_h = PAD + rules_canvas_h + PAD + violations_canvas_h + PAD;
+
+    // The collapsible area.
+    let (area, event) = collapsible_area(is_open.rule_editor, "Rule Editor", ids.side_menu)
+        .align_middle_x_of(ids.side_menu)
+        .down_from(last_area_id, 0.0)
+        .set(ids.rule_editor, ui);
+    if let Some(event) = event {
+        is_open.rule_editor = event.is_open();
+    }
+
+    // If the area is open, get the area.
+    let area = match area {
+        Some(area) => area,
+        None => return ids.rule_editor,
+    };
+
+    // The canvas on which the rule editor will be placed.
+    let canvas = widget::Canvas::new()
+        .pad(PAD)
+        .h(rule_editor_canvas_h);
+    area.set(canvas, ui);
+
+    ///////////
+    // RULES //
+    ///////////
+
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(rules_canvas_h)
+        .mid_top_of(area.id)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.rule_editor_rules_canvas, ui);
+    let rules_canvas_kid_area = ui.kid_area_of(ids.rule_editor_rules_canvas).unwrap();
+
+    widget::Text::new("Rules")
+        .top_left_of(ids.rule_editor_rules_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.rule_editor_rules_text, ui);
+
+    // Collect the targets available to rules.
+    let mut targets = Targets {
+        groups: soundscape_groups
+            .iter()
+            .map(|(&id, group)| (id, group.name.clone()))
+            .collect(),
+        sources: sources
+            .iter()
+            .filter(|&(_, source)| match source.role {
+                Some(audio::source::Role::Soundscape(_)) => true,
+                _ => false,
+            })
+            .map(|(&id, source)| (id, source.name.clone()))
+            .collect(),
+    };
+    targets.groups.sort_by(|a, b| a.1.cmp(&b.1));
+    targets.sources.sort_by(|a, b| a.1.cmp(&b.1));
+
+    // A drop down list for adding new rules.
+    for kind in widget::DropDownList::new(RULE_KINDS, None)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.rule_editor_rules_canvas)
+        .label("ADD RULE")
+        .label_font_size(SMALL_FONT_SIZE)
+        .scrollbar_on_top()
+        .max_visible_items(5)
+        .color(DARK_A)
+        .border_color(color::LIGHT_CHARCOAL)
+        .set(ids.rule_editor_add, ui)
+    {
+        let rule = match new_rule(kind, &targets) {
+            Some(rule) => rule,
+            None => continue,
+        };
+        rules.push(rule);
+        send_rules(channels, rules);
+    }
+
+    // A row for each rule displaying its description, its targets and its window if it has one.
+    let num_rules = rules.len();
+    if ids.rule_editor_rule_texts.len() < num_rules {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.rule_editor_rule_texts.resize(num_rules, id_gen);
+        ids.rule_editor_rule_targets.resize(num_rules, id_gen);
+        ids.rule_editor_rule_removes.resize(num_rules, id_gen);
+        ids.rule_editor_rule_others.resize(num_rules, id_gen);
+        ids.rule_editor_rule_withins.resize(num_rules, id_gen);
+    }
+    let (labels, all_targets) = target_labels(&targets);
+    let target_w = rules_canvas_kid_area.w() - ITEM_HEIGHT - PAD;
+    let mut last_row_id = ids.rule_editor_add;
+    let mut maybe_remove_index = None;
+    for i in 0..num_rules {
+        let mut rule = rules[i].clone();
+
+        let description = rule_description(&rule, soundscape_groups, sources);
+        widget::Text::new(&description)
+            .align_left_of(ids.rule_editor_add)
+            .down_from(last_row_id, PAD)
+            .font_size(SMALL_FONT_SIZE)
+            .parent(ids.rule_editor_rules_canvas)
+            .set(ids.rule_editor_rule_texts[i], ui);
+
+        // The target constrained by the rule.
+        let selected = all_targets.iter().position(|t| t == rule.target());
+        for index in widget::DropDownList::new(&labels, selected)
+            .align_left()
+            .down(PAD)
+            .w_h(target_w, ITEM_HEIGHT)
+            .label_font_size(SMALL_FONT_SIZE)
+            .scrollbar_on_top()
+            .max_visible_items(5)
+            .color(DARK_A)
+            .border_color(color::LIGHT_CHARCOAL)
+            .parent(ids.rule_editor_rules_canvas)
+            .set(ids.rule_editor_rule_targets[i], ui)
+        {
+            match rule {
+                Rule::NotWhile { ref mut target, .. }
+                | Rule::OnlyAfter { ref mut target, .. } => *target = all_targets[index],
+            }
+        }
+
+        // A button for removing the rule.
+        if widget::Button::new()
+            .label("X")
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::DARK_RED.alpha(0.5))
+            .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+            .right(PAD)
+            .parent(ids.rule_editor_rules_canvas)
+            .set(ids.rule_editor_rule_removes[i], ui)
+            .was_clicked()
+        {
+            maybe_remove_index = Some(i);
+        }
+
+        // The target whose activity determines whether or not the rule is satisfied.
+        let selected = all_targets.iter().position(|t| t == rule.other());
+        for index in widget::DropDownList::new(&labels, selected)
+            .align_left_of(ids.rule_editor_rule_targets[i])
+            .down_from(ids.rule_editor_rule_targets[i], PAD)
+            .w_h(target_w, ITEM_HEIGHT)
+            .label_font_size(SMALL_FONT_SIZE)
+            .scrollbar_on_top()
+            .max_visible_items(5)
+            .color(DARK_A)
+            .border_color(color::LIGHT_CHARCOAL)
+            .parent(ids.rule_editor_rules_canvas)
+            .set(ids.rule_editor_rule_others[i], ui)
+        {
+            match rule {
+                Rule::NotWhile { ref mut other, .. }
+                | Rule::OnlyAfter { ref mut other, .. } => *other = all_targets[index],
+            }
+        }
+        last_row_id = ids.rule_editor_rule_others[i];
+
+        // A slider for the window following the other target, if any.
+        if let Rule::OnlyAfter { ref mut within, .. } = rule {
+            let within_secs = within.ms() / SEC_MS;
+            let label = format!("Within: {:.1} secs", within_secs);
+            for new_within_secs in widget::Slider::new(within_secs, 0.0, MAX_WITHIN_SECS)
+                .align_left_of(last_row_id)
+                .down_from(last_row_id, PAD)
+                .w_h(rules_canvas_kid_area.w(), SLIDER_H)
+                .label(&label)
+                .label_font_size(SMALL_FONT_SIZE)
+                .color(ui::color::LIGHT_CHARCOAL)
+                .parent(ids.rule_editor_rules_canvas)
+                .set(ids.rule_editor_rule_withins[i], ui)
+            {
+                let new_within_secs = (new_within_secs * 10.0).round() / 10.0;
+                *within = Ms(new_within_secs * SEC_MS);
+            }
+            last_row_id = ids.rule_editor_rule_withins[i];
+        }
+
+        // Update the local copy and the soundscape if the rule was edited.
+        if rule != rules[i] {
+            rules[i] = rule;
+            send_rules(channels, rules);
+        }
+    }
+
+    // Remove a rule if necessary.
+    if let Some(i) = maybe_remove_index {
+        rules.remove(i);
+        send_rules(channels, rules);
+    }
+
+    ////////////////
+    // VIOLATIONS //
+    ////////////////
+
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(violations_canvas_h)
+        .align_middle_x_of(area.id)
+        .down_from(ids.rule_editor_rules_canvas, PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.rule_editor_violations_canvas, ui);
+
+    widget::Text::new("Withheld Sources")
+        .top_left_of(ids.rule_editor_violations_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.rule_editor_violations_text, ui);
+
+    // If no sources are withheld, say so.
+    if violations.is_empty() {
+        widget::Text::new("No sources are currently withheld by rules.")
+            .font_size(SMALL_FONT_SIZE)
+            .align_left()
+            .down(PAD * 2.0)
+            .color(color::LIGHT_CHARCOAL)
+            .set(ids.rule_editor_violations_none, ui);
+        return area.id;
+    }
+
+    // A line explaining each withheld source.
+    if ids.rule_editor_violation_texts.len() < violations.len() {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.rule_editor_violation_texts.resize(violations.len(), id_gen);
+    }
+    for (i, violation) in violations.iter().enumerate() {
+        let text = violation_description(violation, rules, soundscape_groups, sources);
+        let down = if i == 0 { PAD * 2.0 } else { PAD };
+        widget::Text::new(&text)
+            .font_size(SMALL_FONT_SIZE)
+            .align_left_of(ids.rule_editor_violations_text)
+            .down(down)
+            .parent(ids.rule_editor_violations_canvas)
+            .set(ids.rule_editor_violation_texts[i], ui);
+    }
+
+    area.id
+}
+
+// Send a copy of the updated rules to the soundscape thread.
+fn send_rules(channels: &Channels, rules: &Rules) {
+    let clone = rules.clone();
+    channels
+        .soundscape
+        .send(move |soundscape| soundscape.set_rules(clone))
+        .expect("failed to send updated rules to soundscape thread");
+}
+
+// Whether or not the rule has a window following the other target.
+fn is_only_after(rule: &Rule) -> bool {
+    match *rule {
+        Rule::OnlyAfter { .. } => true,
+        _ => false,
+    }
+}
+
+// Create a new rule of the kind at the given index within `RULE_KINDS`.
+//
+// Rules constrain the first source while the first group is active by default. Returns `None` if
+// there is nothing to target.
+fn new_rule(kind: usize, targets: &Targets) -> Option<Rule> {
+    let source = targets.sources.first().map(|&(id, _)| Target::Source(id));
+    let group = targets.groups.first().map(|&(id, _)| Target::Group(id));
+    let target = source.or(group)?;
+    let other = group.unwrap_or(target);
+    let rule = match kind {
+        0 => Rule::NotWhile { target, other },
+        1 => Rule::OnlyAfter { target, other, within: rules::default::WITHIN },
+        _ => return None,
+    };
+    Some(rule)
+}
+
+// The labels of all targets along with the targets themselves, groups first.
+fn target_labels(targets: &Targets) -> (Vec<String>, Vec<Target>) {
+    let groups = targets
+        .groups
+        .iter()
+        .map(|&(id, ref name)| (format!("{} (Group)", name), Target::Group(id)));
+    let sources = targets
+        .sources
+        .iter()
+        .map(|&(id, ref name)| (name.clone(), Target::Source(id)));
+    groups.chain(sources).unzip()
+}
+
+// The name of the source or group targeted.
+fn target_name(
+    target: &Target,
+    groups: &project::SoundscapeGroups,
+    sources: &project::Sources,
+) -> String {
+    let name = match *target {
+        Target::Group(ref id) => groups.get(id).map(|g| format!("the {} group", g.name)),
+        Target::Source(ref id) => sources.get(id).map(|s| s.name.clone()),
+    };
+    name.unwrap_or_else(|| "<removed>".to_string())
+}
+
+// A human-readable sentence describing the rule.
+fn rule_description(
+    rule: &Rule,
+    groups: &project::SoundscapeGroups,
+    sources: &project::Sources,
+) -> String {
+    let target = target_name(rule.target(), groups, sources);
+    let other = target_name(rule.other(), groups, sources);
+    match *rule {
+        Rule::NotWhile { .. } => format!("Never play {} while {} is active", target, other),
+        Rule::OnlyAfter { within, .. } => format!(
+            "Only play {} within {:.1} secs after {}",
+            target,
+            within.ms() / SEC_MS,
+            other,
+        ),
+    }
+}
+
+// A human-readable sentence explaining why the source is withheld.
+fn violation_description(
+    violation: &rules::Violation,
+    rules: &Rules,
+    groups: &project::SoundscapeGroups,
+    sources: &project::Sources,
+) -> String {
+    let source = target_name(&Target::Source(violation.source), groups, sources);
+    let rule = match rules.get(violation.rule) {
+        None => return format!("{}: withheld by a removed rule", source),
+        Some(rule) => rule,
+    };
+    let other = target_name(rule.other(), groups, sources);
+    match (violation.reason, rule) {
+        (Reason::OtherActive, _) => format!("{}: {} is active", source, other),
+        (Reason::OtherNeverSpawned, _) => format!("{}: waiting for {} to play", source, other),
+        (Reason::OtherSpawnedTooLongAgo, &Rule::OnlyAfter { within, .. }) => format!(
+            "{}: {} has not played within {:.1} secs",
+            source,
+            other,
+            within.ms() / SEC_MS,
+        ),
+        (Reason::OtherSpawnedTooLongAgo, _) => format!("{}: {} played too long ago", source, other),
+    }
+}
@@ -0,0 +1,347 @@
+//! A `Scenes` panel displaying:
+//!
+//! - A list for capturing and removing scenes.
+//! - The name and crossfade duration of the selected scene along with buttons for re-capturing and
+//!   recalling it.
+
+use gui::{collapsible_area, Gui, ProjectState, State};
+use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
+use project::{self, Project};
+use nannou::ui;
+use nannou::ui::prelude::*;
+use scene;
+use time_calc::Ms;
+use utils::SEC_MS;
+
+/// GUI state related to the scene editor area.
+#[derive(Default)]
+pub struct SceneEditor {
+    pub selected: Option<Selected>,
+}
+
+/// The currently selected scene.
+pub struct Selected {
+    id: scene::Id,
+    name: String,
+}
+
+// The maximum crossfade duration selectable via the fade slider.
+const MAX_FADE_SECS: f64 = 60.0;
+
+/// Sets all widgets in the scene area and returns the `Id` of the last area.
+pub fn set(
+    last_area_id: widget::Id,
+    gui: &mut Gui,
+    project: &mut Project,
+    project_state: &mut ProjectState,
+) -> widget::Id {
+    let Gui {
+        ref mut ui,
+        ref ids,
+        channels,
+        state: &mut State {
+            ref mut is_open,
+            ..
+        },
+        ..
+    } = *gui;
+
+    let ProjectState {
+        ref mut scene_editor,
+        ..
+    } = *project_state;
+
+    // Constants to use as widget heights.
+    const PAD: Scalar = 6.0;
+    const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
+    const PLUS_SCENE_H: Scalar = ITEM_HEIGHT;
+    const SCENE_LIST_MAX_H: Scalar = ITEM_HEIGHT * 5.0;
+    const SCENE_CANVAS_H: Scalar =
+        PAD + TITLE_H + PAD * 2.0 + PLUS_SCENE_H + SCENE_LIST_MAX_H + PAD;
+    const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const SELECTED_CANVAS_H: Scalar = PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD
+        + SLIDER_H + PAD
+        + ITEM_HEIGHT + PAD;
+
+    // Unselect the scene if it no longer exists.
+    if scene_editor.selected.as_ref().map(|s| !project.scenes.contains_key(&s.id)) == Some(true) {
+        scene_editor.selected = None;
+    }
+
+    let selected_canvas_h = match scene_editor.selected {
+        None => 0.0,
+        Some(_) => PAD + SELECTED_CANVAS_H,
+    };
+    let scene_editor_canvas_h = PAD + SCENE_CANVAS_H + selected_canvas_h + PAD;
+
+    // The collapsible area.
+    let (area, event) = collapsible_area(is_open.scene_editor, "Scene Editor", ids.side_menu)
+        .align_middle_x_of(ids.side_menu)
+        .down_from(last_area_id, 0.0)
+        .set(ids.scene_editor, ui);
+    if let Some(event) = event {
+        is_open.scene_editor = event.is_open();
+    }
+
+    // If the area is open, get the area.
+    let area = match area {
+        Some(area) => area,
+        None => return ids.scene_editor,
+    };
+
+    // The canvas on which the scene editor will be placed.
+    let canvas = widget::Canvas::new()
+        .pad(PAD)
+        .h(scene_editor_canvas_h);
+    area.set(canvas, ui);
+
+    ////////////
+    // SCENES //
+    ////////////
+
+    // A canvas on which scene selection takes place.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(SCENE_CANVAS_H)
+        .mid_top_of(area.id)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.scene_editor_list_canvas, ui);
+
+    widget::Text::new("Scenes")
+        .top_left_of(ids.scene_editor_list_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.scene_editor_list_text, ui);
+
+    // A button for capturing the current state as a new scene.
+    for _click in widget::Button::new()
+        .label("+")
+        .kid_area_w_of(ids.scene_editor_list_canvas)
+        .h(PLUS_SCENE_H)
+        .align_middle_x_of(ids.scene_editor_list_canvas)
+        .down(PAD * 2.0)
+        .set(ids.scene_editor_add, ui)
+    {
+        let id = project::next_scene_id(&project.scenes);
+        let name = format!("Scene {}", id.0);
+        let scene = project.capture_scene(name.clone());
+        project.scenes.insert(id, scene);
+        scene_editor.selected = Some(Selected { id, name });
+    }
+
+    // If there are no scenes, display some text for capturing a scene.
+    if project.scenes.is_empty() {
+        widget::Text::new("Capture the current state with the \"+\" button above!")
+            .font_size(SMALL_FONT_SIZE)
+            .align_middle_x_of(ids.scene_editor_list_canvas)
+            .down(PAD + ITEM_HEIGHT)
+            .set(ids.scene_editor_none, ui);
+        return area.id;
+    }
+
+    // Display the scenes in the order in which they were captured.
+    let mut scenes_vec: Vec<_> = project
+        .scenes
+        .iter()
+        .map(|(&id, scene)| (id, scene.name.clone()))
+        .collect();
+    scenes_vec.sort_by_key(|&(id, _)| id.0);
+
+    let (mut events, scrollbar) = widget::ListSelect::single(scenes_vec.len())
+        .down(0.0)
+        .flow_down()
+        .item_size(ITEM_HEIGHT)
+        .h(SCENE_LIST_MAX_H)
+        .kid_area_w_of(ids.scene_editor_list_canvas)
+        .scrollbar_next_to()
+        .set(ids.scene_editor_list, ui);
+
+    // Track whether or not an item was removed.
+    let mut maybe_remove_index = None;
+    let selected_id = scene_editor.selected.as_ref().map(|s| s.id);
+    while let Some(event) = events.next(ui, |i| Some(scenes_vec[i].0) == selected_id) {
+        use self::ui::widget::list_select::Event;
+        match event {
+            // Instantiate the widget for this item.
+            Event::Item(item) => {
+                let (id, ref name) = scenes_vec[item.i];
+                let is_selected = selected_id == Some(id);
+
+                // Blue if selected, gray otherwise.
+                let color = if is_selected {
+                    color::BLUE
+                } else {
+                    color::DARK_CHARCOAL
+                };
+
+                // Use a button widget for each item.
+                let label_x = position::Relative::Place(position::Place::Start(Some(10.0)));
+                let button = widget::Button::new()
+                    .label(name)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .label_x(label_x)
+                    .color(color);
+                item.set(button, ui);
+
+                // If the button or any of its children are capturing the mouse, display
+                // the `remove` button.
+                let show_remove_button = ui.global_input()
+                    .current
+                    .widget_capturing_mouse
+                    .map(|id| {
+                        id == item.widget_id
+                            || ui.widget_graph()
+                                .does_recursive_depth_edge_exist(item.widget_id, id)
+                    })
+                    .unwrap_or(false);
+
+                if !show_remove_button {
+                    continue;
+                }
+
+                if widget::Button::new()
+                    .label("X")
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(color::DARK_RED.alpha(0.5))
+                    .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+                    .align_right_of(item.widget_id)
+                    .align_middle_y_of(item.widget_id)
+                    .parent(item.widget_id)
+                    .set(ids.scene_editor_remove, ui)
+                    .was_clicked()
+                {
+                    maybe_remove_index = Some(item.i);
+                }
+            },
+
+            // Update the selected scene.
+            Event::Selection(idx) => {
+                let (id, ref name) = scenes_vec[idx];
+                let name = name.clone();
+                scene_editor.selected = Some(Selected { id, name });
+            },
+
+            _ => (),
+        }
+    }
+
+    // The scrollbar for the list.
+    if let Some(s) = scrollbar {
+        s.set(ui);
+    }
+
+    // Remove a scene if necessary.
+    if let Some(i) = maybe_remove_index {
+        let (id, _) = scenes_vec[i];
+        project.scenes.remove(&id);
+        if selected_id == Some(id) {
+            scene_editor.selected = None;
+        }
+    }
+
+    ////////////////////
+    // SELECTED SCENE //
+    ////////////////////
+
+    // Only continue if there is some selected scene.
+    let SceneEditor { ref mut selected } = *scene_editor;
+    let selected = match selected.as_mut() {
+        Some(selected) => selected,
+        None => return area.id,
+    };
+    let id = selected.id;
+
+    // A canvas for parameters specific to the selected scene.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(SELECTED_CANVAS_H)
+        .align_middle_x_of(area.id)
+        .down_from(ids.scene_editor_list_canvas, PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.scene_editor_selected_canvas, ui);
+    let selected_canvas_kid_area = ui.kid_area_of(ids.scene_editor_selected_canvas).unwrap();
+
+    widget::Text::new("Selected Scene")
+        .top_left_of(ids.scene_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.scene_editor_selected_text, ui);
+
+    // Display a textbox for editing the name.
+    for event in widget::TextBox::new(&selected.name)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.scene_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::BLACK)
+        .set(ids.scene_editor_selected_name, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp selected name.
+            Event::Update(new_name) => {
+                selected.name = new_name;
+            },
+            // Only when enter is pressed do we update the actual name.
+            Event::Enter => {
+                if let Some(scene) = project.scenes.get_mut(&id) {
+                    scene.name = selected.name.clone();
+                }
+            },
+        }
+    }
+
+    // A slider for the duration of the crossfade when the scene is recalled.
+    let fade_secs = project.scenes[&id].fade.ms() / SEC_MS;
+    let label = format!("Fade: {:.1} secs", fade_secs);
+    for new_fade_secs in widget::Slider::new(fade_secs, 0.0, MAX_FADE_SECS)
+        .kid_area_w_of(ids.scene_editor_selected_canvas)
+        .h(SLIDER_H)
+        .align_left()
+        .down(PAD)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .set(ids.scene_editor_selected_fade, ui)
+    {
+        let new_fade_secs = (new_fade_secs * 10.0).round() / 10.0;
+        project.scenes.get_mut(&id).unwrap().fade = Ms(new_fade_secs * SEC_MS);
+    }
+
+    // Buttons for re-capturing the scene from the current state and for recalling it.
+    let button_w = (selected_canvas_kid_area.w() - PAD) / 2.0;
+    for _click in widget::Button::new()
+        .label("Update")
+        .label_font_size(SMALL_FONT_SIZE)
+        .w_h(button_w, ITEM_HEIGHT)
+        .align_left()
+        .down(PAD)
+        .color(color::DARK_CHARCOAL)
+        .set(ids.scene_editor_selected_update, ui)
+    {
+        let (name, fade) = {
+            let scene = &project.scenes[&id];
+            (scene.name.clone(), scene.fade)
+        };
+        let scene = scene::Scene { fade, ..project.capture_scene(name) };
+        project.scenes.insert(id, scene);
+    }
+
+    for _click in widget::Button::new()
+        .label("Recall")
+        .label_font_size(SMALL_FONT_SIZE)
+        .w_h(button_w, ITEM_HEIGHT)
+        .right(PAD)
+        .color(color::BLUE)
+        .set(ids.scene_editor_selected_recall, ui)
+    {
+        let fade = project.scenes[&id].fade;
+        project.recall_scene(&id, fade, channels);
+    }
+
+    area.id
+}
@@ -0,0 +1,932 @@
+//! A `Schedule` panel displaying:
+//!
+//! - A weekly timeline of all schedule windows along with the current local time.
+//! - The offset of the exhibition's local time from UTC.
+//! - A list for adding and removing schedule windows.
+//! - The days, times and actions of the selected window.
+
+use audio;
+use gui::{collapsible_area, hz_label, Channels, Gui, ProjectState, State};
+use gui::{DARK_A, ITEM_HEIGHT, SMALL_FONT_SIZE};
+use installation;
+use project::{self, Project};
+use nannou::ui;
+use nannou::ui::prelude::*;
+use scene;
+use soundscape::group;
+use soundscape::schedule::{self, Action, Schedule, Window, MINUTES_PER_DAY};
+use time_calc::Ms;
+use utils::{self, Range};
+
+/// GUI state related to the schedule editor area.
+#[derive(Default)]
+pub struct ScheduleEditor {
+    pub selected: Option<Selected>,
+}
+
+/// The currently selected schedule window.
+pub struct Selected {
+    index: usize,
+    name: String,
+    // The date text as it is being edited.
+    date: String,
+    // Whether or not the submitted date was invalid.
+    date_error: bool,
+}
+
+// Start and end times and the UTC offset are stepped by this many minutes.
+const MINUTE_STEP: u32 = 15;
+
+// The kinds of actions that may be added to a window via the "ADD ACTION" list.
+const ACTION_KINDS: &'static [&'static str] = &[
+    "Play Soundscape",
+    "Pause Soundscape",
+    "Disable Group",
+    "Mute Group",
+    "Disable Source",
+    "Mute Source",
+    "Group Occurrence Rate",
+    "Group Simultaneous Sounds",
+    "Installation Simultaneous Sounds",
+    "Recall Scene",
+];
+
+// The targets available to actions, each sorted by name.
+struct Targets {
+    groups: Vec<(group::Id, String)>,
+    sources: Vec<(audio::source::Id, String)>,
+    installations: Vec<(installation::Id, String)>,
+    scenes: Vec<(scene::Id, String)>,
+}
+
+impl Selected {
+    fn new(index: usize, window: &Window) -> Self {
+        let name = window.name.clone();
+        let date = window.date.map(|d| d.to_string()).unwrap_or_default();
+        let date_error = false;
+        Selected { index, name, date, date_error }
+    }
+}
+
+/// Sets all widgets in the schedule area and returns the `Id` of the last area.
+pub fn set(
+    last_area_id: widget::Id,
+    gui: &mut Gui,
+    project: &mut Project,
+    project_state: &mut ProjectState,
+) -> widget::Id {
+    let Gui {
+        ref mut ui,
+        ref mut ids,
+        channels,
+        state: &mut State {
+            ref mut is_open,
+            ..
+        },
+        ..
+    } = *gui;
+
+    let Project {
+        state: project::State {
+            ref mut schedule,
+            ref soundscape_groups,
+            ref installations,
+            ref sources,
+            ref scenes,
+            ..
+        },
+        ..
+    } = *project;
+
+    let ProjectState {
+        ref mut schedule_editor,
+        ..
+    } = *project_state;
+
+    // Constants to use as widget heights.
+    const PAD: Scalar = 6.0;
+    const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
+    const DAY_ROW_H: Scalar = ITEM_HEIGHT * 0.6;
+    const DAY_LABEL_W: Scalar = 36.0;
+    const TIMELINE_CANVAS_H: Scalar = PAD + TITLE_H + PAD * 2.0 + DAY_ROW_H * 7.0 + PAD;
+    const UTC_OFFSET_H: Scalar = ITEM_HEIGHT;
+    const UTC_OFFSET_TOGGLE_W: Scalar = 100.0;
+    const PLUS_WINDOW_H: Scalar = ITEM_HEIGHT;
+    const WINDOW_LIST_MAX_H: Scalar = ITEM_HEIGHT * 4.0;
+    const WINDOW_CANVAS_H: Scalar =
+        PAD + TITLE_H + PAD * 2.0 + PLUS_WINDOW_H + WINDOW_LIST_MAX_H + PAD;
+    const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const SELECTED_CANVAS_BASE_H: Scalar = PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD
+        + ITEM_HEIGHT + PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD
+        + SLIDER_H + PAD + SLIDER_H + PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD;
+    const ACTION_ROW_H: Scalar = TITLE_H + PAD + ITEM_HEIGHT + PAD;
+    const ACTION_PRESET_H: Scalar = SLIDER_H + PAD;
+
+    // Unselect the window if it no longer exists.
+    if schedule_editor.selected.as_ref().map(|s| s.index >= schedule.windows.len()) == Some(true) {
+        schedule_editor.selected = None;
+    }
+
+    // The height of the selected window canvas depends on its number of actions.
+    let selected_canvas_h = match schedule_editor.selected {
+        None => 0.0,
+        Some(ref selected) => {
+            let actions = &schedule.windows[selected.index].actions;
+            let num_presets = actions.iter().filter(|a| a.is_preset()).count();
+            SELECTED_CANVAS_BASE_H
+                + ACTION_ROW_H * actions.len() as Scalar
+                + ACTION_PRESET_H * num_presets as Scalar
+                + PAD
+        },
+    };
+    let schedule_editor_canvas_h = PAD + TIMELINE_CANVAS_H + PAD + UTC_OFFSET_H + PAD
+        + WINDOW_CANVAS_H + PAD + selected_canvas_h;
+
+    // The collapsible area.
+    let (area, event) = collapsible_area(is_open.schedule_editor, "Schedule Editor", ids.side_menu)
+        .align_middle_x_of(ids.side_menu)
+        .down_from(last_area_id, 0.0)
+        .set(ids.schedule_editor, ui);
+    if let Some(event) = event {
+        is_open.schedule_editor = event.is_open();
+    }
+
+    // If the area is open, get the area.
+    let area = match area {
+        Some(area) => area,
+        None => return ids.schedule_editor,
+    };
+
+    // The canvas on which the schedule editor will be placed.
+    let canvas = widget::Canvas::new()
+        .pad(PAD)
+        .h(schedule_editor_canvas_h);
+    area.set(canvas, ui);
+
+    //////////////
+    // TIMELINE //
+    //////////////
+
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(TIMELINE_CANVAS_H)
+        .mid_top_of(area.id)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.schedule_editor_timeline_canvas, ui);
+
+    let now = schedule.now();
+    let title = format!(
+        "This Week - Now: {} {} {}",
+        schedule::DAY_NAMES[now.weekday],
+        now.date(),
+        schedule::format_minute(now.minute),
+    );
+    widget::Text::new(&title)
+        .top_left_of(ids.schedule_editor_timeline_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_timeline_text, ui);
+
+    // Lay out one row per day of the current week with the hours of the day along the x axis.
+    let kid_area = ui.kid_area_of(ids.schedule_editor_timeline_canvas).unwrap();
+    let hours_left = kid_area.left() + DAY_LABEL_W;
+    let hours_w = kid_area.right() - hours_left;
+    let minute_to_x = |minute: u32| {
+        hours_left + hours_w * minute as Scalar / MINUTES_PER_DAY as Scalar
+    };
+    let days_top = kid_area.top() - TITLE_H - PAD * 2.0;
+    let day_y = |weekday: usize| days_top - DAY_ROW_H * (weekday as Scalar + 0.5);
+    let week_start = now.day - now.weekday as i64;
+
+    if ids.schedule_editor_timeline_days.len() < 7 {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.schedule_editor_timeline_days.resize(7, id_gen);
+        ids.schedule_editor_timeline_rows.resize(7, id_gen);
+    }
+    for weekday in 0..7 {
+        widget::Text::new(schedule::DAY_NAMES[weekday])
+            .font_size(SMALL_FONT_SIZE)
+            .x_y(kid_area.left() + DAY_LABEL_W / 2.0, day_y(weekday))
+            .parent(ids.schedule_editor_timeline_canvas)
+            .set(ids.schedule_editor_timeline_days[weekday], ui);
+        widget::Rectangle::fill([hours_w, DAY_ROW_H - 2.0])
+            .x_y(hours_left + hours_w / 2.0, day_y(weekday))
+            .color(color::DARK_CHARCOAL)
+            .parent(ids.schedule_editor_timeline_canvas)
+            .graphics_for(ids.schedule_editor_timeline_canvas)
+            .set(ids.schedule_editor_timeline_rows[weekday], ui);
+    }
+
+    // Collect the spans of each window on each day of the week.
+    let selected_index = schedule_editor.selected.as_ref().map(|s| s.index);
+    let mut spans = vec![];
+    for (i, window) in schedule.windows.iter().enumerate() {
+        // Highlight the selected window and dim those that are not currently active.
+        let color = if selected_index == Some(i) {
+            color::BLUE
+        } else {
+            color::LIGHT_CHARCOAL
+        };
+        let color = if window.is_active(&now) { color } else { color.alpha(0.5) };
+        for weekday in 0..7 {
+            for (start, end) in window.spans_on(week_start + weekday as i64) {
+                spans.push((weekday, start, end, color));
+            }
+        }
+    }
+    if ids.schedule_editor_timeline_windows.len() < spans.len() {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.schedule_editor_timeline_windows.resize(spans.len(), id_gen);
+    }
+    for (i, &(weekday, start, end, color)) in spans.iter().enumerate() {
+        let (start_x, end_x) = (minute_to_x(start), minute_to_x(end));
+        widget::Rectangle::fill([end_x - start_x, DAY_ROW_H - 4.0])
+            .x_y(start_x + (end_x - start_x) / 2.0, day_y(weekday))
+            .color(color)
+            .parent(ids.schedule_editor_timeline_canvas)
+            .graphics_for(ids.schedule_editor_timeline_canvas)
+            .set(ids.schedule_editor_timeline_windows[i], ui);
+    }
+
+    // A line indicating the current time.
+    let now_x = minute_to_x(now.minute);
+    let now_y = day_y(now.weekday);
+    widget::Line::abs([now_x, now_y - DAY_ROW_H / 2.0], [now_x, now_y + DAY_ROW_H / 2.0])
+        .color(color::WHITE)
+        .thickness(2.0)
+        .parent(ids.schedule_editor_timeline_canvas)
+        .graphics_for(ids.schedule_editor_timeline_canvas)
+        .set(ids.schedule_editor_timeline_now, ui);
+
+    ////////////////
+    // UTC OFFSET //
+    ////////////////
+
+    // By default the offset follows the OS timezone, including daylight saving changes.
+    let is_os_timezone = schedule.utc_offset_minutes.is_none();
+    for new_is_os_timezone in widget::Toggle::new(is_os_timezone)
+        .color(color::BLUE)
+        .label("OS Timezone")
+        .label_font_size(SMALL_FONT_SIZE)
+        .w_h(UTC_OFFSET_TOGGLE_W, UTC_OFFSET_H)
+        .align_left_of(ids.schedule_editor_timeline_canvas)
+        .down_from(ids.schedule_editor_timeline_canvas, PAD)
+        .parent(area.id)
+        .set(ids.schedule_editor_utc_offset_os_timezone, ui)
+    {
+        schedule.utc_offset_minutes = match new_is_os_timezone {
+            true => None,
+            false => Some(schedule.current_utc_offset_minutes()),
+        };
+        send_schedule(channels, schedule);
+    }
+
+    // Moving the slider sets an explicit offset. This is fixed regardless of the OS timezone, so
+    // remind the user that it does not follow daylight saving.
+    let offset = schedule.current_utc_offset_minutes();
+    let sign = if offset < 0 { "-" } else { "+" };
+    let minute = schedule::format_minute(offset.abs() as u32);
+    let label = match schedule.utc_offset_minutes {
+        None => format!("UTC Offset: {}{} (OS timezone)", sign, minute),
+        Some(_) => format!("UTC Offset: {}{} (fixed, adjust for DST)", sign, minute),
+    };
+    let max_offset = 14.0 * 60.0;
+    let slider_w = ui.kid_area_of(area.id).unwrap().w() - UTC_OFFSET_TOGGLE_W - PAD;
+    for value in widget::Slider::new(offset as f64, -max_offset, max_offset)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .w_h(slider_w, UTC_OFFSET_H)
+        .right(PAD)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .parent(area.id)
+        .set(ids.schedule_editor_utc_offset, ui)
+    {
+        let step = MINUTE_STEP as f64;
+        let new_offset = ((value / step).round() * step) as i32;
+        if Some(new_offset) != schedule.utc_offset_minutes {
+            schedule.utc_offset_minutes = Some(new_offset);
+            send_schedule(channels, schedule);
+        }
+    }
+
+    /////////////
+    // WINDOWS //
+    /////////////
+
+    // A canvas on which window selection takes place.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(WINDOW_CANVAS_H)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.schedule_editor_window_canvas, ui);
+
+    widget::Text::new("Windows (later windows take precedence)")
+        .top_left_of(ids.schedule_editor_window_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_window_text, ui);
+
+    // A button for adding new windows.
+    for _click in widget::Button::new()
+        .label("+")
+        .kid_area_w_of(ids.schedule_editor_window_canvas)
+        .h(PLUS_WINDOW_H)
+        .align_middle_x_of(ids.schedule_editor_window_canvas)
+        .down(PAD * 2.0)
+        .set(ids.schedule_editor_window_add, ui)
+    {
+        let window = Window {
+            name: "<unnamed>".to_string(),
+            start_minute: 9 * 60,
+            end_minute: 17 * 60,
+            ..Default::default()
+        };
+        let index = schedule.windows.len();
+        schedule_editor.selected = Some(Selected::new(index, &window));
+        schedule.windows.push(window);
+        send_schedule(channels, schedule);
+    }
+
+    // If there are no windows, display some text for adding a window.
+    if schedule.windows.is_empty() {
+        widget::Text::new("Add a window with the \"+\" button above!")
+            .font_size(SMALL_FONT_SIZE)
+            .align_middle_x_of(ids.schedule_editor_window_canvas)
+            .down(PAD + ITEM_HEIGHT)
+            .set(ids.schedule_editor_window_none, ui);
+        return area.id;
+    }
+
+    // A list of all windows in order of precedence.
+    let num_windows = schedule.windows.len();
+    let (mut events, scrollbar) = widget::ListSelect::single(num_windows)
+        .down(0.0)
+        .flow_down()
+        .item_size(ITEM_HEIGHT)
+        .h(WINDOW_LIST_MAX_H)
+        .kid_area_w_of(ids.schedule_editor_window_canvas)
+        .scrollbar_next_to()
+        .set(ids.schedule_editor_window_list, ui);
+
+    // Track whether or not an item was removed.
+    let mut maybe_remove_index = None;
+    let selected_index = schedule_editor.selected.as_ref().map(|s| s.index);
+    while let Some(event) = events.next(ui, |i| Some(i) == selected_index) {
+        use self::ui::widget::list_select::Event;
+        match event {
+            // Instantiate the widget for this item.
+            Event::Item(item) => {
+                let is_selected = selected_index == Some(item.i);
+
+                // Blue if selected, gray otherwise.
+                let color = if is_selected {
+                    color::BLUE
+                } else {
+                    color::DARK_CHARCOAL
+                };
+
+                let label = window_label(&schedule.windows[item.i]);
+
+                // Use a button widget for each item.
+                let label_x = position::Relative::Place(position::Place::Start(Some(10.0)));
+                let button = widget::Button::new()
+                    .label(&label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .label_x(label_x)
+                    .color(color);
+                item.set(button, ui);
+
+                // If the button or any of its children are capturing the mouse, display
+                // the `remove` button.
+                let show_remove_button = ui.global_input()
+                    .current
+                    .widget_capturing_mouse
+                    .map(|id| {
+                        id == item.widget_id
+                            || ui.widget_graph()
+                                .does_recursive_depth_edge_exist(item.widget_id, id)
+                    })
+                    .unwrap_or(false);
+
+                if !show_remove_button {
+                    continue;
+                }
+
+                if widget::Button::new()
+                    .label("X")
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(color::DARK_RED.alpha(0.5))
+                    .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+                    .align_right_of(item.widget_id)
+                    .align_middle_y_of(item.widget_id)
+                    .parent(item.widget_id)
+                    .set(ids.schedule_editor_window_remove, ui)
+                    .was_clicked()
+                {
+                    maybe_remove_index = Some(item.i);
+                }
+            },
+
+            // Update the selected window.
+            Event::Selection(idx) => {
+                schedule_editor.selected = Some(Selected::new(idx, &schedule.windows[idx]));
+            },
+
+            _ => (),
+        }
+    }
+
+    // The scrollbar for the list.
+    if let Some(s) = scrollbar {
+        s.set(ui);
+    }
+
+    // Remove a window if necessary, shifting the selection to follow the selected window.
+    if let Some(i) = maybe_remove_index {
+        schedule.windows.remove(i);
+        let selected_index = schedule_editor.selected.as_ref().map(|s| s.index);
+        match selected_index {
+            Some(index) if index == i => schedule_editor.selected = None,
+            Some(index) if index > i => {
+                schedule_editor.selected.as_mut().unwrap().index -= 1;
+            },
+            _ => (),
+        }
+        send_schedule(channels, schedule);
+    }
+
+    /////////////////////
+    // SELECTED WINDOW //
+    /////////////////////
+
+    // Only continue if there is some selected window.
+    let ScheduleEditor { ref mut selected } = *schedule_editor;
+    let selected = match selected.as_mut() {
+        Some(selected) => selected,
+        None => return area.id,
+    };
+    let index = selected.index;
+
+    // A canvas for parameters specific to the selected window.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(selected_canvas_h - PAD)
+        .align_middle_x_of(area.id)
+        .down_from(ids.schedule_editor_window_canvas, PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.schedule_editor_selected_canvas, ui);
+    let selected_canvas_kid_area = ui.kid_area_of(ids.schedule_editor_selected_canvas).unwrap();
+
+    let title = match schedule.windows[index].is_active(&now) {
+        true => "Selected Window (Active)",
+        false => "Selected Window",
+    };
+    widget::Text::new(title)
+        .top_left_of(ids.schedule_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_selected_text, ui);
+
+    // Display a textbox for editing the name.
+    for event in widget::TextBox::new(&selected.name)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.schedule_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::BLACK)
+        .set(ids.schedule_editor_selected_name, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp selected name.
+            Event::Update(new_name) => {
+                selected.name = new_name;
+            },
+            // Only when enter is pressed do we update the actual name.
+            Event::Enter => {
+                schedule.windows[index].name = selected.name.clone();
+                send_schedule(channels, schedule);
+            },
+        }
+    }
+
+    // A toggle for each day of the week.
+    if ids.schedule_editor_selected_days.len() < 7 {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.schedule_editor_selected_days.resize(7, id_gen);
+    }
+    let day_w = (selected_canvas_kid_area.w() - PAD * 6.0) / 7.0;
+    for weekday in 0..7 {
+        let is_enabled = schedule.windows[index].days[weekday];
+        let toggle = widget::Toggle::new(is_enabled)
+            .color(color::BLUE)
+            .w_h(day_w, ITEM_HEIGHT)
+            .label(schedule::DAY_NAMES[weekday])
+            .label_font_size(SMALL_FONT_SIZE)
+            .parent(ids.schedule_editor_selected_canvas);
+        let toggle = match weekday {
+            0 => toggle.align_left_of(ids.schedule_editor_selected_name).down(PAD),
+            _ => toggle.right(PAD),
+        };
+        for new_is_enabled in toggle.set(ids.schedule_editor_selected_days[weekday], ui) {
+            schedule.windows[index].days[weekday] = new_is_enabled;
+            send_schedule(channels, schedule);
+        }
+    }
+
+    // A textbox for limiting the window to a single date.
+    widget::Text::new("Date (YYYY-MM-DD, or empty for weekly)")
+        .align_left_of(ids.schedule_editor_selected_name)
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_selected_date_text, ui);
+
+    let color = if selected.date_error { color::DARK_RED } else { color::BLACK };
+    for event in widget::TextBox::new(&selected.date)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.schedule_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color)
+        .set(ids.schedule_editor_selected_date, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            Event::Update(new_date) => {
+                selected.date = new_date;
+            },
+            Event::Enter => {
+                let date = match selected.date.trim() {
+                    "" => None,
+                    s => match s.parse::<schedule::Date>() {
+                        Ok(date) => Some(date),
+                        Err(_) => {
+                            selected.date_error = true;
+                            continue;
+                        },
+                    },
+                };
+                selected.date_error = false;
+                selected.date = date.map(|d| d.to_string()).unwrap_or_default();
+                schedule.windows[index].date = date;
+                send_schedule(channels, schedule);
+            },
+        }
+    }
+
+    // Sliders for the start and end of the window.
+    let step = MINUTE_STEP as f64;
+    let to_minute = |value: f64| ((value / step).round() * step) as u32;
+    let slider = |minute: u32| {
+        widget::Slider::new(minute as f64, 0.0, MINUTES_PER_DAY as f64)
+            .kid_area_w_of(ids.schedule_editor_selected_canvas)
+            .h(SLIDER_H)
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(ui::color::LIGHT_CHARCOAL)
+    };
+
+    let start = schedule.windows[index].start_minute;
+    let label = format!("Start: {}", schedule::format_minute(start));
+    for value in slider(start)
+        .align_left()
+        .down(PAD)
+        .label(&label)
+        .set(ids.schedule_editor_selected_start, ui)
+    {
+        let new_start = to_minute(value);
+        if new_start != schedule.windows[index].start_minute {
+            schedule.windows[index].start_minute = new_start;
+            send_schedule(channels, schedule);
+        }
+    }
+
+    let end = schedule.windows[index].end_minute;
+    let label = match end <= start {
+        true => format!("End: {} (next day)", schedule::format_minute(end)),
+        false => format!("End: {}", schedule::format_minute(end)),
+    };
+    for value in slider(end)
+        .align_left()
+        .down(PAD)
+        .label(&label)
+        .set(ids.schedule_editor_selected_end, ui)
+    {
+        let new_end = to_minute(value);
+        if new_end != schedule.windows[index].end_minute {
+            schedule.windows[index].end_minute = new_end;
+            send_schedule(channels, schedule);
+        }
+    }
+
+    /////////////
+    // ACTIONS //
+    /////////////
+
+    widget::Text::new("Actions")
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.schedule_editor_selected_actions_text, ui);
+
+    // Collect the targets available to actions.
+    let mut targets = Targets {
+        groups: soundscape_groups
+            .iter()
+            .map(|(&id, group)| (id, group.name.clone()))
+            .collect(),
+        sources: sources
+            .iter()
+            .filter(|&(_, source)| match source.role {
+                Some(audio::source::Role::Soundscape(_)) => true,
+                _ => false,
+            })
+            .map(|(&id, source)| (id, source.name.clone()))
+            .collect(),
+        installations: installations
+            .iter()
+            .map(|(&id, installation)| (id, installation.name.clone()))
+            .collect(),
+        scenes: scenes
+            .iter()
+            .map(|(&id, scene)| (id, scene.name.clone()))
+            .collect(),
+    };
+    targets.groups.sort_by(|a, b| a.1.cmp(&b.1));
+    targets.sources.sort_by(|a, b| a.1.cmp(&b.1));
+    targets.installations.sort_by(|a, b| a.1.cmp(&b.1));
+    targets.scenes.sort_by(|a, b| a.1.cmp(&b.1));
+
+    // A drop down list for adding new actions.
+    for kind in widget::DropDownList::new(ACTION_KINDS, None)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(ITEM_HEIGHT)
+        .kid_area_w_of(ids.schedule_editor_selected_canvas)
+        .label("ADD ACTION")
+        .label_font_size(SMALL_FONT_SIZE)
+        .scrollbar_on_top()
+        .max_visible_items(5)
+        .color(DARK_A)
+        .border_color(color::LIGHT_CHARCOAL)
+        .set(ids.schedule_editor_selected_add_action, ui)
+    {
+        let action = match new_action(kind, &targets, soundscape_groups, installations) {
+            Some(action) => action,
+            None => continue,
+        };
+        schedule.windows[index].actions.push(action);
+        send_schedule(channels, schedule);
+    }
+
+    // A row for each action displaying its kind, its target and its preset if it has one.
+    let num_actions = schedule.windows[index].actions.len();
+    if ids.schedule_editor_action_texts.len() < num_actions {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.schedule_editor_action_texts.resize(num_actions, id_gen);
+        ids.schedule_editor_action_targets.resize(num_actions, id_gen);
+        ids.schedule_editor_action_removes.resize(num_actions, id_gen);
+        ids.schedule_editor_action_presets.resize(num_actions, id_gen);
+    }
+    let target_w = selected_canvas_kid_area.w() - ITEM_HEIGHT - PAD;
+    let mut last_row_id = ids.schedule_editor_selected_add_action;
+    let mut maybe_remove_index = None;
+    for i in 0..num_actions {
+        let mut action = schedule.windows[index].actions[i].clone();
+
+        widget::Text::new(action.kind_name())
+            .align_left_of(ids.schedule_editor_selected_add_action)
+            .down_from(last_row_id, PAD)
+            .font_size(SMALL_FONT_SIZE)
+            .parent(ids.schedule_editor_selected_canvas)
+            .set(ids.schedule_editor_action_texts[i], ui);
+
+        // The target of the action.
+        let (labels, selected_target) = action_targets(&action, &targets);
+        for target_index in widget::DropDownList::new(&labels, selected_target)
+            .align_left()
+            .down(PAD)
+            .w_h(target_w, ITEM_HEIGHT)
+            .label_font_size(SMALL_FONT_SIZE)
+            .scrollbar_on_top()
+            .max_visible_items(5)
+            .color(DARK_A)
+            .border_color(color::LIGHT_CHARCOAL)
+            .parent(ids.schedule_editor_selected_canvas)
+            .set(ids.schedule_editor_action_targets[i], ui)
+        {
+            set_action_target(&mut action, target_index, &targets);
+        }
+        last_row_id = ids.schedule_editor_action_targets[i];
+
+        // A button for removing the action.
+        if widget::Button::new()
+            .label("X")
+            .label_font_size(SMALL_FONT_SIZE)
+            .color(color::DARK_RED.alpha(0.5))
+            .w_h(ITEM_HEIGHT, ITEM_HEIGHT)
+            .right(PAD)
+            .parent(ids.schedule_editor_selected_canvas)
+            .set(ids.schedule_editor_action_removes[i], ui)
+            .was_clicked()
+        {
+            maybe_remove_index = Some(i);
+        }
+
+        // A slider for the preset, if any.
+        let preset_above = last_row_id;
+        let preset_slider = |start, end, min, max| {
+            widget::RangeSlider::new(start, end, min, max)
+                .align_left_of(preset_above)
+                .down_from(preset_above, PAD)
+                .w_h(selected_canvas_kid_area.w(), SLIDER_H)
+                .label_font_size(SMALL_FONT_SIZE)
+                .color(ui::color::LIGHT_CHARCOAL)
+                .parent(ids.schedule_editor_selected_canvas)
+        };
+        match action {
+            Action::GroupOccurrenceRate(_, ref mut rate) => {
+                let max_hz = utils::ms_interval_to_hz(rate.min);
+                let min_hz = utils::ms_interval_to_hz(rate.max);
+                let label = format!("{} to {}", hz_label(min_hz), hz_label(max_hz));
+                let total_min_hz = utils::ms_interval_to_hz(Ms(utils::DAY_MS));
+                let total_max_hz = utils::ms_interval_to_hz(Ms(1.0));
+                for (edge, value) in preset_slider(min_hz, max_hz, total_min_hz, total_max_hz)
+                    .skew(0.1)
+                    .label(&label)
+                    .set(ids.schedule_editor_action_presets[i], ui)
+                {
+                    let hz = {
+                        let (unit, times_per_unit) = utils::human_readable_hz(value as _);
+                        unit.times_per_unit_to_hz(times_per_unit.round())
+                    };
+                    let ms = utils::hz_to_ms_interval(hz);
+                    match edge {
+                        widget::range_slider::Edge::Start => rate.max = ms,
+                        widget::range_slider::Edge::End => rate.min = ms,
+                    }
+                }
+                last_row_id = ids.schedule_editor_action_presets[i];
+            },
+            Action::GroupSimultaneousSounds(_, ref mut range)
+            | Action::InstallationSimultaneousSounds(_, ref mut range) => {
+                let label = format!("{} to {} sounds at once", range.min, range.max);
+                let (min, max) = (range.min as f64, range.max as f64);
+                for (edge, value) in preset_slider(min, max, 0.0, 100.0)
+                    .skew(0.5)
+                    .label(&label)
+                    .set(ids.schedule_editor_action_presets[i], ui)
+                {
+                    let num = value as _;
+                    match edge {
+                        widget::range_slider::Edge::Start => range.min = num,
+                        widget::range_slider::Edge::End => range.max = num,
+                    }
+                }
+                last_row_id = ids.schedule_editor_action_presets[i];
+            },
+            _ => (),
+        }
+
+        // Update the local copy and the soundscape if the action was edited.
+        if action != schedule.windows[index].actions[i] {
+            schedule.windows[index].actions[i] = action;
+            send_schedule(channels, schedule);
+        }
+    }
+
+    // Remove an action if necessary.
+    if let Some(i) = maybe_remove_index {
+        schedule.windows[index].actions.remove(i);
+        send_schedule(channels, schedule);
+    }
+
+    area.id
+}
+
+// Send a copy of the updated schedule to the soundscape thread.
+fn send_schedule(channels: &Channels, schedule: &Schedule) {
+    let clone = schedule.clone();
+    channels
+        .soundscape
+        .send(move |soundscape| soundscape.set_schedule(clone))
+        .expect("failed to send updated schedule to soundscape thread");
+}
+
+// A short description of the window for the window list.
+fn window_label(window: &Window) -> String {
+    let days = match window.date {
+        Some(date) => date.to_string(),
+        None if window.days.iter().all(|&d| d) => "Daily".to_string(),
+        None => {
+            let days: Vec<_> = (0..7)
+                .filter(|&d| window.days[d])
+                .map(|d| schedule::DAY_NAMES[d])
+                .collect();
+            days.join(",")
+        },
+    };
+    format!(
+        "{} - {} {}-{}",
+        window.name,
+        days,
+        schedule::format_minute(window.start_minute),
+        schedule::format_minute(window.end_minute),
+    )
+}
+
+// Create a new action of the kind at the given index within `ACTION_KINDS`.
+//
+// Actions are targeted at the first group, source, installation or scene by default and presets
+// are initialised to the target's current constraints. Returns `None` if there is nothing to
+// target.
+fn new_action(
+    kind: usize,
+    targets: &Targets,
+    groups: &project::SoundscapeGroups,
+    installations: &project::Installations,
+) -> Option<Action> {
+    let group = targets.groups.first().map(|&(id, _)| id);
+    let source = targets.sources.first().map(|&(id, _)| id);
+    let installation = targets.installations.first().map(|&(id, _)| id);
+    let scene = targets.scenes.first().map(|&(id, _)| id);
+    let action = match kind {
+        0 => Action::PlaySoundscape,
+        1 => Action::PauseSoundscape,
+        2 => Action::DisableGroup(group?),
+        3 => Action::MuteGroup(group?),
+        4 => Action::DisableSource(source?),
+        5 => Action::MuteSource(source?),
+        6 => {
+            let id = group?;
+            Action::GroupOccurrenceRate(id, groups[&id].soundscape.occurrence_rate)
+        },
+        7 => {
+            let id = group?;
+            Action::GroupSimultaneousSounds(id, groups[&id].soundscape.simultaneous_sounds)
+        },
+        8 => {
+            let id = installation?;
+            let range: Range<usize> = installations[&id].soundscape.simultaneous_sounds;
+            Action::InstallationSimultaneousSounds(id, range)
+        },
+        9 => Action::RecallScene(scene?),
+        _ => return None,
+    };
+    Some(action)
+}
+
+// The labels of all possible targets of the action along with the index of the current target.
+fn action_targets(action: &Action, targets: &Targets) -> (Vec<String>, Option<usize>) {
+    fn labels_and_index<T>(list: &[(T, String)], id: &T) -> (Vec<String>, Option<usize>)
+    where
+        T: PartialEq,
+    {
+        let labels = list.iter().map(|&(_, ref name)| name.clone()).collect();
+        let index = list.iter().position(|&(ref other, _)| other == id);
+        (labels, index)
+    }
+    match *action {
+        Action::PlaySoundscape | Action::PauseSoundscape => {
+            (vec!["Whole Soundscape".to_string()], Some(0))
+        },
+        Action::DisableGroup(ref id)
+        | Action::MuteGroup(ref id)
+        | Action::GroupOccurrenceRate(ref id, _)
+        | Action::GroupSimultaneousSounds(ref id, _) => labels_and_index(&targets.groups, id),
+        Action::DisableSource(ref id) | Action::MuteSource(ref id) => {
+            labels_and_index(&targets.sources, id)
+        },
+        Action::InstallationSimultaneousSounds(ref id, _) => {
+            labels_and_index(&targets.installations, id)
+        },
+        Action::RecallScene(ref id) => labels_and_index(&targets.scenes, id),
+    }
+}
+
+// Target the action at the group, source, installation or scene at the given index.
+fn set_action_target(action: &mut Action, index: usize, targets: &Targets) {
+    match *action {
+        Action::PlaySoundscape | Action::PauseSoundscape => (),
+        Action::DisableGroup(ref mut id)
+        | Action::MuteGroup(ref mut id)
+        | Action::GroupOccurrenceRate(ref mut id, _)
+        | Action::GroupSimultaneousSounds(ref mut id, _) => *id = targets.groups[index].0,
+        Action::DisableSource(ref mut id) | Action::MuteSource(ref mut id) => {
+            *id = targets.sources[index].0
+        },
+        Action::InstallationSimultaneousSounds(ref mut id, _) => {
+            *id = targets.installations[index].0
+        },
+        Action::RecallScene(ref mut id) => *id = targets.scenes[index].0,
+    }
+}
@@ -1,27 +1,64 @@
 //! A `Soundscape` panel displaying:
 //!
 //! - Play/Pause toggle for the soundscape.
+//! - Simulation of the soundscape over some hours of playback, saving a statistics report.
 //! - Groups panel for creating/removing soundscape source groups.
+//! - The volume, mute, solo and constraints of the selected group.
+//! - The expected share of plays for each source within the selected group.
 
+use audio;
 use gui::{collapsible_area, hz_label, Gui, ProjectState, State};
 use gui::{ITEM_HEIGHT, SMALL_FONT_SIZE};
 use project::{self, Project};
 use nannou::ui;
 use nannou::ui::prelude::*;
 use soundscape;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time;
 use time_calc::Ms;
 use utils;
 
+/// The number of hours of playback simulated by default.
+const DEFAULT_SIMULATION_HOURS: f32 = 8.0;
+/// The maximum number of hours of playback that may be simulated at once.
+const MAX_SIMULATION_HOURS: f32 = 24.0 * 7.0;
+
 /// GUI state related to the soundscape editor area.
-#[derive(Default)]
 pub struct SoundscapeEditor {
     pub selected: Option<Selected>,
+    /// The number of hours of playback to simulate.
+    pub simulation_hours: f32,
+    /// The most recently started simulation, if any.
+    pub simulation: Option<Simulation>,
+}
+
+/// The state of a simulation started via the soundscape editor.
+pub enum Simulation {
+    /// Waiting for the path to the report, or an error, from the simulation thread.
+    Running(mpsc::Receiver<Result<PathBuf, String>>),
+    /// The simulation is complete.
+    Complete(Result<PathBuf, String>),
 }
 
 /// The currently selected group.
 pub struct Selected {
     name: String,
     id: soundscape::group::Id,
+    // The tag query text as it is being edited.
+    tag_query: String,
+    // The error produced when the edited tag query was last submitted, if any.
+    tag_query_error: Option<String>,
+}
+
+impl Default for SoundscapeEditor {
+    fn default() -> Self {
+        SoundscapeEditor {
+            selected: None,
+            simulation_hours: DEFAULT_SIMULATION_HOURS,
+            simulation: None,
+        }
+    }
 }
 
 /// Sets all widgets in the soundscape area and returns the `Id` of the last area.
@@ -35,6 +72,7 @@ pub fn set(
         ref mut ui,
         ref ids,
         channels,
+        assets,
         state: &mut State {
             ref mut is_open,
             ..
@@ -44,7 +82,9 @@ pub fn set(
 
     let Project {
         state: project::State {
+            ref installations,
             ref mut soundscape_groups,
+            ref sources,
             ..
         },
         ..
@@ -58,17 +98,33 @@ pub fn set(
     // Constants to use as widget heights.
     const PAD: Scalar = 6.0;
     const IS_PLAYING_H: Scalar = ITEM_HEIGHT;
+    const SIMULATION_CANVAS_H: Scalar = PAD
+        + TITLE_H + PAD * 2.0 + ITEM_HEIGHT + PAD + ITEM_HEIGHT + PAD + TITLE_H + PAD;
     const PLUS_GROUP_H: Scalar = ITEM_HEIGHT;
     const GROUP_LIST_MAX_H: Scalar = ITEM_HEIGHT * 5.0;
     const TEXT_BOX_H: Scalar = ITEM_HEIGHT;
     const TITLE_H: Scalar = SMALL_FONT_SIZE as Scalar * 1.333;
     const GROUP_CANVAS_H: Scalar = PAD + TITLE_H + PAD + PLUS_GROUP_H + GROUP_LIST_MAX_H + PAD;
     const SLIDER_H: Scalar = ITEM_HEIGHT;
+    const SHARE_LIST_H: Scalar = ITEM_HEIGHT * 5.0;
     const SELECTED_CANVAS_H: Scalar = PAD
         + TITLE_H + PAD * 2.0 + TEXT_BOX_H + PAD
+        + TITLE_H + PAD * 2.0 + SLIDER_H + PAD + ITEM_HEIGHT + PAD
         + TITLE_H + PAD * 2.0 + SLIDER_H + PAD
-        + TITLE_H + PAD + SLIDER_H + PAD;
-    let soundscape_editor_canvas_h = PAD + IS_PLAYING_H + PAD + GROUP_CANVAS_H + PAD + SELECTED_CANVAS_H + PAD;
+        + TITLE_H + PAD + SLIDER_H + PAD
+        + TITLE_H + PAD * 2.0 + TEXT_BOX_H + PAD + TITLE_H + PAD
+        + TITLE_H + PAD * 2.0 + SHARE_LIST_H + PAD;
+    let soundscape_editor_canvas_h = PAD + IS_PLAYING_H + PAD + SIMULATION_CANVAS_H + PAD
+        + GROUP_CANVAS_H + PAD + SELECTED_CANVAS_H + PAD;
+
+    // Check whether or not a running simulation has completed.
+    let completed = match soundscape_editor.simulation {
+        Some(Simulation::Running(ref rx)) => rx.try_recv().ok(),
+        _ => None,
+    };
+    if let Some(result) = completed {
+        soundscape_editor.simulation = Some(Simulation::Complete(result));
+    }
 
     // The collapsible area.
     let (area, event) = collapsible_area(is_open.soundscape_editor, "Soundscape Editor", ids.side_menu)
@@ -119,6 +175,99 @@ pub fn set(
         }
     }
 
+    ////////////////
+    // SIMULATION //
+    ////////////////
+
+    // A canvas for simulating the soundscape on a virtual clock.
+    widget::Canvas::new()
+        .parent(area.id)
+        .kid_area_w_of(area.id)
+        .h(SIMULATION_CANVAS_H)
+        .align_middle_x_of(area.id)
+        .down(PAD)
+        .pad(PAD)
+        .color(color::CHARCOAL)
+        .set(ids.soundscape_editor_simulation_canvas, ui);
+
+    widget::Text::new("Simulation")
+        .top_left_of(ids.soundscape_editor_simulation_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.soundscape_editor_simulation_text, ui);
+
+    // A slider for the number of hours of playback to simulate.
+    let hours = soundscape_editor.simulation_hours;
+    let label = format!("{} hours", hours);
+    for new_hours in widget::Slider::new(hours, 1.0, MAX_SIMULATION_HOURS)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .kid_area_w_of(ids.soundscape_editor_simulation_canvas)
+        .h(ITEM_HEIGHT)
+        .align_left()
+        .down(PAD * 2.0)
+        .color(ui::color::LIGHT_CHARCOAL)
+        .set(ids.soundscape_editor_simulation_hours_slider, ui)
+    {
+        soundscape_editor.simulation_hours = new_hours.round();
+    }
+
+    // A button for starting a simulation, disabled while one is already running.
+    let is_running = match soundscape_editor.simulation {
+        Some(Simulation::Running(_)) => true,
+        _ => false,
+    };
+    let label = match is_running {
+        true => "Simulating...",
+        false => "Simulate",
+    };
+    for _click in widget::Button::new()
+        .label(label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .kid_area_w_of(ids.soundscape_editor_simulation_canvas)
+        .h(ITEM_HEIGHT)
+        .align_left()
+        .down(PAD)
+        .set(ids.soundscape_editor_simulation_button, ui)
+    {
+        if is_running {
+            continue;
+        }
+
+        // Collect the names with which the report is labelled.
+        let names = soundscape::simulation::Names {
+            sources: sources.iter().map(|(&id, s)| (id, s.name.clone())).collect(),
+            groups: soundscape_groups.iter().map(|(&id, g)| (id, g.name.clone())).collect(),
+            installations: installations.iter().map(|(&id, i)| (id, i.name.clone())).collect(),
+        };
+        let secs = (soundscape_editor.simulation_hours * 60.0 * 60.0) as u64;
+        let duration = time::Duration::from_secs(secs);
+        let directory = soundscape::simulation::directory(assets);
+        let (tx, rx) = mpsc::channel();
+        soundscape_editor.simulation = Some(Simulation::Running(rx));
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape::simulation::spawn(soundscape, duration, names, directory, tx);
+            })
+            .expect("failed to send simulation request to soundscape thread");
+    }
+
+    // Display the location of the last report or any error that occurred.
+    let status = match soundscape_editor.simulation {
+        None => "Reports are saved within \"assets/reports/\"".to_string(),
+        Some(Simulation::Running(_)) => format!("Simulating {} hours...", hours),
+        Some(Simulation::Complete(Ok(ref path))) => {
+            let file_name = path.file_name().map(|f| f.to_string_lossy().into_owned());
+            format!("Saved \"{}\" (.json and .csv)", file_name.unwrap_or_default())
+        },
+        Some(Simulation::Complete(Err(ref err))) => err.clone(),
+    };
+    widget::Text::new(&status)
+        .font_size(SMALL_FONT_SIZE)
+        .align_left()
+        .down(PAD)
+        .set(ids.soundscape_editor_simulation_status, ui);
+
     //////////////////
     // GROUP EDITOR //
     //////////////////
@@ -129,7 +278,7 @@ pub fn set(
         .kid_area_w_of(area.id)
         .h(GROUP_CANVAS_H)
         .align_middle_x_of(area.id)
-        .down(PAD)
+        .down_from(ids.soundscape_editor_simulation_canvas, PAD)
         .pad(PAD)
         .color(color::CHARCOAL)
         .set(ids.soundscape_editor_group_canvas, ui);
@@ -157,8 +306,17 @@ pub fn set(
             soundscape,
         };
         let id = project::next_soundscape_group_id(soundscape_groups);
+        let clone = group.soundscape.clone();
         soundscape_groups.insert(id, group);
-        soundscape_editor.selected = Some(Selected { id, name });
+        soundscape_editor.selected = Some(Selected::new(id, name, None));
+
+        // Send the new group to the soundscape thread.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.insert_group(id, clone);
+            })
+            .expect("failed to send new soundscape group to soundscape thread");
     }
 
     // If there are no groups, display some text for adding a group.
@@ -260,7 +418,8 @@ pub fn set(
             Event::Selection(idx) => {
                 soundscape_editor.selected = {
                     let (id, ref name) = groups_vec[idx];
-                    Some(Selected { id, name: name.clone() })
+                    let tag_query = soundscape_groups[&id].tag_query.as_ref();
+                    Some(Selected::new(id, name.clone(), tag_query))
                 };
             }
 
@@ -351,12 +510,118 @@ pub fn set(
         }
     }
 
+    ////////////
+    // VOLUME //
+    ////////////
+
+    widget::Text::new("Volume")
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.soundscape_editor_volume_text, ui);
+
+    let volume = soundscape_groups[&selected.id].volume;
+    let label = format!("{:.3}", volume);
+    for new_volume in widget::Slider::new(volume, 0.0, 1.0)
+        .label(&label)
+        .label_font_size(SMALL_FONT_SIZE)
+        .kid_area_w_of(ids.soundscape_editor_selected_canvas)
+        .h(SLIDER_H)
+        .align_left()
+        .down(PAD * 2.0)
+        .color(color::DARK_GREEN)
+        .set(ids.soundscape_editor_volume_slider, ui)
+    {
+        let id = selected.id;
+
+        // Update the local copy.
+        soundscape_groups.get_mut(&id).unwrap().volume = new_volume;
+
+        // Update the soundscape copy, which in turn updates the audio output thread.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_group(&id, |group| group.volume = new_volume);
+            })
+            .expect("failed to send updated group volume to soundscape thread");
+    }
+
+    // Buttons for solo and mute behaviour.
+    let selected_canvas_kid_area = ui.kid_area_of(ids.soundscape_editor_selected_canvas).unwrap();
+    let button_w = selected_canvas_kid_area.w() / 2.0 - PAD / 2.0;
+    let toggle = |value: bool| widget::Toggle::new(value)
+        .w(button_w)
+        .h(ITEM_HEIGHT)
+        .label_font_size(SMALL_FONT_SIZE);
+
+    let solo = soundscape_groups[&selected.id].soloed;
+    for new_solo in toggle(solo)
+        .label("SOLO")
+        .align_left()
+        .down(PAD)
+        .color(color::DARK_YELLOW)
+        .set(ids.soundscape_editor_solo, ui)
+    {
+        let id = selected.id;
+
+        // If the CTRL key was down, unsolo all other groups.
+        let ctrl = ui::input::keyboard::ModifierKey::CTRL;
+        let unsolo_others = ui.global_input().current.modifiers.contains(ctrl);
+        let others: Vec<_> = match unsolo_others {
+            false => vec![],
+            true => soundscape_groups
+                .iter_mut()
+                .filter(|&(&other, ref group)| other != id && group.soloed)
+                .map(|(&other, group)| {
+                    group.soloed = false;
+                    other
+                })
+                .collect(),
+        };
+
+        // Update the local copy.
+        soundscape_groups.get_mut(&id).unwrap().soloed = new_solo;
+
+        // Update the soundscape copy.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                for other in &others {
+                    soundscape.update_group(other, |group| group.soloed = false);
+                }
+                soundscape.update_group(&id, |group| group.soloed = new_solo);
+            })
+            .expect("failed to send updated group solo to soundscape thread");
+    }
+
+    let mute = soundscape_groups[&selected.id].muted;
+    for new_mute in toggle(mute)
+        .label("MUTE")
+        .align_top()
+        .right(PAD)
+        .color(color::BLUE)
+        .set(ids.soundscape_editor_mute, ui)
+    {
+        let id = selected.id;
+
+        // Update the local copy.
+        soundscape_groups.get_mut(&id).unwrap().muted = new_mute;
+
+        // Update the soundscape copy.
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_group(&id, |group| group.muted = new_mute);
+            })
+            .expect("failed to send updated group mute to soundscape thread");
+    }
+
     /////////////////////
     // OCCURRENCE RATE //
     /////////////////////
 
     widget::Text::new("Occurrence Rate")
-        .align_left()
+        .align_left_of(ids.soundscape_editor_solo)
         .down(PAD)
         .font_size(SMALL_FONT_SIZE)
         .set(ids.soundscape_editor_occurrence_rate_text, ui);
@@ -470,5 +735,155 @@ pub fn set(
             .expect("failed to send updated simultaneous sounds constraint to soundscape thread");
     }
 
+    ///////////////
+    // TAG QUERY //
+    ///////////////
+
+    widget::Text::new("Tag Query (e.g. birds AND NOT night)")
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.soundscape_editor_tag_query_text, ui);
+
+    for event in widget::TextBox::new(&selected.tag_query)
+        .align_left()
+        .down(PAD * 2.0)
+        .h(TEXT_BOX_H)
+        .kid_area_w_of(ids.soundscape_editor_selected_canvas)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::BLACK)
+        .set(ids.soundscape_editor_tag_query, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp query text.
+            Event::Update(new_query) => {
+                selected.tag_query = new_query;
+            },
+            // Only when enter is pressed do we parse and apply the query. An empty query removes
+            // all tag-based membership from the group.
+            Event::Enter => {
+                let tag_query = match audio::source::tag::Query::parse(&selected.tag_query) {
+                    Ok(query) => Some(query),
+                    Err(audio::source::tag::ParseError::Empty) => None,
+                    Err(err) => {
+                        selected.tag_query_error = Some(format!("{}", err));
+                        continue;
+                    },
+                };
+                selected.tag_query_error = None;
+                selected.tag_query = tag_query.as_ref().map(|q| q.to_string()).unwrap_or_default();
+
+                // Update the local copy.
+                let id = selected.id;
+                if let Some(group) = soundscape_groups.get_mut(&id) {
+                    group.tag_query = tag_query.clone();
+                }
+
+                // Update the soundscape copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_group(&id, |group| {
+                            group.tag_query = tag_query;
+                        });
+                    })
+                    .expect("failed to send updated tag query to soundscape thread");
+            },
+        }
+    }
+
+    // Display either the error within the submitted query or the number of member sources.
+    let status = match selected.tag_query_error {
+        Some(ref err) => format!("Invalid query: {}", err),
+        None => {
+            let group = &soundscape_groups[&selected.id];
+            let num_members = sources
+                .values()
+                .filter(|source| match source.role {
+                    Some(audio::source::Role::Soundscape(ref s)) => {
+                        group.contains_source(&selected.id, &s.groups, &source.tags)
+                    },
+                    _ => false,
+                })
+                .count();
+            format!("{} member sources", num_members)
+        },
+    };
+    widget::Text::new(&status)
+        .align_left()
+        .down(PAD)
+        .font_size(SMALL_FONT_SIZE)
+        .color(color::WHITE.alpha(0.5))
+        .set(ids.soundscape_editor_tag_query_status, ui);
+
+    /////////////////////
+    // EXPECTED SHARES //
+    /////////////////////
+
+    widget::Text::new("Expected Share of Plays")
+        .align_left()
+        .down(PAD * 2.0)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.soundscape_editor_share_text, ui);
+
+    // The share of plays expected for each member source according to its weight, assuming that
+    // the members share a suitability band, i.e. none need sounds to reach their minimum
+    // simultaneous sounds and none are overdue according to their maximum occurrence interval.
+    let members = {
+        let group = &soundscape_groups[&selected.id];
+        sources
+            .values()
+            .filter_map(|source| match source.role {
+                Some(audio::source::Role::Soundscape(ref s))
+                    if group.contains_source(&selected.id, &s.groups, &source.tags) =>
+                {
+                    Some((source.name.as_str(), s.weight))
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let weights = members.iter().map(|&(_, weight)| weight).collect::<Vec<_>>();
+    let mut shares = members
+        .iter()
+        .map(|&(name, _)| name)
+        .zip(soundscape::expected_shares(&weights))
+        .collect::<Vec<_>>();
+    shares.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(b.0)));
+
+    let (mut items, scrollbar) = widget::List::flow_down(shares.len())
+        .item_size(ITEM_HEIGHT)
+        .h(SHARE_LIST_H)
+        .kid_area_w_of(ids.soundscape_editor_selected_canvas)
+        .align_left()
+        .down(PAD * 2.0)
+        .scrollbar_next_to()
+        .scrollbar_color(color::LIGHT_CHARCOAL)
+        .set(ids.soundscape_editor_share_list, ui);
+    while let Some(item) = items.next(ui) {
+        let (name, share) = shares[item.i];
+        let label = format!("{:.1}%  {}", share * 100.0, name);
+        let text = widget::Text::new(&label)
+            .font_size(SMALL_FONT_SIZE)
+            .color(color::WHITE);
+        item.set(text, ui);
+    }
+    if let Some(s) = scrollbar {
+        s.set(ui);
+    }
+
     area.id
 }
+
+impl Selected {
+    fn new(
+        id: soundscape::group::Id,
+        name: String,
+        tag_query: Option<&audio::source::tag::Query>,
+    ) -> Self {
+        let tag_query = tag_query.map(|q| q.to_string()).unwrap_or_default();
+        let tag_query_error = None;
+        Selected { name, id, tag_query, tag_query_error }
+    }
+}
@@ -1,7 +1,9 @@
 use audio;
 use audio::source::Role;
 use audio::source::wav::Playback;
-use gui::{collapsible_area, duration_label, hz_label, Gui, ProjectState, State};
+use fxhash::FxHashMap;
+use gui::{collapsible_area, duration_label, hz_label, AudioMonitor, Channels, Gui, ProjectState};
+use gui::State;
 use gui::{DARK_A, ITEM_HEIGHT, SMALL_FONT_SIZE};
 use metres::Metres;
 use nannou::prelude::*;
@@ -9,7 +11,7 @@ use nannou::ui;
 use nannou::ui::prelude::*;
 use project::{self, Project};
 use soundscape;
-use std::{self, cmp, mem, ops};
+use std::{self, cmp, io, mem};
 use std::sync::atomic;
 use time_calc::{Ms, Samples};
 use utils;
@@ -21,6 +23,19 @@ pub struct SourceEditor {
     pub selected: Option<audio::source::Id>,
     /// The source currently being previewed via the source editor GUI.
     pub preview: SourcePreview,
+    /// A tag query used to filter the list of sources.
+    pub tag_filter: String,
+    /// The tags text of the given source while it is being edited, before `Enter` is pressed.
+    pub tags_edit: Option<(audio::source::Id, String)>,
+    /// The port text of the given network source while it is being edited, before `Enter` is
+    /// pressed.
+    pub port_edit: Option<(audio::source::Id, String)>,
+    /// Waveform overviews of WAV sources, requested when each source is first selected.
+    ///
+    /// `None` while the peaks are being generated or if generation failed.
+    pub waveforms: FxHashMap<audio::source::Id, Option<audio::waveform::Peaks>>,
+    /// Whether or not the path of the selected source is being edited over the floorplan.
+    pub editing_path: bool,
 }
 
 /// A source currently being previewed.
@@ -40,13 +55,225 @@ pub enum SourcePreviewMode {
 /// Sort sources by kind and then name when displaying in the list.
 fn source_display_order(a: &project::Source, b: &project::Source) -> cmp::Ordering {
     match (&a.kind, &b.kind) {
-        (&audio::source::Kind::Wav(_), &audio::source::Kind::Realtime(_)) => {
+        (&audio::source::Kind::Wav(_), &audio::source::Kind::Realtime(_))
+        | (&audio::source::Kind::Wav(_), &audio::source::Kind::Network(_)) => {
             cmp::Ordering::Less
         }
         _ => a.name.cmp(&b.name),
     }
 }
 
+/// Remove the source with the given `Id` from the project and from all threads.
+///
+/// Any sounds currently playing the source are released.
+pub fn remove_source(
+    channels: &Channels,
+    audio_monitor: &mut AudioMonitor,
+    sources: &mut project::SourcesMap,
+    remove_id: audio::source::Id,
+) {
+    // Remove any monitored sounds using this source ID.
+    audio_monitor.active_sounds.retain(|_, s| s.source_id != remove_id);
+
+    // Remove the local copy.
+    sources.remove(&remove_id);
+
+    // Remove audio input copy.
+    channels
+        .audio_input
+        .send(move |audio| {
+            audio.sources.remove(&remove_id);
+            audio.network_sources.remove(&remove_id);
+            audio.active_sounds.remove(&remove_id);
+        })
+        .expect("failed to remove source from audio input thread");
+
+    // Remove soundscape copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            soundscape.remove_source(&remove_id);
+        })
+        .expect("failed to remove source from soundscape thread");
+}
+
+/// Update the generative movement of the source with the given `Id` on all threads.
+///
+/// Active sounds of the same movement kind are updated in place, with their randomly chosen
+/// values clamped to the new constraints.
+pub fn update_generative_movement(
+    channels: &Channels,
+    sources: &mut project::SourcesMap,
+    id: audio::source::Id,
+    generative: audio::source::movement::Generative,
+) {
+    // Update the local copy.
+    let soundscape = match sources
+        .get_mut(&id)
+        .and_then(|s| s.audio.role.as_mut())
+        .and_then(|role| role.soundscape_mut())
+    {
+        Some(soundscape) => soundscape,
+        None => return,
+    };
+    soundscape.movement = audio::source::Movement::Generative(generative.clone());
+
+    // Update the soundscape thread copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            // Update all active sounds.
+            soundscape.update_active_sounds_with_source(id, |_, sound| {
+                use audio::source::movement::{Generative, OrbitDirection};
+                let gen = match sound.movement {
+                    soundscape::Movement::Generative(ref mut gen) => gen,
+                    _ => return,
+                };
+                match (gen, &generative) {
+                    (
+                        &mut soundscape::movement::Generative::Agent(ref mut sound_agent),
+                        &Generative::Agent(ref agent),
+                    ) => {
+                        sound_agent.max_speed = agent.max_speed.clamp(sound_agent.max_speed);
+                        sound_agent.max_force = agent.max_force.clamp(sound_agent.max_force);
+                        sound_agent.max_rotation =
+                            agent.max_rotation.clamp(sound_agent.max_rotation);
+                        sound_agent.directional = agent.directional;
+                        sound_agent.flocking = agent.flocking;
+                    },
+                    (
+                        &mut soundscape::movement::Generative::Path(ref mut sound_path),
+                        &Generative::Path(ref path),
+                    ) => {
+                        sound_path.set_path(&path.points, path.mode);
+                        sound_path.clamp_timing(&path.timing);
+                    },
+                    (
+                        &mut soundscape::movement::Generative::Orbit(ref mut sound_orbit),
+                        &Generative::Orbit(ref orbit),
+                    ) => {
+                        sound_orbit.centre = orbit.centre;
+                        sound_orbit.radius = orbit.radius.clamp(sound_orbit.radius);
+                        sound_orbit.speed = orbit.speed.clamp(sound_orbit.speed);
+                        sound_orbit.clockwise = match orbit.direction {
+                            OrbitDirection::Clockwise => true,
+                            OrbitDirection::Anticlockwise => false,
+                            OrbitDirection::Either => sound_orbit.clockwise,
+                        };
+                        sound_orbit.directional = orbit.directional;
+                    },
+                    (
+                        &mut soundscape::movement::Generative::Lissajous(ref mut sound_lissajous),
+                        &Generative::Lissajous(ref lissajous),
+                    ) => {
+                        let l = sound_lissajous;
+                        l.frequency_x = lissajous.frequency_x.clamp(l.frequency_x);
+                        l.frequency_y = lissajous.frequency_y.clamp(l.frequency_y);
+                        l.phase = lissajous.phase.clamp(l.phase);
+                        l.rate = lissajous.rate.clamp(l.rate);
+                        l.normalised_dimensions = lissajous.normalised_dimensions;
+                        l.directional = lissajous.directional;
+                    },
+                    (
+                        &mut soundscape::movement::Generative::Wander(ref mut sound_wander),
+                        &Generative::Wander(ref wander),
+                    ) => {
+                        sound_wander.speed = wander.speed.clamp(sound_wander.speed);
+                        sound_wander.turbulence = wander.turbulence.clamp(sound_wander.turbulence);
+                        sound_wander.edge_repulsion = wander.edge_repulsion;
+                        sound_wander.directional = wander.directional;
+                    },
+                    (
+                        &mut soundscape::movement::Generative::Tracked(ref mut sound_tracked),
+                        &Generative::Tracked(ref tracked),
+                    ) => {
+                        sound_tracked.target = tracked.target.clone();
+                        sound_tracked.smoothing = tracked.smoothing;
+                        sound_tracked.timeout = tracked.timeout;
+                        sound_tracked.directional = tracked.directional;
+                    },
+                    _ => (),
+                }
+            });
+
+            // Update the source.
+            soundscape.update_source(&id, |source| {
+                source.movement = audio::source::Movement::Generative(generative);
+            });
+        })
+        .expect("failed to send generative movement update to soundscape thread");
+}
+
+/// Spawn a receiver for the given network source and send it to the audio input thread.
+///
+/// Replaces any existing receiver for the source. Returns `Err` if the port could not be bound, in
+/// which case any existing receiver is left untouched.
+fn spawn_network_receiver(
+    channels: &Channels,
+    id: audio::source::Id,
+    network: &audio::source::Network,
+) -> io::Result<()> {
+    let receiver = audio::source::network::Receiver::spawn(network)?;
+    channels
+        .audio_input
+        .send(move |audio| {
+            audio.network_sources.insert(id, receiver);
+        })
+        .expect("failed to send network source to audio input thread");
+    Ok(())
+}
+
+/// Rebind the given network source to a new port.
+///
+/// The local and soundscape copies are only updated once the new port has been bound, so that
+/// they never refer to a port on which nothing is listening.
+fn update_network_port(
+    channels: &Channels,
+    id: audio::source::Id,
+    network: &mut audio::source::Network,
+    port: u16,
+) -> io::Result<()> {
+    let new_network = audio::source::Network { port, ..network.clone() };
+    spawn_network_receiver(channels, id, &new_network)?;
+    update_network(channels, id, network, new_network);
+    Ok(())
+}
+
+/// Update the local, soundscape and audio input copies of a network source.
+///
+/// The port of the receiver is left unchanged. Use `update_network_port` to rebind the source.
+fn update_network(
+    channels: &Channels,
+    id: audio::source::Id,
+    network: &mut audio::source::Network,
+    new_network: audio::source::Network,
+) {
+    *network = new_network.clone();
+
+    // Update the audio input thread copy.
+    let clone = new_network.clone();
+    channels
+        .audio_input
+        .send(move |audio| {
+            if let Some(receiver) = audio.network_sources.get_mut(&id) {
+                receiver.update(&clone);
+            }
+        })
+        .expect("failed to send network source update to audio input thread");
+
+    // Update the soundscape thread copy.
+    channels
+        .soundscape
+        .send(move |soundscape| {
+            soundscape.update_source(&id, |source| {
+                if let audio::source::Kind::Network(ref mut network) = source.kind {
+                    *network = new_network;
+                }
+            });
+        })
+        .expect("failed to send network source update to soundscape thread");
+}
+
 const SOUNDSCAPE_COLOR: ui::Color = ui::color::DARK_RED;
 const INTERACTIVE_COLOR: ui::Color = ui::color::DARK_GREEN;
 const SCRIBBLES_COLOR: ui::Color = ui::color::DARK_PURPLE;
@@ -64,6 +291,7 @@ pub fn set(
         ref mut audio_monitor,
         channels,
         sound_id_gen,
+        assets,
         state:
             &mut State {
                 ref mut is_open,
@@ -75,6 +303,7 @@ pub fn set(
 
     let Project {
         state: project::State {
+            ref name,
             ref camera,
             ref master,
             ref soundscape_groups,
@@ -92,6 +321,7 @@ pub fn set(
 
     const PAD: Scalar = 6.0;
     const TEXT_PAD: Scalar = 20.0;
+    const TAG_FILTER_H: Scalar = ITEM_HEIGHT;
     const LIST_HEIGHT: Scalar = 140.0;
     const PREVIEW_CANVAS_H: Scalar = 66.0;
     const INSTALLATION_LIST_H: Scalar = ITEM_HEIGHT * 3.0;
@@ -100,31 +330,59 @@ pub fn set(
     const SLIDER_H: Scalar = ITEM_HEIGHT;
     const SOUNDSCAPE_GROUP_LIST_H: Scalar = ITEM_HEIGHT * 3.0;
     const BUTTON_H: Scalar = ITEM_HEIGHT;
+    const CURVE_PREVIEW_H: Scalar = ITEM_HEIGHT * 2.0;
     const SOUNDSCAPE_CANVAS_H: Scalar = PAD + TEXT_PAD + PAD
         + TEXT_PAD + PAD + SLIDER_H + PAD
         + TEXT_PAD + PAD + SLIDER_H + PAD
         + TEXT_PAD + PAD + SLIDER_H + PAD
         + TEXT_PAD + PAD + SLIDER_H + PAD
         + TEXT_PAD + PAD + SLIDER_H + PAD
+        + TEXT_PAD + PAD + SLIDER_H + PAD
+        + TEXT_PAD + PAD + SLIDER_H + PAD
+        + TEXT_PAD + PAD + ITEM_HEIGHT + PAD + SLIDER_H + PAD + CURVE_PREVIEW_H + PAD
         + TEXT_PAD + PAD * 3.5 + SOUNDSCAPE_GROUP_LIST_H + PAD
         + TEXT_PAD + PAD * 2.0 + BUTTON_H + PAD + BUTTON_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
         + TEXT_PAD + PAD * 2.0 + SLIDER_H * 2.0 + PAD
-        + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD;
+        + TEXT_PAD + PAD * 2.0 + SLIDER_H + PAD
+        + TEXT_PAD + PAD * 2.0 + BUTTON_H + (PAD + SLIDER_H) * 5.0 + PAD;
     const LOOP_TOGGLE_H: Scalar = ITEM_HEIGHT;
+    const NORMALISE_TOGGLE_H: Scalar = ITEM_HEIGHT;
     const PLAYBACK_MODE_H: Scalar = ITEM_HEIGHT;
-    const WAV_CANVAS_H: Scalar =
-        100.0 + PAD + LOOP_TOGGLE_H + PAD * 4.0 + PLAYBACK_MODE_H + PAD;
+    const WAVEFORM_H: Scalar = ITEM_HEIGHT * 2.0;
+    const WAV_CANVAS_H: Scalar = 120.0 + PAD + WAVEFORM_H + PAD + LOOP_TOGGLE_H + PAD
+        + NORMALISE_TOGGLE_H + PAD * 4.0 + PLAYBACK_MODE_H + PAD;
     const REALTIME_CANVAS_H: Scalar = 94.0;
+    const NETWORK_CANVAS_H: Scalar = 94.0 + ITEM_HEIGHT + PAD;
     const CHANNEL_LAYOUT_H: Scalar = 200.0;
     const COMMON_CANVAS_H: Scalar = TEXT_PAD + PAD + SLIDER_H + PAD + CHANNEL_LAYOUT_H;
         PAD + ITEM_HEIGHT * 2.0 + PAD + INSTALLATION_LIST_H + PAD;
-    let kind_specific_h = WAV_CANVAS_H.max(REALTIME_CANVAS_H);
-    let selected_canvas_h = ITEM_HEIGHT * 2.0 + PAD * 7.0 + PREVIEW_CANVAS_H + kind_specific_h
-        + COMMON_CANVAS_H + INSTALLATIONS_CANVAS_H + PAD + SOUNDSCAPE_CANVAS_H;
-    let source_editor_canvas_h = LIST_HEIGHT + ITEM_HEIGHT + selected_canvas_h;
+    // The realtime canvas grows with the number of inputs of the selected source.
+    let num_realtime_inputs = source_editor
+        .selected
+        .and_then(|id| sources.get(&id))
+        .map(|s| match s.audio.kind {
+            audio::source::Kind::Realtime(ref rt) => rt.channels.len(),
+            _ => 0,
+        })
+        .unwrap_or(0);
+    let realtime_canvas_h =
+        REALTIME_CANVAS_H + num_realtime_inputs as Scalar * (ITEM_HEIGHT + PAD);
+    let kind_specific_h = WAV_CANVAS_H.max(realtime_canvas_h).max(NETWORK_CANVAS_H);
+    // The effects canvas grows with the number of effects and parameters of the selected source.
+    let num_effect_rows = source_editor
+        .selected
+        .and_then(|id| sources.get(&id))
+        .map(|s| s.effects.iter().map(|e| 1 + e.param_names().len()).sum::<usize>())
+        .unwrap_or(0);
+    let effects_canvas_h = TEXT_PAD + PAD + ITEM_HEIGHT + PAD
+        + num_effect_rows as Scalar * (ITEM_HEIGHT + PAD);
+    let selected_canvas_h = ITEM_HEIGHT * 3.0 + PAD * 9.0 + PREVIEW_CANVAS_H + kind_specific_h
+        + COMMON_CANVAS_H + effects_canvas_h + INSTALLATIONS_CANVAS_H + PAD
+        + SOUNDSCAPE_CANVAS_H;
+    let source_editor_canvas_h = TAG_FILTER_H + LIST_HEIGHT + ITEM_HEIGHT + selected_canvas_h;
 
     let (area, event) = collapsible_area(is_open.source_editor, "Source Editor", ids.side_menu)
         .align_middle_x_of(ids.side_menu)
@@ -157,23 +415,48 @@ pub fn set(
     // TODO: Possibly store this within source_editor for re-use.
     let mut sources_vec = sorted_sources_vec(sources);
 
+    // A text box for filtering the list by a tag query, e.g. `birds AND NOT night`.
+    //
+    // The filter is only applied while the query is valid.
+    let tag_filter = audio::source::tag::Query::parse(&source_editor.tag_filter);
+    let filter_color = match tag_filter {
+        Err(audio::source::tag::ParseError::Empty) | Ok(_) => color::BLACK,
+        Err(_) => color::DARK_RED,
+    };
+    for event in widget::TextBox::new(&source_editor.tag_filter)
+        .align_middle_x_of(area.id)
+        .align_top_of(area.id)
+        .kid_area_w_of(area.id)
+        .h(TAG_FILTER_H)
+        .color(filter_color)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.source_editor_tag_filter, ui)
+    {
+        if let widget::text_box::Event::Update(string) = event {
+            source_editor.tag_filter = string;
+        }
+    }
+    if let Ok(ref query) = tag_filter {
+        sources_vec.retain(|id| query.matches(&sources[id].tags));
+    }
+
     // If there are no sources, display a message saying how to add some.
     if sources.is_empty() {
         widget::Text::new("Add some source outputs with the `+` button")
             .padded_w_of(area.id, TEXT_PAD)
-            .mid_top_with_margin_on(area.id, TEXT_PAD)
+            .mid_top_with_margin_on(area.id, TAG_FILTER_H + TEXT_PAD)
             .font_size(SMALL_FONT_SIZE)
             .center_justify()
             .set(ids.source_editor_no_sources, ui);
 
     // Otherwise display the source list.
     } else {
-        let num_items = sources.len();
+        let num_items = sources_vec.len();
         let (mut events, scrollbar) = widget::ListSelect::single(num_items)
             .item_size(ITEM_HEIGHT)
             .h(LIST_HEIGHT)
             .align_middle_x_of(area.id)
-            .align_top_of(area.id)
+            .down_from(ids.source_editor_tag_filter, 0.0)
             .scrollbar_next_to()
             .scrollbar_color(color::LIGHT_CHARCOAL)
             .set(ids.source_editor_list, ui);
@@ -200,10 +483,14 @@ pub fn set(
                                 (format!("[{}CH WAV] {}", wav.channels, source.name), true)
                             }
                             audio::source::Kind::Realtime(ref rt) => (
+                                format!("[{}CH RT] {}", rt.channels.len(), source.name),
+                                false,
+                            ),
+                            audio::source::Kind::Network(ref net) => (
                                 format!(
-                                    "[{}-{}CH RT] {}",
-                                    rt.channels.start + 1,
-                                    (rt.channels.end - 1) + 1,
+                                    "[{}CH NET :{}] {}",
+                                    net.channels,
+                                    net.port,
                                     source.name
                                 ),
                                 false,
@@ -295,39 +582,18 @@ pub fn set(
                 }
             }
 
-            // Remove any monitored sounds using this source ID.
-            audio_monitor.active_sounds.retain(|_, s| s.source_id != remove_id);
-
-            // Remove the local copy.
-            sources.remove(&remove_id);
-
-            // Remove audio input copy.
-            channels
-                .audio_input
-                .send(move |audio| {
-                    audio.sources.remove(&remove_id);
-                    audio.active_sounds.remove(&remove_id);
-                })
-                .expect("failed to remove source from audio input thread");
-
-            // Remove soundscape copy.
-            channels
-                .soundscape
-                .send(move |soundscape| {
-                    soundscape.remove_source(&remove_id);
-                })
-                .expect("failed to remove source from soundscape thread");
+            remove_source(channels, audio_monitor, sources, remove_id);
         }
     }
 
-    let plus_button_w = ui.rect_of(area.id).unwrap().w() / 2.0;
+    let plus_button_w = ui.rect_of(area.id).unwrap().w() / 3.0;
     let plus_button = || -> widget::Button<widget::button::Flat> {
         widget::Button::new()
             .color(DARK_A)
             .w(plus_button_w)
             .label_font_size(SMALL_FONT_SIZE)
             .parent(area.id)
-            .mid_top_with_margin_on(area.id, LIST_HEIGHT)
+            .mid_top_with_margin_on(area.id, TAG_FILTER_H + LIST_HEIGHT)
     };
 
     let new_wav = plus_button()
@@ -342,6 +608,12 @@ pub fn set(
         .set(ids.source_editor_add_realtime, ui)
         .was_clicked();
 
+    let new_network = plus_button()
+        .label("+ Network")
+        .align_middle_x_of(area.id)
+        .set(ids.source_editor_add_network, ui)
+        .was_clicked();
+
     // Add a new WAV source.
     if new_wav {
         // Not sure if we want to support this in software yet.
@@ -350,12 +622,12 @@ pub fn set(
     // Add a new realtime source.
     if new_realtime {
         // Create the Realtime.
-        const DEFAULT_CHANNELS: ops::Range<usize> = 0..1;
+        const DEFAULT_CHANNEL: usize = 0;
         const DEFAULT_DURATION: Ms = Ms(3_000.0);
-        let n_channels = DEFAULT_CHANNELS;
+        let inputs = vec![audio::source::realtime::Input::new(DEFAULT_CHANNEL)];
         let duration = DEFAULT_DURATION;
         let realtime = audio::source::Realtime {
-            channels: n_channels,
+            channels: inputs,
             duration,
         };
 
@@ -368,6 +640,8 @@ pub fn set(
         let channel_radians = audio::source::default::CHANNEL_RADIANS;
         let volume = audio::source::default::VOLUME;
         let muted = bool::default();
+        let effects = vec![];
+        let tags = vec![];
         let audio = audio::Source {
             kind,
             role,
@@ -375,6 +649,8 @@ pub fn set(
             channel_radians,
             volume,
             muted,
+            effects,
+            tags,
         };
         let source = project::Source { name, audio };
 
@@ -390,6 +666,52 @@ pub fn set(
             .expect("failed to send new source to audio input thread");
     }
 
+    // Add a new network source.
+    if new_network {
+        // Create the Network.
+        const DEFAULT_CHANNELS: usize = 2;
+        const DEFAULT_DURATION: Ms = Ms(3_000.0);
+        const DEFAULT_PORT: u16 = 5_004;
+        let network = audio::source::Network {
+            duration: DEFAULT_DURATION,
+            channels: DEFAULT_CHANNELS,
+            port: DEFAULT_PORT,
+            format: audio::source::network::Format::RtpL24,
+            jitter_buffer: audio::source::default::JITTER_BUFFER,
+        };
+
+        // Create the Source.
+        let id = sources.next_id();
+        let name = format!("Source {}", id.0);
+        let kind = audio::source::Kind::Network(network.clone());
+        let role = Default::default();
+        let spread = audio::source::default::SPREAD;
+        let channel_radians = audio::source::default::CHANNEL_RADIANS;
+        let volume = audio::source::default::VOLUME;
+        let muted = bool::default();
+        let effects = vec![];
+        let tags = vec![];
+        let audio = audio::Source {
+            kind,
+            role,
+            spread,
+            channel_radians,
+            volume,
+            muted,
+            effects,
+            tags,
+        };
+        let source = project::Source { name, audio };
+
+        // Insert the source into the map.
+        sources.insert(id, source);
+
+        // Start receiving and send the receiver to the audio input thread.
+        if let Err(err) = spawn_network_receiver(channels, id, &network) {
+            eprintln!("failed to receive network source on port {}: {}", network.port, err);
+        }
+    }
+
     let area_rect = ui.rect_of(area.id).unwrap();
     let start = area_rect.y.start;
     let end = start + selected_canvas_h;
@@ -436,6 +758,49 @@ pub fn set(
         }
     }
 
+    // A text box for editing the source's tags as a comma separated list.
+    let tags_text = match source_editor.tags_edit {
+        Some((edit_id, ref text)) if edit_id == id => text.clone(),
+        _ => audio::source::tag::format_list(&sources[&id].tags),
+    };
+    for event in widget::TextBox::new(&tags_text)
+        .align_middle_x_of(ids.source_editor_selected_canvas)
+        .down_from(ids.source_editor_selected_name, PAD)
+        .w(selected_canvas_kid_area.w())
+        .parent(ids.source_editor_selected_canvas)
+        .h(ITEM_HEIGHT)
+        .color(DARK_A)
+        .font_size(SMALL_FONT_SIZE)
+        .set(ids.source_editor_selected_tags, ui)
+    {
+        use self::ui::widget::text_box::Event;
+        match event {
+            // When typing generally, only update the temp tags text.
+            Event::Update(string) => {
+                source_editor.tags_edit = Some((id, string));
+            },
+            // Only when enter is pressed do we update the actual tags.
+            Event::Enter => {
+                let text = match source_editor.tags_edit.take() {
+                    Some((edit_id, text)) if edit_id == id => text,
+                    _ => continue,
+                };
+                let tags = audio::source::tag::parse_list(&text);
+
+                // Update the local copy.
+                sources.get_mut(&id).unwrap().tags = tags.clone();
+
+                // Update the soundscape copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, |source| source.tags = tags);
+                    })
+                    .expect("failed to send updated source tags to soundscape thread");
+            },
+        }
+    }
+
     // 4 Role Buttons
     let role_button_w = selected_canvas_kid_area.w() / 4.0;
     const NUM_ROLES: usize = 4;
@@ -444,7 +809,7 @@ pub fn set(
         .item_size(role_button_w)
         .h(ITEM_HEIGHT)
         .align_middle_x_of(ids.source_editor_selected_canvas)
-        .down_from(ids.source_editor_selected_name, PAD)
+        .down_from(ids.source_editor_selected_tags, PAD)
         .set(ids.source_editor_selected_role_list, ui);
 
     fn int_to_role(i: usize) -> Option<Role> {
@@ -533,20 +898,15 @@ pub fn set(
                     // If it is no longer a soundscape.
                     (Some(Role::Soundscape(_)), _) => {
                         // Remove the source from the soundscape.
+                        //
+                        // The soundscape releases all sounds with this source on the audio output
+                        // thread.
                         channels
                             .soundscape
                             .send(move |soundscape| {
                                 soundscape.remove_source(&id);
                             })
                             .expect("failed to remove soundscape source from soundscape thread");
-
-                        // Remove all sounds with this source from the audio output thread.
-                        channels
-                            .audio_output
-                            .send(move |audio| {
-                                audio.remove_sounds_with_source(&id);
-                            })
-                            .expect("failed to remove soundscape source sounds from audio output thread");
                     },
 
                     _ => (),
@@ -620,9 +980,10 @@ pub fn set(
                         SourcePreviewMode::Continuous => true,
                     };
 
-                    // No attack or release for previews.
+                    // No attack, release or rate variation for previews.
                     let attack_duration = Samples(0);
                     let release_duration = Samples(0);
+                    let playback_rate = 1.0;
                     let max_duration = None;
                     let position = audio::sound::Position {
                         point: preview.point.unwrap(),
@@ -634,6 +995,12 @@ pub fn set(
                     let mut audio = source.audio.clone();
                     audio.role = None;
 
+                    // Preview effects using the middle of each parameter range.
+                    let effects = audio::effect::Chain::middle(
+                        &audio.effects,
+                        audio.channel_count(),
+                    );
+
                     let _handle = audio::sound::spawn_from_source(
                         sound_id,
                         source_id,
@@ -641,6 +1008,8 @@ pub fn set(
                         position,
                         attack_duration,
                         release_duration,
+                        playback_rate,
+                        effects,
                         should_cycle,
                         max_duration,
                         channels.frame_count.load(atomic::Ordering::Relaxed) as _,
@@ -703,6 +1072,12 @@ pub fn set(
         );
     }
 
+    // The longest duration for which the soundscape may play the source, if it has that role.
+    let max_playback_duration = match sources[&id].audio.role {
+        Some(Role::Soundscape(ref soundscape)) => Some(soundscape.playback_duration.max),
+        _ => None,
+    };
+
     // Kind-specific data.
     let (kind_canvas_id, num_channels) = match sources.get_mut(&id).unwrap().audio.kind {
         audio::source::Kind::Wav(ref mut wav) => {
@@ -728,9 +1103,16 @@ pub fn set(
                 format!("Duration: {:.4} milliseconds", duration_ms.ms())
             };
             let file_line = format!("File: {}", wav.path.file_name().unwrap().to_str().unwrap());
+            let loudness_line = match wav.loudness {
+                None => format!("Loudness: analysing..."),
+                Some(ref analysis) => format!(
+                    "Loudness: {:.1} LUFS, True Peak: {:.1} dBTP",
+                    analysis.integrated_lufs, analysis.true_peak_dbtp
+                ),
+            };
             let data = format!(
-                "{}\nChannels: {}\nSample Rate: {}\n{}",
-                file_line, wav.channels, wav.sample_hz, duration_line
+                "{}\nChannels: {}\nSample Rate: {}\n{}\n{}",
+                file_line, wav.channels, wav.sample_hz, duration_line, loudness_line
             );
             widget::Text::new(&data)
                 .font_size(SMALL_FONT_SIZE)
@@ -739,14 +1121,103 @@ pub fn set(
                 .line_spacing(PAD)
                 .set(ids.source_editor_selected_wav_data, ui);
 
+            // The waveform overview, requested from the waveform thread on first display.
+            let canvas_kid_area = ui.kid_area_of(ids.source_editor_selected_wav_canvas).unwrap();
+            widget::Canvas::new()
+                .down(PAD * 2.0)
+                .align_middle_x_of(ids.source_editor_selected_wav_canvas)
+                .w(canvas_kid_area.w())
+                .h(WAVEFORM_H)
+                .pad(0.0)
+                .color(DARK_A)
+                .parent(ids.source_editor_selected_wav_canvas)
+                .set(ids.source_editor_selected_wav_waveform_canvas, ui);
+
+            if !source_editor.waveforms.contains_key(&id) {
+                let project_directory = project::project_directory_path(assets, name);
+                channels.waveform.request(id, wav.path.clone(), &project_directory);
+                source_editor.waveforms.insert(id, None);
+            }
+
+            match source_editor.waveforms[&id] {
+                None => {
+                    widget::Text::new("Generating waveform...")
+                        .font_size(SMALL_FONT_SIZE)
+                        .color(color::WHITE.alpha(0.5))
+                        .middle_of(ids.source_editor_selected_wav_waveform_canvas)
+                        .set(ids.source_editor_selected_wav_waveform_text, ui);
+                },
+                Some(ref peaks) => {
+                    let rect = ui.rect_of(ids.source_editor_selected_wav_waveform_canvas).unwrap();
+                    let frames = peaks.frames as f64;
+
+                    // The region of the WAV that may be heard: the whole WAV if it loops or
+                    // plays continuously, otherwise up to the soundscape's max playback duration.
+                    let out_frame = match (wav.should_loop, wav.playback, max_playback_duration) {
+                        (false, Playback::Retrigger, Some(max)) => {
+                            (max.to_samples(wav.sample_hz).0 as f64).min(frames)
+                        },
+                        _ => frames,
+                    };
+                    let frame_to_x = |frame: f64| {
+                        rect.left() + rect.w() * if frames > 0.0 { frame / frames } else { 0.0 }
+                    };
+                    let (in_x, out_x) = (frame_to_x(0.0), frame_to_x(out_frame));
+                    widget::Rectangle::fill([out_x - in_x, rect.h()])
+                        .x(in_x + (out_x - in_x) / 2.0)
+                        .align_middle_y_of(ids.source_editor_selected_wav_waveform_canvas)
+                        .color(color::LIGHT_CHARCOAL.alpha(0.5))
+                        .parent(ids.source_editor_selected_wav_waveform_canvas)
+                        .set(ids.source_editor_selected_wav_waveform_region, ui);
+
+                    // Zig-zag between the max and min of each column so that the path is filled.
+                    let num_columns = rect.w().max(0.0) as usize;
+                    let half_h = rect.h() / 2.0;
+                    let columns = peaks.columns(0, peaks.frames, num_columns);
+                    let mut points = Vec::with_capacity(columns.len() * 2);
+                    for (i, peak) in columns.iter().enumerate() {
+                        let x = rect.left() + i as Scalar + 0.5;
+                        points.push([x, rect.y() + peak.max as Scalar * half_h]);
+                        points.push([x, rect.y() + peak.min as Scalar * half_h]);
+                    }
+                    widget::PointPath::abs(points)
+                        .color(color::LIGHT_BLUE)
+                        .thickness(1.0)
+                        .parent(ids.source_editor_selected_wav_waveform_canvas)
+                        .graphics_for(ids.source_editor_selected_wav_waveform_canvas)
+                        .set(ids.source_editor_selected_wav_waveform_path, ui);
+
+                    // A playhead for each active sound playing this source.
+                    let progresses: Vec<f64> = audio_monitor
+                        .active_sounds
+                        .values()
+                        .filter(|s| s.source_id == id)
+                        .filter_map(|s| s.normalised_progress)
+                        .collect();
+                    if ids.source_editor_selected_wav_playheads.len() < progresses.len() {
+                        let id_gen = &mut ui.widget_id_generator();
+                        let num = progresses.len();
+                        ids.source_editor_selected_wav_playheads.resize(num, id_gen);
+                    }
+                    for (i, progress) in progresses.into_iter().enumerate() {
+                        let x = in_x + (out_x - in_x) * progress.min(1.0).max(0.0);
+                        widget::Line::abs([x, rect.bottom()], [x, rect.top()])
+                            .color(color::WHITE)
+                            .thickness(1.0)
+                            .parent(ids.source_editor_selected_wav_waveform_canvas)
+                            .graphics_for(ids.source_editor_selected_wav_waveform_canvas)
+                            .set(ids.source_editor_selected_wav_playheads[i], ui);
+                    }
+                },
+            }
+
             // A `Toggle` for whether or not the WAV should loop.
             let label = if wav.should_loop { "Looping: ON" } else { "Looping: OFF" };
-            let canvas_kid_area = ui.kid_area_of(ids.source_editor_selected_wav_canvas).unwrap();
             for new_loop in widget::Toggle::new(wav.should_loop)
                 .color(color::LIGHT_CHARCOAL)
                 .label(label)
                 .label_font_size(SMALL_FONT_SIZE)
-                .down(PAD * 2.0)
+                .down_from(ids.source_editor_selected_wav_waveform_canvas, PAD)
                 .h(LOOP_TOGGLE_H)
                 .w(canvas_kid_area.w())
                 .align_middle_x_of(ids.source_editor_selected_wav_canvas)
@@ -771,6 +1242,46 @@ pub fn set(
                 // with a looping version.
             }
 
+            // A `Toggle` for whether or not the WAV should be normalised to the loudness target.
+            let label = if wav.normalise {
+                let gain = wav.normalisation_gain(master.loudness_target_lufs);
+                format!("Normalise: ON ({:+.1} dB)", utils::gain_to_db(gain))
+            } else {
+                "Normalise: OFF".to_string()
+            };
+            for new_normalise in widget::Toggle::new(wav.normalise)
+                .color(color::LIGHT_CHARCOAL)
+                .label(&label)
+                .label_font_size(SMALL_FONT_SIZE)
+                .down(PAD)
+                .h(NORMALISE_TOGGLE_H)
+                .w(canvas_kid_area.w())
+                .align_middle_x_of(ids.source_editor_selected_wav_canvas)
+                .set(ids.source_editor_selected_wav_normalise_toggle, ui)
+            {
+                // Update the local copy.
+                wav.normalise = new_normalise;
+
+                // Update the soundscape thread copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, |source| {
+                            if let audio::source::Kind::Wav(ref mut wav) = source.kind {
+                                wav.normalise = new_normalise;
+                            }
+                        });
+                    })
+                    .expect("failed to send source normalise toggle to soundscape thread");
+
+                // Update the audio output thread's gain for the source.
+                let gain = wav.normalisation_gain(master.loudness_target_lufs);
+                channels
+                    .audio_output
+                    .send(move |audio| audio.set_source_gain(id, gain))
+                    .expect("failed to send source gain to audio output thread");
+            }
+
             // The playback mode selection.
             widget::Text::new("Playback Mode")
                 .font_size(SMALL_FONT_SIZE)
@@ -879,7 +1390,7 @@ pub fn set(
                 .parent(ids.source_editor_selected_canvas)
                 .w(selected_canvas_kid_area.w())
                 .color(color::CHARCOAL)
-                .h(REALTIME_CANVAS_H)
+                .h(realtime_canvas_h)
                 .pad(PAD)
                 .set(ids.source_editor_selected_realtime_canvas, ui);
 
@@ -946,59 +1457,268 @@ pub fn set(
                 update_realtime!(|realtime: &mut audio::source::Realtime| realtime.duration = new_duration);
             }
 
-            // Starting channel index (to the left).
-            let start_channel_indices = 0..realtime.channels.end;
-            let start_channel_labels = start_channel_indices
-                .clone()
-                .map(|ch| format!("Start Channel: {}", ch + 1))
+            // Ensure there are enough widget IDs for each of the source's inputs.
+            let num_inputs = realtime.channels.len();
+            if ids.source_editor_selected_realtime_inputs.len() < num_inputs {
+                let id_gen = &mut ui.widget_id_generator();
+                ids.source_editor_selected_realtime_inputs.resize(num_inputs, id_gen);
+                ids.source_editor_selected_realtime_input_trims.resize(num_inputs, id_gen);
+                ids.source_editor_selected_realtime_input_removes.resize(num_inputs, id_gen);
+            }
+
+            // A device channel selector, trim slider and remove button for each input.
+            let kid_w = ui.kid_area_of(ids.source_editor_selected_realtime_canvas)
+                .unwrap()
+                .w();
+            let remove_button_w = ITEM_HEIGHT;
+            let channel_w = (kid_w - remove_button_w) / 2.0 - PAD;
+            let trim_w = kid_w - channel_w - remove_button_w - PAD * 2.0;
+            let channel_labels = (0..audio_channels.input)
+                .map(|ch| format!("Input Channel: {}", ch + 1))
                 .collect::<Vec<_>>();
-            let selected_start = Some(realtime.channels.start as usize);
-            let channel_w = ui.kid_area_of(ids.source_editor_selected_realtime_canvas)
+            for i in 0..num_inputs {
+                let input = realtime.channels[i];
+                let row = i as Scalar * (ITEM_HEIGHT + PAD);
+                let selected = if input.channel < audio_channels.input {
+                    Some(input.channel)
+                } else {
+                    None
+                };
+                for new_channel in widget::DropDownList::new(&channel_labels, selected)
+                    .down_from(ids.source_editor_selected_realtime_duration, PAD + row)
+                    .align_left_of(ids.source_editor_selected_realtime_duration)
+                    .label(&format!("Input Channel: {}", input.channel + 1))
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .scrollbar_on_top()
+                    .max_visible_items(5)
+                    .w(channel_w)
+                    .h(ITEM_HEIGHT)
+                    .set(ids.source_editor_selected_realtime_inputs[i], ui)
+                {
+                    update_realtime!(|rt: &mut audio::source::Realtime| {
+                        if let Some(input) = rt.channels.get_mut(i) {
+                            input.channel = new_channel;
+                        }
+                    });
+                }
+
+                let label = format!("Trim: {:.1} dB", input.trim_db);
+                for new_trim in widget::Slider::new(input.trim_db, -24.0, 12.0)
+                    .label(&label)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(ui::color::LIGHT_CHARCOAL)
+                    .right(PAD)
+                    .align_top()
+                    .w(trim_w)
+                    .h(ITEM_HEIGHT)
+                    .set(ids.source_editor_selected_realtime_input_trims[i], ui)
+                {
+                    update_realtime!(|rt: &mut audio::source::Realtime| {
+                        if let Some(input) = rt.channels.get_mut(i) {
+                            input.trim_db = new_trim;
+                        }
+                    });
+                }
+
+                // Sources must always have at least one input.
+                if num_inputs > 1 {
+                    for _click in widget::Button::new()
+                        .label("X")
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .right(PAD)
+                        .align_top()
+                        .w(remove_button_w)
+                        .h(ITEM_HEIGHT)
+                        .set(ids.source_editor_selected_realtime_input_removes[i], ui)
+                    {
+                        update_realtime!(|rt: &mut audio::source::Realtime| {
+                            if i < rt.channels.len() && rt.channels.len() > 1 {
+                                rt.channels.remove(i);
+                            }
+                        });
+                    }
+                }
+            }
+
+            // Add a new input reading from the channel following the last input.
+            let num_inputs = realtime.channels.len();
+            let last_row = num_inputs as Scalar * (ITEM_HEIGHT + PAD);
+            for _click in widget::Button::new()
+                .label("+ Input")
+                .label_font_size(SMALL_FONT_SIZE)
+                .down_from(ids.source_editor_selected_realtime_duration, PAD + last_row)
+                .align_left_of(ids.source_editor_selected_realtime_duration)
+                .w(kid_w)
+                .h(ITEM_HEIGHT)
+                .set(ids.source_editor_selected_realtime_add_input, ui)
+            {
+                let channel = realtime
+                    .channels
+                    .last()
+                    .map(|input| cmp::min(input.channel + 1, audio_channels.input.max(1) - 1))
+                    .unwrap_or(0);
+                let new_input = audio::source::realtime::Input::new(channel);
+                update_realtime!(|rt: &mut audio::source::Realtime| rt.channels.push(new_input));
+            }
+
+            (
+                ids.source_editor_selected_realtime_canvas,
+                realtime.channels.len(),
+            )
+        }
+        audio::source::Kind::Network(ref mut network) => {
+            // Instantiate a small canvas for displaying network-specific stuff.
+            widget::Canvas::new()
+                .down_from(ids.source_editor_preview_canvas, PAD)
+                .parent(ids.source_editor_selected_canvas)
+                .w(selected_canvas_kid_area.w())
+                .color(color::CHARCOAL)
+                .h(NETWORK_CANVAS_H)
+                .pad(PAD)
+                .set(ids.source_editor_selected_network_canvas, ui);
+
+            widget::Text::new("NETWORK DATA")
+                .font_size(SMALL_FONT_SIZE)
+                .top_left_of(ids.source_editor_selected_network_canvas)
+                .set(ids.source_editor_selected_network_text, ui);
+
+            // Maximum playback duration, as with realtime sources.
+            let label = duration_label(&network.duration);
+            let min = 0.0;
+            let max = utils::HR_MS;
+            for new_ms in widget::Slider::new(network.duration.ms(), min, max)
+                .label(&format!("Duration: {}", label))
+                .label_font_size(SMALL_FONT_SIZE)
+                .kid_area_w_of(ids.source_editor_selected_network_canvas)
+                .h(ITEM_HEIGHT)
+                .down(PAD)
+                .skew(10.0)
+                .set(ids.source_editor_selected_network_duration, ui)
+            {
+                let new_network = audio::source::Network {
+                    duration: Ms(new_ms as _),
+                    ..network.clone()
+                };
+                update_network(channels, id, network, new_network);
+            }
+
+            let half_w = ui.kid_area_of(ids.source_editor_selected_network_canvas)
                 .unwrap()
                 .w() / 2.0 - PAD / 2.0;
-            for new_start in widget::DropDownList::new(&start_channel_labels, selected_start)
+
+            // The UDP port on which the stream is received.
+            //
+            // The port is only rebound once `Enter` is pressed. The text is green while it differs
+            // from the bound port and red if it is not a valid port.
+            let port_text = match source_editor.port_edit {
+                Some((edit_id, ref text)) if edit_id == id => text.clone(),
+                _ => format!("{}", network.port),
+            };
+            let parse_port = |text: &str| match text.trim().parse::<u16>() {
+                Ok(port) if port >= 1_024 => Some(port),
+                _ => None,
+            };
+            let color = match parse_port(&port_text) {
+                Some(port) if port == network.port => DARK_A,
+                Some(_) => color::DARK_GREEN.with_luminance(0.1),
+                None => color::DARK_RED.with_luminance(0.1),
+            };
+            for event in widget::TextBox::new(&port_text)
+                .font_size(SMALL_FONT_SIZE)
                 .down(PAD)
                 .align_left()
-                .label("Start Channel")
-                .label_font_size(SMALL_FONT_SIZE)
-                .scrollbar_on_top()
-                .max_visible_items(5)
-                .w(channel_w)
+                .w(half_w)
                 .h(ITEM_HEIGHT)
-                .set(ids.source_editor_selected_realtime_start_channel, ui)
+                .color(color)
+                .set(ids.source_editor_selected_network_port, ui)
             {
-                // Update the local copy.
-                update_realtime!(|rt: &mut audio::source::Realtime| rt.channels.start = new_start);
+                use self::ui::widget::text_box::Event;
+                match event {
+                    Event::Update(string) => {
+                        source_editor.port_edit = Some((id, string));
+                    },
+                    Event::Enter => {
+                        let port = match parse_port(&port_text) {
+                            Some(port) if port != network.port => port,
+                            _ => continue,
+                        };
+                        match update_network_port(channels, id, network, port) {
+                            Ok(()) => source_editor.port_edit = None,
+                            Err(err) => eprintln!(
+                                "failed to receive network source on port {}: {}",
+                                port,
+                                err
+                            ),
+                        }
+                    },
+                }
             }
 
-            // End channel index (to the right).
-            let mut end_channel_indices = realtime.channels.start..audio_channels.input;
-            let end_channel_labels = end_channel_indices
-                .clone()
-                .map(|ch| format!("End Channel: {}", ch + 1))
+            // The encoding of received packets.
+            let format_labels = (0..audio::source::network::Format::VARIANT_COUNT)
+                .map(audio::source::network::Format::label_from_index)
                 .collect::<Vec<_>>();
-            let selected_end =
-                Some((realtime.channels.end - (realtime.channels.start + 1)) as usize);
-            for new_end in widget::DropDownList::new(&end_channel_labels, selected_end)
+            let selected = Some(network.format.to_index());
+            for index in widget::DropDownList::new(&format_labels, selected)
                 .right(PAD)
                 .align_top()
-                .label("End Channel")
+                .label("Format")
+                .label_font_size(SMALL_FONT_SIZE)
+                .scrollbar_on_top()
+                .max_visible_items(4)
+                .w(half_w)
+                .h(ITEM_HEIGHT)
+                .set(ids.source_editor_selected_network_format, ui)
+            {
+                if let Some(format) = audio::source::network::Format::from_index(index) {
+                    let new_network = audio::source::Network { format, ..network.clone() };
+                    update_network(channels, id, network, new_network);
+                }
+            }
+
+            // The number of interleaved channels within the stream.
+            let channel_labels = (1..audio::MAX_CHANNELS + 1)
+                .map(|ch| format!("Channels: {}", ch))
+                .collect::<Vec<_>>();
+            let selected = Some(network.channels.saturating_sub(1));
+            for index in widget::DropDownList::new(&channel_labels, selected)
+                .down(PAD)
+                .align_left_of(ids.source_editor_selected_network_port)
+                .label("Channels")
                 .label_font_size(SMALL_FONT_SIZE)
                 .scrollbar_on_top()
                 .max_visible_items(5)
-                .w(channel_w)
+                .w(half_w)
                 .h(ITEM_HEIGHT)
-                .set(ids.source_editor_selected_realtime_end_channel, ui)
+                .set(ids.source_editor_selected_network_channels, ui)
             {
-                let new_end = end_channel_indices.nth(new_end).unwrap() + 1;
-                // Update the local copy.
-                update_realtime!(|rt: &mut audio::source::Realtime| rt.channels.end = new_end);
+                let new_network = audio::source::Network {
+                    channels: index + 1,
+                    ..network.clone()
+                };
+                update_network(channels, id, network, new_network);
             }
 
-            (
-                ids.source_editor_selected_realtime_canvas,
-                realtime.channels.len(),
-            )
+            // The amount of audio buffered to absorb network jitter.
+            const MAX_JITTER_BUFFER_MS: f32 = 500.0;
+            let label = format!("Jitter Buffer: {:.0} ms", network.jitter_buffer.ms());
+            for new_ms in widget::Slider::new(network.jitter_buffer.ms() as f32, 0.0, MAX_JITTER_BUFFER_MS)
+                .label(&label)
+                .label_font_size(SMALL_FONT_SIZE)
+                .right(PAD)
+                .align_top()
+                .w(half_w)
+                .h(ITEM_HEIGHT)
+                .set(ids.source_editor_selected_network_jitter_buffer, ui)
+            {
+                let new_network = audio::source::Network {
+                    jitter_buffer: Ms(new_ms.round() as _),
+                    ..network.clone()
+                };
+                update_network(channels, id, network, new_network);
+            }
+
+            (ids.source_editor_selected_network_canvas, network.channels)
         }
     };
 
@@ -1299,6 +2019,153 @@ pub fn set(
             .set(label_id, ui);
     }
 
+    /////////////
+    // Effects //
+    /////////////
+
+    widget::Canvas::new()
+        .down_from(ids.source_editor_selected_common_canvas, PAD)
+        .h(effects_canvas_h)
+        .w(selected_canvas_kid_area.w())
+        .pad(PAD)
+        .parent(ids.source_editor_selected_canvas)
+        .color(color::CHARCOAL)
+        .set(ids.source_editor_selected_effects_canvas, ui);
+
+    widget::Text::new("EFFECTS")
+        .font_size(SMALL_FONT_SIZE)
+        .top_left_of(ids.source_editor_selected_effects_canvas)
+        .set(ids.source_editor_selected_effects_text, ui);
+
+    // Send the local copy of the source's effects to the soundscape.
+    //
+    // Sounds that are already playing keep their current effects.
+    fn update_effects(
+        sources: &project::SourcesMap,
+        channels: &Channels,
+        id: audio::source::Id,
+    ) {
+        let effects = sources[&id].effects.clone();
+        channels
+            .soundscape
+            .send(move |soundscape| {
+                soundscape.update_source(&id, |source| source.effects = effects);
+            })
+            .expect("failed to send source effects to soundscape thread");
+    }
+
+    // A drop down list for appending a new effect to the chain.
+    let effects_kid_area = ui.kid_area_of(ids.source_editor_selected_effects_canvas).unwrap();
+    let effect_labels = (0..audio::effect::Effect::VARIANT_COUNT)
+        .map(audio::effect::Effect::label_from_index)
+        .collect::<Vec<_>>();
+    for index in widget::DropDownList::new(&effect_labels, None)
+        .down(PAD * 1.5)
+        .align_left()
+        .label("Add Effect")
+        .label_font_size(SMALL_FONT_SIZE)
+        .scrollbar_on_top()
+        .max_visible_items(5)
+        .w(effects_kid_area.w())
+        .h(ITEM_HEIGHT)
+        .set(ids.source_editor_selected_effects_add, ui)
+    {
+        if let Some(effect) = audio::effect::Effect::from_index(index) {
+            sources.get_mut(&id).unwrap().effects.push(effect);
+            update_effects(sources, channels, id);
+        }
+    }
+
+    // Ensure there are enough widget ids for each effect and parameter.
+    let num_effects = sources[&id].effects.len();
+    let num_params = sources[&id]
+        .effects
+        .iter()
+        .map(|e| e.param_names().len())
+        .sum::<usize>();
+    if ids.source_editor_selected_effects_labels.len() < num_effects {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.source_editor_selected_effects_labels.resize(num_effects, id_gen);
+        ids.source_editor_selected_effects_removes.resize(num_effects, id_gen);
+    }
+    if ids.source_editor_selected_effects_params.len() < num_params {
+        let id_gen = &mut ui.widget_id_generator();
+        ids.source_editor_selected_effects_params.resize(num_params, id_gen);
+    }
+
+    // A label, remove button and a range slider for each parameter of each effect.
+    let remove_button_w = ITEM_HEIGHT * 2.0;
+    let mut maybe_remove_index = None;
+    let mut param_index = 0;
+    for i in 0..num_effects {
+        let effect = sources[&id].effects[i].clone();
+        let label = format!("{}: {}", i, audio::effect::Effect::label_from_index(effect.to_index()));
+        widget::Text::new(&label)
+            .font_size(SMALL_FONT_SIZE)
+            .down(PAD * 1.5)
+            .align_left_of(ids.source_editor_selected_effects_add)
+            .set(ids.source_editor_selected_effects_labels[i], ui);
+
+        for _click in widget::Button::new()
+            .label("X")
+            .label_font_size(SMALL_FONT_SIZE)
+            .w(remove_button_w)
+            .h(ITEM_HEIGHT)
+            .align_right_of(ids.source_editor_selected_effects_add)
+            .align_middle_y_of(ids.source_editor_selected_effects_labels[i])
+            .color(color::DARK_RED)
+            .set(ids.source_editor_selected_effects_removes[i], ui)
+        {
+            maybe_remove_index = Some(i);
+        }
+
+        for &name in effect.param_names() {
+            let range = effect.param(name).expect("no param for name");
+            let limits = audio::effect::Effect::param_limits(name);
+            let label = format!("{}: {:.2} to {:.2}", name, range.min, range.max);
+            let skew = if name.ends_with("_hz") || name.ends_with("_ms") { 0.3 } else { 1.0 };
+            let slider_id = ids.source_editor_selected_effects_params[param_index];
+            param_index += 1;
+            for (edge, value) in widget::RangeSlider::new(
+                range.min as f64,
+                range.max as f64,
+                limits.min as f64,
+                limits.max as f64,
+            )
+                .skew(skew)
+                .label(&label)
+                .label_font_size(SMALL_FONT_SIZE)
+                .color(ui::color::LIGHT_CHARCOAL)
+                .w(effects_kid_area.w())
+                .h(SLIDER_H)
+                .down(PAD)
+                .align_left_of(ids.source_editor_selected_effects_add)
+                .set(slider_id, ui)
+            {
+                let value = value as f32;
+
+                // Update the local copy.
+                {
+                    let source = sources.get_mut(&id).unwrap();
+                    let range = source.effects[i].param_mut(name).expect("no param for name");
+                    match edge {
+                        widget::range_slider::Edge::Start => range.min = value,
+                        widget::range_slider::Edge::End => range.max = value,
+                    }
+                }
+
+                // Update the soundscape copy.
+                update_effects(sources, channels, id);
+            }
+        }
+    }
+
+    // Remove the effect if its button was clicked.
+    if let Some(i) = maybe_remove_index {
+        sources.get_mut(&id).unwrap().effects.remove(i);
+        update_effects(sources, channels, id);
+    }
+
     ///////////////////
     // Role-specific //
     ///////////////////
@@ -1312,9 +2179,13 @@ pub fn set(
                 groups,
                 occurrence_rate,
                 simultaneous_sounds,
+                weight,
                 playback_duration,
                 attack_duration,
                 release_duration,
+                playback_rate,
+                attack_curve,
+                release_curve,
                 movement,
             } = soundscape;
 
@@ -1324,7 +2195,7 @@ pub fn set(
                 .h(INSTALLATIONS_CANVAS_H)
                 .align_middle_x_of(ids.source_editor_selected_canvas)
                 .parent(ids.source_editor_selected_canvas)
-                .down_from(ids.source_editor_selected_common_canvas, PAD)
+                .down_from(ids.source_editor_selected_effects_canvas, PAD)
                 .pad(PAD)
                 .color(color::CHARCOAL)
                 .set(ids.source_editor_selected_installations_canvas, ui);
@@ -1594,6 +2465,46 @@ pub fn set(
                     .expect("failed to send updated source occurrence rate to soundscape thread");
             }
 
+            ////////////
+            // Weight //
+            ////////////
+
+            widget::Text::new("Weight")
+                .align_left()
+                .down(PAD * 2.0)
+                .font_size(SMALL_FONT_SIZE)
+                .set(ids.source_editor_selected_soundscape_weight_text, ui);
+
+            // The likelihood of choosing this source over other equally suitable sources.
+            let label = format!("{:.2}x", weight);
+            for value in widget::Slider::new(weight, 0.0, audio::source::MAX_WEIGHT)
+                .skew(0.5)
+                .kid_area_w_of(ids.source_editor_selected_soundscape_canvas)
+                .h(SLIDER_H)
+                .label_font_size(SMALL_FONT_SIZE)
+                .color(ui::color::LIGHT_CHARCOAL)
+                .align_left()
+                .label(&label)
+                .down(PAD * 2.0)
+                .set(ids.source_editor_selected_soundscape_weight_slider, ui)
+            {
+                // Snap to the nearest hundredth.
+                let new_weight = (value * 100.0).round() / 100.0;
+
+                // Update the local copy.
+                expect_soundscape_mut(sources, &id).weight = new_weight;
+
+                // Update the soundscape copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, |source| {
+                            source.weight = new_weight;
+                        });
+                    })
+                    .expect("failed to send source weight to soundscape thread");
+            }
+
             /////////////////////////
             // Simultaneous Sounds //
             /////////////////////////
@@ -1661,6 +2572,7 @@ pub fn set(
             let skew = sources[&id].kind.playback_duration_skew();
             let max_duration = match sources[&id].kind {
                 audio::source::Kind::Realtime(ref realtime) => realtime.duration,
+                audio::source::Kind::Network(ref network) => network.duration,
                 audio::source::Kind::Wav(ref wav) => match wav.should_loop {
                     true => audio::source::MAX_PLAYBACK_DURATION,
                     false => wav.duration.to_ms(audio::SAMPLE_RATE),
@@ -1823,13 +2735,221 @@ pub fn set(
                     .expect("failed to send source release duration to soundscape thread");
             }
 
+            ///////////////////
+            // Playback Rate //
+            ///////////////////
+
+            widget::Text::new("Playback Rate")
+                .align_left()
+                .down(PAD * 2.0)
+                .font_size(SMALL_FONT_SIZE)
+                .set(ids.source_editor_selected_soundscape_playback_rate_text, ui);
+
+            let total_max = audio::source::MAX_PLAYBACK_RATE_SEMITONES;
+            let total_min = -total_max;
+            let range = playback_rate;
+            let label = format!("{:+.1} to {:+.1} semitones", range.min, range.max);
+            let start = range.min as f64;
+            let end = range.max as f64;
+            for (edge, value) in range_slider(start, end, total_min as f64, total_max as f64)
+                .align_left()
+                .label(&label)
+                .down(PAD * 2.0)
+                .set(ids.source_editor_selected_soundscape_playback_rate_slider, ui)
+            {
+                // Snap to the nearest tenth of a semitone.
+                let semitones = (value * 10.0).round() as f32 / 10.0;
+
+                // Update the local copy.
+                let new_rate = {
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    match edge {
+                        widget::range_slider::Edge::Start => {
+                            soundscape.playback_rate.min = semitones;
+                        },
+                        widget::range_slider::Edge::End => {
+                            soundscape.playback_rate.max = semitones;
+                        }
+                    }
+                    soundscape.playback_rate
+                };
+
+                // Update the soundscape copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, |source| {
+                            source.playback_rate = new_rate;
+                        });
+                    })
+                    .expect("failed to send source playback rate to soundscape thread");
+            }
+
+            /////////////////
+            // Fade Curves //
+            /////////////////
+
+            widget::Text::new("Fade Curves")
+                .align_left()
+                .down(PAD * 2.0)
+                .font_size(SMALL_FONT_SIZE)
+                .set(ids.source_editor_selected_soundscape_curve_text, ui);
+
+            let curve_kid_area = ui.kid_area_of(ids.source_editor_selected_soundscape_canvas).unwrap();
+            let half_w = curve_kid_area.w() / 2.0 - PAD / 2.0;
+            let quarter_w = half_w / 2.0 - PAD / 2.0;
+            let curve_labels = (0..audio::source::Curve::VARIANT_COUNT)
+                .map(audio::source::Curve::label_from_index)
+                .collect::<Vec<_>>();
+
+            // Update the local and soundscape copies of a curve.
+            //
+            // `is_attack` specifies whether the attack or release curve should be updated.
+            fn update_curve(
+                sources: &mut project::SourcesMap,
+                channels: &Channels,
+                id: audio::source::Id,
+                is_attack: bool,
+                curve: audio::source::Curve,
+            ) {
+                // Update the local copy.
+                {
+                    let soundscape = expect_soundscape_mut(sources, &id);
+                    match is_attack {
+                        true => soundscape.attack_curve = curve,
+                        false => soundscape.release_curve = curve,
+                    }
+                }
+
+                // Update the soundscape copy.
+                channels
+                    .soundscape
+                    .send(move |soundscape| {
+                        soundscape.update_source(&id, |source| match is_attack {
+                            true => source.attack_curve = curve,
+                            false => source.release_curve = curve,
+                        });
+                    })
+                    .expect("failed to send source fade curve to soundscape thread");
+            }
+
+            // Attack curve selection.
+            let selected = Some(attack_curve.to_index());
+            for index in widget::DropDownList::new(&curve_labels, selected)
+                .down(PAD)
+                .align_left()
+                .label("Fade-In Curve")
+                .label_font_size(SMALL_FONT_SIZE)
+                .scrollbar_on_top()
+                .max_visible_items(5)
+                .w(half_w)
+                .h(ITEM_HEIGHT)
+                .set(ids.source_editor_selected_soundscape_attack_curve_ddl, ui)
+            {
+                if let Some(curve) = audio::source::Curve::from_index(index) {
+                    update_curve(sources, channels, id, true, curve);
+                }
+            }
+
+            // Release curve selection.
+            let selected = Some(release_curve.to_index());
+            for index in widget::DropDownList::new(&curve_labels, selected)
+                .right(PAD)
+                .align_top()
+                .label("Fade-Out Curve")
+                .label_font_size(SMALL_FONT_SIZE)
+                .scrollbar_on_top()
+                .max_visible_items(5)
+                .w(half_w)
+                .h(ITEM_HEIGHT)
+                .set(ids.source_editor_selected_soundscape_release_curve_ddl, ui)
+            {
+                if let Some(curve) = audio::source::Curve::from_index(index) {
+                    update_curve(sources, channels, id, false, curve);
+                }
+            }
+
+            // Bezier control point sliders.
+            let bezier_slider = |value: f32| {
+                widget::Slider::new(value, 0.0, 1.0)
+                    .w(quarter_w)
+                    .h(SLIDER_H)
+                    .label_font_size(SMALL_FONT_SIZE)
+                    .color(ui::color::LIGHT_CHARCOAL)
+            };
+            if let audio::source::Curve::Bezier(bezier) = attack_curve {
+                for a in bezier_slider(bezier.a)
+                    .label(&format!("A: {:.2}", bezier.a))
+                    .down_from(ids.source_editor_selected_soundscape_attack_curve_ddl, PAD)
+                    .align_left_of(ids.source_editor_selected_soundscape_attack_curve_ddl)
+                    .set(ids.source_editor_selected_soundscape_attack_curve_bezier_a, ui)
+                {
+                    let curve = audio::source::Curve::Bezier(audio::source::envelope::Bezier { a, ..bezier });
+                    update_curve(sources, channels, id, true, curve);
+                }
+                for b in bezier_slider(bezier.b)
+                    .label(&format!("B: {:.2}", bezier.b))
+                    .right(PAD)
+                    .align_top()
+                    .set(ids.source_editor_selected_soundscape_attack_curve_bezier_b, ui)
+                {
+                    let curve = audio::source::Curve::Bezier(audio::source::envelope::Bezier { b, ..bezier });
+                    update_curve(sources, channels, id, true, curve);
+                }
+            }
+            if let audio::source::Curve::Bezier(bezier) = release_curve {
+                for a in bezier_slider(bezier.a)
+                    .label(&format!("A: {:.2}", bezier.a))
+                    .down_from(ids.source_editor_selected_soundscape_release_curve_ddl, PAD)
+                    .align_left_of(ids.source_editor_selected_soundscape_release_curve_ddl)
+                    .set(ids.source_editor_selected_soundscape_release_curve_bezier_a, ui)
+                {
+                    let curve = audio::source::Curve::Bezier(audio::source::envelope::Bezier { a, ..bezier });
+                    update_curve(sources, channels, id, false, curve);
+                }
+                for b in bezier_slider(bezier.b)
+                    .label(&format!("B: {:.2}", bezier.b))
+                    .right(PAD)
+                    .align_top()
+                    .set(ids.source_editor_selected_soundscape_release_curve_bezier_b, ui)
+                {
+                    let curve = audio::source::Curve::Bezier(audio::source::envelope::Bezier { b, ..bezier });
+                    update_curve(sources, channels, id, false, curve);
+                }
+            }
+
+            // A preview of the fade-in followed by the fade-out.
+            widget::Canvas::new()
+                .down_from(ids.source_editor_selected_soundscape_attack_curve_ddl, PAD * 2.0 + SLIDER_H)
+                .align_left_of(ids.source_editor_selected_soundscape_attack_curve_ddl)
+                .w(curve_kid_area.w())
+                .h(CURVE_PREVIEW_H)
+                .pad(PAD / 2.0)
+                .color(DARK_A)
+                .parent(ids.source_editor_selected_soundscape_canvas)
+                .set(ids.source_editor_selected_soundscape_curve_preview_canvas, ui);
+            let preview_kid_area = ui.kid_area_of(ids.source_editor_selected_soundscape_curve_preview_canvas).unwrap();
+            let plot_w = preview_kid_area.w() / 2.0;
+            widget::PlotPath::new(0.0, 1.0, 0.0, 1.0, |t: f32| attack_curve.gain(t))
+                .w_h(plot_w, preview_kid_area.h())
+                .mid_left_of(ids.source_editor_selected_soundscape_curve_preview_canvas)
+                .color(color::LIGHT_BLUE)
+                .thickness(2.0)
+                .set(ids.source_editor_selected_soundscape_attack_curve_plot, ui);
+            widget::PlotPath::new(0.0, 1.0, 0.0, 1.0, |t: f32| release_curve.gain(1.0 - t))
+                .w_h(plot_w, preview_kid_area.h())
+                .mid_right_of(ids.source_editor_selected_soundscape_curve_preview_canvas)
+                .color(color::LIGHT_BLUE)
+                .thickness(2.0)
+                .set(ids.source_editor_selected_soundscape_release_curve_plot, ui);
+
             //////////////////////////////////
             // Soundscape Group Assignments //
             //////////////////////////////////
 
             widget::Text::new("Soundscape Groups")
-                .align_left()
-                .down(PAD * 2.0)
+                .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                .down_from(ids.source_editor_selected_soundscape_curve_preview_canvas, PAD * 2.0)
                 .font_size(SMALL_FONT_SIZE)
                 .set(ids.source_editor_selected_soundscape_groups_text, ui);
 
@@ -2056,6 +3176,8 @@ pub fn set(
             match generative {
                 // Agent-specific widgets.
                 audio::source::movement::Generative::Agent(mut agent) => {
+                    use audio::source::movement::{FlockNeighbours, Generative};
+
                     /////////////////
                     // Directional //
                     /////////////////
@@ -2312,6 +3434,128 @@ pub fn set(
                             })
                             .expect("failed to send movement update to soundscape thread");
                     }
+
+                    //////////////
+                    // Flocking //
+                    //////////////
+
+                    widget::Text::new("Flocking")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flocking_text, ui);
+
+                    // A rightward flowing list for the kinds of flock neighbours.
+                    let n_items = FlockNeighbours::VARIANT_COUNT;
+                    let item_w = canvas_kid_area.w() / n_items as Scalar;
+                    let (mut events, _scrollbar) = widget::ListSelect::single(n_items)
+                        .flow_right()
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .item_size(item_w)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flock_neighbours, ui);
+                    let selected_index = agent.flocking.neighbours.to_index();
+                    let is_selected = |i| i == selected_index;
+                    while let Some(event) = events.next(ui, &is_selected) {
+                        use nannou::ui::widget::list_select::Event;
+                        match event {
+                            Event::Item(item) => {
+                                let index = item.i;
+                                let selected = is_selected(index);
+                                let color = if selected { color::BLUE } else { color::DARK_CHARCOAL };
+                                let label = FlockNeighbours::label_from_index(index);
+                                let button = widget::Button::new()
+                                    .label(&label)
+                                    .label_font_size(SMALL_FONT_SIZE)
+                                    .color(color);
+
+                                // If the button was clicked.
+                                for _click in item.set(button, ui) {
+                                    agent.flocking.neighbours = match FlockNeighbours::from_index(index) {
+                                        None => continue,
+                                        Some(neighbours) => neighbours,
+                                    };
+                                    let generative = Generative::Agent(agent.clone());
+                                    update_generative_movement(channels, sources, id, generative);
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    let slider = |value, min, max| {
+                        widget::Slider::new(value, min, max)
+                            .h(SLIDER_H)
+                            .w(canvas_kid_area.w())
+                            .label_font_size(SMALL_FONT_SIZE)
+                            .color(ui::color::LIGHT_CHARCOAL)
+                    };
+
+                    let max_radius = audio::source::movement::MAX_FLOCK_RADIUS;
+                    let radius = agent.flocking.radius;
+                    let label = format!("Radius: {:.2} metres", radius);
+                    for new_radius in slider(radius, 0.0, max_radius)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flock_radius, ui)
+                    {
+                        agent.flocking.radius = new_radius;
+                        let generative = Generative::Agent(agent.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let distance = agent.flocking.separation_distance;
+                    let label = format!("Separation Distance: {:.2} metres", distance);
+                    for new_distance in slider(distance, 0.0, max_radius)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flock_separation_distance, ui)
+                    {
+                        agent.flocking.separation_distance = new_distance;
+                        let generative = Generative::Agent(agent.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let max_weight = audio::source::movement::MAX_FLOCK_WEIGHT;
+                    let label = format!("Separation: {:.2}", agent.flocking.separation);
+                    for new_weight in slider(agent.flocking.separation, 0.0, max_weight)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flock_separation, ui)
+                    {
+                        agent.flocking.separation = new_weight;
+                        let generative = Generative::Agent(agent.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let label = format!("Alignment: {:.2}", agent.flocking.alignment);
+                    for new_weight in slider(agent.flocking.alignment, 0.0, max_weight)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flock_alignment, ui)
+                    {
+                        agent.flocking.alignment = new_weight;
+                        let generative = Generative::Agent(agent.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let label = format!("Cohesion: {:.2}", agent.flocking.cohesion);
+                    for new_weight in slider(agent.flocking.cohesion, 0.0, max_weight)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_agent_flock_cohesion, ui)
+                    {
+                        agent.flocking.cohesion = new_weight;
+                        let generative = Generative::Agent(agent.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
                 },
 
                 // Ngon-specific widgets.
@@ -2707,6 +3951,846 @@ pub fn set(
                             .expect("failed to send movement update to soundscape thread");
                     }
                 },
+
+                // Path-specific widgets.
+                audio::source::movement::Generative::Path(mut path) => {
+                    use audio::source::movement::{Generative, PathMode, PathTiming};
+
+                    ///////////////
+                    // Path Mode //
+                    ///////////////
+
+                    widget::Text::new("Path Mode")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_path_mode_text, ui);
+
+                    // A rightward flowing list for the path modes.
+                    let n_items = PathMode::VARIANT_COUNT;
+                    let item_w = canvas_kid_area.w() / n_items as Scalar;
+                    let (mut events, _scrollbar) = widget::ListSelect::single(n_items)
+                        .flow_right()
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .item_size(item_w)
+                        .set(ids.source_editor_selected_soundscape_movement_path_mode_list, ui);
+                    let selected_index = path.mode.to_index();
+                    let is_selected = |i| i == selected_index;
+                    while let Some(event) = events.next(ui, &is_selected) {
+                        use nannou::ui::widget::list_select::Event;
+                        match event {
+                            Event::Item(item) => {
+                                let index = item.i;
+                                let selected = is_selected(index);
+                                let color = if selected { color::BLUE } else { color::DARK_CHARCOAL };
+                                let label = PathMode::label_from_index(index);
+                                let button = widget::Button::new()
+                                    .label(&label)
+                                    .label_font_size(SMALL_FONT_SIZE)
+                                    .color(color);
+
+                                // If the button was clicked.
+                                for _click in item.set(button, ui) {
+                                    path.mode = match PathMode::from_index(index) {
+                                        None => continue,
+                                        Some(mode) => mode,
+                                    };
+                                    let generative = Generative::Path(path.clone());
+                                    update_generative_movement(channels, sources, id, generative);
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    /////////////////
+                    // Path Timing //
+                    /////////////////
+
+                    widget::Text::new("Timing")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_path_timing_text, ui);
+
+                    // A rightward flowing list for selecting either speed or traversal duration.
+                    let n_items = PathTiming::VARIANT_COUNT;
+                    let item_w = canvas_kid_area.w() / n_items as Scalar;
+                    let (mut events, _scrollbar) = widget::ListSelect::single(n_items)
+                        .flow_right()
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .item_size(item_w)
+                        .set(ids.source_editor_selected_soundscape_movement_path_timing_list, ui);
+                    let selected_index = path.timing.to_index();
+                    let is_selected = |i| i == selected_index;
+                    while let Some(event) = events.next(ui, &is_selected) {
+                        use nannou::ui::widget::list_select::Event;
+                        match event {
+                            Event::Item(item) => {
+                                let index = item.i;
+                                let selected = is_selected(index);
+                                let color = if selected { color::BLUE } else { color::DARK_CHARCOAL };
+                                let label = PathTiming::label_from_index(index);
+                                let button = widget::Button::new()
+                                    .label(&label)
+                                    .label_font_size(SMALL_FONT_SIZE)
+                                    .color(color);
+
+                                // If the button was clicked.
+                                for _click in item.set(button, ui) {
+                                    path.timing = match PathTiming::from_index(index) {
+                                        None => continue,
+                                        Some(timing) => timing,
+                                    };
+                                    let generative = Generative::Path(path.clone());
+                                    update_generative_movement(channels, sources, id, generative);
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    // A range slider for the speed or the duration of a single traversal.
+                    match path.timing {
+                        PathTiming::Speed(mut speed) => {
+                            let total_min = 0.0;
+                            let total_max = audio::source::movement::MAX_SPEED;
+                            let (min, max) = (speed.min, speed.max);
+                            let label = format!("{:.2} to {:.2} metres per second", min, max);
+                            for (edge, value) in range_slider(min, max, total_min, total_max)
+                                .skew(audio::source::movement::MAX_SPEED_SKEW)
+                                .align_left()
+                                .label(&label)
+                                .down(PAD)
+                                .set(ids.source_editor_selected_soundscape_movement_path_timing_slider, ui)
+                            {
+                                match edge {
+                                    widget::range_slider::Edge::Start => speed.min = value,
+                                    widget::range_slider::Edge::End => speed.max = value,
+                                }
+                                path.timing = PathTiming::Speed(speed);
+                                let generative = Generative::Path(path.clone());
+                                update_generative_movement(channels, sources, id, generative);
+                            }
+                        },
+                        PathTiming::Duration(mut duration) => {
+                            let total_min = utils::SEC_MS;
+                            let total_max = audio::source::movement::MAX_PATH_DURATION.ms();
+                            let label = format!(
+                                "{} to {}",
+                                duration_label(&duration.min),
+                                duration_label(&duration.max),
+                            );
+                            let (start, end) = (duration.min.ms(), duration.max.ms());
+                            for (edge, value) in range_slider(start, end, total_min, total_max)
+                                .skew(audio::source::movement::PATH_DURATION_SKEW)
+                                .align_left()
+                                .label(&label)
+                                .down(PAD)
+                                .set(ids.source_editor_selected_soundscape_movement_path_timing_slider, ui)
+                            {
+                                let value = Ms((value / utils::SEC_MS).round() * utils::SEC_MS);
+                                match edge {
+                                    widget::range_slider::Edge::Start => duration.min = value,
+                                    widget::range_slider::Edge::End => duration.max = value,
+                                }
+                                path.timing = PathTiming::Duration(duration);
+                                let generative = Generative::Path(path.clone());
+                                update_generative_movement(channels, sources, id, generative);
+                            }
+                        },
+                    }
+
+                    ///////////////////
+                    // Random Offset //
+                    ///////////////////
+
+                    let on_off = if path.random_offset { "ON" } else { "OFF" };
+                    let label = format!("Random Offset: {}", on_off);
+                    for new_random_offset in widget::Toggle::new(path.random_offset)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(canvas_kid_area.w())
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_path_random_offset, ui)
+                    {
+                        // The offset only applies to newly spawned sounds.
+                        path.random_offset = new_random_offset;
+                        let generative = Generative::Path(path.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    /////////////////
+                    // Path Points //
+                    /////////////////
+
+                    let label = format!("Points: {}", path.points.len());
+                    widget::Text::new(&label)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_path_points_text, ui);
+
+                    // Toggle editing of the path over the floorplan.
+                    let button_w = (canvas_kid_area.w() - PAD) / 2.0;
+                    let label = match source_editor.editing_path {
+                        true => "Editing On Floorplan",
+                        false => "Edit On Floorplan",
+                    };
+                    for new_editing in widget::Toggle::new(source_editor.editing_path)
+                        .label(label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(button_w)
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_path_edit, ui)
+                    {
+                        source_editor.editing_path = new_editing;
+                    }
+
+                    // Remove all points from the path.
+                    for _click in widget::Button::new()
+                        .label("Clear Points")
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .right(PAD)
+                        .h(ITEM_HEIGHT)
+                        .w(button_w)
+                        .color(color::DARK_RED.alpha(0.5))
+                        .set(ids.source_editor_selected_soundscape_movement_path_clear, ui)
+                    {
+                        path.points.clear();
+                        let generative = Generative::Path(path.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+                },
+
+                // Orbit-specific widgets.
+                audio::source::movement::Generative::Orbit(mut orbit) => {
+                    use audio::source::movement::{Generative, OrbitDirection};
+
+                    ////////////
+                    // Centre //
+                    ////////////
+
+                    widget::Text::new("Centre")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_centre_text, ui);
+
+                    let slider = |value, min, max| {
+                        widget::Slider::new(value, min, max)
+                            .h(SLIDER_H)
+                            .w(canvas_kid_area.w())
+                            .label_font_size(SMALL_FONT_SIZE)
+                            .color(ui::color::LIGHT_CHARCOAL)
+                    };
+
+                    let label = format!("{:.2}% of installation width", orbit.centre.x * 100.0);
+                    for new_x in slider(orbit.centre.x, 0.0, 1.0)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_centre_x_slider, ui)
+                    {
+                        orbit.centre.x = new_x;
+                        let generative = Generative::Orbit(orbit.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let label = format!("{:.2}% of installation height", orbit.centre.y * 100.0);
+                    for new_y in slider(orbit.centre.y, 0.0, 1.0)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_centre_y_slider, ui)
+                    {
+                        orbit.centre.y = new_y;
+                        let generative = Generative::Orbit(orbit.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ////////////
+                    // Radius //
+                    ////////////
+
+                    widget::Text::new("Radius")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_radius_text, ui);
+
+                    let min = orbit.radius.min;
+                    let max = orbit.radius.max;
+                    let (min_pc, max_pc) = (min * 100.0, max * 100.0);
+                    let label = format!("{:.2}% to {:.2}% of the nearest bound", min_pc, max_pc);
+                    for (edge, value) in range_slider(min, max, 0.0, 1.0)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_radius_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => orbit.radius.min = value,
+                            widget::range_slider::Edge::End => orbit.radius.max = value,
+                        }
+                        let generative = Generative::Orbit(orbit.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ///////////
+                    // Speed //
+                    ///////////
+
+                    widget::Text::new("Speed")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_speed_text, ui);
+
+                    let min = orbit.speed.min;
+                    let max = orbit.speed.max;
+                    let total_min = 0.0;
+                    let total_max = audio::source::movement::MAX_SPEED;
+                    let label = format!("{:.2} to {:.2} metres per second", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .skew(audio::source::movement::MAX_SPEED_SKEW)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_speed_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => orbit.speed.min = value,
+                            widget::range_slider::Edge::End => orbit.speed.max = value,
+                        }
+                        let generative = Generative::Orbit(orbit.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ///////////////
+                    // Direction //
+                    ///////////////
+
+                    widget::Text::new("Direction")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_direction_text, ui);
+
+                    // A rightward flowing list for the orbit directions.
+                    let n_items = OrbitDirection::VARIANT_COUNT;
+                    let item_w = canvas_kid_area.w() / n_items as Scalar;
+                    let (mut events, _scrollbar) = widget::ListSelect::single(n_items)
+                        .flow_right()
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .item_size(item_w)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_direction_list, ui);
+                    let selected_index = orbit.direction.to_index();
+                    let is_selected = |i| i == selected_index;
+                    while let Some(event) = events.next(ui, &is_selected) {
+                        use nannou::ui::widget::list_select::Event;
+                        match event {
+                            Event::Item(item) => {
+                                let index = item.i;
+                                let selected = is_selected(index);
+                                let color = if selected { color::BLUE } else { color::DARK_CHARCOAL };
+                                let label = OrbitDirection::label_from_index(index);
+                                let button = widget::Button::new()
+                                    .label(&label)
+                                    .label_font_size(SMALL_FONT_SIZE)
+                                    .color(color);
+
+                                // If the button was clicked.
+                                for _click in item.set(button, ui) {
+                                    orbit.direction = match OrbitDirection::from_index(index) {
+                                        None => continue,
+                                        Some(direction) => direction,
+                                    };
+                                    let generative = Generative::Orbit(orbit.clone());
+                                    update_generative_movement(channels, sources, id, generative);
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    /////////////////
+                    // Directional //
+                    /////////////////
+
+                    let on_off = if orbit.directional { "ON" } else { "OFF" };
+                    let label = format!("Directional: {}", on_off);
+                    for new_directional in widget::Toggle::new(orbit.directional)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(canvas_kid_area.w())
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_orbit_directional, ui)
+                    {
+                        orbit.directional = new_directional;
+                        let generative = Generative::Orbit(orbit.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+                },
+
+                // Lissajous-specific widgets.
+                audio::source::movement::Generative::Lissajous(mut lissajous) => {
+                    use audio::source::movement::Generative;
+
+                    ///////////////
+                    // Frequency //
+                    ///////////////
+
+                    widget::Text::new("Frequency Ratio")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_frequency_text, ui);
+
+                    let total_min = 1.0;
+                    let total_max = audio::source::movement::MAX_LISSAJOUS_FREQUENCY as f64;
+                    let min = lissajous.frequency_x.min as f64;
+                    let max = lissajous.frequency_x.max as f64;
+                    let label = format!("{} to {} horizontal", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_frequency_x_slider, ui)
+                    {
+                        let value = value as usize;
+                        match edge {
+                            widget::range_slider::Edge::Start => lissajous.frequency_x.min = value,
+                            widget::range_slider::Edge::End => lissajous.frequency_x.max = value,
+                        }
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let min = lissajous.frequency_y.min as f64;
+                    let max = lissajous.frequency_y.max as f64;
+                    let label = format!("{} to {} vertical", min, max);
+                    for (edge, value) in range_slider(min, max, total_min, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_frequency_y_slider, ui)
+                    {
+                        let value = value as usize;
+                        match edge {
+                            widget::range_slider::Edge::Start => lissajous.frequency_y.min = value,
+                            widget::range_slider::Edge::End => lissajous.frequency_y.max = value,
+                        }
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ///////////
+                    // Phase //
+                    ///////////
+
+                    widget::Text::new("Phase")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_phase_text, ui);
+
+                    let min = lissajous.phase.min;
+                    let max = lissajous.phase.max;
+                    let total_max = 2.0 * PI_F64;
+                    let label = format!("{:.2} to {:.2} radians", min, max);
+                    for (edge, value) in range_slider(min, max, 0.0, total_max)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_phase_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => lissajous.phase.min = value,
+                            widget::range_slider::Edge::End => lissajous.phase.max = value,
+                        }
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    //////////
+                    // Rate //
+                    //////////
+
+                    widget::Text::new("Rate")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_rate_text, ui);
+
+                    let min = lissajous.rate.min;
+                    let max = lissajous.rate.max;
+                    let total_max = audio::source::movement::MAX_LISSAJOUS_RATE;
+                    let label = format!("{:.3} to {:.3} cycles per second", min, max);
+                    for (edge, value) in range_slider(min, max, 0.0, total_max)
+                        .skew(audio::source::movement::LISSAJOUS_RATE_SKEW)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_rate_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => lissajous.rate.min = value,
+                            widget::range_slider::Edge::End => lissajous.rate.max = value,
+                        }
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ////////////////
+                    // Dimensions //
+                    ////////////////
+
+                    widget::Text::new("Dimensions")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_dimensions_text, ui);
+
+                    let slider = |value, min, max| {
+                        widget::Slider::new(value, min, max)
+                            .h(SLIDER_H)
+                            .w(canvas_kid_area.w())
+                            .label_font_size(SMALL_FONT_SIZE)
+                            .color(ui::color::LIGHT_CHARCOAL)
+                    };
+
+                    let dimensions = lissajous.normalised_dimensions;
+                    let label = format!("{:.2}% of installation width", dimensions.x * 100.0);
+                    for new_width in slider(dimensions.x, 0.0, 1.0)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_width_slider, ui)
+                    {
+                        lissajous.normalised_dimensions.x = new_width;
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    let label = format!("{:.2}% of installation height", dimensions.y * 100.0);
+                    for new_height in slider(dimensions.y, 0.0, 1.0)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_height_slider, ui)
+                    {
+                        lissajous.normalised_dimensions.y = new_height;
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    /////////////////
+                    // Directional //
+                    /////////////////
+
+                    let on_off = if lissajous.directional { "ON" } else { "OFF" };
+                    let label = format!("Directional: {}", on_off);
+                    for new_directional in widget::Toggle::new(lissajous.directional)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(canvas_kid_area.w())
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_lissajous_directional, ui)
+                    {
+                        lissajous.directional = new_directional;
+                        let generative = Generative::Lissajous(lissajous.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+                },
+
+                // Wander-specific widgets.
+                audio::source::movement::Generative::Wander(mut wander) => {
+                    use audio::source::movement::Generative;
+
+                    ///////////
+                    // Speed //
+                    ///////////
+
+                    widget::Text::new("Speed")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_speed_text, ui);
+
+                    let min = wander.speed.min;
+                    let max = wander.speed.max;
+                    let total_max = audio::source::movement::MAX_SPEED;
+                    let label = format!("{:.2} to {:.2} metres per second", min, max);
+                    for (edge, value) in range_slider(min, max, 0.0, total_max)
+                        .skew(audio::source::movement::MAX_SPEED_SKEW)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_speed_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => wander.speed.min = value,
+                            widget::range_slider::Edge::End => wander.speed.max = value,
+                        }
+                        let generative = Generative::Wander(wander.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ////////////////
+                    // Turbulence //
+                    ////////////////
+
+                    widget::Text::new("Turbulence")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_turbulence_text, ui);
+
+                    let min = wander.turbulence.min;
+                    let max = wander.turbulence.max;
+                    let total_max = audio::source::movement::MAX_TURBULENCE;
+                    let label = format!("{:.2} to {:.2} radians per second", min, max);
+                    for (edge, value) in range_slider(min, max, 0.0, total_max)
+                        .skew(audio::source::movement::TURBULENCE_SKEW)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_turbulence_slider, ui)
+                    {
+                        match edge {
+                            widget::range_slider::Edge::Start => wander.turbulence.min = value,
+                            widget::range_slider::Edge::End => wander.turbulence.max = value,
+                        }
+                        let generative = Generative::Wander(wander.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    ////////////////////
+                    // Edge Repulsion //
+                    ////////////////////
+
+                    widget::Text::new("Edge Repulsion")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_edge_repulsion_text, ui);
+
+                    let label = format!("{:.2}%", wander.edge_repulsion * 100.0);
+                    for new_edge_repulsion in widget::Slider::new(wander.edge_repulsion, 0.0, 1.0)
+                        .h(SLIDER_H)
+                        .w(canvas_kid_area.w())
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_edge_repulsion_slider, ui)
+                    {
+                        wander.edge_repulsion = new_edge_repulsion;
+                        let generative = Generative::Wander(wander.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    /////////////////
+                    // Directional //
+                    /////////////////
+
+                    let on_off = if wander.directional { "ON" } else { "OFF" };
+                    let label = format!("Directional: {}", on_off);
+                    for new_directional in widget::Toggle::new(wander.directional)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(canvas_kid_area.w())
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_wander_directional, ui)
+                    {
+                        wander.directional = new_directional;
+                        let generative = Generative::Wander(wander.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+                },
+
+                // Tracked-specific widgets.
+                audio::source::movement::Generative::Tracked(mut tracked) => {
+                    use audio::source::movement::{Generative, TrackedTarget};
+
+                    ////////////
+                    // Target //
+                    ////////////
+
+                    widget::Text::new("Target")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_target_text, ui);
+
+                    // A rightward flowing list for the kinds of tracked targets.
+                    let n_items = TrackedTarget::VARIANT_COUNT;
+                    let item_w = canvas_kid_area.w() / n_items as Scalar;
+                    let (mut events, _scrollbar) = widget::ListSelect::single(n_items)
+                        .flow_right()
+                        .align_left()
+                        .down(PAD * 2.0)
+                        .w(canvas_kid_area.w())
+                        .h(BUTTON_H)
+                        .item_size(item_w)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_target_list, ui);
+                    let selected_index = tracked.target.to_index();
+                    let is_selected = |i| i == selected_index;
+                    while let Some(event) = events.next(ui, &is_selected) {
+                        use nannou::ui::widget::list_select::Event;
+                        match event {
+                            Event::Item(item) => {
+                                let index = item.i;
+                                let selected = is_selected(index);
+                                let color = if selected { color::BLUE } else { color::DARK_CHARCOAL };
+                                let label = TrackedTarget::label_from_index(index);
+                                let button = widget::Button::new()
+                                    .label(&label)
+                                    .label_font_size(SMALL_FONT_SIZE)
+                                    .color(color);
+
+                                // If the button was clicked.
+                                for _click in item.set(button, ui) {
+                                    if selected {
+                                        continue;
+                                    }
+                                    tracked.target = match TrackedTarget::from_index(index) {
+                                        None => continue,
+                                        Some(target) => target,
+                                    };
+                                    let generative = Generative::Tracked(tracked.clone());
+                                    update_generative_movement(channels, sources, id, generative);
+                                }
+                            },
+                            _ => (),
+                        }
+                    }
+
+                    // The name of the tracked point to follow.
+                    if let TrackedTarget::Named(ref name) = tracked.target.clone() {
+                        for event in widget::TextBox::new(name)
+                            .align_left()
+                            .down(PAD)
+                            .w(canvas_kid_area.w())
+                            .h(ITEM_HEIGHT)
+                            .font_size(SMALL_FONT_SIZE)
+                            .color(ui::color::LIGHT_CHARCOAL)
+                            .set(ids.source_editor_selected_soundscape_movement_tracked_name, ui)
+                        {
+                            use nannou::ui::widget::text_box::Event;
+                            if let Event::Update(new_name) = event {
+                                tracked.target = TrackedTarget::Named(new_name);
+                                let generative = Generative::Tracked(tracked.clone());
+                                update_generative_movement(channels, sources, id, generative);
+                            }
+                        }
+                    }
+
+                    ///////////////
+                    // Smoothing //
+                    ///////////////
+
+                    widget::Text::new("Smoothing")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_smoothing_text, ui);
+
+                    let slider = |value, min, max| {
+                        widget::Slider::new(value, min, max)
+                            .h(SLIDER_H)
+                            .w(canvas_kid_area.w())
+                            .label_font_size(SMALL_FONT_SIZE)
+                            .color(ui::color::LIGHT_CHARCOAL)
+                    };
+
+                    let max = audio::source::movement::MAX_TRACKED_SMOOTHING;
+                    let max_secs = max.ms() / utils::SEC_MS;
+                    let secs = tracked.smoothing.ms() / utils::SEC_MS;
+                    let label = format!("{:.2} secs", secs);
+                    for new_secs in slider(secs, 0.0, max_secs)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_smoothing_slider, ui)
+                    {
+                        tracked.smoothing = Ms(new_secs * utils::SEC_MS);
+                        let generative = Generative::Tracked(tracked.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    /////////////
+                    // Timeout //
+                    /////////////
+
+                    widget::Text::new("Timeout")
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .font_size(SMALL_FONT_SIZE)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_timeout_text, ui);
+
+                    let max = audio::source::movement::MAX_TRACKED_TIMEOUT;
+                    let max_secs = max.ms() / utils::SEC_MS;
+                    let secs = tracked.timeout.ms() / utils::SEC_MS;
+                    let label = format!("Release after {:.1} secs without tracking", secs);
+                    for new_secs in slider(secs, 0.0, max_secs)
+                        .align_left()
+                        .label(&label)
+                        .down(PAD * 2.0)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_timeout_slider, ui)
+                    {
+                        tracked.timeout = Ms(new_secs * utils::SEC_MS);
+                        let generative = Generative::Tracked(tracked.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+
+                    /////////////////
+                    // Directional //
+                    /////////////////
+
+                    let on_off = if tracked.directional { "ON" } else { "OFF" };
+                    let label = format!("Directional: {}", on_off);
+                    for new_directional in widget::Toggle::new(tracked.directional)
+                        .label(&label)
+                        .label_font_size(SMALL_FONT_SIZE)
+                        .mid_left_of(ids.source_editor_selected_soundscape_canvas)
+                        .down(PAD * 2.0)
+                        .h(ITEM_HEIGHT)
+                        .w(canvas_kid_area.w())
+                        .color(ui::color::LIGHT_CHARCOAL)
+                        .set(ids.source_editor_selected_soundscape_movement_tracked_directional, ui)
+                    {
+                        tracked.directional = new_directional;
+                        let generative = Generative::Tracked(tracked.clone());
+                        update_generative_movement(channels, sources, id, generative);
+                    }
+                },
             }
         },
 
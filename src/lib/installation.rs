@@ -4,6 +4,8 @@
 //! hard-coded and rather identified via dynamically generated unique IDs. Otherwise, most
 //! of the logic should remain the same.
 
+use metres::Metres;
+use nannou::math::Point2;
 use serde::{Deserialize, Deserializer};
 use slug::slugify;
 use utils::Range;
@@ -75,6 +77,12 @@ pub struct Installation {
     /// Constraints related to the soundscape.
     #[serde(default)]
     pub soundscape: Soundscape,
+    /// The outline of the installation's area on the floorplan.
+    ///
+    /// While fewer than three vertices are given, the bounding box of the installation's assigned
+    /// speakers is used instead.
+    #[serde(default)]
+    pub polygon: Vec<Point2<Metres>>,
 }
 
 impl Default for Installation {
@@ -82,7 +90,8 @@ impl Default for Installation {
         let name = default::name().into();
         let computers = Default::default();
         let soundscape = Default::default();
-        Installation { name, computers, soundscape }
+        let polygon = Default::default();
+        Installation { name, computers, soundscape, polygon }
     }
 }
 
@@ -91,12 +100,87 @@ impl Default for Installation {
 pub struct Soundscape {
     #[serde(default = "default::simultaneous_sounds")]
     pub simultaneous_sounds: Range<usize>,
+    /// How the soundscape responds to the number of visitors reported within the installation.
+    #[serde(default)]
+    pub occupancy: Occupancy,
+}
+
+/// How an installation's soundscape responds to its occupancy.
+///
+/// Occupancy is reported as a number of visitors via OSC and normalised by the `capacity`. The
+/// responses only take effect once the installation's occupancy has been reported.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Occupancy {
+    /// The number of visitors at which the installation is considered full.
+    #[serde(default = "default::capacity")]
+    pub capacity: f64,
+    /// Scales the target number of simultaneous sounds from the minimum of the range (at `0.0`)
+    /// to the maximum (at `1.0`).
+    #[serde(default = "default::response")]
+    pub density: Response,
+    /// The gain applied to the speakers assigned to the installation.
+    #[serde(default = "default::response")]
+    pub volume: Response,
+}
+
+/// A curve mapping the normalised occupancy of an installation to some value.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Response {
+    /// The value while the installation is empty.
+    pub empty: f64,
+    /// The value while the installation is at or over capacity.
+    pub full: f64,
+    /// The exponent applied to the normalised occupancy before interpolating between `empty` and
+    /// `full`. Values below `1.0` respond more strongly to the first visitors.
+    pub curve: f64,
 }
 
 impl Default for Soundscape {
     fn default() -> Self {
         let simultaneous_sounds = default::SIMULTANEOUS_SOUNDS;
-        Soundscape { simultaneous_sounds }
+        let occupancy = Default::default();
+        Soundscape { simultaneous_sounds, occupancy }
+    }
+}
+
+impl Default for Occupancy {
+    fn default() -> Self {
+        let capacity = default::CAPACITY;
+        let density = default::RESPONSE;
+        let volume = default::RESPONSE;
+        Occupancy { capacity, density, volume }
+    }
+}
+
+impl Occupancy {
+    /// The given number of visitors normalised by the capacity to the range `[0.0, 1.0]`.
+    pub fn normalise(&self, visitors: f64) -> f64 {
+        if self.capacity <= 0.0 {
+            return 1.0;
+        }
+        (visitors / self.capacity).max(0.0).min(1.0)
+    }
+
+    /// Narrow the given range of simultaneous sounds for the given normalised occupancy.
+    pub fn simultaneous_sounds(&self, range: Range<usize>, occupancy: f64) -> Range<usize> {
+        let density = self.density.value(occupancy).max(0.0).min(1.0);
+        let diff = range.max.saturating_sub(range.min);
+        let max = range.min + (diff as f64 * density).round() as usize;
+        Range { min: range.min, max }
+    }
+
+    /// The gain applied to the installation's speakers for the given normalised occupancy.
+    pub fn gain(&self, occupancy: f64) -> f32 {
+        self.volume.value(occupancy).max(0.0) as f32
+    }
+}
+
+impl Response {
+    /// The value of the curve at the given normalised occupancy.
+    pub fn value(&self, occupancy: f64) -> f64 {
+        let occupancy = occupancy.max(0.0).min(1.0);
+        let t = occupancy.powf(self.curve.max(0.0));
+        self.empty + (self.full - self.empty) * t
     }
 }
 
@@ -124,9 +208,12 @@ pub fn beyond_perception_default_num_computers(name: &str) -> Option<usize> {
 
 /// Default soundscape constraints.
 pub mod default {
+    use super::Response;
     use utils::Range;
 
     pub const SIMULTANEOUS_SOUNDS: Range<usize> = Range { min: 1, max: 8 };
+    pub const CAPACITY: f64 = 20.0;
+    pub const RESPONSE: Response = Response { empty: 1.0, full: 1.0, curve: 1.0 };
 
     pub fn name() -> &'static str {
         "<unnamed>"
@@ -139,6 +226,14 @@ pub mod default {
     pub fn simultaneous_sounds() -> Range<usize> {
         SIMULTANEOUS_SOUNDS
     }
+
+    pub fn capacity() -> f64 {
+        CAPACITY
+    }
+
+    pub fn response() -> Response {
+        RESPONSE
+    }
 }
 
 /// State related to the computers available to an installation.
@@ -162,3 +257,28 @@ pub mod computer {
     /// A map from all computer Ids to their addresses.
     pub type Addresses = FxHashMap<Id, Address>;
 }
+
+#[test]
+fn test_occupancy_response() {
+    let occupancy = Occupancy {
+        capacity: 10.0,
+        density: Response { empty: 0.0, full: 1.0, curve: 1.0 },
+        volume: Response { empty: 0.5, full: 1.0, curve: 0.5 },
+    };
+    let range = Range { min: 2, max: 10 };
+
+    // An empty room plays sparse and quiet.
+    assert_eq!(occupancy.normalise(0.0), 0.0);
+    assert_eq!(occupancy.simultaneous_sounds(range, 0.0), Range { min: 2, max: 2 });
+    assert_eq!(occupancy.gain(0.0), 0.5);
+
+    // A room at or over capacity plays the full range at full volume.
+    assert_eq!(occupancy.normalise(25.0), 1.0);
+    assert_eq!(occupancy.simultaneous_sounds(range, 1.0), range);
+    assert_eq!(occupancy.gain(1.0), 1.0);
+
+    // The curve shapes the response in between.
+    let quarter = occupancy.normalise(2.5);
+    assert_eq!(occupancy.simultaneous_sounds(range, quarter), Range { min: 2, max: 4 });
+    assert_eq!(occupancy.gain(quarter), 0.75);
+}
@@ -1,6 +1,7 @@
 // Extend the macro recursion limit to allow for many GUI widget IDs.
 #![recursion_limit = "256"]
 
+extern crate chrono; // local timezone
 #[macro_use]
 extern crate conrod;
 #[macro_use]
@@ -42,6 +43,7 @@ mod master;
 mod metres;
 mod project;
 mod osc;
+mod scene;
 mod soundscape;
 mod utils;
 
@@ -68,6 +70,12 @@ where
     assets.as_ref().join("config.json")
 }
 
+// The path to a soundscape event log to replay, given via `--replay <path>`.
+fn replay_path() -> Option<PathBuf> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay").skip(1);
+    args.next().map(PathBuf::from)
+}
+
 // Initialise the state of the application.
 fn model(app: &App) -> Model {
     // If on macos, set the loop to wait mode.
@@ -104,8 +112,22 @@ fn model(app: &App) -> Model {
     let (audio_monitor, audio_monitor_tx, audio_monitor_rx) = gui::monitor::spawn(app_proxy)
         .expect("failed to spawn audio_monitor thread");
 
-    // Spawn the thread used for reading wavs.
-    let wav_reader = audio::source::wav::reader::spawn();
+    // Spawn the thread used for reading wavs, playing short wavs from memory.
+    let wav_cache = audio::source::wav::cache::Cache::new(
+        config.wav_cache_max_file_bytes,
+        config.wav_cache_budget_bytes,
+    );
+    let wav_reader = audio::source::wav::reader::spawn(wav_cache);
+
+    // Spawn the thread used for analysing the loudness of WAV sources.
+    let loudness = audio::loudness::spawn();
+
+    // Spawn the thread used for generating the waveform overviews displayed in the source editor.
+    let waveform = audio::waveform::spawn();
+
+    // Spawn the thread used for watching the audio directory for added, changed or removed WAVs.
+    let audio_directory = project::audio_directory(&assets);
+    let wav_watcher = audio::source::wav::watcher::spawn(audio_directory, app.create_proxy());
 
     // A channel for sending and receiving on the soundscape thread.
     let (soundscape_tx, soundscape_rx) = mpsc::channel();
@@ -164,8 +186,21 @@ fn model(app: &App) -> Model {
         audio_input_stream.clone(),
         audio_output_stream.clone(),
         sound_id_gen.clone(),
+        soundscape::event_log::directory(&assets),
     );
 
+    // Replay a soundscape event log in place of the soundscape's usual choices if requested.
+    if let Some(path) = replay_path() {
+        match soundscape::event_log::read(&path) {
+            Ok(records) => {
+                soundscape
+                    .send(move |soundscape| soundscape.replay(records))
+                    .expect("failed to send event log replay to soundscape thread");
+            },
+            Err(err) => eprintln!("failed to read event log \"{}\": {}", path.display(), err),
+        }
+    }
+
     // Create a window.
     let window = app.new_window()
         .with_title("Audio Server")
@@ -184,6 +219,9 @@ fn model(app: &App) -> Model {
         control_rx,
         soundscape.clone(),
         wav_reader.clone(),
+        loudness,
+        wav_watcher,
+        waveform,
         audio_input_stream.clone(),
         audio_output_stream.clone(),
         audio_monitor_rx,
@@ -233,7 +271,7 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
 // Re-join with spawned threads on application exit.
 fn exit(app: &App, model: Model) {
     let Model {
-        gui,
+        mut gui,
         mut config,
         soundscape,
         audio_monitor,
@@ -241,6 +279,11 @@ fn exit(app: &App, model: Model) {
         ..
     } = model;
 
+    // Stop watching the audio directory.
+    if let Some(wav_watcher_thread) = gui.exit_wav_watcher() {
+        wav_watcher_thread.join().expect("failed to join the wav_watcher thread when exiting");
+    }
+
     // Update whether or not cpu saving mode should be enabled when re-opening.
     config.cpu_saving_mode = gui.cpu_saving_mode;
 
@@ -1,4 +1,5 @@
 use audio;
+use audio::loudness;
 use time_calc::Ms;
 
 /// Master state of the project.
@@ -13,6 +14,9 @@ pub struct Master {
     /// The rolloff decibel amount, used to attenuate speaker gains over distances.
     #[serde(default = "default_dbap_rolloff_db")]
     pub dbap_rolloff_db: f64,
+    /// The loudness toward which normalised sources are adjusted.
+    #[serde(default = "default_loudness_target_lufs")]
+    pub loudness_target_lufs: f32,
 }
 
 impl Default for Master {
@@ -20,7 +24,8 @@ impl Default for Master {
         let volume = default_master_volume();
         let realtime_source_latency = default_realtime_source_latency();
         let dbap_rolloff_db = default_dbap_rolloff_db();
-        Master { volume, realtime_source_latency, dbap_rolloff_db }
+        let loudness_target_lufs = default_loudness_target_lufs();
+        Master { volume, realtime_source_latency, dbap_rolloff_db, loudness_target_lufs }
     }
 }
 
@@ -35,3 +40,7 @@ fn default_realtime_source_latency() -> Ms {
 fn default_dbap_rolloff_db() -> f64 {
     audio::DEFAULT_DBAP_ROLLOFF_DB
 }
+
+fn default_loudness_target_lufs() -> f32 {
+    loudness::DEFAULT_TARGET_LUFS
+}
@@ -1,12 +1,26 @@
+use audio;
+use metres::Metres;
+use nannou::math::Point2;
 use nannou::osc;
-use nannou::osc::Type::Float;
+use nannou::osc::Type::{Float, Int};
 use std;
 use std::net::SocketAddr;
 use std::sync::mpsc;
 
 const BEYOND_PERCEPTION_ADDR: &'static str = "/bp";
 const SOURCE_VOLUME_ADDR: &'static str = "/source_volume";
+const SOURCE_EFFECT_ADDR: &'static str = "/source_effect";
+const TAG_VOLUME_ADDR: &'static str = "/tag_volume";
+const TAG_MUTE_ADDR: &'static str = "/tag_mute";
+const GROUP_VOLUME_ADDR: &'static str = "/group_volume";
+const GROUP_MUTE_ADDR: &'static str = "/group_mute";
+const GROUP_SOLO_ADDR: &'static str = "/group_solo";
+const SCENE_ADDR: &'static str = "/scene";
 const MASTER_VOLUME_ADDR: &'static str = "/master_volume";
+const TRACKED_ADDR: &'static str = "/tracked";
+const TRACKED_LOST_ADDR: &'static str = "/tracked_lost";
+const CHAIN_ADDR: &'static str = "/chain";
+const OCCUPANCY_ADDR: &'static str = "/occupancy";
 const PLAY_SOUNDSCAPE: &'static str = "/play_soundscape";
 const PAUSE_SOUNDSCAPE: &'static str = "/pause_soundscape";
 
@@ -23,7 +37,17 @@ pub struct Log {
 #[derive(Clone, Debug)]
 pub enum Control {
     SourceVolume(SourceVolume),
+    SourceEffect(SourceEffect),
+    TagVolume(TagVolume),
+    TagMute(TagMute),
+    GroupVolume(GroupVolume),
+    GroupMute(GroupMute),
+    GroupSolo(GroupSolo),
+    RecallScene(RecallScene),
     MasterVolume(MasterVolume),
+    TrackedPoint(TrackedPoint),
+    TriggerChain(TriggerChain),
+    Occupancy(Occupancy),
     PauseSoundscape,
     PlaySoundscape,
 }
@@ -49,6 +73,155 @@ pub struct SourceVolume {
     pub volume: f32,
 }
 
+/// An OSC input message that was parsed as a parameter value for one of a source's effects.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/source_effect/<source_name>"
+/// - Arguments: `Int`, `String`, `Float` where `Int` is the index of the effect within the
+///   source's effects chain, `String` is the name of the parameter and `Float` is the value.
+#[derive(Clone, Debug)]
+pub struct SourceEffect {
+    /// The name of the source to which this will be applied.
+    ///
+    /// As with `SourceVolume`, this will be applied to the first source whose name matches.
+    pub name: String,
+    /// The index of the effect within the source's effects chain.
+    pub effect: usize,
+    /// The name of the effect parameter, e.g. "cutoff_hz".
+    pub param: String,
+    /// The value that will be assigned to the parameter.
+    pub value: f32,
+}
+
+/// An OSC input message that was parsed as the volume for all sources with a given tag.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/tag_volume/<tag>"
+/// - Arguments: `Float` where `Float` is the volume.
+#[derive(Clone, Debug)]
+pub struct TagVolume {
+    /// The tag of the sources to which this will be applied.
+    pub tag: String,
+    /// The value that will be assigned to each matching `audio::Source`'s `volume` field.
+    pub volume: f32,
+}
+
+/// An OSC input message that was parsed as muting or unmuting all sources with a given tag.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/tag_mute/<tag>"
+/// - Arguments: `Int` or `Float` where a non-zero value mutes and zero unmutes.
+#[derive(Clone, Debug)]
+pub struct TagMute {
+    /// The tag of the sources to which this will be applied.
+    pub tag: String,
+    /// The value that will be assigned to each matching `audio::Source`'s `muted` field.
+    pub muted: bool,
+}
+
+/// An OSC input message that was parsed as the volume for a soundscape group.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/group_volume/<group_name>"
+/// - Arguments: `Float` where `Float` is the volume.
+#[derive(Clone, Debug)]
+pub struct GroupVolume {
+    /// The slugified name of the group to which this will be applied.
+    pub slug: String,
+    /// The value that will be assigned to the `soundscape::Group`'s `volume` field.
+    pub volume: f32,
+}
+
+/// An OSC input message that was parsed as muting or unmuting a soundscape group.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/group_mute/<group_name>"
+/// - Arguments: `Int` or `Float` where a non-zero value mutes and zero unmutes.
+#[derive(Clone, Debug)]
+pub struct GroupMute {
+    /// The slugified name of the group to which this will be applied.
+    pub slug: String,
+    /// The value that will be assigned to the `soundscape::Group`'s `muted` field.
+    pub muted: bool,
+}
+
+/// An OSC input message that was parsed as soloing or unsoloing a soundscape group.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/group_solo/<group_name>"
+/// - Arguments: `Int` or `Float` where a non-zero value solos and zero unsolos.
+#[derive(Clone, Debug)]
+pub struct GroupSolo {
+    /// The slugified name of the group to which this will be applied.
+    pub slug: String,
+    /// The value that will be assigned to the `soundscape::Group`'s `soloed` field.
+    pub soloed: bool,
+}
+
+/// An OSC input message that was parsed as recalling the scene with the given name.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/scene/<scene_name>"
+/// - Arguments: Optionally a `Float` or `Int` specifying the crossfade duration in seconds.
+#[derive(Clone, Debug)]
+pub struct RecallScene {
+    /// The slugified name of the scene to recall.
+    pub slug: String,
+    /// The crossfade duration in seconds. If `None`, the scene's own fade duration is used.
+    pub fade_secs: Option<f32>,
+}
+
+/// An OSC input message that was parsed as the location of a named point tracked by an external
+/// system, e.g. a visitor tracking camera.
+///
+/// Expects one of the following OSC messages:
+///
+/// - Address: "/bp/tracked/<point_name>"
+/// - Arguments: `Float`, `Float` where the `Float`s are the x and y location in exhibition metres.
+///
+/// - Address: "/bp/tracked_lost/<point_name>"
+/// - Arguments: None.
+#[derive(Clone, Debug)]
+pub struct TrackedPoint {
+    /// The name of the tracked point.
+    pub name: String,
+    /// The location of the point, or `None` if the tracking system has lost the point.
+    pub point: Option<Point2<Metres>>,
+}
+
+/// An OSC input message that was parsed as triggering the chain with the given name.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/chain/<chain_name>"
+/// - Arguments: None.
+#[derive(Clone, Debug)]
+pub struct TriggerChain {
+    /// The slugified name of the chain to trigger.
+    pub slug: String,
+}
+
+/// An OSC input message that was parsed as the number of visitors within an installation.
+///
+/// Expects the following OSC message:
+///
+/// - Address: "/bp/occupancy/<installation_name>"
+/// - Arguments: `Float` or `Int` where the number is the count of visitors present.
+#[derive(Clone, Debug)]
+pub struct Occupancy {
+    /// The slugified name of the installation.
+    pub slug: String,
+    /// The number of visitors within the installation.
+    pub visitors: f32,
+}
+
 impl From<MasterVolume> for Control {
     fn from(mv: MasterVolume) -> Self {
         Control::MasterVolume(mv)
@@ -61,6 +234,66 @@ impl From<SourceVolume> for Control {
     }
 }
 
+impl From<SourceEffect> for Control {
+    fn from(se: SourceEffect) -> Self {
+        Control::SourceEffect(se)
+    }
+}
+
+impl From<TagVolume> for Control {
+    fn from(tv: TagVolume) -> Self {
+        Control::TagVolume(tv)
+    }
+}
+
+impl From<TagMute> for Control {
+    fn from(tm: TagMute) -> Self {
+        Control::TagMute(tm)
+    }
+}
+
+impl From<GroupVolume> for Control {
+    fn from(gv: GroupVolume) -> Self {
+        Control::GroupVolume(gv)
+    }
+}
+
+impl From<GroupMute> for Control {
+    fn from(gm: GroupMute) -> Self {
+        Control::GroupMute(gm)
+    }
+}
+
+impl From<GroupSolo> for Control {
+    fn from(gs: GroupSolo) -> Self {
+        Control::GroupSolo(gs)
+    }
+}
+
+impl From<RecallScene> for Control {
+    fn from(rs: RecallScene) -> Self {
+        Control::RecallScene(rs)
+    }
+}
+
+impl From<TrackedPoint> for Control {
+    fn from(tp: TrackedPoint) -> Self {
+        Control::TrackedPoint(tp)
+    }
+}
+
+impl From<TriggerChain> for Control {
+    fn from(tc: TriggerChain) -> Self {
+        Control::TriggerChain(tc)
+    }
+}
+
+impl From<Occupancy> for Control {
+    fn from(o: Occupancy) -> Self {
+        Control::Occupancy(o)
+    }
+}
+
 // Finds the "/bp" string and returns the remainder if any.
 fn parse_bp(s: &str) -> Option<&str> {
     if s.starts_with(BEYOND_PERCEPTION_ADDR) {
@@ -84,6 +317,28 @@ fn parse_source_volume(s: &str) -> Option<&str> {
     }
 }
 
+// Finds the given address string and returns the name of the source, group or scene that follows
+// it.
+//
+// The address must be followed by a `/` so that e.g. "/tracked_lost/a" does not match "/tracked".
+fn parse_name_addr<'a>(s: &'a str, addr: &str) -> Option<&'a str> {
+    if s.starts_with(addr) && s[addr.len()..].starts_with('/') {
+        let slice_start = addr.len() + "/".len();
+        if slice_start < s.len() {
+            Some(&s[slice_start..])
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+// Finds the given address string and returns the normalised tag that follows it.
+fn parse_tag_addr(s: &str, addr: &str) -> Option<String> {
+    parse_name_addr(s, addr).and_then(audio::source::tag::normalise)
+}
+
 // Finds the "/master_volume" string. Returns `true` if found.
 fn parse_master_volume(s: &str) -> bool {
     s == MASTER_VOLUME_ADDR
@@ -120,6 +375,117 @@ impl Control {
                     _ => (),
                 }
 
+                match (parse_name_addr(s, SOURCE_EFFECT_ADDR), msg.args.as_ref().map(|args| &args[..])) {
+                    (Some(name), Some(&[Int(effect), osc::Type::String(ref param), Float(value)]))
+                        if effect >= 0 =>
+                    {
+                        let name = name.into();
+                        let effect = effect as usize;
+                        let param = param.clone();
+                        let source_effect = SourceEffect { name, effect, param, value };
+                        return Some(source_effect.into())
+                    }
+                    _ => (),
+                }
+
+                let first_arg = msg.args.as_ref().and_then(|args| args.get(0));
+
+                match (parse_tag_addr(s, TAG_VOLUME_ADDR), first_arg) {
+                    (Some(tag), Some(&Float(volume))) => {
+                        let tag_volume = TagVolume { tag, volume };
+                        return Some(tag_volume.into())
+                    }
+                    _ => (),
+                }
+
+                match (parse_tag_addr(s, TAG_MUTE_ADDR), first_arg) {
+                    (Some(tag), Some(&Int(i))) => {
+                        let tag_mute = TagMute { tag, muted: i != 0 };
+                        return Some(tag_mute.into())
+                    }
+                    (Some(tag), Some(&Float(f))) => {
+                        let tag_mute = TagMute { tag, muted: f != 0.0 };
+                        return Some(tag_mute.into())
+                    }
+                    _ => (),
+                }
+
+                match (parse_name_addr(s, GROUP_VOLUME_ADDR), first_arg) {
+                    (Some(slug), Some(&Float(volume))) => {
+                        let slug = slug.into();
+                        let group_volume = GroupVolume { slug, volume };
+                        return Some(group_volume.into())
+                    }
+                    _ => (),
+                }
+
+                match (parse_name_addr(s, GROUP_MUTE_ADDR), first_arg) {
+                    (Some(slug), Some(&Int(i))) => {
+                        let group_mute = GroupMute { slug: slug.into(), muted: i != 0 };
+                        return Some(group_mute.into())
+                    }
+                    (Some(slug), Some(&Float(f))) => {
+                        let group_mute = GroupMute { slug: slug.into(), muted: f != 0.0 };
+                        return Some(group_mute.into())
+                    }
+                    _ => (),
+                }
+
+                match (parse_name_addr(s, GROUP_SOLO_ADDR), first_arg) {
+                    (Some(slug), Some(&Int(i))) => {
+                        let group_solo = GroupSolo { slug: slug.into(), soloed: i != 0 };
+                        return Some(group_solo.into())
+                    }
+                    (Some(slug), Some(&Float(f))) => {
+                        let group_solo = GroupSolo { slug: slug.into(), soloed: f != 0.0 };
+                        return Some(group_solo.into())
+                    }
+                    _ => (),
+                }
+
+                if let Some(name) = parse_name_addr(s, SCENE_ADDR) {
+                    let slug = name.into();
+                    let fade_secs = match first_arg {
+                        Some(&Float(f)) => Some(f.max(0.0)),
+                        Some(&Int(i)) => Some(i.max(0) as f32),
+                        _ => None,
+                    };
+                    let recall_scene = RecallScene { slug, fade_secs };
+                    return Some(recall_scene.into());
+                }
+
+                if let Some(name) = parse_name_addr(s, TRACKED_LOST_ADDR) {
+                    let tracked_point = TrackedPoint { name: name.into(), point: None };
+                    return Some(tracked_point.into());
+                }
+
+                match (parse_name_addr(s, TRACKED_ADDR), msg.args.as_ref().map(|args| &args[..])) {
+                    (Some(name), Some(&[Float(x), Float(y)])) => {
+                        let point = Some(Point2 { x: Metres(x as f64), y: Metres(y as f64) });
+                        let tracked_point = TrackedPoint { name: name.into(), point };
+                        return Some(tracked_point.into());
+                    }
+                    _ => (),
+                }
+
+                if let Some(name) = parse_name_addr(s, CHAIN_ADDR) {
+                    let trigger_chain = TriggerChain { slug: name.into() };
+                    return Some(trigger_chain.into());
+                }
+
+                match (parse_name_addr(s, OCCUPANCY_ADDR), first_arg) {
+                    (Some(slug), Some(&Float(f))) => {
+                        let occupancy = Occupancy { slug: slug.into(), visitors: f.max(0.0) };
+                        return Some(occupancy.into());
+                    }
+                    (Some(slug), Some(&Int(i))) => {
+                        let visitors = i.max(0) as f32;
+                        let occupancy = Occupancy { slug: slug.into(), visitors };
+                        return Some(occupancy.into());
+                    }
+                    _ => (),
+                }
+
                 if parse_play_soundscape(s) {
                     return Some(Control::PlaySoundscape);
                 }
@@ -182,3 +548,13 @@ fn run(
         }
     }
 }
+
+#[test]
+fn test_parse_name_addr() {
+    assert_eq!(parse_name_addr("/source_effect/rain", SOURCE_EFFECT_ADDR), Some("rain"));
+    assert_eq!(parse_name_addr("/source_effectX/rain", SOURCE_EFFECT_ADDR), None);
+    assert_eq!(parse_name_addr("/source_effect/", SOURCE_EFFECT_ADDR), None);
+    assert_eq!(parse_name_addr("/source_effect", SOURCE_EFFECT_ADDR), None);
+    assert_eq!(parse_name_addr("/tracked_lost/a", TRACKED_ADDR), None);
+    assert_eq!(parse_name_addr("/tracked_lost/a", TRACKED_LOST_ADDR), Some("a"));
+}
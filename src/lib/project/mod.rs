@@ -19,14 +19,15 @@ use installation::{self, Installation};
 use master::Master;
 use nannou;
 use osc;
+use scene::{self, Scene};
 use slug::slugify;
 use soundscape;
 use std::{cmp, fs, io};
 use std::ffi::OsStr;
-use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use time_calc::Ms;
 use utils;
 use walkdir::WalkDir;
 
@@ -100,6 +101,18 @@ pub struct State {
     /// The state of the camera over the floorplan.
     #[serde(default)]
     pub camera: Camera,
+    /// The weekly schedule for soundscape playback, mutes and constraint presets.
+    #[serde(default)]
+    pub schedule: soundscape::Schedule,
+    /// Rules describing when sources may be played in relation to one another.
+    #[serde(default)]
+    pub rules: soundscape::Rules,
+    /// Graphs of links between sources for sequencing follow-on sounds.
+    #[serde(default)]
+    pub chains: soundscape::Chains,
+    /// Named snapshots of soundscape parameters that may be recalled with a crossfade.
+    #[serde(default)]
+    pub scenes: Scenes,
 }
 
 /// A map of all installations within the exhibition to their soundscape constraints.
@@ -108,6 +121,9 @@ pub type Installations = FxHashMap<installation::Id, Installation>;
 /// A map of all soundscape groups within the exhibition for the project.
 pub type SoundscapeGroups = FxHashMap<soundscape::group::Id, SoundscapeGroup>;
 
+/// A map of all scenes captured for the project.
+pub type Scenes = FxHashMap<scene::Id, Scene>;
+
 /// A map of all speakers within the exhibition for the project.
 pub type Speakers = FxHashMap<audio::speaker::Id, Speaker>;
 
@@ -163,6 +179,10 @@ impl State {
         let speakers = Default::default();
         let sources = Default::default();
         let camera = Default::default();
+        let schedule = Default::default();
+        let rules = Default::default();
+        let chains = Default::default();
+        let scenes = Default::default();
         State {
             name,
             master,
@@ -171,6 +191,10 @@ impl State {
             speakers,
             sources,
             camera,
+            schedule,
+            rules,
+            chains,
+            scenes,
         }
     }
 
@@ -200,6 +224,37 @@ impl State {
         }
     }
 
+    /// Capture the current source levels, soundscape constraints and master settings as a scene.
+    pub fn capture_scene(&self, name: String) -> Scene {
+        let sources = self
+            .sources
+            .iter()
+            .map(|(&id, source)| {
+                let level = scene::Level { volume: source.volume, muted: source.muted };
+                (id, level)
+            })
+            .collect();
+        let groups = self
+            .soundscape_groups
+            .iter()
+            .map(|(&id, group)| {
+                let occurrence_rate = group.occurrence_rate;
+                let simultaneous_sounds = group.simultaneous_sounds;
+                (id, scene::GroupConstraints { occurrence_rate, simultaneous_sounds })
+            })
+            .collect();
+        let installations = self
+            .installations
+            .iter()
+            .map(|(&id, installation)| (id, installation.soundscape.simultaneous_sounds))
+            .collect();
+        let master = scene::MasterLevels {
+            volume: self.master.volume,
+            dbap_rolloff_db: self.master.dbap_rolloff_db,
+        };
+        let fade = scene::default::fade();
+        Scene { name, fade, sources, groups, installations, master }
+    }
 }
 
 impl Project {
@@ -224,8 +279,7 @@ impl Project {
         // Load any sources that have not yet been loaded //
         ////////////////////////////////////////////////////
 
-        let assets = assets.as_ref();
-        let audio_path = assets.join(AUDIO_DIRECTORY_STEM);
+        let audio_path = audio_directory(assets);
         state.auto_name_installations_if_all_unnamed();
         state.sources.remove_invalid_sources(&audio_path);
         state.sources.load_missing_sources(audio_path);
@@ -282,10 +336,12 @@ impl Project {
         for (&id, installation) in self.installations.iter() {
             // Soundscape.
             let clone = installation.soundscape.clone();
+            let polygon = installation.polygon.clone();
             channels
                 .soundscape
                 .send(move |soundscape| {
                     soundscape.insert_installation(id, clone);
+                    soundscape.set_installation_polygon(id, polygon);
                 })
                 .expect("failed to send loaded installation soundscape state");
 
@@ -341,8 +397,20 @@ impl Project {
                 .expect("failed to send speaker to soundscape thread");
         }
 
-        // Sources to the audio input and soundscape threads.
+        // Sources to the audio input, audio output and soundscape threads.
+        let loudness_target_lufs = self.master.loudness_target_lufs;
         for (&id, source) in self.sources.iter() {
+            if let audio::source::Kind::Wav(ref wav) = source.kind {
+                // Analyse the loudness of any WAVs that have not yet been analysed.
+                if wav.loudness.is_none() {
+                    channels.loudness.request(id, wav.path.clone());
+                }
+                let gain = wav.normalisation_gain(loudness_target_lufs);
+                channels
+                    .audio_output
+                    .send(move |audio| audio.set_source_gain(id, gain))
+                    .expect("failed to send source gain to audio output thread");
+            }
             if let audio::source::Kind::Realtime(ref realtime) = source.kind {
                 let clone = realtime.clone();
                 channels
@@ -352,6 +420,24 @@ impl Project {
                     })
                     .expect("failed to send source to audio input thread");
             }
+            if let audio::source::Kind::Network(ref network) = source.kind {
+                match audio::source::network::Receiver::spawn(network) {
+                    Ok(receiver) => {
+                        channels
+                            .audio_input
+                            .send(move |audio| {
+                                audio.network_sources.insert(id, receiver);
+                            })
+                            .expect("failed to send network source to audio input thread");
+                    },
+                    Err(err) => {
+                        eprintln!(
+                            "failed to receive network source \"{}\" on port {}: {}",
+                            source.name, network.port, err,
+                        );
+                    },
+                }
+            }
             if let Some(clone) = soundscape::Source::from_audio_source(&source) {
                 channels
                     .soundscape
@@ -362,7 +448,112 @@ impl Project {
             }
         }
 
+        // The schedule to the soundscape thread.
+        let schedule = self.schedule.clone();
+        channels
+            .soundscape
+            .send(move |soundscape| soundscape.set_schedule(schedule))
+            .expect("failed to send schedule to soundscape thread");
+
+        // The rules to the soundscape thread.
+        let rules = self.rules.clone();
+        channels
+            .soundscape
+            .send(move |soundscape| soundscape.set_rules(rules))
+            .expect("failed to send rules to soundscape thread");
+
+        // The chains to the soundscape thread.
+        let chains = self.chains.clone();
+        channels
+            .soundscape
+            .send(move |soundscape| soundscape.set_chains(chains))
+            .expect("failed to send chains to soundscape thread");
+    }
+
+    /// Recall the scene with the given `Id`, crossfading toward its levels over `fade`.
+    ///
+    /// The project state is updated to the scene's levels immediately while the soundscape thread
+    /// carries out the crossfade. Group and installation constraints are applied immediately as
+    /// they only affect which sounds are spawned next.
+    ///
+    /// Returns `false` if there is no scene for the given `Id`.
+    pub fn recall_scene(&mut self, id: &scene::Id, fade: Ms, channels: &gui::Channels) -> bool {
+        let scene = match self.scenes.get(id) {
+            None => return false,
+            Some(scene) => scene.clone(),
+        };
+
+        // Sources whose levels differ from those of the scene are crossfaded.
+        let mut sources = vec![];
+        for (&source_id, &to) in scene.sources.iter() {
+            let source = match self.sources.get_mut(&source_id) {
+                None => continue,
+                Some(source) => source,
+            };
+            let from = scene::Level { volume: source.volume, muted: source.muted };
+            if from == to {
+                continue;
+            }
+            source.volume = to.volume;
+            source.muted = to.muted;
+            sources.push(scene::SourceFade { id: source_id, from, to });
+        }
+
+        // Soundscape group constraints.
+        for (&group_id, constraints) in scene.groups.iter() {
+            let group = match self.soundscape_groups.get_mut(&group_id) {
+                None => continue,
+                Some(group) => group,
+            };
+            let scene::GroupConstraints { occurrence_rate, simultaneous_sounds } = *constraints;
+            group.occurrence_rate = occurrence_rate;
+            group.simultaneous_sounds = simultaneous_sounds;
+            channels
+                .soundscape
+                .send(move |soundscape| {
+                    soundscape.update_group(&group_id, |group| {
+                        group.occurrence_rate = occurrence_rate;
+                        group.simultaneous_sounds = simultaneous_sounds;
+                    });
+                })
+                .expect("failed to send scene group constraints to soundscape thread");
+        }
+
+        // Installation constraints.
+        for (&installation_id, &simultaneous_sounds) in scene.installations.iter() {
+            let installation = match self.installations.get_mut(&installation_id) {
+                None => continue,
+                Some(installation) => installation,
+            };
+            installation.soundscape.simultaneous_sounds = simultaneous_sounds;
+            channels
+                .soundscape
+                .send(move |soundscape| {
+                    soundscape.update_installation(&installation_id, |installation| {
+                        installation.simultaneous_sounds = simultaneous_sounds;
+                    });
+                })
+                .expect("failed to send scene installation constraints to soundscape thread");
+        }
+
+        // Master levels.
+        let master_from = scene::MasterLevels {
+            volume: self.master.volume,
+            dbap_rolloff_db: self.master.dbap_rolloff_db,
+        };
+        self.master.volume = scene.master.volume;
+        self.master.dbap_rolloff_db = scene.master.dbap_rolloff_db;
 
+        let crossfade = scene::Crossfade {
+            duration: fade,
+            sources,
+            master: (master_from, scene.master),
+        };
+        channels
+            .soundscape
+            .send(move |soundscape| soundscape.crossfade(crossfade))
+            .expect("failed to send scene crossfade to soundscape thread");
+        true
     }
 
     /// Create a new project with a unique, default name.
@@ -467,6 +658,48 @@ impl Project {
     }
 }
 
+impl Source {
+    /// Create a source with default parameters for the WAV at the given path.
+    ///
+    /// The source is named after the file name without the extension.
+    ///
+    /// Returns `None` if the WAV could not be loaded.
+    pub fn from_wav_path(path: PathBuf) -> Option<Self> {
+        // Set the name as the file name without the extension.
+        let name = match path.file_stem().and_then(OsStr::to_str) {
+            Some(name) => name.to_string(),
+            None => return None,
+        };
+        // Load the `Wav`.
+        let wav = match audio::source::Wav::from_path(path) {
+            Ok(w) => w,
+            Err(e) => {
+                eprintln!("Failed to load wav file {:?}: {}", name, e);
+                return None;
+            }
+        };
+        let kind = audio::source::Kind::Wav(wav);
+        let role = None;
+        let spread = audio::source::default::SPREAD;
+        let channel_radians = audio::source::default::CHANNEL_RADIANS;
+        let volume = audio::source::default::VOLUME;
+        let muted = bool::default();
+        let effects = vec![];
+        let tags = vec![];
+        let audio = audio::Source {
+            kind,
+            role,
+            spread,
+            channel_radians,
+            volume,
+            muted,
+            effects,
+            tags,
+        };
+        Some(Source { name, audio })
+    }
+}
+
 impl Sources {
     /// Find the next available source ID for the `Sources`.
     pub fn next_id(&self) -> audio::source::Id {
@@ -474,6 +707,17 @@ impl Sources {
         audio::source::Id(next_id)
     }
 
+    /// The ID of the WAV source with the given path, if there is one.
+    pub fn wav_id(&self, path: &Path) -> Option<audio::source::Id> {
+        self.map
+            .iter()
+            .find(|&(_, s)| match s.audio.kind {
+                audio::source::Kind::Wav(ref wav) => wav.path == path,
+                _ => false,
+            })
+            .map(|(&id, _)| id)
+    }
+
     /// Check for invalid WAV sources
     ///
     /// If there are any ".wav" files in `assets/audio` that have not yet been loaded into the
@@ -571,17 +815,10 @@ where
             if let Some(new_path) = new_path {
                 if new_path.exists() {
                     // Reload the WAV file to make sure we have up-to-date info.
-                    let mut new_wav = match audio::source::Wav::from_path(new_path.clone()) {
-                        Ok(wav) => wav,
-                        Err(err) => {
-                            eprintln!("Failed to load wav from path \"{}\": {}. It will be ignored.",
-                                      new_path.display(), err);
-                            continue;
-                        },
-                    };
-                    new_wav.should_loop = wav.should_loop;
-                    new_wav.playback = wav.playback;
-                    mem::swap(wav, &mut new_wav);
+                    if let Err(err) = wav.reload(new_path.clone()) {
+                        eprintln!("Failed to load wav from path \"{}\": {}. It will be ignored.",
+                                  new_path.display(), err);
+                    }
                     continue;
                 }
                 eprintln!("Could not find WAV source at \"{}\" or at \"{}\". It will be ignored.",
@@ -617,18 +854,8 @@ where
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
-            .filter_map(|e| {
-                let file_name = e.file_name();
-                let file_path = Path::new(&file_name);
-                let ext = file_path
-                    .extension()
-                    .and_then(OsStr::to_str)
-                    .map(str::to_ascii_lowercase);
-                match ext.as_ref().map(|e| &e[..]) {
-                    Some("wav") | Some("wave") => Some(e.path().to_path_buf()),
-                    _ => None,
-                }
-            });
+            .filter(|e| audio::source::wav::is_wav_path(e.path()))
+            .map(|e| e.path().to_path_buf());
 
         // Find the next available ID in case we find new sources.
         let mut next_id = sources.next_id();
@@ -646,34 +873,10 @@ where
                     _ => (),
                 }
             }
-            // Set the name as the file name without the extension.
-            let name = match path.file_stem().and_then(OsStr::to_str) {
-                Some(name) => name.to_string(),
+            let source = match Source::from_wav_path(path) {
                 None => continue,
+                Some(source) => source,
             };
-            // Load the `Wav`.
-            let wav = match audio::source::Wav::from_path(path) {
-                Ok(w) => w,
-                Err(e) => {
-                    eprintln!("Failed to load wav file {:?}: {}", name, e);
-                    continue;
-                }
-            };
-            let kind = audio::source::Kind::Wav(wav);
-            let role = None;
-            let spread = audio::source::default::SPREAD;
-            let channel_radians = audio::source::default::CHANNEL_RADIANS;
-            let volume = audio::source::default::VOLUME;
-            let muted = bool::default();
-            let audio = audio::Source {
-                kind,
-                role,
-                spread,
-                channel_radians,
-                volume,
-                muted,
-            };
-            let source = Source { name, audio };
             sources.map.insert(next_id, source);
             next_id = audio::source::Id(next_id.0 + 1);
         }
@@ -692,6 +895,12 @@ pub fn next_soundscape_group_id(groups: &SoundscapeGroups) -> soundscape::group:
     soundscape::group::Id(next_id)
 }
 
+/// Search for and return the next available scene ID.
+pub fn next_scene_id(scenes: &Scenes) -> scene::Id {
+    let next_id = scenes.keys().map(|id| id.0).fold(0, cmp::max) + 1;
+    scene::Id(next_id)
+}
+
 /// Given the map of speakers, produce the next available unique `Id`.
 pub fn next_speaker_id(speakers: &Speakers) -> audio::speaker::Id {
     let next_id = speakers.keys().map(|id| id.0).fold(0, cmp::max) + 1;
@@ -801,7 +1010,8 @@ pub fn default_beyond_perception_installations() -> Installations {
                 .collect();
             let soundscape = Default::default();
             let name = name.into();
-            let installation = Installation { name, computers, soundscape };
+            let polygon = Default::default();
+            let installation = Installation { name, computers, soundscape, polygon };
             (id, installation)
         })
         .collect()
@@ -812,6 +1022,14 @@ pub fn default_project_name() -> String {
     "My Project".into()
 }
 
+/// The path of the "assets/audio/" directory from which all WAV sources are loaded.
+pub fn audio_directory<P>(assets: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    assets.as_ref().join(AUDIO_DIRECTORY_STEM)
+}
+
 /// The path of the "assetes/projects/" directory.
 pub fn projects_directory<P>(assets: P) -> PathBuf
 where
@@ -0,0 +1,171 @@
+//! Named snapshots of soundscape parameters that may be recalled with a timed crossfade.
+//!
+//! A `Scene` captures the volume and mute of each source, the constraints of each soundscape
+//! group, the simultaneous sounds of each installation and the master settings. Scenes are
+//! captured and recalled via the `Project`, while the `Crossfade` between the previous and
+//! recalled volumes is carried out on the soundscape thread.
+
+use audio;
+use fxhash::FxHashMap;
+use installation;
+use soundscape::group;
+use time_calc::Ms;
+use utils::{lerp, Range};
+
+/// A unique identifier for a scene.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub struct Id(pub usize);
+
+/// A snapshot of soundscape parameters.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Scene {
+    /// A human-friendly name for the scene.
+    ///
+    /// The slugified name is used to recall the scene via OSC.
+    pub name: String,
+    /// The duration over which volumes are crossfaded when the scene is recalled.
+    #[serde(default = "default::fade")]
+    pub fade: Ms,
+    /// The volume and mute of each source.
+    #[serde(default)]
+    pub sources: FxHashMap<audio::source::Id, Level>,
+    /// The constraints of each soundscape group.
+    #[serde(default)]
+    pub groups: FxHashMap<group::Id, GroupConstraints>,
+    /// The simultaneous sounds of each installation.
+    #[serde(default)]
+    pub installations: FxHashMap<installation::Id, Range<usize>>,
+    /// The master settings.
+    pub master: MasterLevels,
+}
+
+/// The volume and mute of a source.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Level {
+    pub volume: f32,
+    pub muted: bool,
+}
+
+/// The constraints of a soundscape group captured by a scene.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct GroupConstraints {
+    pub occurrence_rate: Range<Ms>,
+    pub simultaneous_sounds: Range<usize>,
+}
+
+/// The master settings captured by a scene.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MasterLevels {
+    pub volume: f32,
+    pub dbap_rolloff_db: f64,
+}
+
+/// A crossfade of volumes from their current levels to those of a recalled scene.
+#[derive(Clone, Debug)]
+pub struct Crossfade {
+    /// The duration of the crossfade.
+    pub duration: Ms,
+    /// The sources whose levels change throughout the crossfade.
+    pub sources: Vec<SourceFade>,
+    /// The master settings at the beginning and end of the crossfade.
+    pub master: (MasterLevels, MasterLevels),
+}
+
+/// The levels of a single source at the beginning and end of a crossfade.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SourceFade {
+    pub id: audio::source::Id,
+    pub from: Level,
+    pub to: Level,
+}
+
+pub mod default {
+    use time_calc::Ms;
+    pub fn fade() -> Ms {
+        Ms(5_000.0)
+    }
+}
+
+impl Crossfade {
+    /// The progress through the crossfade in the range `0.0..=1.0` after the given duration.
+    pub fn progress(&self, elapsed: Ms) -> f32 {
+        if self.duration.ms() <= 0.0 {
+            return 1.0;
+        }
+        (elapsed.ms() / self.duration.ms()).min(1.0).max(0.0) as f32
+    }
+
+    /// The master settings at the given progress through the crossfade.
+    pub fn master_at(&self, progress: f32) -> MasterLevels {
+        let (ref from, ref to) = self.master;
+        MasterLevels {
+            volume: lerp(from.volume, to.volume, progress),
+            dbap_rolloff_db: lerp(from.dbap_rolloff_db as f32, to.dbap_rolloff_db as f32, progress)
+                as f64,
+        }
+    }
+
+    /// Begin this crossfade from the current levels of a crossfade that is already in progress.
+    ///
+    /// Sources that are only faded by the `current` crossfade continue toward their targets so
+    /// that no source is left between levels.
+    pub fn continue_from(&mut self, current: &Crossfade, progress: f32) {
+        for fade in current.sources.iter() {
+            let level = fade.level_at(progress);
+            match self.sources.iter_mut().find(|s| s.id == fade.id) {
+                Some(s) => s.from = level,
+                None => self.sources.push(SourceFade { from: level, ..*fade }),
+            }
+        }
+        self.master.0 = current.master_at(progress);
+    }
+}
+
+impl SourceFade {
+    /// The level of the source at the given progress through the crossfade.
+    ///
+    /// Muting and unmuting are faded via the volume so that sounds are never cut off abruptly.
+    pub fn level_at(&self, progress: f32) -> Level {
+        if progress >= 1.0 || (self.from.muted && self.to.muted) {
+            return self.to;
+        }
+        let from = if self.from.muted { 0.0 } else { self.from.volume };
+        let to = if self.to.muted { 0.0 } else { self.to.volume };
+        let volume = lerp(from, to, progress);
+        Level { volume, muted: false }
+    }
+}
+
+#[test]
+fn test_crossfade_levels() {
+    let master = MasterLevels { volume: 1.0, dbap_rolloff_db: 4.0 };
+    let fade_out = SourceFade {
+        id: audio::source::Id(0),
+        from: Level { volume: 0.8, muted: false },
+        to: Level { volume: 0.8, muted: true },
+    };
+    let mut crossfade = Crossfade {
+        duration: Ms(1_000.0),
+        sources: vec![fade_out],
+        master: (master, MasterLevels { volume: 0.5, ..master }),
+    };
+    assert_eq!(crossfade.progress(Ms(250.0)), 0.25);
+    assert_eq!(crossfade.progress(Ms(2_000.0)), 1.0);
+    assert_eq!(fade_out.level_at(0.5), Level { volume: 0.4, muted: false });
+    assert_eq!(fade_out.level_at(1.0), fade_out.to);
+    assert_eq!(crossfade.master_at(0.5).volume, 0.75);
+
+    // A second crossfade half way through continues the first's fade out from where it was.
+    let mut next = Crossfade {
+        duration: Ms(1_000.0),
+        sources: vec![],
+        master: (master, master),
+    };
+    next.continue_from(&crossfade, 0.5);
+    assert_eq!(next.sources[0].from, Level { volume: 0.4, muted: false });
+    assert_eq!(next.sources[0].to, fade_out.to);
+    assert_eq!(next.master.0.volume, 0.75);
+
+    crossfade.duration = Ms(0.0);
+    assert_eq!(crossfade.progress(Ms(0.0)), 1.0);
+}
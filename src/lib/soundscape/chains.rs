@@ -0,0 +1,191 @@
+//! Sequenced chains of sources for authoring narrative sequences within the soundscape.
+//!
+//! A `Chain` is a graph of `Link`s between sources. When a sound from a link's `from` source ends
+//! or reaches the link's progress, the soundscape thread spawns a sound from the link's `to`
+//! source after a random delay, e.g. "when the door creaks, play footsteps 1 to 3 seconds later".
+//!
+//! Links apply to every sound of their `from` source, whether it was spawned by the soundscape, by
+//! another link or by triggering the chain by hand or via OSC. All links from the same source with
+//! the same trigger describe a single choice: their probabilities are accumulated in order and at
+//! most one of them is followed, while the remaining probability ends the chain.
+
+use audio;
+use time_calc::Ms;
+use utils::Range;
+
+/// All chains declared for a project.
+pub type Chains = Vec<Chain>;
+
+/// A named graph of links between sources.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Chain {
+    /// A human-friendly name for the chain, used to trigger the chain over OSC.
+    #[serde(default)]
+    pub name: String,
+    /// The source played when the chain is triggered by hand or via OSC.
+    #[serde(default)]
+    pub start: Option<audio::source::Id>,
+    /// The links between sources that make up the chain.
+    #[serde(default)]
+    pub links: Vec<Link>,
+}
+
+/// An edge between two sources within a chain.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Link {
+    /// The source whose sounds trigger the link.
+    pub from: audio::source::Id,
+    /// The source from which a sound is spawned when the link is followed.
+    pub to: audio::source::Id,
+    /// The moment within a sound of the `from` source at which the link is evaluated.
+    #[serde(default)]
+    pub trigger: Trigger,
+    /// The probability with which the link is followed when triggered.
+    #[serde(default = "default::probability")]
+    pub probability: f64,
+    /// The range of delays between the trigger and the sound being spawned.
+    #[serde(default = "default::delay")]
+    pub delay: Range<Ms>,
+    /// Whether or not the new sound is fixed at the position of the sound that triggered it.
+    ///
+    /// Otherwise the new sound moves according to its source's movement.
+    #[serde(default)]
+    pub same_position: bool,
+}
+
+/// The moment within a sound at which a link is evaluated.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Trigger {
+    /// When the sound ends.
+    End,
+    /// When the sound reaches the given normalised progress through its playback duration.
+    Progress(f64),
+}
+
+pub mod default {
+    use time_calc::Ms;
+    use utils::Range;
+    pub const PROBABILITY: f64 = 1.0;
+    pub const DELAY: Range<Ms> = Range { min: Ms(0.0), max: Ms(0.0) };
+    pub const PROGRESS: f64 = 0.5;
+    pub fn probability() -> f64 {
+        PROBABILITY
+    }
+    pub fn delay() -> Range<Ms> {
+        DELAY
+    }
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Trigger::End
+    }
+}
+
+/// The distinct progress triggers of links from the given source crossed by a sound whose
+/// progress advanced from `previous` (exclusive) to `current` (inclusive).
+///
+/// `previous` is `None` upon the sound's first update, in which case the progress is considered
+/// to have advanced from before the start of the sound so that triggers at `0.0` are crossed.
+pub fn progress_triggers_crossed(
+    chains: &[Chain],
+    source: audio::source::Id,
+    previous: Option<f64>,
+    current: f64,
+) -> Vec<Trigger> {
+    let previous = previous.unwrap_or(::std::f64::NEG_INFINITY);
+    let mut triggers = vec![];
+    let links = chains.iter().flat_map(|chain| chain.links.iter());
+    for link in links.filter(|l| l.from == source) {
+        if let Trigger::Progress(progress) = link.trigger {
+            if previous < progress && progress <= current && !triggers.contains(&link.trigger) {
+                triggers.push(link.trigger);
+            }
+        }
+    }
+    triggers
+}
+
+/// Choose which link, if any, to follow from a sound of the given source for the given trigger.
+///
+/// `r` is a random value within the range `[0.0, 1.0)`. The probabilities of all matching links
+/// are accumulated in order and the first link whose accumulated probability exceeds `r` is
+/// chosen.
+pub fn choose<'a>(
+    chains: &'a [Chain],
+    source: audio::source::Id,
+    trigger: &Trigger,
+    r: f64,
+) -> Option<&'a Link> {
+    let mut accumulated = 0.0;
+    let links = chains.iter().flat_map(|chain| chain.links.iter());
+    for link in links.filter(|l| l.from == source && l.trigger == *trigger) {
+        accumulated += link.probability.max(0.0);
+        if r < accumulated {
+            return Some(link);
+        }
+    }
+    None
+}
+
+/// Find the chain with the given name.
+pub fn find<'a>(chains: &'a [Chain], name: &str) -> Option<&'a Chain> {
+    chains.iter().find(|chain| chain.name == name)
+}
+
+#[test]
+fn test_chain_choice() {
+    let (door, steps, voice, bell) = (0, 1, 2, 3);
+    let link = |from, to, trigger, probability| Link {
+        from: audio::source::Id(from),
+        to: audio::source::Id(to),
+        trigger,
+        probability,
+        delay: default::DELAY,
+        same_position: false,
+    };
+    let chains = vec![
+        Chain {
+            name: "Arrival".into(),
+            start: Some(audio::source::Id(door)),
+            links: vec![
+                link(door, steps, Trigger::End, 0.6),
+                link(door, voice, Trigger::End, 0.3),
+                link(door, bell, Trigger::Progress(0.5), 1.0),
+            ],
+        },
+        Chain {
+            name: "Departure".into(),
+            start: None,
+            links: vec![link(steps, door, Trigger::End, 1.0)],
+        },
+    ];
+    let to = |link: Option<&Link>| link.map(|l| l.to.0);
+    let door_id = audio::source::Id(door);
+
+    // Links from the same source and trigger are a single weighted choice.
+    assert_eq!(to(choose(&chains, door_id, &Trigger::End, 0.0)), Some(steps));
+    assert_eq!(to(choose(&chains, door_id, &Trigger::End, 0.59)), Some(steps));
+    assert_eq!(to(choose(&chains, door_id, &Trigger::End, 0.6)), Some(voice));
+    assert_eq!(to(choose(&chains, door_id, &Trigger::End, 0.95)), None);
+
+    // Links are found across all chains.
+    let steps_id = audio::source::Id(steps);
+    assert_eq!(to(choose(&chains, steps_id, &Trigger::End, 0.99)), Some(door));
+    assert_eq!(find(&chains, "Arrival").and_then(|c| c.start), Some(door_id));
+    assert!(find(&chains, "Unknown").is_none());
+
+    // Progress triggers fire once as the progress passes them.
+    let halfway = vec![Trigger::Progress(0.5)];
+    assert_eq!(progress_triggers_crossed(&chains, door_id, Some(0.4), 0.5), halfway);
+    assert!(progress_triggers_crossed(&chains, door_id, Some(0.5), 0.6).is_empty());
+    assert!(progress_triggers_crossed(&chains, steps_id, Some(0.0), 1.0).is_empty());
+
+    // Triggers at the very start of a sound fire upon its first update only.
+    let mut chains = chains;
+    chains[1].links.push(link(steps, voice, Trigger::Progress(0.0), 1.0));
+    let start = vec![Trigger::Progress(0.0)];
+    assert_eq!(progress_triggers_crossed(&chains, steps_id, None, 0.0), start);
+    assert_eq!(progress_triggers_crossed(&chains, steps_id, None, 0.1), start);
+    assert!(progress_triggers_crossed(&chains, steps_id, Some(0.0), 0.1).is_empty());
+}
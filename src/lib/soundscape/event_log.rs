@@ -0,0 +1,301 @@
+//! A persistent log of the sounds spawned and ended by the soundscape, for auditing and replay.
+//!
+//! The soundscape thread appends a `Record` for every sound it spawns and every sound that ends to
+//! a JSON Lines file, one JSON object per line. A new file is started for each day of the
+//! exhibition's local time, e.g. `soundscape-2018-06-21.jsonl`.
+//!
+//! Each spawn records the seed from which all of the sound's random parameters were generated. A
+//! `Replay` of the log uses these seeds to reproduce the same sequence of sounds in place of the
+//! soundscape's usual choices.
+
+use audio;
+use installation;
+use serde_json;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time;
+use time_calc::Ms;
+use utils::{self, Seed};
+use super::group;
+use super::schedule::{self, Date, LocalTime};
+
+/// A single line of the event log.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Record {
+    /// Milliseconds since the unix epoch at which the event occurred.
+    pub unix_ms: u64,
+    /// The total duration over which the soundscape had played when the event occurred.
+    pub playback: Ms,
+    /// The event itself.
+    pub event: Event,
+}
+
+/// An event within the lifetime of a soundscape sound.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Event {
+    Spawn(Spawn),
+    End(End),
+}
+
+/// A sound spawned by the soundscape.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Spawn {
+    /// The unique identifier of the sound.
+    pub sound: u64,
+    /// The source from which the sound was spawned.
+    pub source: audio::source::Id,
+    /// All soundscape groups containing the source at the time.
+    pub groups: Vec<group::Id>,
+    /// The installation for which the sound was spawned.
+    pub installation: installation::Id,
+    /// The initial location and orientation of the sound.
+    pub position: audio::sound::Position,
+    /// The duration over which the sound was to play.
+    pub duration: Ms,
+    /// The seed of the random number generator used to generate the sound's parameters.
+    pub seed: Seed,
+    /// Whether or not the sound was fixed at its initial position rather than moving according to
+    /// its source's movement, e.g. when following a chain link at the same position.
+    #[serde(default)]
+    pub fixed: bool,
+}
+
+/// A sound that has ended.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct End {
+    /// The unique identifier of the sound.
+    pub sound: u64,
+    /// The source from which the sound was spawned.
+    pub source: audio::source::Id,
+    /// The location and orientation of the sound as it ended.
+    pub position: audio::sound::Position,
+}
+
+/// The interval in milliseconds at which buffered records are written to the event log file.
+pub const FLUSH_INTERVAL_MS: u64 = 1_000;
+
+/// Appends records to the event log file for the current day.
+///
+/// Records are buffered and written to the file at most once per `FLUSH_INTERVAL_MS`, whenever the
+/// file changes and when the writer is dropped.
+pub struct Writer {
+    // The directory in which files are stored, or `None` if the writer is disabled.
+    directory: Option<PathBuf>,
+    // The local date of the currently open file along with the buffered file itself.
+    file: Option<(Date, io::BufWriter<fs::File>)>,
+    // The moment at which the buffered records were last written to the file.
+    last_flush: time::Instant,
+    // Whether or not the last attempt to write to the log failed.
+    failing: bool,
+}
+
+/// The sounds of an event log in the process of being replayed.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    // Each logged spawn along with its offset from the beginning of the replay.
+    spawns: VecDeque<(time::Duration, Spawn)>,
+    // The playback duration at which the replay began.
+    start: Option<time::Duration>,
+}
+
+/// The path of the "assets/logs/" directory in which event logs are stored.
+pub fn directory<P>(assets: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    assets.as_ref().join("logs")
+}
+
+/// The name of the event log file for the given local date.
+pub fn file_name(date: &Date) -> String {
+    format!("soundscape-{}.jsonl", date)
+}
+
+/// Read all records from the event log at the given path.
+pub fn read(path: &Path) -> Result<Vec<Record>, utils::FileError<serde_json::Error>> {
+    let file = fs::File::open(path)?;
+    let mut records = vec![];
+    for line in io::BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
+
+impl Writer {
+    /// A writer that stores its files within the given directory.
+    ///
+    /// No files are created until the first record is appended.
+    pub fn new(directory: PathBuf) -> Self {
+        Writer::with_directory(Some(directory))
+    }
+
+    /// A writer that discards all records, e.g. for simulated soundscapes.
+    pub fn disabled() -> Self {
+        Writer::with_directory(None)
+    }
+
+    fn with_directory(directory: Option<PathBuf>) -> Self {
+        let file = None;
+        let last_flush = time::Instant::now();
+        let failing = false;
+        Writer { directory, file, last_flush, failing }
+    }
+
+    /// Append the record to the buffer for its day within the exhibition's local time.
+    ///
+    /// A new file is started whenever the local date changes. If `utc_offset_minutes` is `None`,
+    /// the OS's local timezone at the time of the record is used.
+    ///
+    /// Only the first of a run of consecutive failures is returned so that a missing or full disk
+    /// is reported once rather than for every sound.
+    pub fn append(&mut self, record: &Record, utc_offset_minutes: Option<i32>) -> io::Result<()> {
+        let result = self.try_append(record, utc_offset_minutes);
+        self.report(result)
+    }
+
+    /// Write the buffered records to the file if `FLUSH_INTERVAL_MS` has passed since the last
+    /// write.
+    ///
+    /// Failures are reported in the same manner as for `append`.
+    pub fn update(&mut self, now: time::Instant) -> io::Result<()> {
+        if now.duration_since(self.last_flush) < time::Duration::from_millis(FLUSH_INTERVAL_MS) {
+            return Ok(());
+        }
+        self.last_flush = now;
+        let result = match self.file {
+            Some((_, ref mut file)) => file.flush(),
+            None => Ok(()),
+        };
+        self.report(result)
+    }
+
+    fn try_append(&mut self, record: &Record, utc_offset_minutes: Option<i32>) -> io::Result<()> {
+        let directory = match self.directory {
+            Some(ref directory) => directory,
+            None => return Ok(()),
+        };
+        let secs = (record.unix_ms / 1_000) as i64;
+        let utc_offset_minutes = schedule::utc_offset_minutes_at(secs, utc_offset_minutes);
+        let date = LocalTime::from_unix_secs(secs, utc_offset_minutes).date();
+        if self.file.as_ref().map(|&(d, _)| d != date).unwrap_or(true) {
+            // Finish writing the previous day's records before starting the new file.
+            if let Some((_, mut file)) = self.file.take() {
+                file.flush()?;
+            }
+            fs::create_dir_all(directory)?;
+            let path = directory.join(file_name(&date));
+            let file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+            self.file = Some((date, io::BufWriter::new(file)));
+        }
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let file = &mut self.file.as_mut().expect("no event log file open").1;
+        file.write_all(line.as_bytes())
+    }
+
+    // Only return an error if the previous attempt succeeded.
+    fn report(&mut self, result: io::Result<()>) -> io::Result<()> {
+        let was_failing = self.failing;
+        self.failing = result.is_err();
+        match result {
+            Err(_) if was_failing => Ok(()),
+            result => result,
+        }
+    }
+}
+
+impl Replay {
+    /// Prepare the spawns of the given records for replay.
+    ///
+    /// Spawns are replayed with the same spacing in playback time as when they were logged. If the
+    /// playback time goes backwards, e.g. because the server was restarted, the following records
+    /// continue from the preceding ones.
+    pub fn new(records: &[Record]) -> Self {
+        let mut spawns = VecDeque::new();
+        let mut offset = Ms(0.0);
+        let mut last_playback = None;
+        for record in records {
+            let delta = match last_playback {
+                None => Ms(0.0),
+                Some(last) if record.playback >= last => record.playback - last,
+                Some(_) => record.playback,
+            };
+            offset = offset + delta;
+            last_playback = Some(record.playback);
+            if let Event::Spawn(ref spawn) = record.event {
+                let offset = time::Duration::from_millis(offset.ms() as u64);
+                spawns.push_back((offset, spawn.clone()));
+            }
+        }
+        Replay { spawns, start: None }
+    }
+
+    /// Remove and return all spawns that are due by the given playback duration.
+    ///
+    /// The replay begins upon the first call.
+    pub fn due(&mut self, playback_duration: time::Duration) -> Vec<Spawn> {
+        let start = *self.start.get_or_insert(playback_duration);
+        let elapsed = playback_duration
+            .checked_sub(start)
+            .unwrap_or(time::Duration::from_secs(0));
+        let mut due = vec![];
+        while self.spawns.front().map(|&(offset, _)| offset <= elapsed) == Some(true) {
+            due.push(self.spawns.pop_front().unwrap().1);
+        }
+        due
+    }
+
+    /// Whether or not all spawns have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.spawns.is_empty()
+    }
+}
+
+#[test]
+fn test_replay_timing() {
+    use metres::Metres;
+    use nannou::math::Point2;
+
+    let spawn = |sound| Spawn {
+        sound,
+        source: audio::source::Id(0),
+        groups: vec![],
+        installation: installation::Id(0),
+        position: audio::sound::Position {
+            point: Point2 { x: Metres(0.0), y: Metres(0.0) },
+            radians: 0.0,
+        },
+        duration: Ms(1_000.0),
+        seed: [1, 2, 3, 4],
+        fixed: false,
+    };
+    let record = |playback, event| Record { unix_ms: 0, playback: Ms(playback), event };
+    let end = |sound| End { sound, source: audio::source::Id(0), position: spawn(sound).position };
+    let records = vec![
+        record(10_000.0, Event::Spawn(spawn(0))),
+        record(12_000.0, Event::Spawn(spawn(1))),
+        record(13_000.0, Event::End(end(0))),
+        // The server restarted, so playback begins again from zero.
+        record(500.0, Event::Spawn(spawn(2))),
+    ];
+    let mut replay = Replay::new(&records);
+    let ms = |ms| time::Duration::from_millis(ms);
+    let sounds = |spawns: Vec<Spawn>| spawns.iter().map(|s| s.sound).collect::<Vec<_>>();
+
+    // The replay begins from the first call, regardless of the logged playback time.
+    assert_eq!(sounds(replay.due(ms(60_000))), vec![0]);
+    assert_eq!(sounds(replay.due(ms(61_999))), Vec::<u64>::new());
+    assert_eq!(sounds(replay.due(ms(62_000))), vec![1]);
+    assert!(!replay.is_finished());
+
+    // The restarted session follows on from the end record.
+    assert_eq!(sounds(replay.due(ms(63_499))), Vec::<u64>::new());
+    assert_eq!(sounds(replay.due(ms(63_500))), vec![2]);
+    assert!(replay.is_finished());
+}
@@ -2,6 +2,8 @@
 //!
 //! Soundscape groups allow for describing rules/constraints for multiple sounds at once.
 
+use audio::source::tag;
+use fxhash::FxHashSet;
 use time_calc::Ms;
 use utils::Range;
 
@@ -18,6 +20,24 @@ pub struct Id(pub usize);
 pub struct Group {
     pub occurrence_rate: Range<Ms>,
     pub simultaneous_sounds: Range<usize>,
+    /// Sources whose tags match this query are members of the group in addition to those that
+    /// were assigned to the group by hand.
+    #[serde(default)]
+    pub tag_query: Option<tag::Query>,
+    /// An amplitude modulator applied to the sounds of all member sources.
+    ///
+    /// A sound's volume is multiplied by the volume of each group to which its source belongs.
+    #[serde(default = "default::volume")]
+    pub volume: f32,
+    /// Whether or not all sounds of member sources are muted.
+    #[serde(default)]
+    pub muted: bool,
+    /// Whether or not the group is soloed.
+    ///
+    /// While any group is soloed, sounds of soundscape sources that are not a member of a soloed
+    /// group are silenced.
+    #[serde(default)]
+    pub soloed: bool,
 }
 
 pub mod default {
@@ -28,15 +48,36 @@ pub mod default {
         max: Ms(HR_MS as _),
     };
     pub const SIMULTANEOUS_SOUNDS: Range<usize> = Range { min: 1, max: 10 };
+    pub const VOLUME: f32 = 1.0;
+
+    pub fn volume() -> f32 {
+        VOLUME
+    }
+}
+
+impl Group {
+    /// Whether or not a source with the given explicit group membership and tags belongs to this
+    /// group.
+    pub fn contains_source(&self, id: &Id, source_groups: &FxHashSet<Id>, tags: &[String]) -> bool {
+        source_groups.contains(id) || self.tag_query.as_ref().map_or(false, |q| q.matches(tags))
+    }
 }
 
 impl Default for Group {
     fn default() -> Self {
         let occurrence_rate = default::OCCURRENCE_RATE;
         let simultaneous_sounds = default::SIMULTANEOUS_SOUNDS;
+        let tag_query = None;
+        let volume = default::VOLUME;
+        let muted = false;
+        let soloed = false;
         Group {
             occurrence_rate,
             simultaneous_sounds,
+            tag_query,
+            volume,
+            muted,
+            soloed,
         }
     }
 }
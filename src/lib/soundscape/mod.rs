@@ -6,24 +6,44 @@ use mindtree_utils::noise_walk;
 use nannou;
 use nannou::prelude::*;
 use nannou::rand::{Rng, SeedableRng, XorShiftRng};
+use scene;
 use std::cmp;
 use std::ops;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::{atomic, mpsc, Arc, Mutex};
 use std::thread;
-use std::time;
+use std::time::{self, SystemTime, UNIX_EPOCH};
 use time_calc::Ms;
 use utils::{self, duration_to_secs, Range, Seed};
 
+pub use self::chains::Chains;
 pub use self::group::Group;
 pub use self::movement::Movement;
-use self::movement::BoundingRect;
+pub use self::rules::Rules;
+pub use self::schedule::Schedule;
+use self::movement::{BoundingRect, TrackedPoints};
+use self::schedule::LocalTime;
 
+pub mod chains;
+pub mod event_log;
 pub mod group;
 pub mod movement;
+pub mod rules;
+pub mod schedule;
+pub mod simulation;
 
 const TICK_RATE_MS: u64 = 16;
 
+/// The interval at which the schedule is re-evaluated.
+const SCHEDULE_INTERVAL_MS: u64 = 1_000;
+
+/// The duration after which a tracked point that has not been updated is considered lost.
+const TRACKED_POINT_LIFETIME_MS: u64 = 1_000;
+
+/// The duration over which changes in the reported occupancy of an installation are smoothed.
+const OCCUPANCY_SMOOTHING_MS: u64 = 5_000;
+
 type Installations = FxHashMap<installation::Id, installation::Soundscape>;
 type Groups = FxHashMap<group::Id, Group>;
 type Sources = FxHashMap<audio::source::Id, Source>;
@@ -31,13 +51,18 @@ type Speakers = FxHashMap<audio::speaker::Id, Speaker>;
 type GroupsLastUsed = FxHashMap<group::Id, time::Instant>;
 type SourcesLastUsed = FxHashMap<audio::source::Id, time::Instant>;
 type InstallationAreas = FxHashMap<installation::Id, movement::Area>;
+type InstallationPolygons = FxHashMap<installation::Id, Arc<[Point2<Metres>]>>;
 type InstallationSpeakers = FxHashMap<installation::Id, Vec<audio::speaker::Id>>;
 type ActiveSounds = FxHashMap<audio::sound::Id, ActiveSound>;
 type ActiveSoundPositions = FxHashMap<audio::sound::Id, ActiveSoundPosition>;
+type ActiveAgents = Vec<ActiveAgent>;
+type AgentNeighbours = Vec<movement::agent::Neighbour>;
+type PendingChainSounds = Vec<PendingChainSound>;
 type ActiveSoundsPerInstallation = FxHashMap<installation::Id, Vec<audio::sound::Id>>;
 type TargetSoundsPerInstallation = FxHashMap<installation::Id, usize>;
 type AvailableGroups = Vec<AvailableGroup>;
 type AvailableSources = Vec<AvailableSource>;
+type Occupancies = FxHashMap<installation::Id, Occupancy>;
 
 /// The kinds of messages received by the soundscape thread.
 pub enum Message {
@@ -56,7 +81,13 @@ pub enum Message {
 #[derive(Copy, Clone, Debug)]
 pub struct Tick {
     instant: time::Instant,
-    /// The time that accumulated since the last tick occurred only while playback was enabled.
+    /// Whether or not playback was enabled at the time of the tick.
+    ///
+    /// Ticks are sent while paused so that the schedule may still be evaluated.
+    playing: bool,
+    /// The wall-clock time of the tick, used to evaluate the schedule.
+    system_time: SystemTime,
+    /// The time that accumulated since the last tick occurred.
     since_last_tick: time::Duration,
     /// The total duration over which the soundscape has played.
     ///
@@ -79,6 +110,19 @@ pub struct Soundscape {
     thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     /// Whether or not the soundscape is currently playing.
     is_playing: Arc<AtomicBool>,
+    /// The sources withheld by the project's rules as of the last tick.
+    rule_violations: Arc<Mutex<rules::Violations>>,
+    /// The occupancy reported for each installation.
+    occupancies: Arc<Mutex<Occupancies>>,
+}
+
+/// The occupancy reported for a single installation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Occupancy {
+    /// The number of visitors most recently reported within the installation.
+    pub visitors: f64,
+    /// The occupancy normalised by the installation's capacity and smoothed over time.
+    pub level: f64,
 }
 
 /// Data related to a single speaker that is relevant to the soundscape.
@@ -91,6 +135,7 @@ pub struct Speaker {
 }
 
 /// Properties of an audio source that are relevant to the soundscape thread.
+#[derive(Clone)]
 pub struct Source {
     pub constraints: audio::source::Soundscape,
     pub kind: audio::source::Kind,
@@ -98,6 +143,8 @@ pub struct Source {
     pub channel_radians: f32,
     pub volume: f32,
     pub muted: bool,
+    pub effects: Vec<audio::effect::Effect>,
+    pub tags: Vec<String>,
     /// The time at which the source was last used to create a sound.
     pub last_sound_created: Option<time::Instant>,
 }
@@ -110,6 +157,14 @@ pub struct ActiveSound {
     pub movement: Movement,
     /// The handle associated with this sound.
     handle: audio::sound::Handle,
+    /// The duration over which the sound plays.
+    duration: Ms,
+    /// The soundscape playback duration at which the sound was spawned.
+    spawned_at: time::Duration,
+    /// The normalised progress through the sound's duration as of the last tick.
+    ///
+    /// `None` until the sound's first tick.
+    progress: Option<f64>,
 }
 
 // The current positioning of an active sound.
@@ -120,6 +175,28 @@ struct ActiveSoundPosition {
     position: audio::sound::Position,
 }
 
+// A sound that will be spawned once its delay has passed as the next step of a chain.
+struct PendingChainSound {
+    // The source from which the sound will be spawned.
+    source_id: audio::source::Id,
+    // The soundscape playback duration at which the sound should be spawned.
+    due: time::Duration,
+    // The position of the sound that triggered this one, if any.
+    origin: Option<audio::sound::Position>,
+    // Whether or not the sound should be fixed at the position of its origin.
+    same_position: bool,
+}
+
+// The state of an active sound with agent movement, as perceived by its potential neighbours.
+struct ActiveAgent {
+    // The active sound driven by the agent.
+    sound_id: audio::sound::Id,
+    // The source from which this active sound was produced.
+    source_id: audio::source::Id,
+    // The location and velocity of the agent.
+    neighbour: movement::agent::Neighbour,
+}
+
 /// The model containing all state running on the soundscape thread.
 pub struct Model {
     /// Tracks the current frame count updated via the audio output thread.
@@ -142,12 +219,55 @@ pub struct Model {
     sources: Sources,
     /// All speakers within the exhibition.
     speakers: Speakers,
+    /// The polygons drawn over the floorplan to outline the area of each installation, if any.
+    installation_polygons: InstallationPolygons,
     /// The moment at which each `Group` was last used to spawn a sound.
     groups_last_used: GroupsLastUsed,
     /// The moment at which each `Source` was last used to spawn a sound.
     sources_last_used: SourcesLastUsed,
     /// All sounds currently being played that were spawned by the soundscape thread.
     active_sounds: ActiveSounds,
+    /// Whether or not the soundscape is currently playing, shared with the `Soundscape` handle.
+    is_playing: Arc<AtomicBool>,
+    /// The weekly schedule of the project.
+    schedule: Schedule,
+    /// Rules describing when sources may be played in relation to one another.
+    rules: Rules,
+    /// Graphs of links between sources for sequencing follow-on sounds.
+    chains: Chains,
+    /// Sounds waiting for their delay to pass before being spawned as the next step of a chain.
+    pending_chain_sounds: PendingChainSounds,
+    /// The log to which every spawned and ended sound is appended.
+    event_log: event_log::Writer,
+    /// The event log being replayed in place of the soundscape's usual choices, if any.
+    replay: Option<event_log::Replay>,
+    /// The sources withheld by the rules as of the last tick, shared with the `Soundscape` handle.
+    rule_violations: Arc<Mutex<rules::Violations>>,
+    /// The combined actions of all schedule windows active as of the last evaluation.
+    schedule_overrides: schedule::Overrides,
+    /// The set of sources last sent to the audio output thread as muted by the schedule.
+    scheduled_mutes: FxHashSet<audio::source::Id>,
+    /// The moment at which the schedule was last evaluated.
+    ///
+    /// `None` if the schedule should be evaluated upon the next tick.
+    schedule_last_evaluated: Option<time::Instant>,
+    /// The instant of the most recently received tick.
+    ///
+    /// Crossfades are timed against this rather than the wall clock so that they are measured
+    /// only by the ticks that drive the soundscape.
+    last_tick_instant: time::Instant,
+    /// The crossfade toward the levels of the most recently recalled scene along with the tick
+    /// instant at which it began.
+    crossfade: Option<(time::Instant, scene::Crossfade)>,
+    /// Whether or not the group volumes, mutes and solos of each source must be re-sent to the
+    /// audio output thread due to a change in groups or sources.
+    group_levels_outdated: bool,
+    /// Points within the exhibition tracked by external systems, e.g. visitor tracking cameras.
+    tracked_points: TrackedPoints,
+    /// The occupancy reported for each installation, shared with the `Soundscape` handle.
+    occupancies: Arc<Mutex<Occupancies>>,
+    /// The gains last sent to the audio output thread for the speakers of each installation.
+    installation_gains: FxHashMap<installation::Id, f32>,
 
     // The following are intermediary buffers re-used between calculations.
     //
@@ -165,21 +285,40 @@ pub struct Model {
     active_sounds_per_installation: ActiveSoundsPerInstallation,
     /// Tracks the position of all active sounds.
     active_sound_positions: ActiveSoundPositions,
+    /// Tracks the location and velocity of all active sounds with agent movement.
+    active_agents: ActiveAgents,
+    /// A buffer to re-use when collecting the flocking neighbours of each agent.
+    neighbours: AgentNeighbours,
+    /// A buffer to re-use when collecting the groups of each agent's source.
+    flock_groups: Vec<group::Id>,
     /// A buffer to re-use when calculating currently available groups.
     available_groups: AvailableGroups,
     /// A buffer to re-use when calculating currently available sources.
     available_sources: AvailableSources,
+    /// The activity of each rule target, checked against the rules when choosing sources.
+    rule_activity: rules::Activity,
 
     // Communication channels.
 
-    /// A handle to the wav reader thread.
-    wav_reader: audio::source::wav::reader::Handle,
-    /// A handle for submitting new sounds to the input stream.
-    audio_input_stream: audio::input::Stream,
-    /// A handle for submitting new sounds to the output stream.
-    audio_output_stream: audio::output::Stream,
-    // A handle to the ticker thread.
-    _tick_thread: thread::JoinHandle<()>,
+    /// The audio streams through which sounds are played.
+    audio_streams: AudioStreams,
+    // A handle to the ticker thread, if any.
+    _tick_thread: Option<thread::JoinHandle<()>>,
+}
+
+/// The audio streams through which the soundscape's sounds are played.
+enum AudioStreams {
+    /// Sounds are sent to the audio input and output streams to be heard.
+    Live {
+        /// A handle to the wav reader thread.
+        wav_reader: audio::source::wav::reader::Handle,
+        /// A handle for submitting new sounds to the input stream.
+        input: audio::input::Stream,
+        /// A handle for submitting new sounds to the output stream.
+        output: audio::output::Stream,
+    },
+    /// Sounds are only simulated and never heard, e.g. while collecting statistics.
+    Simulated,
 }
 
 // Data related to the suitability of a group or source for selection of use within the soundscape.
@@ -227,10 +366,13 @@ struct AvailableSource {
     id: audio::source::Id,
     // Params that describe the suitability of the source for use with a sound.
     suitability: Suitability,
+    // The likelihood of choosing the source over other equally suitable sources.
+    weight: f64,
     // Ranges used to trigger playback.
     playback_duration: Range<Ms>,
     attack_duration: Range<Ms>,
     release_duration: Range<Ms>,
+    playback_rate: Range<f32>,
 }
 
 impl ActiveSound {
@@ -250,6 +392,17 @@ impl ActiveSound {
     }
 }
 
+impl Suitability {
+    // Whether or not a sound is due, i.e. it has never been played or its maximum occurrence
+    // interval has passed.
+    fn is_sound_due(&self) -> bool {
+        match self.timing {
+            None => true,
+            Some(ref timing) => timing.duration_until_sound_needed <= Ms(0.0),
+        }
+    }
+}
+
 impl Speaker {
     pub fn from_audio_speaker(s: &audio::Speaker) -> Self {
         Speaker {
@@ -273,6 +426,8 @@ impl Source {
         let channel_radians = source.channel_radians;
         let volume = source.volume;
         let muted = source.muted;
+        let effects = source.effects.clone();
+        let tags = source.tags.clone();
         let last_sound_created = None;
         Some(Source {
             constraints,
@@ -281,6 +436,8 @@ impl Source {
             channel_radians,
             volume,
             muted,
+            effects,
+            tags,
             last_sound_created,
         })
     }
@@ -293,6 +450,8 @@ impl Source {
         let channel_radians = self.channel_radians;
         let volume = self.volume;
         let muted = self.muted;
+        let effects = self.effects.clone();
+        let tags = self.tags.clone();
         audio::Source {
             kind,
             role,
@@ -300,6 +459,8 @@ impl Source {
             channel_radians,
             volume,
             muted,
+            effects,
+            tags,
         }
     }
 }
@@ -347,6 +508,16 @@ impl Soundscape {
             .map_err(|_| mpsc::SendError(()))
     }
 
+    /// The sources withheld by the project's rules as of the last tick.
+    pub fn rule_violations(&self) -> rules::Violations {
+        self.rule_violations.lock().unwrap().clone()
+    }
+
+    /// The occupancy last reported for the given installation, if any.
+    pub fn occupancy(&self, installation: &installation::Id) -> Option<Occupancy> {
+        self.occupancies.lock().unwrap().get(installation).cloned()
+    }
+
     /// Stops the soundscape thread and returns the raw handle to its thread.
     pub fn exit(self) -> Option<thread::JoinHandle<()>> {
         self.tx.send(Message::Exit).ok();
@@ -355,6 +526,99 @@ impl Soundscape {
 }
 
 impl Model {
+    // Create a model with no project-specific state.
+    fn new(
+        frame_count: Arc<AtomicUsize>,
+        seed: Seed,
+        is_playing: Arc<AtomicBool>,
+        rule_violations: Arc<Mutex<rules::Violations>>,
+        occupancies: Arc<Mutex<Occupancies>>,
+        event_log: event_log::Writer,
+        audio_streams: AudioStreams,
+        sound_id_gen: audio::sound::IdGenerator,
+        _tick_thread: Option<thread::JoinHandle<()>>,
+    ) -> Self {
+        let realtime_source_latency = audio::DEFAULT_REALTIME_SOURCE_LATENCY;
+        let playback_duration = time::Duration::from_secs(0);
+        let installations = Default::default();
+        let groups = Default::default();
+        let sources = Default::default();
+        let speakers = Default::default();
+        let installation_polygons = Default::default();
+        let active_sounds = Default::default();
+        let installation_speakers = Default::default();
+        let installation_areas = Default::default();
+        let groups_last_used = Default::default();
+        let sources_last_used = Default::default();
+        let target_sounds_per_installation = Default::default();
+        let active_sound_positions = Default::default();
+        let active_agents = Default::default();
+        let neighbours = Default::default();
+        let flock_groups = Default::default();
+        let active_sounds_per_installation = Default::default();
+        let available_groups = Default::default();
+        let available_sources = Default::default();
+        let schedule = Default::default();
+        let schedule_overrides = Default::default();
+        let scheduled_mutes = Default::default();
+        let schedule_last_evaluated = None;
+        let rules = Default::default();
+        let rule_activity = Default::default();
+        let chains = Default::default();
+        let pending_chain_sounds = Default::default();
+        let replay = None;
+        let last_tick_instant = time::Instant::now();
+        let crossfade = None;
+        let group_levels_outdated = true;
+        let tracked_points = Default::default();
+        let installation_gains = Default::default();
+        Model {
+            frame_count,
+            realtime_source_latency,
+            seed,
+            playback_duration,
+            installations,
+            groups,
+            sources,
+            speakers,
+            installation_polygons,
+            active_sounds,
+            is_playing,
+            schedule,
+            schedule_overrides,
+            scheduled_mutes,
+            schedule_last_evaluated,
+            rules,
+            rule_violations,
+            chains,
+            pending_chain_sounds,
+            event_log,
+            replay,
+            last_tick_instant,
+            crossfade,
+            group_levels_outdated,
+            tracked_points,
+            occupancies,
+            installation_gains,
+            groups_last_used,
+            sources_last_used,
+            installation_speakers,
+            installation_areas,
+            target_sounds_per_installation,
+            active_sounds_per_installation,
+            active_sound_positions,
+            active_agents,
+            neighbours,
+            flock_groups,
+            available_groups,
+            available_sources,
+            rule_activity,
+            audio_streams,
+            sound_id_gen,
+            _tick_thread,
+        }
+    }
+
     /// Insert a new installation.
     pub fn insert_installation(
         &mut self,
@@ -384,6 +648,8 @@ impl Model {
             source.installations.remove(id);
         }
 
+        self.occupancies.lock().unwrap().remove(id);
+        self.installation_polygons.remove(id);
         self.installations.remove(id)
     }
 
@@ -403,8 +669,25 @@ impl Model {
         }
     }
 
+    /// Set the polygon outlining the area of the given installation.
+    ///
+    /// If the polygon has less than three vertices, the area of the installation is the bounding
+    /// box of its speakers.
+    pub fn set_installation_polygon(
+        &mut self,
+        installation: installation::Id,
+        polygon: Vec<Point2<Metres>>,
+    ) {
+        if polygon.len() < 3 {
+            self.installation_polygons.remove(&installation);
+        } else {
+            self.installation_polygons.insert(installation, polygon.into());
+        }
+    }
+
     /// Insert a new soundscape group.
     pub fn insert_group(&mut self, id: group::Id, group: Group) -> Option<Group> {
+        self.group_levels_outdated = true;
         self.groups.insert(id, group)
     }
 
@@ -415,6 +698,7 @@ impl Model {
     where
         F: FnOnce(&mut Group),
     {
+        self.group_levels_outdated = true;
         match self.groups.get_mut(id) {
             None => false,
             Some(s) => {
@@ -426,6 +710,7 @@ impl Model {
 
     /// Remove the given soundscape group.
     pub fn remove_group(&mut self, id: &group::Id) -> Option<Group> {
+        self.group_levels_outdated = true;
         self.groups.remove(id)
     }
 
@@ -457,6 +742,7 @@ impl Model {
 
     /// Insert a source into the inner hashmap.
     pub fn insert_source(&mut self, id: audio::source::Id, source: Source) -> Option<Source> {
+        self.group_levels_outdated = true;
         self.sources.insert(id, source)
     }
 
@@ -467,6 +753,7 @@ impl Model {
     where
         F: FnOnce(&mut Source),
     {
+        self.group_levels_outdated = true;
         match self.sources.get_mut(id) {
             None => false,
             Some(s) => {
@@ -494,7 +781,11 @@ impl Model {
             ref sources,
             ref speakers,
             ref installations,
+            ref schedule_overrides,
+            ref occupancies,
             ref mut active_sounds,
+            ref tracked_points,
+            ref installation_polygons,
 
             // Intermediary buffers.
             ref mut installation_speakers,
@@ -506,11 +797,18 @@ impl Model {
 
         // Collect the necessary data for generating a `Movement` instance from the constraints.
         update_installation_speakers(speakers, installation_speakers);
-        update_installation_areas(speakers, installation_speakers, installation_areas);
+        update_installation_areas(
+            speakers,
+            installation_speakers,
+            installation_polygons,
+            installation_areas,
+        );
         update_target_sounds_per_installation(
             seed,
             playback_duration,
             installations,
+            schedule_overrides,
+            &occupancies.lock().unwrap(),
             &installation_areas,
             target_sounds_per_installation,
         );
@@ -527,15 +825,19 @@ impl Model {
             match closest_assigned_installation(&sound, sources, &installation_areas) {
                 None => continue,
                 Some(installation) => {
-                    // Generate the movement.
+                    // Generate the movement, seeded so that it may be reproduced.
+                    let mut rng = XorShiftRng::from_seed(sound_seed(&seed, source_id, sound_id));
                     let movement = generate_movement(
+                        &mut rng,
                         *source_id,
                         sources,
                         installation,
                         installations,
+                        schedule_overrides,
                         &installation_areas,
                         &target_sounds_per_installation,
                         &active_sounds,
+                        tracked_points,
                     );
                     // Update the sound.
                     active_sounds.get_mut(&sound_id).unwrap().movement = movement;
@@ -545,15 +847,83 @@ impl Model {
     }
 
     /// Remove a source from the inner hashmap.
+    ///
+    /// All active sounds spawned via the source are released early using their release curve.
     pub fn remove_source(&mut self, id: &audio::source::Id) -> Option<Source> {
+        let source_id = *id;
+        self.audio_streams
+            .send_output(move |audio| {
+                audio.release_sounds_with_source(&source_id);
+            })
+            .ok();
         self.active_sounds
             .retain(|_, s| *id != s.handle.source_id());
+        self.group_levels_outdated = true;
         self.sources.remove(id)
     }
 
+    /// Create or update the tracked point with the given name.
+    pub fn update_tracked_point(&mut self, name: String, point: Point2<Metres>) {
+        let last_updated = time::Instant::now();
+        let tracked_point = movement::TrackedPoint { point, last_updated };
+        self.tracked_points.insert(name, tracked_point);
+    }
+
+    /// Remove the tracked point with the given name, e.g. when the tracking system loses it.
+    pub fn remove_tracked_point(&mut self, name: &str) -> Option<movement::TrackedPoint> {
+        self.tracked_points.remove(name)
+    }
+
+    /// Set the number of visitors reported within the given installation.
+    ///
+    /// The first report for an installation applies immediately, while following reports are
+    /// smoothed over `OCCUPANCY_SMOOTHING_MS`.
+    ///
+    /// Returns `false` if there is no installation with the given `Id`.
+    pub fn set_occupancy(&mut self, installation: installation::Id, visitors: f64) -> bool {
+        let level = match self.installations.get(&installation) {
+            None => return false,
+            Some(constraints) => constraints.occupancy.normalise(visitors),
+        };
+        let mut occupancies = self.occupancies.lock().unwrap();
+        let occupancy = occupancies.entry(installation).or_insert(Occupancy { visitors, level });
+        occupancy.visitors = visitors;
+        true
+    }
+
     /// Remove an active sound from the hashmap.
+    ///
+    /// This is called as the sound ends, at which point any chain links from the sound's source
+    /// that are triggered by the end of a sound are followed.
     pub fn remove_active_sound(&mut self, id: &audio::sound::Id) -> Option<ActiveSound> {
-        self.active_sounds.remove(id)
+        let sound = self.active_sounds.remove(id)?;
+        if self.replay.is_none() {
+            let end = event_log::End {
+                sound: id.to_u64(),
+                source: sound.handle.source_id(),
+                position: sound.position(),
+            };
+            log_event(
+                &mut self.event_log,
+                self.schedule.utc_offset_minutes,
+                self.playback_duration,
+                event_log::Event::End(end),
+            );
+        }
+        // Sounds that end while the soundscape is paused should not trigger their links.
+        if self.is_playing.load(atomic::Ordering::Relaxed) {
+            let mut rng = nannou::rand::thread_rng();
+            queue_chain_sound(
+                &mut rng,
+                &self.chains,
+                sound.handle.source_id(),
+                &chains::Trigger::End,
+                Some(sound.position()),
+                self.playback_duration,
+                &mut self.pending_chain_sounds,
+            );
+        }
+        Some(sound)
     }
 
     /// Update the state of all active sounds spawned via the source with the given `Id`.
@@ -585,6 +955,7 @@ impl Model {
         self.groups.clear();
         self.sources.clear();
         self.speakers.clear();
+        self.installation_polygons.clear();
         self.groups_last_used.clear();
         self.sources_last_used.clear();
         self.active_sounds.clear();
@@ -593,8 +964,112 @@ impl Model {
         self.target_sounds_per_installation.clear();
         self.active_sounds_per_installation.clear();
         self.active_sound_positions.clear();
+        self.active_agents.clear();
+        self.neighbours.clear();
+        self.flock_groups.clear();
         self.available_groups.clear();
         self.available_sources.clear();
+        self.schedule = Default::default();
+        self.schedule_overrides = Default::default();
+        self.scheduled_mutes.clear();
+        self.schedule_last_evaluated = None;
+        self.rules.clear();
+        self.rule_activity.clear();
+        self.rule_violations.lock().unwrap().clear();
+        self.chains.clear();
+        self.pending_chain_sounds.clear();
+        self.crossfade = None;
+        self.group_levels_outdated = true;
+        self.occupancies.lock().unwrap().clear();
+        self.installation_gains.clear();
+    }
+
+    /// Replace the schedule, re-evaluating it upon the next tick.
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+        self.schedule_last_evaluated = None;
+    }
+
+    /// Replace the rules describing when sources may be played in relation to one another.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    /// Replace the chains of links between sources.
+    ///
+    /// Sounds already waiting to be spawned by a chain are still spawned.
+    pub fn set_chains(&mut self, chains: Chains) {
+        self.chains = chains;
+    }
+
+    /// Spawn a sound from the start source of the chain with the given name upon the next tick.
+    ///
+    /// Returns `false` if there is no chain with the given name or if it has no start source.
+    pub fn trigger_chain(&mut self, name: &str) -> bool {
+        let source_id = match chains::find(&self.chains, name).and_then(|chain| chain.start) {
+            None => return false,
+            Some(source_id) => source_id,
+        };
+        let pending = PendingChainSound {
+            source_id,
+            due: self.playback_duration,
+            origin: None,
+            same_position: false,
+        };
+        self.pending_chain_sounds.push(pending);
+        true
+    }
+
+    /// Replay the sounds of the given event log records in place of the soundscape's usual choices.
+    ///
+    /// The replay begins upon the next tick while playing. Once all sounds have been spawned, the
+    /// soundscape resumes choosing sounds as usual. Replayed sounds are not logged.
+    pub fn replay(&mut self, records: Vec<event_log::Record>) {
+        self.replay = Some(event_log::Replay::new(&records));
+    }
+
+    /// Begin crossfading source volumes and master levels toward those of a recalled scene.
+    ///
+    /// If a crossfade is already in progress, the new crossfade begins from the current levels.
+    ///
+    /// The crossfade begins from the instant of the most recent tick.
+    pub fn crossfade(&mut self, mut crossfade: scene::Crossfade) {
+        let now = self.last_tick_instant;
+        if let Some((start, ref current)) = self.crossfade {
+            let elapsed = Ms(duration_to_secs(&now.duration_since(start)) * 1_000.0);
+            let progress = current.progress(elapsed);
+            crossfade.continue_from(current, progress);
+        }
+        self.crossfade = Some((now, crossfade));
+    }
+
+    // Play or pause all active sounds.
+    fn set_playing(&mut self, playing: bool) {
+        self.is_playing.store(playing, atomic::Ordering::Relaxed);
+        for sound in self.active_sounds.values() {
+            if playing {
+                sound.handle.play();
+            } else {
+                sound.handle.pause();
+            }
+        }
+    }
+}
+
+impl AudioStreams {
+    // Send the given update to the audio output thread.
+    //
+    // Updates are discarded while simulating.
+    fn send_output<F>(&self, update: F) -> Result<(), mpsc::SendError<()>>
+    where
+        F: FnOnce(&mut audio::output::Model) + Send + 'static,
+    {
+        match *self {
+            AudioStreams::Live { ref output, .. } => {
+                output.send(update).map_err(|_| mpsc::SendError(()))
+            },
+            AudioStreams::Simulated => Ok(()),
+        }
     }
 }
 
@@ -672,8 +1147,11 @@ pub fn spawn(
     audio_input_stream: audio::input::Stream,
     audio_output_stream: audio::output::Stream,
     sound_id_gen: audio::sound::IdGenerator,
+    event_log_directory: PathBuf,
 ) -> Soundscape {
     let is_playing = Arc::new(AtomicBool::new(true));
+    let rule_violations = Arc::new(Mutex::new(Vec::new()));
+    let occupancies = Arc::new(Mutex::new(Default::default()));
 
     // Spawn a thread to generate and send ticks.
     let tick_tx = tx.clone();
@@ -686,14 +1164,17 @@ pub fn spawn(
             loop {
                 thread::sleep(time::Duration::from_millis(TICK_RATE_MS));
                 let instant = time::Instant::now();
+                let system_time = SystemTime::now();
                 let since_last_tick = instant.duration_since(last);
                 last = instant;
-                if !tick_is_playing.load(atomic::Ordering::Relaxed) {
-                    continue;
+                let playing = tick_is_playing.load(atomic::Ordering::Relaxed);
+                if playing {
+                    playback_duration += since_last_tick;
                 }
-                playback_duration += since_last_tick;
                 let tick = Tick {
                     instant,
+                    system_time,
+                    playing,
                     since_last_tick,
                     playback_duration,
                 };
@@ -705,47 +1186,22 @@ pub fn spawn(
         .unwrap();
 
     // The model maintaining state between messages.
-    let realtime_source_latency = audio::DEFAULT_REALTIME_SOURCE_LATENCY;
-    let playback_duration = time::Duration::from_secs(0);
-    let installations = Default::default();
-    let groups = Default::default();
-    let sources = Default::default();
-    let speakers = Default::default();
-    let active_sounds = Default::default();
-    let installation_speakers = Default::default();
-    let installation_areas = Default::default();
-    let groups_last_used = Default::default();
-    let sources_last_used = Default::default();
-    let target_sounds_per_installation = Default::default();
-    let active_sound_positions = Default::default();
-    let active_sounds_per_installation = Default::default();
-    let available_groups = Default::default();
-    let available_sources = Default::default();
-    let model = Model {
+    let audio_streams = AudioStreams::Live {
+        wav_reader,
+        input: audio_input_stream,
+        output: audio_output_stream,
+    };
+    let model = Model::new(
         frame_count,
-        realtime_source_latency,
         seed,
-        playback_duration,
-        installations,
-        groups,
-        sources,
-        speakers,
-        active_sounds,
-        groups_last_used,
-        sources_last_used,
-        installation_speakers,
-        installation_areas,
-        target_sounds_per_installation,
-        active_sounds_per_installation,
-        active_sound_positions,
-        available_groups,
-        available_sources,
-        wav_reader,
-        audio_input_stream,
-        audio_output_stream,
+        is_playing.clone(),
+        rule_violations.clone(),
+        occupancies.clone(),
+        event_log::Writer::new(event_log_directory),
+        audio_streams,
         sound_id_gen,
-        _tick_thread,
-    };
+        Some(_tick_thread),
+    );
 
     // Spawn the soundscape thread.
     let thread = thread::Builder::new()
@@ -757,6 +1213,8 @@ pub fn spawn(
         tx,
         thread,
         is_playing,
+        rule_violations,
+        occupancies,
     }
 }
 
@@ -772,22 +1230,209 @@ fn run(mut model: Model, msg_rx: mpsc::Receiver<Message>) {
             Message::Exit => break,
 
             // Step forward the state of the soundscape.
-            Message::Tick(t) => tick(&mut model, t),
+            Message::Tick(t) => step(&mut model, t),
 
             // Play all active sounds.
-            Message::Play => {
-                for sound in model.active_sounds.values() {
-                    sound.handle.play();
-                }
-            }
+            Message::Play => model.set_playing(true),
 
             // Pause all active sounds.
-            Message::Pause => {
-                for sound in model.active_sounds.values() {
-                    sound.handle.pause();
-                }
+            Message::Pause => model.set_playing(false),
+        }
+    }
+}
+
+// Step forward the state of the soundscape by a single tick.
+fn step(model: &mut Model, t: Tick) {
+    model.last_tick_instant = t.instant;
+    update_schedule(model, &t);
+    update_crossfade(model, &t);
+    update_group_levels(model);
+    update_occupancy(model, &t);
+    if t.playing {
+        tick(model, t);
+    }
+    if let Err(err) = model.event_log.update(t.instant) {
+        eprintln!("failed to write to soundscape event log: {}", err);
+    }
+}
+
+// Evaluate the schedule if it has changed or if the schedule interval has passed since it was last
+// evaluated, applying any changes in playback or scheduled mutes.
+fn update_schedule(model: &mut Model, tick: &Tick) {
+    if let Some(last) = model.schedule_last_evaluated {
+        let interval = time::Duration::from_millis(SCHEDULE_INTERVAL_MS);
+        if tick.instant.duration_since(last) < interval {
+            return;
+        }
+    }
+    model.schedule_last_evaluated = Some(tick.instant);
+    let now = LocalTime::from_system_time(tick.system_time, model.schedule.utc_offset_minutes);
+    let overrides = model.schedule.overrides(&now);
+
+    // Only play or pause as windows begin or end so that staff may still play or pause the
+    // soundscape by hand within a window.
+    match (model.schedule_overrides.playing, overrides.playing) {
+        (Some(a), Some(b)) if a == b => (),
+        (_, Some(playing)) => model.set_playing(playing),
+        (Some(playing), None) => model.set_playing(!playing),
+        (None, None) => (),
+    }
+
+    // Collect all sources that are muted either directly or via one of their groups.
+    let mutes: FxHashSet<audio::source::Id> = {
+        let Model { ref sources, ref groups, .. } = *model;
+        sources
+            .iter()
+            .filter(|&(id, source)| {
+                overrides.muted_sources.contains(id)
+                    || overrides.muted_groups.iter().any(|group_id| match groups.get(group_id) {
+                        None => false,
+                        Some(group) => {
+                            group.contains_source(group_id, &source.groups, &source.tags)
+                        }
+                    })
+            })
+            .map(|(&id, _)| id)
+            .collect()
+    };
+    if mutes != model.scheduled_mutes {
+        model.scheduled_mutes = mutes.clone();
+        model
+            .audio_streams
+            .send_output(move |audio| audio.set_scheduled_mutes(mutes))
+            .expect("failed to send scheduled mutes to audio output thread");
+    }
+
+    model.schedule_overrides = overrides;
+}
+
+// Send the group volumes, mutes and solos of each source to the audio output thread if the groups
+// or sources have changed since they were last sent.
+fn update_group_levels(model: &mut Model) {
+    if !model.group_levels_outdated {
+        return;
+    }
+    model.group_levels_outdated = false;
+
+    let mut gains = FxHashMap::default();
+    let mut mutes = FxHashSet::default();
+    {
+        let Model { ref sources, ref groups, .. } = *model;
+        let any_soloed = groups.values().any(|group| group.soloed);
+        for (&id, source) in sources {
+            let mut gain = 1.0;
+            let mut muted = false;
+            let mut soloed = false;
+            let members = groups
+                .iter()
+                .filter(|&(group_id, group)| {
+                    group.contains_source(group_id, &source.groups, &source.tags)
+                });
+            for (_, group) in members {
+                gain *= group.volume;
+                muted |= group.muted;
+                soloed |= group.soloed;
+            }
+            if gain != 1.0 {
+                gains.insert(id, gain);
             }
+            if muted || (any_soloed && !soloed) {
+                mutes.insert(id);
+            }
+        }
+    }
+
+    model
+        .audio_streams
+        .send_output(move |audio| audio.set_group_levels(gains, mutes))
+        .expect("failed to send group levels to audio output thread");
+}
+
+// Smooth the occupancy of each installation toward its most recently reported number of visitors
+// and send the resulting installation gains to the audio output thread if they have changed.
+fn update_occupancy(model: &mut Model, tick: &Tick) {
+    let Model {
+        ref installations,
+        ref occupancies,
+        ref mut installation_gains,
+        ref audio_streams,
+        ..
+    } = *model;
+
+    let mut occupancies = occupancies.lock().unwrap();
+    if occupancies.is_empty() && installation_gains.is_empty() {
+        return;
+    }
+
+    let smoothing_secs = OCCUPANCY_SMOOTHING_MS as f64 / 1_000.0;
+    let amount = (duration_to_secs(&tick.since_last_tick) / smoothing_secs).min(1.0);
+    let mut gains = FxHashMap::default();
+    for (id, occupancy) in occupancies.iter_mut() {
+        let constraints = match installations.get(id) {
+            None => continue,
+            Some(installation) => &installation.occupancy,
+        };
+        let target = constraints.normalise(occupancy.visitors);
+        occupancy.level += (target - occupancy.level) * amount;
+        if (target - occupancy.level).abs() < 0.001 {
+            occupancy.level = target;
         }
+        let gain = constraints.gain(occupancy.level);
+        if gain != 1.0 {
+            gains.insert(*id, gain);
+        }
+    }
+
+    if gains != *installation_gains {
+        *installation_gains = gains.clone();
+        audio_streams
+            .send_output(move |audio| audio.set_installation_gains(gains))
+            .expect("failed to send installation gains to audio output thread");
+    }
+}
+
+// Step forward any crossfade toward a recalled scene, applying the interpolated levels to the
+// soundscape sources and to the sounds and master of the audio output thread.
+fn update_crossfade(model: &mut Model, tick: &Tick) {
+    let finished = {
+        let Model { ref mut sources, ref crossfade, ref audio_streams, .. } = *model;
+        let (start, crossfade) = match *crossfade {
+            None => return,
+            Some((start, ref crossfade)) => (start, crossfade),
+        };
+        let elapsed = Ms(duration_to_secs(&tick.instant.duration_since(start)) * 1_000.0);
+        let progress = crossfade.progress(elapsed);
+        let levels: Vec<_> = crossfade
+            .sources
+            .iter()
+            .map(|fade| {
+                let level = fade.level_at(progress);
+                if let Some(source) = sources.get_mut(&fade.id) {
+                    source.volume = level.volume;
+                    source.muted = level.muted;
+                }
+                (fade.id, level)
+            })
+            .collect();
+        let master = crossfade.master_at(progress);
+
+        // Send all levels for this tick within a single closure to the audio output thread.
+        audio_streams
+            .send_output(move |audio| {
+                for (id, level) in levels {
+                    audio.update_sounds_with_source(&id, |_, sound| {
+                        sound.volume = level.volume;
+                        sound.muted = level.muted;
+                    });
+                }
+                audio.master_volume = master.volume;
+                audio.dbap_rolloff_db = master.dbap_rolloff_db;
+            })
+            .expect("failed to send crossfade levels to audio output thread");
+        progress >= 1.0
+    };
+    if finished {
+        model.crossfade = None;
     }
 }
 
@@ -801,6 +1446,83 @@ fn update_active_sound_positions(
     active_sound_positions.extend(extension);
 }
 
+// Update the location and velocity of all active sounds with agent movement.
+//
+// This snapshot is taken before any agents are updated so that all agents within a flock respond
+// to the same state.
+fn update_active_agents(active_sounds: &ActiveSounds, active_agents: &mut ActiveAgents) {
+    active_agents.clear();
+    for (&sound_id, sound) in active_sounds {
+        if let Movement::Generative(movement::Generative::Agent(ref agent)) = sound.movement {
+            let source_id = sound.handle.source_id();
+            let neighbour = agent.neighbour();
+            active_agents.push(ActiveAgent { sound_id, source_id, neighbour });
+        }
+    }
+}
+
+// Collect the neighbours of the agent driving the given sound.
+//
+// Neighbours are other agents within the flocking radius that were spawned from the same source
+// or, if specified, from a source sharing one of the same groups. Group membership includes
+// membership via a group's tag query.
+//
+// `source_groups` is a buffer re-used for collecting the groups of the agent's source.
+fn agent_neighbours(
+    sound_id: audio::sound::Id,
+    source_id: audio::source::Id,
+    location: Point2<Metres>,
+    flocking: &audio::source::movement::Flocking,
+    sources: &Sources,
+    groups: &Groups,
+    active_agents: &ActiveAgents,
+    source_groups: &mut Vec<group::Id>,
+    neighbours: &mut AgentNeighbours,
+) {
+    neighbours.clear();
+    source_groups.clear();
+    if !flocking.is_enabled() {
+        return;
+    }
+    let source = match sources.get(&source_id) {
+        None => return,
+        Some(source) => source,
+    };
+    let extension = groups
+        .iter()
+        .filter(|&(id, group)| group.contains_source(id, &source.groups, &source.tags))
+        .map(|(&id, _)| id);
+    source_groups.extend(extension);
+    let location = utils::pt2::to_f64(location);
+    for other in active_agents {
+        if other.sound_id == sound_id {
+            continue;
+        }
+        let is_flock = match flocking.neighbours {
+            audio::source::movement::FlockNeighbours::Source => other.source_id == source_id,
+            audio::source::movement::FlockNeighbours::Group => {
+                other.source_id == source_id || sources
+                    .get(&other.source_id)
+                    .map(|s| {
+                        source_groups.iter().any(|id| {
+                            groups
+                                .get(id)
+                                .map_or(false, |g| g.contains_source(id, &s.groups, &s.tags))
+                        })
+                    })
+                    .unwrap_or(false)
+            },
+        };
+        if !is_flock {
+            continue;
+        }
+        let distance = location.distance(utils::pt2::to_f64(other.neighbour.location));
+        if distance <= flocking.radius {
+            neighbours.push(other.neighbour);
+        }
+    }
+}
+
 // Find the sound's closest assigned installation.
 //
 // Returns `None` if:
@@ -873,6 +1595,7 @@ fn agent_installation_data(
     source_id: audio::source::Id,
     sources: &Sources,
     installations: &Installations,
+    schedule_overrides: &schedule::Overrides,
     installation_areas: &InstallationAreas,
     target_sounds_per_installation: &TargetSoundsPerInstallation,
     active_sound_positions: &ActiveSoundPositions,
@@ -907,7 +1630,8 @@ fn agent_installation_data(
                 None => return None,
                 Some(area) => area.clone()
             };
-            let range = &installations[inst].simultaneous_sounds;
+            let range =
+                schedule_overrides.installation_simultaneous_sounds(inst, &installations[inst]);
             let current_num_sounds = active_sounds_per_installation
                 .get(inst)
                 .map(|sounds| {
@@ -942,15 +1666,24 @@ fn agent_installation_data(
 }
 
 // Generate a movement for some source within some given installation.
-fn generate_movement(
+//
+// All random parameters are drawn from the given `rng`, seeded per sound so that the movement may
+// be reproduced.
+fn generate_movement<R>(
+    rng: &mut R,
     source_id: audio::source::Id,
     sources: &Sources,
     installation: installation::Id,
     installations: &Installations,
+    schedule_overrides: &schedule::Overrides,
     installation_areas: &InstallationAreas,
     target_sounds_per_installation: &TargetSoundsPerInstallation,
     active_sounds: &ActiveSounds,
-) -> Movement {
+    tracked_points: &TrackedPoints,
+) -> Movement
+where
+    R: Rng,
+{
     match sources[&source_id].movement {
         audio::source::Movement::Fixed(ref pos) => {
             let area = installation_areas
@@ -958,14 +1691,13 @@ fn generate_movement(
                 .expect("no area for the given installation");
             let x = area.bounding_rect.left + area.bounding_rect.width() * pos.x;
             let y = area.bounding_rect.bottom + area.bounding_rect.height() * pos.y;
-            let point = pt2(x, y);
+            let point = area.clamp(pt2(x, y));
             let radians = 0.0;
             let position = audio::sound::Position { point, radians };
             Movement::Fixed(position)
         },
         audio::source::Movement::Generative(ref gen) => match *gen {
             audio::source::movement::Generative::Agent(ref agent) => {
-                let mut rng = nannou::rand::thread_rng();
                 // TODO: Should these be skewed?
                 let r = &agent.max_speed;
                 let max_speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
@@ -979,18 +1711,20 @@ fn generate_movement(
                     source_id,
                     sources,
                     installations,
+                    schedule_overrides,
                     installation_areas,
                     &target_sounds_per_installation,
                     &active_sound_positions,
                 );
                 let agent = movement::Agent::generate(
-                    rng,
+                    &mut *rng,
                     installation,
                     &installation_data,
                     max_speed,
                     max_force,
                     max_rotation,
                     agent.directional,
+                    agent.flocking,
                 );
                 let generative = movement::Generative::Agent(agent);
                 let movement = Movement::Generative(generative);
@@ -998,7 +1732,6 @@ fn generate_movement(
             },
 
             audio::source::movement::Generative::Ngon(ref ngon) => {
-                let mut rng = nannou::rand::thread_rng();
                 // TODO: Should these be skewed?
                 let r = &ngon.vertices;
                 let vertices = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
@@ -1008,19 +1741,137 @@ fn generate_movement(
                 let speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
                 let r = &ngon.radians_offset;
                 let radians_offset = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
-                let bounding_rect = &installation_areas[&installation].bounding_rect;
+                let area = &installation_areas[&installation];
                 let ngon = movement::Ngon::new(
                     vertices,
                     nth,
                     ngon.normalised_dimensions,
                     radians_offset,
                     speed,
-                    bounding_rect,
+                    area,
                 );
                 let generative = movement::Generative::Ngon(ngon);
                 let movement = Movement::Generative(generative);
                 movement
-            }
+            },
+
+            audio::source::movement::Generative::Path(ref path) => {
+                // A path without any points is fixed to the middle of the installation.
+                let points = match path.points.is_empty() {
+                    true => vec![installation_areas[&installation].middle()],
+                    false => path.points.clone(),
+                };
+                let offset = match path.random_offset {
+                    true => rng.gen(),
+                    false => 0.0,
+                };
+                let timing = path.timing;
+                let mut path = movement::Path::new(&points, path.mode, 0.0, offset);
+                path.speed = match timing {
+                    audio::source::movement::PathTiming::Speed(r) => {
+                        map_range(rng.gen(), 0f64, 1.0, r.min, r.max)
+                    },
+                    audio::source::movement::PathTiming::Duration(r) => {
+                        let ms = map_range(rng.gen(), 0f64, 1.0, r.min.ms(), r.max.ms());
+                        path.speed_for_duration(Ms(ms))
+                    },
+                };
+                let generative = movement::Generative::Path(path);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
+            audio::source::movement::Generative::Orbit(ref orbit) => {
+                let r = &orbit.radius;
+                let radius = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &orbit.speed;
+                let speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let clockwise = match orbit.direction {
+                    audio::source::movement::OrbitDirection::Clockwise => true,
+                    audio::source::movement::OrbitDirection::Anticlockwise => false,
+                    audio::source::movement::OrbitDirection::Either => rng.gen(),
+                };
+                let angle = rng.gen::<f64>() * 2.0 * PI_F64;
+                let area = &installation_areas[&installation];
+                let orbit = movement::Orbit::new(
+                    orbit.centre,
+                    radius,
+                    speed,
+                    clockwise,
+                    orbit.directional,
+                    angle,
+                    area,
+                );
+                let generative = movement::Generative::Orbit(orbit);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
+            audio::source::movement::Generative::Lissajous(ref lissajous) => {
+                let r = &lissajous.frequency_x;
+                let frequency_x = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &lissajous.frequency_y;
+                let frequency_y = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &lissajous.phase;
+                let phase = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &lissajous.rate;
+                let rate = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let theta = rng.gen::<f64>() * 2.0 * PI_F64;
+                let area = &installation_areas[&installation];
+                let lissajous = movement::Lissajous::new(
+                    frequency_x,
+                    frequency_y,
+                    phase,
+                    rate,
+                    lissajous.normalised_dimensions,
+                    lissajous.directional,
+                    theta,
+                    area,
+                );
+                let generative = movement::Generative::Lissajous(lissajous);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
+            audio::source::movement::Generative::Wander(ref wander) => {
+                let r = &wander.speed;
+                let speed = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let r = &wander.turbulence;
+                let turbulence = map_range(rng.gen(), 0f64, 1.0, r.min, r.max);
+                let noise_offset = rng.gen::<f64>() * 1_000.0;
+                let normalised_point = pt2(rng.gen::<f64>(), rng.gen::<f64>());
+                let heading = rng.gen::<f64>() * 2.0 * PI_F64;
+                let area = &installation_areas[&installation];
+                let wander = movement::Wander::new(
+                    speed,
+                    turbulence,
+                    wander.edge_repulsion,
+                    wander.directional,
+                    noise_offset,
+                    normalised_point,
+                    heading,
+                    area,
+                );
+                let generative = movement::Generative::Wander(wander);
+                let movement = Movement::Generative(generative);
+                movement
+            },
+
+            audio::source::movement::Generative::Tracked(ref tracked) => {
+                // Begin from the installation centroid, attaching to the nearest tracked point.
+                let start = installation_areas[&installation].centroid;
+                let tracked = movement::Tracked::new(
+                    tracked.target.clone(),
+                    tracked.smoothing,
+                    tracked.timeout,
+                    tracked.directional,
+                    start,
+                    tracked_points,
+                );
+                let generative = movement::Generative::Tracked(tracked);
+                let movement = Movement::Generative(generative);
+                movement
+            },
         },
     }
 }
@@ -1033,6 +1884,17 @@ fn installation_seed(installation: &installation::Id) -> [u32; 4] {
     seed
 }
 
+// A seed unique to the given sound, offset by the project seed.
+fn sound_seed(seed: &Seed, source: &audio::source::Id, sound: &audio::sound::Id) -> Seed {
+    let (source, sound) = (source.0, sound.to_u64());
+    let sound_seed = [sound as u32, (sound >> 32) as u32, source as u32, (source >> 32) as u32];
+    let mut seed = utils::add_seeds(seed, &sound_seed);
+    if seed == [0, 0, 0, 0] {
+        seed[0] = 1;
+    }
+    seed
+}
+
 // Update the map from installations to speakers.
 fn update_installation_speakers(
     speakers: &Speakers,
@@ -1053,14 +1915,26 @@ fn update_installation_speakers(
 
 // Update the map from installations to their areas.
 //
-// An installations `Area` is determined via the assigned speaker locations.
+// An installations `Area` is outlined by its polygon if one was drawn, otherwise it is determined
+// via the assigned speaker locations. Installations without speakers have no area either way.
 fn update_installation_areas(
     speakers: &Speakers,
     installation_speakers: &InstallationSpeakers,
+    installation_polygons: &InstallationPolygons,
     installation_areas: &mut InstallationAreas,
 ) {
     installation_areas.clear();
     for (&installation, installation_speakers) in installation_speakers {
+        if installation_speakers.is_empty() {
+            continue;
+        }
+        let polygon_area = installation_polygons
+            .get(&installation)
+            .and_then(|polygon| movement::Area::from_polygon(polygon.clone()));
+        if let Some(area) = polygon_area {
+            installation_areas.insert(installation, area);
+            continue;
+        }
         let speaker_points = || installation_speakers.iter().map(|id| speakers[id].point);
         let bounding_rect = match BoundingRect::from_points(speaker_points()) {
             None => continue,
@@ -1070,9 +1944,11 @@ fn update_installation_areas(
             None => continue,
             Some(p) => pt2(Metres(p.x), Metres(p.y)),
         };
+        let polygon = None;
         let area = movement::Area {
             bounding_rect,
             centroid,
+            polygon,
         };
         installation_areas.insert(installation, area);
     }
@@ -1123,16 +1999,28 @@ fn update_target_sounds_per_installation(
     seed: Seed,
     playback_duration: &time::Duration,
     installations: &Installations,
+    schedule_overrides: &schedule::Overrides,
+    occupancies: &Occupancies,
     installation_areas: &InstallationAreas,
     target_sounds_per_installation: &mut TargetSoundsPerInstallation,
 ) {
     target_sounds_per_installation.clear();
     for (installation, installation_constraints) in installations {
+        // Apply any scheduled simultaneous sounds preset.
+        let mut constraints = installation_constraints.clone();
+        constraints.simultaneous_sounds =
+            schedule_overrides.installation_simultaneous_sounds(installation, &constraints);
+        // Narrow the range toward its minimum as the installation empties.
+        if let Some(occupancy) = occupancies.get(installation) {
+            constraints.simultaneous_sounds = constraints
+                .occupancy
+                .simultaneous_sounds(constraints.simultaneous_sounds, occupancy.level);
+        }
         let target_num_sounds = installation_target_sounds(
             seed,
             playback_duration,
             installation,
-            installation_constraints,
+            &constraints,
             installation_areas,
         );
         target_sounds_per_installation.insert(*installation, target_num_sounds);
@@ -1145,6 +2033,7 @@ fn update_available_groups(
     tick: &Tick,
     sources: &Sources,
     groups: &Groups,
+    schedule_overrides: &schedule::Overrides,
     active_sounds: &ActiveSounds,
     groups_last_used: &GroupsLastUsed,
     available_groups: &mut AvailableGroups,
@@ -1153,6 +2042,16 @@ fn update_available_groups(
     let extension = groups
         .iter()
         .filter_map(|(group_id, group)| {
+            // Skip groups that are disabled by the schedule.
+            if schedule_overrides.disabled_groups.contains(group_id) {
+                return None;
+            }
+
+            // Apply any scheduled constraint presets.
+            let occurrence_rate = schedule_overrides.group_occurrence_rate(group_id, group);
+            let simultaneous_sounds =
+                schedule_overrides.group_simultaneous_sounds(group_id, group);
+
             // The total number of active sounds spawned via this group across all installations.
             let num_active_sounds = active_sounds
                 .values()
@@ -1162,19 +2061,19 @@ fn update_available_groups(
                         None => return false,
                         Some(s) => s,
                     };
-                    source.groups.contains(group_id)
+                    group.contains_source(group_id, &source.groups, &source.tags)
                 })
                 .count();
 
             // If there are no available sounds, skip this group.
-            let num_available_sounds = if group.simultaneous_sounds.max > num_active_sounds {
-                group.simultaneous_sounds.max - num_active_sounds
+            let num_available_sounds = if simultaneous_sounds.max > num_active_sounds {
+                simultaneous_sounds.max - num_active_sounds
             } else {
                 return None;
             };
 
-            let num_sounds_needed = if group.simultaneous_sounds.min > num_active_sounds {
-                group.simultaneous_sounds.min - num_active_sounds
+            let num_sounds_needed = if simultaneous_sounds.min > num_active_sounds {
+                simultaneous_sounds.min - num_active_sounds
             } else {
                 0
             };
@@ -1187,13 +2086,13 @@ fn update_available_groups(
                 let duration_since_last_ms =
                     Ms(duration_to_secs(&duration_since_last) * 1_000.0);
                 let duration_since_min_interval =
-                    if duration_since_last_ms > group.occurrence_rate.min {
-                        duration_since_last_ms - group.occurrence_rate.min
+                    if duration_since_last_ms > occurrence_rate.min {
+                        duration_since_last_ms - occurrence_rate.min
                     } else {
                         return None;
                     };
                 let duration_until_sound_needed =
-                    group.occurrence_rate.max - duration_since_last_ms;
+                    occurrence_rate.max - duration_since_last_ms;
                 Some(Timing {
                     duration_since_min_interval,
                     duration_until_sound_needed,
@@ -1202,7 +2101,7 @@ fn update_available_groups(
                 None
             };
 
-            let occurrence_rate_interval = group.occurrence_rate;
+            let occurrence_rate_interval = occurrence_rate;
             let suitability = Suitability {
                 occurrence_rate_interval,
                 num_sounds_needed,
@@ -1225,6 +2124,11 @@ fn update_available_sources(
     sources: &Sources,
     active_sounds: &ActiveSounds,
     sources_last_used: &SourcesLastUsed,
+    groups: &Groups,
+    schedule_overrides: &schedule::Overrides,
+    tracked_points: &TrackedPoints,
+    rules: &[rules::Rule],
+    rule_activity: &rules::Activity,
     available_groups: &AvailableGroups,
     available_sources: &mut AvailableSources,
 ) {
@@ -1236,11 +2140,38 @@ fn update_available_sources(
             return None;
         }
 
-        // We only want sources if they are a part of an available group.
-        if available_groups.iter().all(|g| !source.groups.contains(&g.id)) {
+        // Skip sources that are disabled by the schedule.
+        if schedule_overrides.disabled_sources.contains(source_id) {
+            return None;
+        }
+
+        // Skip tracked sources while there is no tracked point for them to follow.
+        if let audio::source::Movement::Generative(ref generative) = source.movement {
+            if let audio::source::movement::Generative::Tracked(ref tracked) = *generative {
+                if !movement::tracked::target_is_tracked(&tracked.target, tracked_points) {
+                    return None;
+                }
+            }
+        }
+
+        // We only want sources if they are a part of an available group, either by hand or via
+        // the group's tag query.
+        let in_available_group = available_groups.iter().any(|g| match groups.get(&g.id) {
+            None => false,
+            Some(group) => group.contains_source(&g.id, &source.groups, &source.tags),
+        });
+        if !in_available_group {
             return None;
         }
 
+        // Skip sources that are withheld by one of the project's rules.
+        if !rules.is_empty() {
+            let targets = rule_targets(source_id, source, groups);
+            if rules::check(rules, *source_id, &targets, rule_activity, tick.instant).is_some() {
+                return None;
+            }
+        }
+
         // How many instances of this sound are already playing.
         let num_sounds = active_sounds
             .values()
@@ -1294,14 +2225,235 @@ fn update_available_sources(
         Some(AvailableSource {
             id: *source_id,
             suitability,
+            weight: source.weight,
             playback_duration: source.playback_duration,
             attack_duration: source.attack_duration,
             release_duration: source.release_duration,
+            playback_rate: source.playback_rate,
         })
     });
     available_sources.extend(extension);
 }
 
+// Spawn a sound from the given source, returning the resulting active sound.
+//
+// The attack, release and playback durations, the playback rate and the effects of the sound are
+// drawn from the source's ranges via the given `rng`.
+fn spawn_active_sound<R>(
+    rng: &mut R,
+    sound_id: audio::sound::Id,
+    source_id: audio::source::Id,
+    sources: &Sources,
+    installation: installation::Id,
+    initial_position: audio::sound::Position,
+    movement: Movement,
+    playback_duration: time::Duration,
+    frame_count: &AtomicUsize,
+    audio_streams: &AudioStreams,
+    realtime_source_latency: Ms,
+) -> ActiveSound
+where
+    R: Rng,
+{
+    let source = &sources[&source_id];
+
+    // Generate the attack and release durations based on their source ranges.
+    let attack_duration_frames =
+        audio::source::random_playback_duration(&mut *rng, source.attack_duration)
+            .to_samples(audio::SAMPLE_RATE);
+    let release_duration_frames =
+        audio::source::random_playback_duration(&mut *rng, source.release_duration)
+            .to_samples(audio::SAMPLE_RATE);
+    let duration = audio::source::random_playback_duration(&mut *rng, source.playback_duration);
+    let duration_frames = duration.to_samples(audio::SAMPLE_RATE);
+    let playback_rate = audio::source::random_playback_rate(&mut *rng, source.playback_rate);
+
+    // This is not a continuous preview (this is only used for GUI sounds).
+    let continuous_preview = false;
+
+    // Choose the effect parameters for this sound from the source's ranges.
+    let audio_source = source.to_audio_source();
+    let effects = audio::effect::Chain::random(
+        &audio_source.effects,
+        audio_source.channel_count(),
+        &mut *rng,
+    );
+
+    let handle = match *audio_streams {
+        AudioStreams::Live { ref wav_reader, ref input, ref output } => {
+            audio::sound::spawn_from_source(
+                sound_id,
+                source_id,
+                &audio_source,
+                initial_position,
+                attack_duration_frames,
+                release_duration_frames,
+                playback_rate,
+                effects,
+                continuous_preview,
+                Some(duration_frames),
+                frame_count.load(atomic::Ordering::Relaxed) as _,
+                wav_reader,
+                input,
+                output,
+                realtime_source_latency,
+            )
+        },
+        AudioStreams::Simulated => audio::sound::Handle::simulated(sound_id, source_id),
+    };
+
+    ActiveSound {
+        initial_installation: installation,
+        movement,
+        handle,
+        duration,
+        spawned_at: playback_duration,
+        progress: None,
+    }
+}
+
+// Follow one of the chain links from a sound of the given source for the given trigger, if any.
+//
+// The sound of the followed link is queued to be spawned once its randomly chosen delay has passed.
+fn queue_chain_sound<R>(
+    rng: &mut R,
+    chains: &[chains::Chain],
+    source_id: audio::source::Id,
+    trigger: &chains::Trigger,
+    origin: Option<audio::sound::Position>,
+    playback_duration: time::Duration,
+    pending_chain_sounds: &mut PendingChainSounds,
+)
+where
+    R: Rng,
+{
+    let link = match chains::choose(chains, source_id, trigger, rng.gen()) {
+        None => return,
+        Some(link) => link,
+    };
+    let delay_ms = map_range(rng.gen(), 0f64, 1.0, link.delay.min.ms(), link.delay.max.ms());
+    let delay = time::Duration::from_millis(delay_ms.max(0.0) as u64);
+    let pending = PendingChainSound {
+        source_id: link.to,
+        due: playback_duration + delay,
+        origin,
+        same_position: link.same_position,
+    };
+    pending_chain_sounds.push(pending);
+}
+
+// Describe a newly spawned sound for the event log.
+fn spawn_event(
+    sound_id: audio::sound::Id,
+    sound: &ActiveSound,
+    sources: &Sources,
+    groups: &Groups,
+    seed: Seed,
+    fixed: bool,
+) -> event_log::Event {
+    let source_id = sound.handle.source_id();
+    let source = &sources[&source_id];
+    let groups = groups
+        .iter()
+        .filter(|&(id, group)| group.contains_source(id, &source.groups, &source.tags))
+        .map(|(&id, _)| id)
+        .collect();
+    let spawn = event_log::Spawn {
+        sound: sound_id.to_u64(),
+        source: source_id,
+        groups,
+        installation: sound.initial_installation,
+        position: sound.position(),
+        duration: sound.duration,
+        seed,
+        fixed,
+    };
+    event_log::Event::Spawn(spawn)
+}
+
+// Append the event to the event log, reporting any failure to do so.
+fn log_event(
+    event_log: &mut event_log::Writer,
+    utc_offset_minutes: Option<i32>,
+    playback_duration: time::Duration,
+    event: event_log::Event,
+) {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(time::Duration::from_secs(0));
+    let unix_ms = since_epoch.as_secs() * 1_000 + since_epoch.subsec_nanos() as u64 / 1_000_000;
+    let playback = Ms(duration_to_secs(&playback_duration) * 1_000.0);
+    let record = event_log::Record { unix_ms, playback, event };
+    if let Err(err) = event_log.append(&record, utc_offset_minutes) {
+        eprintln!("failed to append to soundscape event log: {}", err);
+    }
+}
+
+// Collect the rule targets to which the given source belongs.
+//
+// This includes the source itself along with each group of which it is a member, either by hand or
+// via the group's tag query.
+fn rule_targets(source_id: &audio::source::Id, source: &Source, groups: &Groups) -> rules::Targets {
+    let mut targets = rules::Targets::default();
+    targets.insert(rules::Target::Source(*source_id));
+    for (group_id, group) in groups {
+        if group.contains_source(group_id, &source.groups, &source.tags) {
+            targets.insert(rules::Target::Group(*group_id));
+        }
+    }
+    targets
+}
+
+// Collect the rule targets that are currently active along with the moment at which each was last
+// used to spawn a sound.
+fn update_rule_activity(
+    sources: &Sources,
+    groups: &Groups,
+    active_sounds: &ActiveSounds,
+    sources_last_used: &SourcesLastUsed,
+    rules: &[rules::Rule],
+    rule_activity: &mut rules::Activity,
+) {
+    rule_activity.clear();
+    if rules.is_empty() {
+        return;
+    }
+    for (source_id, source) in sources {
+        let is_active = active_sounds.values().any(|s| s.source_id() == *source_id);
+        let last_used = sources_last_used.get(source_id);
+        if !is_active && last_used.is_none() {
+            continue;
+        }
+        let targets = rule_targets(source_id, source, groups);
+        if is_active {
+            rule_activity.insert_active(&targets);
+        }
+        if let Some(&instant) = last_used {
+            rule_activity.insert_spawned(&targets, instant);
+        }
+    }
+}
+
+// Check all sources against the rules, sharing the violations with the `Soundscape` handle.
+fn update_rule_violations(
+    tick: &Tick,
+    rules: &[rules::Rule],
+    sources: &Sources,
+    groups: &Groups,
+    rule_activity: &rules::Activity,
+    rule_violations: &Mutex<rules::Violations>,
+) {
+    let mut violations = rules::Violations::new();
+    if !rules.is_empty() {
+        let extension = sources.iter().filter_map(|(source_id, source)| {
+            let targets = rule_targets(source_id, source, groups);
+            rules::check(rules, *source_id, &targets, rule_activity, tick.instant)
+        });
+        violations.extend(extension);
+        violations.sort_by_key(|v| v.source.0);
+    }
+    *rule_violations.lock().unwrap() = violations;
+}
 
 // Order the two sets or properties by their suitability for use as the next sound.
 fn suitability(a: &Suitability, b: &Suitability) -> cmp::Ordering {
@@ -1318,6 +2470,80 @@ fn suitability(a: &Suitability, b: &Suitability) -> cmp::Ordering {
     }
 }
 
+/// The share of choices expected for each of the given weights when choosing between sources
+/// within the same suitability band.
+///
+/// This matches the likelihood with which `tick` picks each of the sources in the most suitable
+/// band, e.g. each member of a group whose sources are all past their minimum occurrence interval
+/// and none of which are yet overdue.
+///
+/// If the weights sum to zero, each share is equal.
+pub fn expected_shares(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 {
+        let share = 1.0 / weights.len() as f64;
+        return weights.iter().map(|_| share).collect();
+    }
+    weights.iter().map(|w| w.max(0.0) / total).collect()
+}
+
+// Order the two sets of properties by their suitability band.
+//
+// Weights are only taken into account between sources within the same band, so that a weight
+// never causes a needed or overdue sound to be passed over. Bands are ordered by:
+//
+// 1. The number of sounds needed.
+// 2. Whether or not a sound is due, i.e. it has never been played or its maximum occurrence
+//    interval has passed.
+//
+// Consistent with `suitability`, so that the most suitable band is a prefix of a sorted list.
+fn suitability_band(a: &Suitability, b: &Suitability) -> cmp::Ordering {
+    match b.num_sounds_needed.cmp(&a.num_sounds_needed) {
+        cmp::Ordering::Equal => b.is_sound_due().cmp(&a.is_sound_due()),
+        ord => ord,
+    }
+}
+
+// Choose an index into the given weights at random, with a likelihood proportional to each weight.
+//
+// If the weights sum to zero, each index is equally likely.
+fn weighted_index<R: Rng>(rng: &mut R, weights: &[f64]) -> usize {
+    let total: f64 = weights.iter().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 {
+        return rng.gen_range(0, weights.len());
+    }
+    let r = rng.gen::<f64>() * total;
+    let mut accumulated = 0.0;
+    for (i, w) in weights.iter().enumerate() {
+        accumulated += w.max(0.0);
+        if r < accumulated {
+            return i;
+        }
+    }
+    weights.len() - 1
+}
+
+// Choose the index of the source from which the next sound is played.
+//
+// The given sources must be sorted by `suitability`. If every source within the most suitable
+// band has the same weight, one of the most suitable sources is chosen at random, exactly as if
+// sources had no weights. Otherwise the choice is made across the whole band with a likelihood
+// proportional to each source's weight.
+fn choose_source_index<R: Rng>(rng: &mut R, sources: &[AvailableSource]) -> usize {
+    let num_in_band = utils::count_equal(sources, |a, b| {
+        suitability_band(&a.suitability, &b.suitability)
+    });
+    let band = &sources[..num_in_band];
+    if band.iter().all(|s| s.weight == band[0].weight) {
+        let num_equal = utils::count_equal(band, |a, b| {
+            suitability(&a.suitability, &b.suitability)
+        });
+        return rng.gen_range(0, num_equal);
+    }
+    let weights = band.iter().map(|s| s.weight).collect::<Vec<_>>();
+    weighted_index(rng, &weights)
+}
+
 // Called each time the soundscape thread receives a tick.
 fn tick(model: &mut Model, tick: Tick) {
     let Model {
@@ -1332,17 +2558,30 @@ fn tick(model: &mut Model, tick: Tick) {
         ref mut groups_last_used,
         ref mut sources_last_used,
         ref mut active_sounds,
+        ref schedule_overrides,
+        ref occupancies,
+        ref rules,
+        ref rule_violations,
+        ref schedule,
+        ref chains,
+        ref mut pending_chain_sounds,
+        ref mut event_log,
+        ref mut replay,
+        ref installation_polygons,
         ref mut installation_speakers,
         ref mut installation_areas,
         ref mut target_sounds_per_installation,
         ref mut active_sounds_per_installation,
         ref mut active_sound_positions,
+        ref mut active_agents,
+        ref mut neighbours,
+        ref mut flock_groups,
+        ref mut tracked_points,
         ref mut available_groups,
         ref mut available_sources,
+        ref mut rule_activity,
         ref mut sound_id_gen,
-        ref wav_reader,
-        ref audio_input_stream,
-        ref audio_output_stream,
+        ref audio_streams,
         ..
     } = *model;
 
@@ -1354,8 +2593,13 @@ fn tick(model: &mut Model, tick: Tick) {
 
     // Create the map from installations to their areas.
     //
-    // An installations `Area` is determined via the assigned speaker locations.
-    update_installation_areas(speakers, installation_speakers, installation_areas);
+    // An installations `Area` is determined via its polygon or the assigned speaker locations.
+    update_installation_areas(
+        speakers,
+        installation_speakers,
+        installation_polygons,
+        installation_areas,
+    );
 
     // Determine the target number of sounds per installation.
     //
@@ -1365,14 +2609,21 @@ fn tick(model: &mut Model, tick: Tick) {
         seed,
         &tick.playback_duration,
         installations,
+        schedule_overrides,
+        &occupancies.lock().unwrap(),
         installation_areas,
         target_sounds_per_installation,
     );
 
+    // Forget any tracked points that have not been updated recently.
+    let tracked_point_lifetime = time::Duration::from_millis(TRACKED_POINT_LIFETIME_MS);
+    tracked_points.retain(|_, p| p.last_updated + tracked_point_lifetime > tick.instant);
+
     // Update the movement of each active sound.
     {
         let mut rng = nannou::rand::thread_rng();
         update_active_sound_positions(active_sounds, active_sound_positions);
+        update_active_agents(active_sounds, active_agents);
         for (&sound_id, sound) in active_sounds.iter_mut() {
             let initial_installation_area = installation_areas.get(&sound.initial_installation);
             match sound.movement {
@@ -1384,15 +2635,60 @@ fn tick(model: &mut Model, tick: Tick) {
                             source_id,
                             sources,
                             installations,
+                            schedule_overrides,
                             installation_areas,
                             &target_sounds_per_installation,
                             &active_sound_positions,
                         );
-                        agent.update(&mut rng, &tick.since_last_tick, &installation_data);
+                        agent_neighbours(
+                            sound_id,
+                            source_id,
+                            agent.neighbour().location,
+                            &agent.flocking,
+                            sources,
+                            groups,
+                            &active_agents,
+                            flock_groups,
+                            neighbours,
+                        );
+                        agent.update(
+                            &mut rng,
+                            &tick.since_last_tick,
+                            &installation_data,
+                            &neighbours[..],
+                        );
                     },
                     movement::Generative::Ngon(ref mut ngon) => {
                         if let Some(area) = initial_installation_area {
-                            ngon.update(&tick.since_last_tick, &area.bounding_rect);
+                            ngon.update(&tick.since_last_tick, area);
+                        }
+                    },
+                    movement::Generative::Path(ref mut path) => {
+                        path.update(&tick.since_last_tick);
+                    },
+                    movement::Generative::Orbit(ref mut orbit) => {
+                        if let Some(area) = initial_installation_area {
+                            orbit.update(&tick.since_last_tick, area);
+                        }
+                    },
+                    movement::Generative::Lissajous(ref mut lissajous) => {
+                        if let Some(area) = initial_installation_area {
+                            lissajous.update(&tick.since_last_tick, area);
+                        }
+                    },
+                    movement::Generative::Wander(ref mut wander) => {
+                        if let Some(area) = initial_installation_area {
+                            wander.update(&tick.since_last_tick, area);
+                        }
+                    },
+                    movement::Generative::Tracked(ref mut tracked) => {
+                        // Release the sound once it has lost tracking for longer than the timeout.
+                        if tracked.update(&tick.since_last_tick, tracked_points) {
+                            audio_streams
+                                .send_output(move |audio| {
+                                    audio.update_sound(&sound_id, |sound| sound.signal.release());
+                                })
+                                .expect("failed to send tracked sound release to audio thread");
                         }
                     },
                 },
@@ -1403,13 +2699,198 @@ fn tick(model: &mut Model, tick: Tick) {
             // The audio thread will then notify the GUI of the new position upon the next rendered
             // buffer.
             let position = sound.position();
-            audio_output_stream
-                .send(move |audio| {
+            audio_streams
+                .send_output(move |audio| {
                     audio.update_sound(&sound_id, move |sound| {
                         sound.position = position;
                     });
                 })
                 .expect("failed to send audio output thread updated sound position");
+
+            // Follow any chain links triggered by the sound's progress.
+            let elapsed = tick.playback_duration
+                .checked_sub(sound.spawned_at)
+                .unwrap_or(time::Duration::from_secs(0));
+            let elapsed_ms = duration_to_secs(&elapsed) * 1_000.0;
+            let progress = match sound.duration.ms() > 0.0 {
+                true => (elapsed_ms / sound.duration.ms()).min(1.0),
+                false => 1.0,
+            };
+            let source_id = sound.handle.source_id();
+            let triggers =
+                chains::progress_triggers_crossed(chains, source_id, sound.progress, progress);
+            for trigger in triggers {
+                queue_chain_sound(
+                    &mut rng,
+                    chains,
+                    source_id,
+                    &trigger,
+                    Some(position),
+                    tick.playback_duration,
+                    pending_chain_sounds,
+                );
+            }
+            sound.progress = Some(progress);
+        }
+    }
+
+    // Spawn any logged sounds that are due if replaying an event log.
+    //
+    // The replay waits for the project's sources and speakers to be loaded before beginning.
+    // Chained sounds were logged along with all others, so they are not followed while replaying.
+    let replay_spawns = match *replay {
+        Some(ref mut r) if !sources.is_empty() && !installation_areas.is_empty() => {
+            r.due(tick.playback_duration)
+        },
+        _ => vec![],
+    };
+    if replay.is_some() {
+        pending_chain_sounds.clear();
+    }
+    for spawn in replay_spawns {
+        // Skip sources and installations that no longer exist along with invalid seeds.
+        if !sources.contains_key(&spawn.source)
+            || !installation_areas.contains_key(&spawn.installation)
+            || spawn.seed == [0, 0, 0, 0]
+        {
+            continue;
+        }
+
+        // Reproduce the sound's parameters from its logged seed.
+        let sound_id = sound_id_gen.generate_next();
+        let mut rng = XorShiftRng::from_seed(spawn.seed);
+        let movement = match spawn.fixed {
+            true => Movement::Fixed(spawn.position),
+            false => generate_movement(
+                &mut rng,
+                spawn.source,
+                sources,
+                spawn.installation,
+                installations,
+                schedule_overrides,
+                installation_areas,
+                &target_sounds_per_installation,
+                &active_sounds,
+                tracked_points,
+            ),
+        };
+        let active_sound = spawn_active_sound(
+            &mut rng,
+            sound_id,
+            spawn.source,
+            sources,
+            spawn.installation,
+            spawn.position,
+            movement,
+            tick.playback_duration,
+            frame_count,
+            audio_streams,
+            realtime_source_latency,
+        );
+        active_sounds.insert(sound_id, active_sound);
+    }
+    if replay.as_ref().map(|r| r.is_finished()) == Some(true) {
+        *replay = None;
+    }
+
+    // Spawn any chained sounds whose delay has passed.
+    if pending_chain_sounds.iter().any(|p| p.due <= tick.playback_duration) {
+        let (due, waiting): (PendingChainSounds, PendingChainSounds) = pending_chain_sounds
+            .drain(..)
+            .partition(|p| p.due <= tick.playback_duration);
+        *pending_chain_sounds = waiting;
+        for pending in due {
+            // Skip sources that have since been removed or disabled by the schedule.
+            let source = match sources.get(&pending.source_id) {
+                None => continue,
+                Some(source) => source,
+            };
+            if schedule_overrides.disabled_sources.contains(&pending.source_id) {
+                continue;
+            }
+
+            // Skip sources that are withheld by one of the project's rules.
+            if !rules.is_empty() {
+                let targets = rule_targets(&pending.source_id, source, groups);
+                let source_id = pending.source_id;
+                if rules::check(rules, source_id, &targets, rule_activity, tick.instant).is_some() {
+                    continue;
+                }
+            }
+
+            // Spawn within the installation closest to the origin, otherwise any with an area.
+            let installation = match pending.origin {
+                Some(position) => {
+                    let sound = ActiveSoundPosition { source_id: pending.source_id, position };
+                    closest_assigned_installation(&sound, sources, installation_areas)
+                },
+                None => {
+                    let installations: Vec<_> = source
+                        .installations
+                        .iter()
+                        .filter(|i| installation_areas.contains_key(i))
+                        .cloned()
+                        .collect();
+                    match installations.is_empty() {
+                        true => None,
+                        false => {
+                            let mut rng = nannou::rand::thread_rng();
+                            Some(installations[rng.gen_range(0, installations.len())])
+                        },
+                    }
+                },
+            };
+            let installation = match installation {
+                None => continue,
+                Some(installation) => installation,
+            };
+
+            // Fix the sound at its origin if requested, otherwise use the source's movement.
+            let sound_id = sound_id_gen.generate_next();
+            let rng_seed = sound_seed(&seed, &pending.source_id, &sound_id);
+            let mut rng = XorShiftRng::from_seed(rng_seed);
+            let (movement, fixed) = match (pending.same_position, pending.origin) {
+                (true, Some(origin)) => (Movement::Fixed(origin), true),
+                _ => (generate_movement(
+                    &mut rng,
+                    pending.source_id,
+                    sources,
+                    installation,
+                    installations,
+                    schedule_overrides,
+                    installation_areas,
+                    &target_sounds_per_installation,
+                    &active_sounds,
+                    tracked_points,
+                ), false),
+            };
+            let initial_position = movement.position();
+            let active_sound = spawn_active_sound(
+                &mut rng,
+                sound_id,
+                pending.source_id,
+                sources,
+                installation,
+                initial_position,
+                movement,
+                tick.playback_duration,
+                frame_count,
+                audio_streams,
+                realtime_source_latency,
+            );
+            sources_last_used.insert(pending.source_id, tick.instant);
+
+            // Rules checked for the following sounds must account for this one.
+            if !rules.is_empty() {
+                let source = &sources[&pending.source_id];
+                let targets = rule_targets(&pending.source_id, source, groups);
+                rule_activity.insert_active(&targets);
+                rule_activity.insert_spawned(&targets, tick.instant);
+            }
+
+            let event = spawn_event(sound_id, &active_sound, sources, groups, rng_seed, fixed);
+            log_event(event_log, schedule.utc_offset_minutes, tick.playback_duration, event);
+            active_sounds.insert(sound_id, active_sound);
         }
     }
 
@@ -1426,203 +2907,283 @@ fn tick(model: &mut Model, tick: Tick) {
         active_sounds_per_installation,
     );
 
-    // Determine how many sounds to add (if any) by finding the difference between the target
-    // number and actual number.
-    'installations: for (installation, &num_target_sounds) in target_sounds_per_installation.iter() {
-        let num_active_sounds = match active_sounds_per_installation.get(installation) {
-            None => 0,
-            Some(sounds) => sounds.len(),
-        };
-        let sounds_to_add = if num_target_sounds > num_active_sounds {
-            num_target_sounds - num_active_sounds
-        } else {
-            // If there are no sounds to add, move on to the next installation.
-            continue 'installations;
-        };
-
-        // The movement area associated with this installation.
-        //
-        // If there is no area, there is nowhere we can safely place sounds so we continue.
-        let installation_area = match installation_areas.get(installation) {
-            Some(area) => area,
-            None => continue 'installations,
-        };
-
-        // Find a source from the available groups for each sound that is to be added.
-        //
-        // Each time a sound is added the available group from which it was sourced should be
-        // updated and the vec should be re-sorted.
-        for _ in 0..sounds_to_add {
-            {
-                // Collect available groups of sounds (based on occurrence rate and simultaneous sounds).
-                update_available_groups(
-                    &tick,
-                    sources,
-                    groups,
-                    active_sounds,
-                    groups_last_used,
-                    available_groups,
-                );
+    // Check all sources against the project's rules, sharing any violations with the GUI.
+    update_rule_activity(sources, groups, active_sounds, sources_last_used, rules, rule_activity);
+    update_rule_violations(&tick, rules, sources, groups, rule_activity, rule_violations);
+
+    // While replaying an event log, only the logged sounds are spawned.
+    if replay.is_none() {
+        // Determine how many sounds to add (if any) by finding the difference between the target
+        // number and actual number.
+        let target_sounds = target_sounds_per_installation.iter();
+        'installations: for (installation, &num_target_sounds) in target_sounds {
+            let num_active_sounds = match active_sounds_per_installation.get(installation) {
+                None => 0,
+                Some(sounds) => sounds.len(),
+            };
+            let sounds_to_add = if num_target_sounds > num_active_sounds {
+                num_target_sounds - num_active_sounds
+            } else {
+                // If there are no sounds to add, move on to the next installation.
+                continue 'installations;
+            };
 
-                // If there are no available groups, go to the next installation.
-                if available_groups.is_empty() {
-                    continue 'installations;
-                }
+            // The movement area associated with this installation.
+            //
+            // If there is no area, there is nowhere we can safely place sounds so we continue.
+            let installation_area = match installation_areas.get(installation) {
+                Some(area) => area,
+                None => continue 'installations,
+            };
 
-                // Find all available sources.
-                update_available_sources(
-                    installation,
-                    &tick,
-                    sources,
-                    active_sounds,
-                    sources_last_used,
-                    available_groups,
-                    available_sources,
-                );
+            // Find a source from the available groups for each sound that is to be added.
+            //
+            // Each time a sound is added the available group from which it was sourced should be
+            // updated and the vec should be re-sorted.
+            for _ in 0..sounds_to_add {
+                {
+                    // Collect available groups of sounds (based on occurrence rate and simultaneous sounds).
+                    update_available_groups(
+                        &tick,
+                        sources,
+                        groups,
+                        schedule_overrides,
+                        active_sounds,
+                        groups_last_used,
+                        available_groups,
+                    );
 
-                // If there are no available sources for this group, continue to the next
-                // installtion.
-                if available_sources.is_empty() {
-                    continue 'installations;
-                }
+                    // If there are no available groups, go to the next installation.
+                    if available_groups.is_empty() {
+                        continue 'installations;
+                    }
 
-                // Sort the groups by:
-                //
-                // 1. The number of sounds needed
-                // 2. The duration until a sound is needed to beat the occurrence rate.
-                available_groups.sort_by(|a, b| suitability(&a.suitability, &b.suitability));
-
-                // Sort the groups by:
-                //
-                // 1. The number of sounds needed
-                // 2. The duration until a sound is needed to beat the occurrence rate.
-                available_sources.sort_by(|a, b| suitability(&a.suitability, &b.suitability));
-
-                // Retrieve one of the most suitable groups from which this source will be "picked".
-                let group_index: usize = {
-                    let num_equal = utils::count_equal(&*available_groups, |a, b| {
-                        suitability(&a.suitability, &b.suitability)
-                    });
-                    nannou::rand::thread_rng().gen_range(0, num_equal)
-                };
+                    // Find all available sources.
+                    update_available_sources(
+                        installation,
+                        &tick,
+                        sources,
+                        active_sounds,
+                        sources_last_used,
+                        groups,
+                        schedule_overrides,
+                        tracked_points,
+                        rules,
+                        rule_activity,
+                        available_groups,
+                        available_sources,
+                    );
 
-                // Retrieve one of the most suitable sources.
-                let source_index: usize = {
-                    let num_equal = utils::count_equal(&*available_sources, |a, b| {
-                        suitability(&a.suitability, &b.suitability)
-                    });
-                    nannou::rand::thread_rng().gen_range(0, num_equal)
-                };
+                    // If there are no available sources for this group, continue to the next
+                    // installtion.
+                    if available_sources.is_empty() {
+                        continue 'installations;
+                    }
 
-                // Pick one of the most suitable sources.
-                {
-                    //let source = find_equally_suitable(available_sources.iter().map(|s| &s.suitability));
-                    let source = &available_sources[source_index];
+                    // Sort the groups by:
+                    //
+                    // 1. The number of sounds needed
+                    // 2. The duration until a sound is needed to beat the occurrence rate.
+                    available_groups.sort_by(|a, b| suitability(&a.suitability, &b.suitability));
 
-                    // TODO: Determine the initial position of the sound based on:
+                    // Sort the groups by:
                     //
-                    // 1. Installation for which we're triggereing a sound.
-                    // 2. Movement properties and constraints of the source and group.
-                    let initial_position = {
+                    // 1. The number of sounds needed
+                    // 2. The duration until a sound is needed to beat the occurrence rate.
+                    available_sources.sort_by(|a, b| suitability(&a.suitability, &b.suitability));
+
+                    // Retrieve one of the most suitable groups from which this source will be
+                    // "picked".
+                    let group_index: usize = {
+                        let num_equal = utils::count_equal(&*available_groups, |a, b| {
+                            suitability(&a.suitability, &b.suitability)
+                        });
+                        nannou::rand::thread_rng().gen_range(0, num_equal)
+                    };
+
+                    // Retrieve one of the most suitable sources, taking their weights into
+                    // account.
+                    let source_index: usize = {
                         let mut rng = nannou::rand::thread_rng();
-                        let left: bool = rng.gen();
-                        let x_mag: f64 = rng.gen();
-                        let x = match left {
-                            true => {
-                                Metres(x_mag)
-                                    * (installation_area.centroid.x
-                                        - installation_area.bounding_rect.left)
-                                    + installation_area.centroid.x
-                            }
-                            false => {
-                                Metres(x_mag)
-                                    * (installation_area.centroid.x
-                                        - installation_area.bounding_rect.right)
-                                    + installation_area.centroid.x
-                            }
-                        };
-                        let down: bool = rng.gen();
-                        let y_mag: f64 = rng.gen();
-                        let y = match down {
-                            true => {
-                                Metres(y_mag)
-                                    * (installation_area.centroid.y
-                                        - installation_area.bounding_rect.bottom)
-                                    + installation_area.centroid.y
-                            }
-                            false => {
-                                Metres(y_mag)
-                                    * (installation_area.centroid.y
-                                        - installation_area.bounding_rect.top)
-                                    + installation_area.centroid.y
-                            }
-                        };
-                        let point = Point2 { x, y };
-                        let radians = rng.gen::<f32>() * 2.0 * ::std::f32::consts::PI;
-                        audio::sound::Position { point, radians }
+                        choose_source_index(&mut rng, &*available_sources)
                     };
 
-                    // Generate the attack and release durations based on their source ranges.
-                    let mut rng = nannou::rand::thread_rng();
-                    let attack_duration_frames =
-                        audio::source::random_playback_duration(&mut rng, source.attack_duration)
-                            .to_samples(audio::SAMPLE_RATE);
-                    let release_duration_frames =
-                        audio::source::random_playback_duration(&mut rng, source.release_duration)
-                            .to_samples(audio::SAMPLE_RATE);
-                    let duration_frames =
-                        audio::source::random_playback_duration(&mut rng, source.playback_duration)
-                            .to_samples(audio::SAMPLE_RATE);
-
-                    // This is not a continuous preview (this is only used for GUI sounds).
-                    let continuous_preview = false;
-
-                    // Choose a movement type based on the source's assigned options.
-                    let movement = generate_movement(
-                        source.id,
-                        &sources,
-                        *installation,
-                        installations,
-                        installation_areas,
-                        &target_sounds_per_installation,
-                        &active_sounds,
-                    );
+                    // Pick one of the most suitable sources.
+                    {
+                        //let source = find_equally_suitable(available_sources.iter().map(|s| &s.suitability));
+                        let source = &available_sources[source_index];
+
+                        // TODO: Determine the initial position of the sound based on:
+                        //
+                        // 1. Installation for which we're triggereing a sound.
+                        // 2. Movement properties and constraints of the source and group.
+                        let initial_position = {
+                            let mut rng = nannou::rand::thread_rng();
+                            let left: bool = rng.gen();
+                            let x_mag: f64 = rng.gen();
+                            let x = match left {
+                                true => {
+                                    Metres(x_mag)
+                                        * (installation_area.centroid.x
+                                            - installation_area.bounding_rect.left)
+                                        + installation_area.centroid.x
+                                }
+                                false => {
+                                    Metres(x_mag)
+                                        * (installation_area.centroid.x
+                                            - installation_area.bounding_rect.right)
+                                        + installation_area.centroid.x
+                                }
+                            };
+                            let down: bool = rng.gen();
+                            let y_mag: f64 = rng.gen();
+                            let y = match down {
+                                true => {
+                                    Metres(y_mag)
+                                        * (installation_area.centroid.y
+                                            - installation_area.bounding_rect.bottom)
+                                        + installation_area.centroid.y
+                                }
+                                false => {
+                                    Metres(y_mag)
+                                        * (installation_area.centroid.y
+                                            - installation_area.bounding_rect.top)
+                                        + installation_area.centroid.y
+                                }
+                            };
+                            // Resample within the area if outside of the installation's polygon.
+                            let point = match installation_area.contains(Point2 { x, y }) {
+                                true => Point2 { x, y },
+                                false => installation_area.random_point(&mut rng),
+                            };
+                            let radians = rng.gen::<f32>() * 2.0 * ::std::f32::consts::PI;
+                            audio::sound::Position { point, radians }
+                        };
 
-                    // Spawn the sound from this source
-                    let audio_source = sources[&source.id].to_audio_source();
-                    let source_id = source.id;
-                    let sound_id = sound_id_gen.generate_next();
-                    let sound = audio::sound::spawn_from_source(
-                        sound_id,
-                        source_id,
-                        &audio_source,
-                        initial_position,
-                        attack_duration_frames,
-                        release_duration_frames,
-                        continuous_preview,
-                        Some(duration_frames),
-                        frame_count.load(atomic::Ordering::Relaxed) as _,
-                        wav_reader,
-                        audio_input_stream,
-                        audio_output_stream,
-                        realtime_source_latency,
-                    );
+                        // Choose a movement type based on the source's assigned options.
+                        //
+                        // The sound's parameters are drawn from a seed that is logged so that the
+                        // sound may be replayed.
+                        let sound_id = sound_id_gen.generate_next();
+                        let rng_seed = sound_seed(&seed, &source.id, &sound_id);
+                        let mut rng = XorShiftRng::from_seed(rng_seed);
+                        let movement = generate_movement(
+                            &mut rng,
+                            source.id,
+                            &sources,
+                            *installation,
+                            installations,
+                            schedule_overrides,
+                            installation_areas,
+                            &target_sounds_per_installation,
+                            &active_sounds,
+                            tracked_points,
+                        );
 
-                    // Track the time at which the group and source were last used.
-                    groups_last_used.insert(available_groups[group_index].id, tick.instant);
-                    sources_last_used.insert(source_id, tick.instant);
+                        // Spawn the sound from this source.
+                        let source_id = source.id;
+                        let active_sound = spawn_active_sound(
+                            &mut rng,
+                            sound_id,
+                            source_id,
+                            sources,
+                            *installation,
+                            initial_position,
+                            movement,
+                            tick.playback_duration,
+                            frame_count,
+                            audio_streams,
+                            realtime_source_latency,
+                        );
 
-                    // Create the active sound for out use.
-                    let active_sound = ActiveSound {
-                        initial_installation: *installation,
-                        handle: sound,
-                        movement,
-                    };
+                        // Track the time at which the group and source were last used.
+                        groups_last_used.insert(available_groups[group_index].id, tick.instant);
+                        sources_last_used.insert(source_id, tick.instant);
 
-                    // Store the new active sound.
-                    active_sounds.insert(sound_id, active_sound);
+                        // Rules checked for the following sounds must account for this one.
+                        if !rules.is_empty() {
+                            let targets = rule_targets(&source_id, &sources[&source_id], groups);
+                            rule_activity.insert_active(&targets);
+                            rule_activity.insert_spawned(&targets, tick.instant);
+                        }
+
+                        // Log and store the new active sound.
+                        let event =
+                            spawn_event(sound_id, &active_sound, sources, groups, rng_seed, false);
+                        let utc_offset_minutes = schedule.utc_offset_minutes;
+                        log_event(event_log, utc_offset_minutes, tick.playback_duration, event);
+                        active_sounds.insert(sound_id, active_sound);
+                    }
                 }
             }
         }
     }
 }
+
+#[test]
+fn test_weighted_choice() {
+    // A source with twice the weight is expected to be chosen twice as often.
+    let weights = [1.0, 2.0, 0.0, 1.0];
+    assert_eq!(expected_shares(&weights), vec![0.25, 0.5, 0.0, 0.25]);
+    assert_eq!(expected_shares(&[0.0, 0.0]), vec![0.5, 0.5]);
+
+    // Weights only apply between sources within the same suitability band.
+    let new_suitability = |num_sounds_needed, until: Option<f64>| Suitability {
+        num_sounds_needed,
+        num_available_sounds: num_sounds_needed + 1,
+        timing: until.map(|ms| Timing {
+            duration_since_min_interval: Ms(0.0),
+            duration_until_sound_needed: Ms(ms),
+        }),
+        occurrence_rate_interval: Range { min: Ms(0.0), max: Ms(0.0) },
+    };
+    let mut sorted = vec![
+        new_suitability(0, Some(2_000.0)),
+        new_suitability(0, Some(-500.0)),
+        new_suitability(0, Some(1_000.0)),
+        new_suitability(0, None),
+        new_suitability(1, Some(3_000.0)),
+    ];
+    sorted.sort_by(suitability);
+    assert_eq!(utils::count_equal(&sorted, |a, b| suitability_band(a, b)), 1);
+    assert_eq!(utils::count_equal(&sorted[1..], |a, b| suitability_band(a, b)), 2);
+    assert_eq!(utils::count_equal(&sorted[3..], |a, b| suitability_band(a, b)), 2);
+
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let mut counts = [0; 4];
+    for _ in 0..10_000 {
+        counts[weighted_index(&mut rng, &weights)] += 1;
+    }
+    assert_eq!(counts[2], 0);
+    assert!(counts[1] > counts[0] * 3 / 2 && counts[1] > counts[3] * 3 / 2);
+
+    // With equal weights, only the most suitable source within the band is chosen.
+    let new_source = |id, suitability, weight| AvailableSource {
+        id: audio::source::Id(id),
+        suitability,
+        weight,
+        playback_duration: Range { min: Ms(0.0), max: Ms(0.0) },
+        attack_duration: Range { min: Ms(0.0), max: Ms(0.0) },
+        release_duration: Range { min: Ms(0.0), max: Ms(0.0) },
+        playback_rate: Range { min: 1.0, max: 1.0 },
+    };
+    let mut sources = vec![
+        new_source(0, new_suitability(0, Some(-500.0)), 1.0),
+        new_source(1, new_suitability(0, Some(-100.0)), 1.0),
+        new_source(2, new_suitability(0, Some(2_000.0)), 1.0),
+    ];
+    sources.sort_by(|a, b| suitability(&a.suitability, &b.suitability));
+    let most_suitable = sources[0].id;
+    for _ in 0..100 {
+        let index = choose_source_index(&mut rng, &sources);
+        assert_eq!(sources[index].id, most_suitable);
+    }
+
+    // Differing weights spread the choice across the band.
+    sources.iter_mut().find(|s| s.id != most_suitable).unwrap().weight = 2.0;
+    let mut chosen = FxHashSet::default();
+    for _ in 0..1_000 {
+        chosen.insert(sources[choose_source_index(&mut rng, &sources)].id);
+    }
+    assert_eq!(chosen.len(), 2);
+}
@@ -1,4 +1,5 @@
 use audio;
+use audio::source::movement::Flocking;
 use fxhash::FxHashMap;
 use installation;
 use metres::Metres;
@@ -20,6 +21,7 @@ type Vector = Vector2<Metres>;
 ///
 /// - A desired target location within one of the assigned installations.
 /// - A user-defined movement weight affecting the max velocity and rotation speeds.
+/// - The separation, alignment and cohesion of neighbouring agents within the same flock.
 #[derive(Debug)]
 pub struct Agent {
     /// The current location of the agent.
@@ -37,6 +39,17 @@ pub struct Agent {
     /// Specifies whether or not the orientation of the agent should be summed onto the channel
     /// radians.
     pub directional: bool,
+    /// The weights of the forces applied between the agent and its neighbours.
+    pub flocking: Flocking,
+}
+
+/// The state of a neighbouring agent required for flocking.
+#[derive(Copy, Clone, Debug)]
+pub struct Neighbour {
+    /// The current location of the neighbour.
+    pub location: Point,
+    /// The current velocity of the neighbour.
+    pub velocity: Vector,
 }
 
 /// Information about an installation required by the Agent.
@@ -66,6 +79,7 @@ impl Agent {
         max_force: f64,
         max_rotation: f64,
         directional: bool,
+        flocking: Flocking,
     ) -> Self
     where
         R: Rng,
@@ -93,6 +107,7 @@ impl Agent {
             max_force,
             max_rotation,
             directional,
+            flocking,
         };
         agent
     }
@@ -123,6 +138,29 @@ impl Agent {
         )
     }
 
+    /// The state of the agent as perceived by its neighbours.
+    pub fn neighbour(&self) -> Neighbour {
+        Neighbour {
+            location: self.location,
+            velocity: self.velocity,
+        }
+    }
+
+    /// Produce the agent's flocking force for the given neighbours.
+    ///
+    /// Neighbours beyond the flocking radius are ignored. The given neighbours should not include
+    /// the agent itself.
+    pub fn flock_force(&self, neighbours: &[Neighbour]) -> Vector {
+        flock_force(
+            self.location,
+            self.velocity,
+            neighbours,
+            &self.flocking,
+            self.max_speed,
+            self.max_force,
+        )
+    }
+
     /// Applies the given force to the agent, updating its internal state appropriately.
     pub fn apply_force(&mut self, force: Vector, delta_time: &time::Duration) {
         use std::f64::consts::PI;
@@ -160,6 +198,7 @@ impl Agent {
         mut rng: R,
         delta_time: &time::Duration,
         installations: &InstallationDataMap,
+        neighbours: &[Neighbour],
     ) where
         R: Rng,
     {
@@ -171,7 +210,11 @@ impl Agent {
         }
 
         // Determine the steering force to apply based on how much time has passed.
-        let force = self.seek_force();
+        let mut force = self.seek_force();
+        if self.flocking.is_enabled() && !neighbours.is_empty() {
+            let flock_force = vt2::to_f64(self.flock_force(neighbours));
+            force = vt2::to_metres(vt2::to_f64(force) + flock_force);
+        }
         self.apply_force(force, delta_time);
 
         // If we've reached the target, pick a new one.
@@ -275,15 +318,13 @@ where
 }
 
 /// Generate a target location within the given installation.
-fn generate_installation_target<R>(mut rng: R, installation_area: &super::Area) -> Point
+///
+/// If the installation's area is outlined by a polygon, the target lies within the polygon.
+fn generate_installation_target<R>(rng: R, installation_area: &super::Area) -> Point
 where
     R: Rng,
 {
-    let x_len = installation_area.bounding_rect.right - installation_area.bounding_rect.left;
-    let y_len = installation_area.bounding_rect.top - installation_area.bounding_rect.bottom;
-    let x = installation_area.bounding_rect.left + x_len * rng.gen::<f64>();
-    let y = installation_area.bounding_rect.bottom + y_len * rng.gen::<f64>();
-    Point2 { x, y }
+    installation_area.random_point(rng)
 }
 
 /// Whether or not the current point has reached the target.
@@ -337,3 +378,90 @@ fn seek_force(
     let steering_limited = limit_magnitude(steering_force, max_force);
     steering_limited
 }
+
+/// Produces the weighted sum of the separation, alignment and cohesion forces for an agent.
+///
+/// Each force steers the agent toward a desired velocity of `max_speed` magnitude and is limited
+/// by `max_force` before being weighted.
+fn flock_force(
+    current_position: Point,
+    current_velocity: Vector,
+    neighbours: &[Neighbour],
+    flocking: &Flocking,
+    max_speed: f64,
+    max_force: f64,
+) -> Vector {
+    let position = pt2::to_f64(current_position);
+    let velocity = vt2::to_f64(current_velocity);
+    let steer = |desired: Vector2<f64>| -> Vector2<f64> {
+        if desired.magnitude2() == 0.0 {
+            return vec2(0.0, 0.0);
+        }
+        let desired = desired.normalize() * max_speed;
+        let steering_force = steering_force(current_velocity, vt2::to_metres(desired));
+        vt2::to_f64(limit_magnitude(steering_force, max_force))
+    };
+
+    let mut away = vec2(0.0, 0.0);
+    let mut velocity_sum = vec2(0.0, 0.0);
+    let mut location_sum = vec2(0.0, 0.0);
+    let mut count = 0;
+    for neighbour in neighbours {
+        let location = pt2::to_f64(neighbour.location);
+        let distance = position.distance(location);
+        if distance > flocking.radius {
+            continue;
+        }
+        // Steer away from crowding neighbours more strongly the closer they are.
+        if distance > 0.0 && distance < flocking.separation_distance {
+            away += (position - location).normalize() / distance;
+        }
+        velocity_sum += vt2::to_f64(neighbour.velocity);
+        location_sum += location.to_vec();
+        count += 1;
+    }
+
+    if count == 0 {
+        return vt2::to_metres(vec2(0.0, 0.0));
+    }
+
+    let separation = steer(away);
+    let alignment = steer(velocity_sum / count as f64);
+    let cohesion = steer(location_sum / count as f64 - position.to_vec());
+    let force = separation * flocking.separation
+        + alignment * flocking.alignment
+        + cohesion * flocking.cohesion;
+    vt2::to_metres(force)
+}
+
+#[test]
+fn test_flock_force() {
+    use audio::source::movement::FlockNeighbours;
+    let flocking = Flocking {
+        neighbours: FlockNeighbours::Source,
+        radius: 5.0,
+        separation_distance: 2.0,
+        separation: 1.0,
+        alignment: 0.0,
+        cohesion: 0.0,
+    };
+    let still = vt2::to_metres(vec2(0.0, 0.0));
+    let at = |x, y| pt2::to_metres(pt2(x, y));
+    let neighbour = |x, y| Neighbour { location: at(x, y), velocity: still };
+
+    let force = |neighbours: &[Neighbour], flocking: &Flocking| {
+        vt2::to_f64(flock_force(at(0.0, 0.0), still, neighbours, flocking, 1.0, 0.5))
+    };
+
+    // Separation pushes the agent away from a crowding neighbour, limited by the max force.
+    let f = force(&[neighbour(1.0, 0.0)], &flocking);
+    assert!((f.x + 0.5).abs() < 1e-9 && f.y.abs() < 1e-9);
+
+    // Neighbours beyond the radius are ignored.
+    assert_eq!(force(&[neighbour(6.0, 0.0)], &flocking), vec2(0.0, 0.0));
+
+    // Cohesion pulls the agent toward neighbours outside the separation distance.
+    let flocking = Flocking { separation: 0.0, cohesion: 2.0, ..flocking };
+    let f = force(&[neighbour(0.0, 3.0), neighbour(0.0, 4.0)], &flocking);
+    assert!(f.x.abs() < 1e-9 && (f.y - 1.0).abs() < 1e-9);
+}
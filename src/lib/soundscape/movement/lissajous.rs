@@ -0,0 +1,150 @@
+use audio;
+use nannou::prelude::*;
+use std::time;
+use super::Area;
+use utils::{duration_to_secs, fmod, pt2};
+
+/// A movement implementation that traces a Lissajous figure over the installation area.
+///
+/// The figure is centred within the area of the installation to which it is assigned and scaled
+/// to its bounding rectangle. If the area is outlined by a polygon, any part of the figure outside
+/// of the polygon follows its edge.
+#[derive(Debug)]
+pub struct Lissajous {
+    /// The frequency of the horizontal oscillation relative to the rate.
+    pub frequency_x: usize,
+    /// The frequency of the vertical oscillation relative to the rate.
+    pub frequency_y: usize,
+    /// The phase offset in radians of the horizontal oscillation.
+    pub phase: f64,
+    /// The rate in cycles per second at which the figure is traced.
+    pub rate: f64,
+    /// Describes the dimensions of the figure using a normalised value.
+    pub normalised_dimensions: Vector2<f64>,
+    /// Whether or not the direction of travel should be applied to channel rotation.
+    pub directional: bool,
+    /// State that is updated during a call to `Update`.
+    state: State,
+}
+
+/// State that is updated during a call to `Update`.
+#[derive(Debug)]
+struct State {
+    /// The current phase of the base cycle in radians.
+    theta: f64,
+    /// The same as `theta` but described in "metres" space over the exhibition.
+    sound_position: audio::sound::Position,
+}
+
+impl Lissajous {
+    /// Create a new **Lissajous** movement type starting at the given phase of the base cycle.
+    pub fn new(
+        frequency_x: usize,
+        frequency_y: usize,
+        phase: f64,
+        rate: f64,
+        normalised_dimensions: Vector2<f64>,
+        directional: bool,
+        theta: f64,
+        installation_area: &Area,
+    ) -> Self
+    {
+        let point = installation_area.middle();
+        let radians = 0.0;
+        let sound_position = audio::sound::Position { point, radians };
+        let state = State { theta, sound_position };
+        let mut lissajous = Lissajous {
+            frequency_x,
+            frequency_y,
+            phase,
+            rate,
+            normalised_dimensions,
+            directional,
+            state,
+        };
+        lissajous.update_position(installation_area);
+        lissajous
+    }
+
+    /// The current position along the Lissajous figure.
+    pub fn position(&self) -> audio::sound::Position {
+        self.state.sound_position
+    }
+
+    /// Update the `Lissajous` state for the given past amount of time.
+    pub fn update(&mut self, delta_time: &time::Duration, installation_area: &Area) {
+        let delta_theta = self.rate * duration_to_secs(delta_time) * 2.0 * PI_F64;
+        self.state.theta = fmod(self.state.theta + delta_theta, 2.0 * PI_F64);
+        self.update_position(installation_area);
+    }
+
+    // Update the sound position from the current phase over the installation area.
+    fn update_position(&mut self, installation_area: &Area) {
+        let rect = &installation_area.bounding_rect;
+        let middle = pt2::to_f64(installation_area.middle());
+        let half_w = rect.width().0 * self.normalised_dimensions.x * 0.5;
+        let half_h = rect.height().0 * self.normalised_dimensions.y * 0.5;
+        let (fx, fy) = (self.frequency_x as f64, self.frequency_y as f64);
+        let theta = self.state.theta;
+        let x = middle.x + half_w * (fx * theta + self.phase).sin();
+        let y = middle.y + half_h * (fy * theta).sin();
+        self.state.sound_position.point = installation_area.clamp(pt2::to_metres(pt2(x, y)));
+
+        // The direction of travel is the derivative of the figure with respect to the phase.
+        let dx = half_w * fx * (fx * theta + self.phase).cos();
+        let dy = half_h * fy * (fy * theta).cos();
+        self.state.sound_position.radians = match self.directional {
+            false => 0.0,
+            true if dx == 0.0 && dy == 0.0 => self.state.sound_position.radians,
+            true => dy.atan2(dx) as f32,
+        };
+    }
+}
+
+#[test]
+fn test_lissajous_bounds() {
+    use metres::Metres;
+    use super::BoundingRect;
+    let rect = BoundingRect {
+        left: Metres(0.0),
+        right: Metres(10.0),
+        bottom: Metres(0.0),
+        top: Metres(4.0),
+    };
+    let rect_area = Area { bounding_rect: rect, centroid: rect.middle(), polygon: None };
+
+    // An L-shaped room whose bounding box includes the missing top-right corner.
+    let polygon = [(0.0, 0.0), (10.0, 0.0), (10.0, 2.0), (2.0, 2.0), (2.0, 4.0), (0.0, 4.0)];
+    let polygon: Vec<_> = polygon.iter().map(|&(x, y)| pt2::to_metres(pt2(x, y))).collect();
+    let l_area = Area::from_polygon(polygon.into()).unwrap();
+
+    // Step through a full cycle of a 3:2 figure spanning the whole area.
+    let steps = 1_000;
+    let rate = 0.25;
+    let dt = time::Duration::from_millis(4_000 / steps);
+    let dimensions = vec2(1.0, 1.0);
+    let new = |area: &Area| Lissajous::new(3, 2, PI_F64 / 2.0, rate, dimensions, true, 0.0, area);
+
+    // Within the rect the figure is never clamped, so the direction between the neighbouring
+    // positions of each step follows the derivative of the figure.
+    let mut lissajous = new(&rect_area);
+    let mut positions = vec![lissajous.position()];
+    for _ in 0..steps {
+        lissajous.update(&dt, &rect_area);
+        positions.push(lissajous.position());
+    }
+    for window in positions.windows(3) {
+        assert!(rect_area.contains(window[1].point));
+        let (a, b) = (pt2::to_f64(window[0].point), pt2::to_f64(window[2].point));
+        let heading = (b.y - a.y).atan2(b.x - a.x);
+        let diff = fmod(heading - window[1].radians as f64 + PI_F64, 2.0 * PI_F64) - PI_F64;
+        assert!(diff.abs() < 0.01);
+    }
+
+    // Within the L-shaped polygon the figure follows the edge of the missing corner.
+    let mut lissajous = new(&l_area);
+    for _ in 0..steps {
+        lissajous.update(&dt, &l_area);
+        assert!(l_area.contains(lissajous.position().point));
+    }
+}
@@ -1,12 +1,33 @@
 use audio;
 use metres::Metres;
+use nannou;
 use nannou::prelude::*;
+use nannou::rand::Rng;
+use std::sync::Arc;
+use utils::pt2;
 
 pub use self::agent::Agent;
+pub use self::lissajous::Lissajous;
 pub use self::ngon::Ngon;
+pub use self::orbit::Orbit;
+pub use self::path::Path;
+pub use self::tracked::{Tracked, TrackedPoint, TrackedPoints};
+pub use self::wander::Wander;
 
 pub mod agent;
+pub mod lissajous;
 pub mod ngon;
+pub mod orbit;
+pub mod path;
+pub mod tracked;
+pub mod wander;
+
+// The number of random points sampled within an area's bounding rect while searching for one
+// within its polygon.
+const MAX_POLYGON_SAMPLES: usize = 64;
+
+// The distance in metres from a polygon's edge within which a point is considered to lie on it.
+const POLYGON_EDGE_EPSILON: f64 = 1e-9;
 
 /// Whether the sound has fixed movement or generative movement.
 #[derive(Debug)]
@@ -24,6 +45,16 @@ pub enum Generative {
     Agent(Agent),
     /// A 2D N-sided, symmetrical polygon path tracing movement implementation.
     Ngon(Ngon),
+    /// Follows a smooth path through points authored over the floorplan.
+    Path(Path),
+    /// Circles a centre point within the installation area.
+    Orbit(Orbit),
+    /// Traces a Lissajous figure over the installation area.
+    Lissajous(Lissajous),
+    /// Drifts organically through the installation area, steered by coherent noise.
+    Wander(Wander),
+    /// Follows a point tracked by an external system over OSC.
+    Tracked(Tracked),
 }
 
 /// The bounding box for an iterator yielding points.
@@ -35,11 +66,19 @@ pub struct BoundingRect {
     pub bottom: Metres,
 }
 
-/// Includes the bounding box and
-#[derive(Copy, Clone, Debug)]
+/// The area of an installation within the exhibition.
+///
+/// Includes the bounding box and centroid along with the polygon outlining the area, if one was
+/// drawn over the floorplan.
+#[derive(Clone, Debug)]
 pub struct Area {
     pub bounding_rect: BoundingRect,
     pub centroid: Point2<Metres>,
+    /// The vertices of the polygon outlining the area.
+    ///
+    /// If `None`, the area is the bounding rect of the installation's speakers. The vertices are
+    /// shared with the soundscape's installation polygons rather than copied for each area.
+    pub polygon: Option<Arc<[Point2<Metres>]>>,
 }
 
 impl Generative {
@@ -48,6 +87,11 @@ impl Generative {
         match *self {
             Generative::Agent(ref agent) => agent.position(),
             Generative::Ngon(ref ngon) => ngon.position(),
+            Generative::Path(ref path) => path.position(),
+            Generative::Orbit(ref orbit) => orbit.position(),
+            Generative::Lissajous(ref lissajous) => lissajous.position(),
+            Generative::Wander(ref wander) => wander.position(),
+            Generative::Tracked(ref tracked) => tracked.position(),
         }
     }
 }
@@ -62,6 +106,98 @@ impl Movement {
     }
 }
 
+impl Area {
+    /// The area outlined by the given polygon.
+    ///
+    /// Returns `None` if the polygon has less than three vertices.
+    pub fn from_polygon(polygon: Arc<[Point2<Metres>]>) -> Option<Self> {
+        if polygon.len() < 3 {
+            return None;
+        }
+        let bounding_rect = BoundingRect::from_points(polygon.iter().cloned())?;
+        let centroid = match polygon_centroid(&polygon) {
+            Some(centroid) => centroid,
+            None => {
+                let centroid = nannou::geom::centroid(polygon.iter().map(|&p| pt2::to_f64(p)))?;
+                pt2::to_metres(centroid)
+            },
+        };
+        let polygon = Some(polygon);
+        Some(Area { bounding_rect, centroid, polygon })
+    }
+
+    /// Whether or not the given point lies within the area or on its edge.
+    ///
+    /// Points returned by `clamp` are always contained.
+    pub fn contains(&self, p: Point2<Metres>) -> bool {
+        match self.polygon {
+            Some(ref polygon) => {
+                if polygon_contains(polygon, p) {
+                    return true;
+                }
+                let edge = pt2::to_f64(closest_point_on_polygon(polygon, p));
+                edge.distance2(pt2::to_f64(p)) <= POLYGON_EDGE_EPSILON * POLYGON_EDGE_EPSILON
+            },
+            None => self.bounding_rect.contains(p),
+        }
+    }
+
+    /// The given point if it lies within the area, otherwise the closest point on its edge.
+    pub fn clamp(&self, p: Point2<Metres>) -> Point2<Metres> {
+        if self.contains(p) {
+            return p;
+        }
+        match self.polygon {
+            Some(ref polygon) => closest_point_on_polygon(polygon, p),
+            None => self.bounding_rect.clamp(p),
+        }
+    }
+
+    /// A point within the area that may be used as its middle.
+    ///
+    /// This is the middle of the bounding rect if it lies within the area (e.g. the area has no
+    /// polygon), otherwise the centroid if it lies within the area. If neither do (e.g. for a
+    /// U-shaped area), this is the middle of the widest span of the area at the centroid's height.
+    pub fn middle(&self) -> Point2<Metres> {
+        let middle = self.bounding_rect.middle();
+        if self.contains(middle) {
+            return middle;
+        }
+        if self.contains(self.centroid) {
+            return self.centroid;
+        }
+        self.polygon
+            .as_ref()
+            .and_then(|polygon| widest_span_middle(polygon, self.centroid.y))
+            .unwrap_or_else(|| self.clamp(middle))
+    }
+
+    /// Randomly sample a point within the area.
+    ///
+    /// Points are sampled uniformly within the bounding rect until one lies within the polygon.
+    /// If none is found after `MAX_POLYGON_SAMPLES`, the closest point within the polygon to the
+    /// last sample is returned.
+    pub fn random_point<R>(&self, mut rng: R) -> Point2<Metres>
+    where
+        R: Rng,
+    {
+        let rect = &self.bounding_rect;
+        let mut sample = || {
+            let x = rect.left + rect.width() * rng.gen::<f64>();
+            let y = rect.bottom + rect.height() * rng.gen::<f64>();
+            Point2 { x, y }
+        };
+        let mut p = sample();
+        for _ in 1..MAX_POLYGON_SAMPLES {
+            if self.contains(p) {
+                return p;
+            }
+            p = sample();
+        }
+        self.clamp(p)
+    }
+}
+
 impl BoundingRect {
     /// Initialise a bounding box at a single point in space.
     pub fn from_point(p: Point2<Metres>) -> Self {
@@ -111,4 +247,126 @@ impl BoundingRect {
     pub fn height(&self) -> Metres {
         self.top - self.bottom
     }
+
+    /// Whether or not the given point lies within the bounding box.
+    pub fn contains(&self, p: Point2<Metres>) -> bool {
+        p.x >= self.left && p.x <= self.right && p.y >= self.bottom && p.y <= self.top
+    }
+
+    /// The closest point within the bounding box to the given point.
+    pub fn clamp(&self, p: Point2<Metres>) -> Point2<Metres> {
+        let x = p.x.max(self.left).min(self.right);
+        let y = p.y.max(self.bottom).min(self.top);
+        Point2 { x, y }
+    }
+}
+
+/// Whether or not the given point lies within the polygon described by the given vertices.
+///
+/// Uses the even-odd rule, so the polygon may be concave (e.g. an L-shaped room).
+pub fn polygon_contains(polygon: &[Point2<Metres>], p: Point2<Metres>) -> bool {
+    let (x, y) = (p.x.0, p.y.0);
+    let mut inside = false;
+    let mut j = match polygon.len() {
+        0 => return false,
+        n => n - 1,
+    };
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[j]);
+        if (a.y.0 > y) != (b.y.0 > y) {
+            let cross_x = a.x.0 + (y - a.y.0) * (b.x.0 - a.x.0) / (b.y.0 - a.y.0);
+            if x < cross_x {
+                inside = !inside;
+            }
+        }
+        j = i;
+    }
+    inside
+}
+
+// The centroid of the region enclosed by the given polygon.
+//
+// Returns `None` if the polygon encloses no area.
+fn polygon_centroid(polygon: &[Point2<Metres>]) -> Option<Point2<Metres>> {
+    let (mut area, mut x, mut y) = (0.0, 0.0, 0.0);
+    for i in 0..polygon.len() {
+        let a = pt2::to_f64(polygon[i]);
+        let b = pt2::to_f64(polygon[(i + 1) % polygon.len()]);
+        let cross = a.x * b.y - b.x * a.y;
+        area += cross;
+        x += (a.x + b.x) * cross;
+        y += (a.y + b.y) * cross;
+    }
+    if area.abs() < ::std::f64::EPSILON {
+        return None;
+    }
+    let (x, y) = (Metres(x / (3.0 * area)), Metres(y / (3.0 * area)));
+    Some(Point2 { x, y })
+}
+
+// The middle of the widest span of the polygon's interior along the horizontal line at `y`.
+fn widest_span_middle(polygon: &[Point2<Metres>], y: Metres) -> Option<Point2<Metres>> {
+    let y = y.0;
+    let mut crossings = vec![];
+    for i in 0..polygon.len() {
+        let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+        if (a.y.0 > y) != (b.y.0 > y) {
+            crossings.push(a.x.0 + (y - a.y.0) * (b.x.0 - a.x.0) / (b.y.0 - a.y.0));
+        }
+    }
+    crossings.sort_by(|a, b| a.partial_cmp(b).expect("crossing was NaN"));
+    crossings
+        .chunks(2)
+        .filter(|span| span.len() == 2)
+        .max_by(|a, b| {
+            let (a, b) = (a[1] - a[0], b[1] - b[0]);
+            a.partial_cmp(&b).expect("span was NaN")
+        })
+        .map(|span| Point2 { x: Metres((span[0] + span[1]) * 0.5), y: Metres(y) })
+}
+
+// The closest point to `p` along the edges of the given polygon.
+fn closest_point_on_polygon(polygon: &[Point2<Metres>], p: Point2<Metres>) -> Point2<Metres> {
+    let p = pt2::to_f64(p);
+    let mut closest = None;
+    for i in 0..polygon.len() {
+        let a = pt2::to_f64(polygon[i]);
+        let b = pt2::to_f64(polygon[(i + 1) % polygon.len()]);
+        let ab = b - a;
+        let len2 = ab.magnitude2();
+        let t = match len2 > 0.0 {
+            true => ((p - a).dot(ab) / len2).max(0.0).min(1.0),
+            false => 0.0,
+        };
+        let q = a + ab * t;
+        let dist2 = p.distance2(q);
+        match closest {
+            Some((_, closest_dist2)) if closest_dist2 <= dist2 => (),
+            _ => closest = Some((q, dist2)),
+        }
+    }
+    closest.map(|(q, _)| pt2::to_metres(q)).unwrap_or(pt2::to_metres(p))
+}
+
+#[test]
+fn test_polygon_area() {
+    use nannou::rand::SeedableRng;
+    let m = |x, y| Point2 { x: Metres(x), y: Metres(y) };
+    // An L-shaped room whose bounding box includes the missing top-right corner.
+    let polygon = vec![
+        m(0.0, 0.0), m(4.0, 0.0), m(4.0, 2.0), m(2.0, 2.0), m(2.0, 4.0), m(0.0, 4.0),
+    ];
+    let area = Area::from_polygon(polygon[..].into()).unwrap();
+    assert!(area.contains(m(1.0, 3.0)));
+    assert!(area.contains(m(3.0, 1.0)));
+    assert!(!area.contains(m(3.0, 3.0)));
+    assert!(area.bounding_rect.contains(m(3.0, 3.0)));
+    assert_eq!(area.clamp(m(3.0, 2.5)), m(3.0, 2.0));
+    assert!(area.contains(area.clamp(m(3.0, 2.5))));
+    assert!(area.contains(area.middle()));
+    let mut rng = nannou::rand::XorShiftRng::from_seed([1, 2, 3, 4]);
+    for _ in 0..100 {
+        assert!(area.contains(area.random_point(&mut rng)));
+    }
+    assert!(Area::from_polygon(polygon[..2].into()).is_none());
 }
@@ -1,17 +1,27 @@
 use audio;
 use metres::Metres;
 use nannou::prelude::*;
+use std::sync::Arc;
 use std::time;
-use super::BoundingRect;
+use super::Area;
 use utils::{duration_to_secs, pt2};
 
 // The point and vector types in exhibition space.
 type Point = Point2<Metres>;
 type Vector = Vector2<Metres>;
 
+// The number of points checked along each line of the Ngon path when fitting it within a polygon.
+const FIT_SAMPLES_PER_LINE: usize = 8;
+
+// The number of steps taken when searching for the scale at which the Ngon fits within a polygon.
+const FIT_STEPS: usize = 10;
+
 /// A 2D N-sided, symmetrical polygon path tracing movement implementation.
 ///
 /// The bounding rectangle of the Ngon will match that of the installation to which it is assigned.
+/// If the installation's area is outlined by a polygon, the Ngon is centred within the polygon and
+/// scaled down until its path lies within it. The fitted scale is only searched for again when the
+/// polygon or the Ngon's parameters change.
 #[derive(Debug)]
 pub struct Ngon {
     /// The number of sides (or vertices) in the ngon.
@@ -40,6 +50,23 @@ struct State {
     position: Position,
     /// The same as `position` but described in "metres" space over the exhibition.
     sound_position: audio::sound::Position,
+    /// The path fitted within the polygon of the installation area, if it has one.
+    fit: Option<Fit>,
+}
+
+/// The middle and half dimensions of the path fitted within a polygon.
+#[derive(Debug)]
+struct Fit {
+    /// The polygon within which the path was fitted.
+    polygon: Arc<[Point]>,
+    /// The parameters of the Ngon at the time of fitting.
+    vertices: usize,
+    nth: usize,
+    normalised_dimensions: Vector2<f64>,
+    radians_offset: f64,
+    /// The resulting middle and half dimensions.
+    middle: Point,
+    half_dimensions: Vector,
 }
 
 #[derive(Debug)]
@@ -67,7 +94,7 @@ impl Ngon {
         normalised_dimensions: Vector2<f64>,
         radians_offset: f64,
         speed: f64,
-        installation_bounding_rect: &BoundingRect,
+        installation_area: &Area,
     ) -> Self
     {
         let start = 0;
@@ -76,21 +103,69 @@ impl Ngon {
         let lerp = 0.0;
         let position = Position { line, lerp };
         let radians = 0.0;
-        let (middle, half_dim) = middle_and_half_dimensions(
-            installation_bounding_rect,
-            normalised_dimensions,
-        );
-        let point = vertex_at_index(vertices, middle, half_dim, radians_offset, 0);
+        let point = installation_area.middle();
         let sound_position = audio::sound::Position { point, radians };
-        let state = State { sound_position, position };
-        Ngon {
+        let fit = None;
+        let state = State { sound_position, position, fit };
+        let mut ngon = Ngon {
             vertices,
             nth,
             normalised_dimensions,
             radians_offset,
             speed,
             state,
+        };
+        let (middle, half_dim) = ngon.middle_and_half_dimensions(installation_area);
+        ngon.state.sound_position.point =
+            vertex_at_index(vertices, middle, half_dim, radians_offset, 0);
+        ngon
+    }
+
+    // The middle of the given area and the half dimensions of the path within it.
+    //
+    // If the area is outlined by a polygon, the fit is reused until the polygon or the Ngon's
+    // parameters change.
+    fn middle_and_half_dimensions(&mut self, area: &Area) -> (Point, Vector) {
+        let polygon = match area.polygon {
+            None => {
+                self.state.fit = None;
+                return middle_and_half_dimensions(
+                    area,
+                    self.vertices,
+                    self.nth,
+                    self.normalised_dimensions,
+                    self.radians_offset,
+                );
+            },
+            Some(ref polygon) => polygon,
+        };
+        if let Some(ref fit) = self.state.fit {
+            if Arc::ptr_eq(&fit.polygon, polygon)
+                && fit.vertices == self.vertices
+                && fit.nth == self.nth
+                && fit.normalised_dimensions == self.normalised_dimensions
+                && fit.radians_offset == self.radians_offset
+            {
+                return (fit.middle, fit.half_dimensions);
+            }
         }
+        let (middle, half_dimensions) = middle_and_half_dimensions(
+            area,
+            self.vertices,
+            self.nth,
+            self.normalised_dimensions,
+            self.radians_offset,
+        );
+        self.state.fit = Some(Fit {
+            polygon: polygon.clone(),
+            vertices: self.vertices,
+            nth: self.nth,
+            normalised_dimensions: self.normalised_dimensions,
+            radians_offset: self.radians_offset,
+            middle,
+            half_dimensions,
+        });
+        (middle, half_dimensions)
     }
 }
 
@@ -110,20 +185,61 @@ fn vertex_at_index(
     Point2 { x, y }
 }
 
-// The middle of the given bounding rect and normalised dimensions halved read for use within the
+// The middle of the given area and normalised dimensions halved read for use within the
 // `vertex_at_index` function.
+//
+// If the area is outlined by a polygon, the dimensions are scaled down until the path lies within
+// it.
 fn middle_and_half_dimensions(
-    bounding_rect: &BoundingRect,
+    area: &Area,
+    vertices: usize,
+    nth: usize,
     normalised_dimensions: Vector2<f64>,
+    radians_offset: f64,
 ) -> (Point, Vector)
 {
-    let middle = bounding_rect.middle();
-    let width = bounding_rect.width() * normalised_dimensions.x;
-    let height = bounding_rect.height() * normalised_dimensions.y;
+    let middle = area.middle();
+    let width = area.bounding_rect.width() * normalised_dimensions.x;
+    let height = area.bounding_rect.height() * normalised_dimensions.y;
     let half_width = width * 0.5;
     let half_height = height * 0.5;
     let half_dimensions = Vector2 { x: half_width, y: half_height };
-    (middle, half_dimensions)
+    if area.polygon.is_none() {
+        return (middle, half_dimensions);
+    }
+
+    // Whether or not the path lies within the area when scaled by the given amount.
+    let scaled = |scale: f64| {
+        Vector2 { x: half_dimensions.x * scale, y: half_dimensions.y * scale }
+    };
+    let fits = |scale: f64| {
+        let half_dimensions = scaled(scale);
+        (0..vertices).all(|i| {
+            let start = vertex_at_index(vertices, middle, half_dimensions, radians_offset, i);
+            let end = (i + nth) % vertices;
+            let end = vertex_at_index(vertices, middle, half_dimensions, radians_offset, end);
+            (0..FIT_SAMPLES_PER_LINE + 1).all(|j| {
+                let lerp = j as f64 / FIT_SAMPLES_PER_LINE as f64;
+                let x = start.x + (end.x - start.x) * lerp;
+                let y = start.y + (end.y - start.y) * lerp;
+                area.contains(Point2 { x, y })
+            })
+        })
+    };
+
+    // Search for the greatest scale at which the path fits.
+    if fits(1.0) {
+        return (middle, half_dimensions);
+    }
+    let (mut min, mut max) = (0.0, 1.0);
+    for _ in 0..FIT_STEPS {
+        let scale = (min + max) * 0.5;
+        match fits(scale) {
+            true => min = scale,
+            false => max = scale,
+        }
+    }
+    (middle, scaled(min))
 }
 
 impl Ngon {
@@ -133,21 +249,19 @@ impl Ngon {
     }
 
     /// Update the `Ngon` state for the given past amount of time.
-    pub fn update(&mut self, delta_time: &time::Duration, installation_area: &BoundingRect) {
+    pub fn update(&mut self, delta_time: &time::Duration, installation_area: &Area) {
+        // Find the middle and the half width and height.
+        let (middle, half_dimensions) = self.middle_and_half_dimensions(installation_area);
+
         let Ngon {
             vertices,
             nth,
-            normalised_dimensions,
             radians_offset,
             speed,
             ref mut state,
-
+            ..
         } = *self;
 
-        // Find the middle and the half width and height.
-        let (middle, half_dimensions) =
-            middle_and_half_dimensions(installation_area, normalised_dimensions);
-
         // Shorthand for finding a vertex at a specific index.
         let vertex_at_index = |index| {
             vertex_at_index(vertices, middle, half_dimensions, radians_offset, index)
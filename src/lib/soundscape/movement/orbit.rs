@@ -0,0 +1,134 @@
+use audio;
+use nannou::prelude::*;
+use std::time;
+use super::{Area, BoundingRect};
+use utils::{duration_to_secs, fmod, pt2};
+
+/// A movement implementation that circles a centre point within the installation area.
+///
+/// The orbit is always contained by the bounding rectangle of the installation to which it is
+/// assigned. If the installation's area is outlined by a polygon, any part of the orbit outside of
+/// the polygon follows its edge.
+#[derive(Debug)]
+pub struct Orbit {
+    /// The centre of the orbit normalised to the bounds of the installation area.
+    pub centre: Point2<f64>,
+    /// The radius of the orbit using a normalised value.
+    ///
+    /// `1.0` means the orbit will reach the nearest bound of the installation area.
+    pub radius: f64,
+    /// The rate at which the orbit is travelled in metres per second.
+    pub speed: f64,
+    /// Whether the centre is circled clockwise or anticlockwise.
+    pub clockwise: bool,
+    /// Whether or not the direction of travel should be applied to channel rotation.
+    pub directional: bool,
+    /// State that is updated during a call to `Update`.
+    state: State,
+}
+
+/// State that is updated during a call to `Update`.
+#[derive(Debug)]
+struct State {
+    /// The current angle around the centre in radians.
+    angle: f64,
+    /// The same as `angle` but described in "metres" space over the exhibition.
+    sound_position: audio::sound::Position,
+}
+
+impl Orbit {
+    /// Create a new **Orbit** movement type starting at the given angle around the centre.
+    pub fn new(
+        centre: Point2<f64>,
+        radius: f64,
+        speed: f64,
+        clockwise: bool,
+        directional: bool,
+        angle: f64,
+        installation_area: &Area,
+    ) -> Self
+    {
+        let point = pt2::to_metres(centre);
+        let radians = 0.0;
+        let sound_position = audio::sound::Position { point, radians };
+        let state = State { angle, sound_position };
+        let mut orbit = Orbit { centre, radius, speed, clockwise, directional, state };
+        orbit.update_position(installation_area);
+        orbit
+    }
+
+    /// The current position around the orbit.
+    pub fn position(&self) -> audio::sound::Position {
+        self.state.sound_position
+    }
+
+    /// Update the `Orbit` state for the given past amount of time.
+    pub fn update(&mut self, delta_time: &time::Duration, installation_area: &Area) {
+        let rect = &installation_area.bounding_rect;
+        let (_, radius) = centre_and_radius(rect, self.centre, self.radius);
+        if radius > 0.0 {
+            let delta_angle = self.speed * duration_to_secs(delta_time) / radius;
+            let delta_angle = if self.clockwise { -delta_angle } else { delta_angle };
+            self.state.angle = fmod(self.state.angle + delta_angle, 2.0 * PI_F64);
+        }
+        self.update_position(installation_area);
+    }
+
+    // Update the sound position from the current angle around the installation area.
+    fn update_position(&mut self, installation_area: &Area) {
+        let rect = &installation_area.bounding_rect;
+        let (centre, radius) = centre_and_radius(rect, self.centre, self.radius);
+        let angle = self.state.angle;
+        let point = pt2(centre.x + angle.cos() * radius, centre.y + angle.sin() * radius);
+        let point = installation_area.clamp(pt2::to_metres(point));
+        let radians = match (self.directional, self.clockwise) {
+            (false, _) => 0.0,
+            (true, true) => (angle - PI_F64 * 0.5) as f32,
+            (true, false) => (angle + PI_F64 * 0.5) as f32,
+        };
+        self.state.sound_position = audio::sound::Position { point, radians };
+    }
+}
+
+// The centre of the orbit within the given bounding rect and the radius in metres.
+//
+// The radius is limited by the distance from the centre to the nearest bound.
+fn centre_and_radius(
+    bounding_rect: &BoundingRect,
+    normalised_centre: Point2<f64>,
+    normalised_radius: f64,
+) -> (Point2<f64>, f64)
+{
+    let (left, right) = (bounding_rect.left.0, bounding_rect.right.0);
+    let (bottom, top) = (bounding_rect.bottom.0, bounding_rect.top.0);
+    let x = left + (right - left) * normalised_centre.x;
+    let y = bottom + (top - bottom) * normalised_centre.y;
+    let max_radius = (x - left).min(right - x).min(y - bottom).min(top - y).max(0.0);
+    (pt2(x, y), max_radius * normalised_radius)
+}
+
+#[test]
+fn test_orbit_bounds() {
+    use metres::Metres;
+    let rect = BoundingRect {
+        left: Metres(0.0),
+        right: Metres(10.0),
+        bottom: Metres(0.0),
+        top: Metres(4.0),
+    };
+    let area = Area { bounding_rect: rect, centroid: rect.middle(), polygon: None };
+
+    // The radius is limited by the nearest bound to the centre.
+    let centre = pt2(0.5, 0.5);
+    assert_eq!(centre_and_radius(&rect, centre, 1.0), (pt2(5.0, 2.0), 2.0));
+    assert_eq!(centre_and_radius(&rect, pt2(0.1, 0.5), 1.0).1, 1.0);
+
+    // A quarter of the circumference later, an anticlockwise orbit is at the top of the circle.
+    let speed = PI_F64;
+    let mut orbit = Orbit::new(centre, 1.0, speed, false, true, 0.0, &area);
+    assert_eq!(orbit.position().point, pt2(Metres(7.0), Metres(2.0)));
+    orbit.update(&time::Duration::from_secs(1), &area);
+    let point = orbit.position().point;
+    assert!((point.x.0 - 5.0).abs() < 1e-9 && (point.y.0 - 4.0).abs() < 1e-9);
+    assert!((orbit.position().radians as f64 - PI_F64).abs() < 1e-6);
+}
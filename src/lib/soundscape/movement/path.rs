@@ -0,0 +1,274 @@
+use audio;
+use audio::source::movement::{PathMode, PathTiming};
+use metres::Metres;
+use nannou::prelude::*;
+use std::time;
+use time_calc::Ms;
+use utils::{duration_to_secs, fmod, pt2, Range};
+
+/// The number of lines used to approximate each span of the spline between two control points.
+pub const LINES_PER_SPAN: usize = 16;
+
+/// A path tracing movement implementation that follows a spline through authored control points.
+///
+/// The spline is approximated by a polyline that is rebuilt whenever the points or mode change.
+#[derive(Debug)]
+pub struct Path {
+    /// The rate at which the path is being travelled in metres per second.
+    pub speed: f64,
+    /// What happens upon reaching the end of the path.
+    mode: PathMode,
+    /// The polyline approximating the spline.
+    polyline: Polyline,
+    /// State that is updated during a call to `Update`.
+    state: State,
+}
+
+/// State that is updated during a call to `Update`.
+#[derive(Debug)]
+struct State {
+    /// The distance travelled through the current cycle of the path.
+    ///
+    /// When looping a cycle is the length of the path. When ping-ponging a cycle is twice the
+    /// length of the path, the second half of which is travelled in reverse.
+    travelled: f64,
+    /// The same as `travelled` but described as a position over the exhibition.
+    sound_position: audio::sound::Position,
+}
+
+/// A series of connected lines along with the distance to each point from the start.
+#[derive(Clone, Debug, Default)]
+pub struct Polyline {
+    points: Vec<Point2<f64>>,
+    distances: Vec<f64>,
+}
+
+impl Path {
+    /// Create a new **Path** movement type.
+    ///
+    /// `normalised_offset` describes the starting position as a fraction of a full cycle.
+    pub fn new(
+        points: &[Point2<Metres>],
+        mode: PathMode,
+        speed: f64,
+        normalised_offset: f64,
+    ) -> Self
+    {
+        let polyline = Polyline::spline(points, mode == PathMode::Loop);
+        let point = pt2(Metres(0.0), Metres(0.0));
+        let radians = 0.0;
+        let sound_position = audio::sound::Position { point, radians };
+        let state = State { travelled: 0.0, sound_position };
+        let mut path = Path { speed, mode, polyline, state };
+        path.state.travelled = path.cycle_length() * normalised_offset.min(1.0).max(0.0);
+        path.update_position();
+        path
+    }
+
+    /// The current position along the path.
+    pub fn position(&self) -> audio::sound::Position {
+        self.state.sound_position
+    }
+
+    /// The length of the path from the first point to the last in metres.
+    pub fn length(&self) -> f64 {
+        self.polyline.length()
+    }
+
+    /// The speed at which the path must be travelled to traverse it once over the given duration.
+    pub fn speed_for_duration(&self, duration: Ms) -> f64 {
+        let secs = duration.ms() / 1_000.0;
+        if secs <= 0.0 {
+            return 0.0;
+        }
+        self.length() / secs
+    }
+
+    /// Clamp the current speed to the given timing constraints.
+    pub fn clamp_timing(&mut self, timing: &PathTiming) {
+        self.speed = match *timing {
+            PathTiming::Speed(ref range) => range.clamp(self.speed),
+            PathTiming::Duration(range) => {
+                let length = self.length();
+                let current = if self.speed > 0.0 {
+                    Ms(length / self.speed * 1_000.0)
+                } else {
+                    range.max
+                };
+                let range = Range { min: range.min.ms(), max: range.max.ms() };
+                self.speed_for_duration(Ms(range.clamp(current.ms())))
+            },
+        };
+    }
+
+    /// Rebuild the path from the given points and mode.
+    ///
+    /// The position through the current cycle is preserved as a fraction of the cycle length.
+    pub fn set_path(&mut self, points: &[Point2<Metres>], mode: PathMode) {
+        let cycle_length = self.cycle_length();
+        let normalised = if cycle_length > 0.0 {
+            self.state.travelled / cycle_length
+        } else {
+            0.0
+        };
+        self.mode = mode;
+        self.polyline = Polyline::spline(points, mode == PathMode::Loop);
+        self.state.travelled = self.cycle_length() * normalised;
+        self.update_position();
+    }
+
+    /// Update the `Path` state for the given past amount of time.
+    pub fn update(&mut self, delta_time: &time::Duration) {
+        let cycle_length = self.cycle_length();
+        if cycle_length > 0.0 {
+            let travelled = self.state.travelled + self.speed * duration_to_secs(delta_time);
+            self.state.travelled = fmod(travelled, cycle_length);
+        }
+        self.update_position();
+    }
+
+    // The distance travelled over a single cycle of the path.
+    fn cycle_length(&self) -> f64 {
+        match self.mode {
+            PathMode::Loop => self.length(),
+            PathMode::PingPong => self.length() * 2.0,
+        }
+    }
+
+    // Update the sound position from the distance travelled through the cycle.
+    fn update_position(&mut self) {
+        let length = self.length();
+        let travelled = self.state.travelled;
+        let distance = if travelled <= length { travelled } else { length * 2.0 - travelled };
+        if let Some(point) = self.polyline.point_at(distance) {
+            self.state.sound_position.point = pt2::to_metres(point);
+        }
+    }
+}
+
+impl Polyline {
+    /// Approximate a Catmull-Rom spline passing through each of the given control points.
+    ///
+    /// If `closed` is `true`, the spline continues from the last point back to the first.
+    pub fn spline(controls: &[Point2<Metres>], closed: bool) -> Self {
+        let controls: Vec<Point2<f64>> = controls.iter().map(|&p| pt2::to_f64(p)).collect();
+        let n = controls.len();
+        let mut points = Vec::with_capacity(n * LINES_PER_SPAN + 1);
+        if n > 0 {
+            points.push(controls[0]);
+        }
+
+        // The control point at the given index, wrapped if closed or clamped if open.
+        let control = |i: isize| -> Point2<f64> {
+            let i = if closed {
+                fmod(i as f64, n as f64) as usize
+            } else {
+                i.max(0).min(n as isize - 1) as usize
+            };
+            controls[i]
+        };
+
+        let spans = match (closed, n) {
+            (_, 0) | (_, 1) => 0,
+            (true, n) => n,
+            (false, n) => n - 1,
+        };
+        for span in 0..spans as isize {
+            let p0 = control(span - 1).to_vec();
+            let p1 = control(span).to_vec();
+            let p2 = control(span + 1).to_vec();
+            let p3 = control(span + 2).to_vec();
+            for step in 1..LINES_PER_SPAN + 1 {
+                let t = step as f64 / LINES_PER_SPAN as f64;
+                let (t2, t3) = (t * t, t * t * t);
+                let v = (p1 * 2.0
+                    + (p2 - p0) * t
+                    + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+                    + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5;
+                points.push(pt2(v.x, v.y));
+            }
+        }
+
+        let mut distances = Vec::with_capacity(points.len());
+        let mut distance = 0.0;
+        for (i, &point) in points.iter().enumerate() {
+            if i > 0 {
+                distance += points[i - 1].distance(point);
+            }
+            distances.push(distance);
+        }
+
+        Polyline { points, distances }
+    }
+
+    /// The points along the polyline.
+    pub fn points(&self) -> &[Point2<f64>] {
+        &self.points
+    }
+
+    /// The total length of the polyline.
+    pub fn length(&self) -> f64 {
+        self.distances.last().map(|&d| d).unwrap_or(0.0)
+    }
+
+    /// The point at the given distance from the start of the polyline.
+    ///
+    /// Returns `None` if the polyline has no points.
+    pub fn point_at(&self, distance: f64) -> Option<Point2<f64>> {
+        let last = match self.points.last() {
+            None => return None,
+            Some(&last) => last,
+        };
+        for i in 1..self.points.len() {
+            let (start, end) = (self.distances[i - 1], self.distances[i]);
+            if distance <= end {
+                let lerp = if end > start { (distance - start) / (end - start) } else { 0.0 };
+                let v = self.points[i - 1].to_vec().lerp(self.points[i].to_vec(), lerp.max(0.0));
+                return Some(pt2(v.x, v.y));
+            }
+        }
+        Some(last)
+    }
+}
+
+#[test]
+fn test_path_traversal() {
+    let points = [
+        pt2(Metres(0.0), Metres(0.0)),
+        pt2(Metres(10.0), Metres(0.0)),
+    ];
+
+    // A straight spline between two points has the same length as the line between them.
+    let polyline = Polyline::spline(&points, false);
+    assert_eq!(polyline.points().len(), LINES_PER_SPAN + 1);
+    assert!((polyline.length() - 10.0).abs() < 1e-9);
+    let point = polyline.point_at(2.5).unwrap();
+    assert!((point.x - 2.5).abs() < 1e-9 && point.y == 0.0);
+    assert_eq!(Polyline::spline(&points[..1], false).point_at(1.0), Some(pt2(0.0, 0.0)));
+    assert_eq!(Polyline::spline(&[], false).point_at(0.0), None);
+
+    // Ping-ponging reverses at the end of the path.
+    let mut path = Path::new(&points, PathMode::PingPong, 4.0, 0.0);
+    path.update(&time::Duration::from_secs(3));
+    assert!((path.position().point.x.0 - 8.0).abs() < 1e-9);
+    assert!((path.speed_for_duration(Ms(5_000.0)) - 2.0).abs() < 1e-9);
+
+    // Rebuilding preserves the fraction of the cycle travelled. Closing a path between two points
+    // travels there and back again.
+    path.set_path(&points, PathMode::Loop);
+    assert!((path.length() - 20.0).abs() < 1e-9);
+    assert!((path.position().point.x.0 - 8.0).abs() < 1e-9);
+
+    // A closed spline ends at the first point.
+    let triangle = [points[0], points[1], pt2(Metres(5.0), Metres(5.0))];
+    let closed = Polyline::spline(&triangle, true);
+    assert_eq!(closed.points().len(), LINES_PER_SPAN * 3 + 1);
+    assert!(closed.points().last().unwrap().distance(pt2(0.0, 0.0)) < 1e-9);
+
+    // Duration timing is clamped via the equivalent speed.
+    let mut path = Path::new(&points, PathMode::PingPong, 10.0, 0.25);
+    assert!((path.position().point.x.0 - 5.0).abs() < 1e-9);
+    let range = Range { min: Ms(2_000.0), max: Ms(4_000.0) };
+    path.clamp_timing(&PathTiming::Duration(range));
+    assert!((path.speed - 5.0).abs() < 1e-9);
+}
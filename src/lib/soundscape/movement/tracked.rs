@@ -0,0 +1,211 @@
+use audio;
+use audio::source::movement::TrackedTarget;
+use fxhash::FxHashMap;
+use metres::Metres;
+use nannou::prelude::*;
+use std::time;
+use time_calc::Ms;
+use utils::{duration_to_secs, pt2};
+
+/// All points currently tracked by external systems, keyed by name.
+pub type TrackedPoints = FxHashMap<String, TrackedPoint>;
+
+/// A named point within the exhibition, updated by an external tracking system over OSC.
+#[derive(Copy, Clone, Debug)]
+pub struct TrackedPoint {
+    /// The location of the point in exhibition space.
+    pub point: Point2<Metres>,
+    /// The moment at which the point was last updated.
+    pub last_updated: time::Instant,
+}
+
+/// A movement implementation that follows a point tracked by an external system.
+///
+/// When the tracked point is lost, the sound waits in place for the timeout before being
+/// considered timed out.
+#[derive(Debug)]
+pub struct Tracked {
+    /// The tracked point that the sound should follow.
+    pub target: TrackedTarget,
+    /// Roughly the time taken for the sound to catch up with its tracked point.
+    pub smoothing: Ms,
+    /// How long the sound may go without a tracked point before timing out.
+    pub timeout: Ms,
+    /// Whether or not the direction of travel should be applied to channel rotation.
+    pub directional: bool,
+    /// State that is updated during a call to `Update`.
+    state: State,
+}
+
+/// State that is updated during a call to `Update`.
+#[derive(Debug)]
+struct State {
+    /// The name of the tracked point currently being followed.
+    following: Option<String>,
+    /// The duration in seconds for which the sound has not had a tracked point to follow.
+    lost_secs: f64,
+    /// Whether or not the sound has been without a tracked point for longer than the timeout.
+    timed_out: bool,
+    /// The current location of the sound over the exhibition.
+    sound_position: audio::sound::Position,
+}
+
+impl Tracked {
+    /// Create a new **Tracked** movement type.
+    ///
+    /// The sound begins at the target's tracked point if there is one, otherwise at `start`.
+    pub fn new(
+        target: TrackedTarget,
+        smoothing: Ms,
+        timeout: Ms,
+        directional: bool,
+        start: Point2<Metres>,
+        tracked_points: &TrackedPoints,
+    ) -> Self
+    {
+        let following = find_target(&target, start, tracked_points);
+        let point = following
+            .as_ref()
+            .map(|name| tracked_points[name].point)
+            .unwrap_or(start);
+        let radians = 0.0;
+        let sound_position = audio::sound::Position { point, radians };
+        let state = State { following, lost_secs: 0.0, timed_out: false, sound_position };
+        Tracked { target, smoothing, timeout, directional, state }
+    }
+
+    /// The current position of the tracked sound.
+    pub fn position(&self) -> audio::sound::Position {
+        self.state.sound_position
+    }
+
+    /// The name of the tracked point currently being followed, if any.
+    pub fn following(&self) -> Option<&str> {
+        self.state.following.as_ref().map(|s| &s[..])
+    }
+
+    /// Whether or not the sound has been without a tracked point for longer than the timeout.
+    pub fn has_timed_out(&self) -> bool {
+        self.state.timed_out
+    }
+
+    /// Update the `Tracked` state for the given past amount of time.
+    ///
+    /// Returns `true` if the sound timed out during this update.
+    pub fn update(&mut self, delta_time: &time::Duration, tracked_points: &TrackedPoints) -> bool {
+        let secs = duration_to_secs(delta_time);
+
+        // Forget the followed point if it was lost and look for another.
+        let is_lost = match self.state.following {
+            Some(ref name) => !tracked_points.contains_key(name),
+            None => true,
+        };
+        if is_lost {
+            let current = self.state.sound_position.point;
+            self.state.following = find_target(&self.target, current, tracked_points);
+        }
+
+        let target = match self.state.following {
+            Some(ref name) => tracked_points[name].point,
+            None => {
+                self.state.lost_secs += secs;
+                let timed_out = self.state.lost_secs * 1_000.0 >= self.timeout.ms();
+                let just_timed_out = timed_out && !self.state.timed_out;
+                self.state.timed_out |= timed_out;
+                return just_timed_out;
+            },
+        };
+        self.state.lost_secs = 0.0;
+
+        // Exponentially approach the target point.
+        let point = pt2::to_f64(self.state.sound_position.point);
+        let target = pt2::to_f64(target);
+        let smoothing_secs = self.smoothing.ms() / 1_000.0;
+        let amount = if smoothing_secs > 0.0 { 1.0 - (-secs / smoothing_secs).exp() } else { 1.0 };
+        let delta = (target - point) * amount;
+        let new_point = point + delta;
+
+        self.state.sound_position.point = pt2::to_metres(new_point);
+        self.state.sound_position.radians = match self.directional {
+            false => 0.0,
+            true if delta.x == 0.0 && delta.y == 0.0 => self.state.sound_position.radians,
+            true => delta.y.atan2(delta.x) as f32,
+        };
+        false
+    }
+}
+
+/// Whether or not there is a tracked point that the given target would follow.
+pub fn target_is_tracked(target: &TrackedTarget, tracked_points: &TrackedPoints) -> bool {
+    match *target {
+        TrackedTarget::Nearest => !tracked_points.is_empty(),
+        TrackedTarget::Named(ref name) => tracked_points.contains_key(name),
+    }
+}
+
+// Find the name of the tracked point that should be followed from the given location.
+fn find_target(
+    target: &TrackedTarget,
+    from: Point2<Metres>,
+    tracked_points: &TrackedPoints,
+) -> Option<String> {
+    match *target {
+        TrackedTarget::Named(ref name) => match tracked_points.contains_key(name) {
+            true => Some(name.clone()),
+            false => None,
+        },
+        TrackedTarget::Nearest => {
+            let from = pt2::to_f64(from);
+            tracked_points
+                .iter()
+                .map(|(name, p)| (name, from.distance2(pt2::to_f64(p.point))))
+                .fold(None, |nearest: Option<(&String, f64)>, (name, dist)| match nearest {
+                    Some((_, nearest_dist)) if nearest_dist <= dist => nearest,
+                    _ => Some((name, dist)),
+                })
+                .map(|(name, _)| name.clone())
+        },
+    }
+}
+
+#[test]
+fn test_tracked_timeout() {
+    let at = |x, y| pt2(Metres(x), Metres(y));
+    let now = time::Instant::now();
+    let tracked = |point| TrackedPoint { point, last_updated: now };
+    let mut points = TrackedPoints::default();
+    points.insert("a".into(), tracked(at(2.0, 0.0)));
+    points.insert("b".into(), tracked(at(10.0, 0.0)));
+
+    // The nearest point is followed immediately.
+    let (smoothing, timeout) = (Ms(0.0), Ms(1_000.0));
+    let target = TrackedTarget::Nearest;
+    let mut sound = Tracked::new(target, smoothing, timeout, true, at(0.0, 0.0), &points);
+    assert_eq!(sound.following(), Some("a"));
+    assert_eq!(sound.position().point, at(2.0, 0.0));
+
+    // When the point is lost the sound moves on to the next nearest.
+    let dt = time::Duration::from_millis(600);
+    points.remove("a");
+    assert!(!sound.update(&dt, &points));
+    assert_eq!(sound.following(), Some("b"));
+    assert_eq!(sound.position().point, at(10.0, 0.0));
+    assert_eq!(sound.position().radians, 0.0);
+
+    // Without any points the sound stays in place and times out once.
+    points.clear();
+    assert!(!sound.update(&dt, &points));
+    assert!(sound.update(&dt, &points));
+    assert!(!sound.update(&dt, &points));
+    assert!(sound.has_timed_out());
+    assert_eq!(sound.position().point, at(10.0, 0.0));
+
+    // Smoothing approaches the point gradually.
+    points.insert("b".into(), tracked(at(0.0, 0.0)));
+    let target = TrackedTarget::Named("b".into());
+    let mut sound = Tracked::new(target, Ms(1_000.0), timeout, false, at(0.0, 0.0), &points);
+    points.insert("b".into(), tracked(at(0.0, 10.0)));
+    sound.update(&time::Duration::from_secs(1), &points);
+    let y = sound.position().point.y.0;
+    assert!((y - 10.0 * (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+}
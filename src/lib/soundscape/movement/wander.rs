@@ -0,0 +1,170 @@
+use audio;
+use mindtree_utils::noise_walk;
+use nannou::prelude::*;
+use std::time;
+use super::{Area, BoundingRect};
+use utils::{duration_to_secs, fmod, pt2};
+
+/// The rate at which the noise steering the heading is traversed in cycles per second.
+pub const NOISE_HZ: f64 = 0.5;
+
+/// The distance from each bound at which edge repulsion begins as a fraction of the area size.
+pub const EDGE_MARGIN: f64 = 0.25;
+
+/// The maximum rate in radians per second at which edge repulsion may turn the heading.
+pub const MAX_EDGE_STEER: f64 = 2.0 * PI_F64;
+
+/// A movement implementation that drifts organically through the installation area.
+///
+/// The heading is steered by a coherent noise signal while the sound is gently turned away from
+/// the bounds of the installation to which it is assigned. Upon reaching the edge of the
+/// installation's area, the sound slides along it. Given the same noise offset, starting
+/// point and heading, the movement is entirely deterministic.
+#[derive(Debug)]
+pub struct Wander {
+    /// The speed of the sound in metres per second.
+    pub speed: f64,
+    /// The maximum rate in radians per second at which the noise may turn the heading.
+    pub turbulence: f64,
+    /// The strength with which the sound is steered away from the bounds of the area.
+    pub edge_repulsion: f64,
+    /// Whether or not the heading should be applied to channel rotation.
+    pub directional: bool,
+    /// The phase offset into the noise signal unique to this sound.
+    noise_offset: f64,
+    /// State that is updated during a call to `Update`.
+    state: State,
+}
+
+/// State that is updated during a call to `Update`.
+#[derive(Debug)]
+struct State {
+    /// The total duration in seconds over which the sound has wandered.
+    elapsed_secs: f64,
+    /// The current direction of travel in radians.
+    heading: f64,
+    /// The current location of the sound over the exhibition.
+    sound_position: audio::sound::Position,
+}
+
+impl Wander {
+    /// Create a new **Wander** movement type.
+    ///
+    /// `normalised_point` describes the starting location within the bounding rectangle.
+    pub fn new(
+        speed: f64,
+        turbulence: f64,
+        edge_repulsion: f64,
+        directional: bool,
+        noise_offset: f64,
+        normalised_point: Point2<f64>,
+        heading: f64,
+        installation_area: &Area,
+    ) -> Self
+    {
+        let rect = &installation_area.bounding_rect;
+        let x = rect.left.0 + rect.width().0 * normalised_point.x;
+        let y = rect.bottom.0 + rect.height().0 * normalised_point.y;
+        let point = installation_area.clamp(pt2::to_metres(pt2(x, y)));
+        let radians = if directional { heading as f32 } else { 0.0 };
+        let sound_position = audio::sound::Position { point, radians };
+        let state = State { elapsed_secs: 0.0, heading, sound_position };
+        Wander { speed, turbulence, edge_repulsion, directional, noise_offset, state }
+    }
+
+    /// The current position of the wandering sound.
+    pub fn position(&self) -> audio::sound::Position {
+        self.state.sound_position
+    }
+
+    /// Update the `Wander` state for the given past amount of time.
+    pub fn update(&mut self, delta_time: &time::Duration, installation_area: &Area) {
+        let secs = duration_to_secs(delta_time);
+        self.state.elapsed_secs += secs;
+        let point = pt2::to_f64(self.state.sound_position.point);
+
+        // Turn the heading by the noise signal.
+        let phase = self.noise_offset + self.state.elapsed_secs * NOISE_HZ;
+        let mut heading = self.state.heading + noise_walk(phase) * self.turbulence * secs;
+
+        // Steer away from any bounds that are within the margin.
+        let repulsion = edge_repulsion(&installation_area.bounding_rect, point);
+        let strength = repulsion.magnitude().min(1.0) * self.edge_repulsion;
+        if strength > 0.0 {
+            let desired = repulsion.y.atan2(repulsion.x);
+            let diff = fmod(desired - heading + PI_F64, 2.0 * PI_F64) - PI_F64;
+            let max_turn = strength * MAX_EDGE_STEER * secs;
+            heading += diff.max(-max_turn).min(max_turn);
+        }
+        heading = fmod(heading, 2.0 * PI_F64);
+
+        // Travel along the heading, sliding along the bounds if they are reached.
+        let x = point.x + heading.cos() * self.speed * secs;
+        let y = point.y + heading.sin() * self.speed * secs;
+        let point = installation_area.clamp(pt2::to_metres(pt2(x, y)));
+
+        self.state.heading = heading;
+        self.state.sound_position = audio::sound::Position {
+            point,
+            radians: if self.directional { heading as f32 } else { 0.0 },
+        };
+    }
+}
+
+// The direction and strength with which the given point is pushed away from the nearest bounds.
+//
+// Each axis contributes up to `1.0` as the point approaches the bound from within the margin.
+fn edge_repulsion(bounding_rect: &BoundingRect, point: Point2<f64>) -> Vector2<f64> {
+    let axis = |value: f64, min: f64, max: f64| -> f64 {
+        let margin = (max - min) * EDGE_MARGIN;
+        if margin <= 0.0 {
+            return 0.0;
+        }
+        let low = ((min + margin - value) / margin).max(0.0).min(1.0);
+        let high = ((value - (max - margin)) / margin).max(0.0).min(1.0);
+        low - high
+    };
+    let x = axis(point.x, bounding_rect.left.0, bounding_rect.right.0);
+    let y = axis(point.y, bounding_rect.bottom.0, bounding_rect.top.0);
+    vec2(x, y)
+}
+
+#[test]
+fn test_wander_bounds() {
+    use metres::Metres;
+    let rect = BoundingRect {
+        left: Metres(0.0),
+        right: Metres(10.0),
+        bottom: Metres(0.0),
+        top: Metres(4.0),
+    };
+
+    // Points within the margin are pushed back toward the middle of the area.
+    assert_eq!(edge_repulsion(&rect, pt2(5.0, 2.0)), vec2(0.0, 0.0));
+    assert_eq!(edge_repulsion(&rect, pt2(0.0, 2.0)), vec2(1.0, 0.0));
+    assert_eq!(edge_repulsion(&rect, pt2(10.0, 4.0)), vec2(-1.0, -1.0));
+
+    // The same parameters always produce the same path, which never leaves the area.
+    let area = Area { bounding_rect: rect, centroid: rect.middle(), polygon: None };
+    let new = |area: &Area| Wander::new(3.0, PI_F64, 1.0, true, 0.3, pt2(0.5, 0.5), 0.0, area);
+    let (mut a, mut b) = (new(&area), new(&area));
+    let dt = time::Duration::from_millis(16);
+    for _ in 0..10_000 {
+        a.update(&dt, &area);
+        b.update(&dt, &area);
+        let (pa, pb) = (a.position().point, b.position().point);
+        assert_eq!((pa, a.position().radians), (pb, b.position().radians));
+        assert!(pa.x.0 >= 0.0 && pa.x.0 <= 10.0 && pa.y.0 >= 0.0 && pa.y.0 <= 4.0);
+    }
+
+    // Within an L-shaped polygon, the sound never leaves the polygon.
+    let polygon = [(0.0, 0.0), (10.0, 0.0), (10.0, 2.0), (2.0, 2.0), (2.0, 4.0), (0.0, 4.0)];
+    let polygon: Vec<_> = polygon.iter().map(|&(x, y)| pt2::to_metres(pt2(x, y))).collect();
+    let area = Area::from_polygon(polygon.into()).unwrap();
+    let mut wander = new(&area);
+    for _ in 0..10_000 {
+        wander.update(&dt, &area);
+        let (p, clamped) = (wander.position().point, area.clamp(wander.position().point));
+        assert!((p.x.0 - clamped.x.0).abs() < 1e-9 && (p.y.0 - clamped.y.0).abs() < 1e-9);
+    }
+}
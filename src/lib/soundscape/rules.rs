@@ -0,0 +1,218 @@
+//! Rules describing when sources may be played in relation to one another.
+//!
+//! Rules are declared within the project state and checked by the soundscape thread while
+//! collecting the sources available for spawning new sounds, e.g. "never play the thunder source
+//! while the birdsong group is active" or "only play the echo source within 5 seconds after the
+//! shout source".
+//!
+//! Each rule applies to a `Target`, being either a single source or all sources within a group.
+//! Sources withheld by a rule are reported as `Violation`s so that they may be explained within
+//! the GUI.
+
+use audio;
+use fxhash::{FxHashMap, FxHashSet};
+use std::time;
+use time_calc::Ms;
+use utils::duration_to_secs;
+use super::group;
+
+/// All rules declared for a project.
+pub type Rules = Vec<Rule>;
+
+/// The sources withheld by the rules at some moment.
+pub type Violations = Vec<Violation>;
+
+/// The set of targets to which a single source belongs: the source itself and all of its groups.
+pub type Targets = FxHashSet<Target>;
+
+/// A source or group of sources to which a rule applies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Target {
+    Source(audio::source::Id),
+    Group(group::Id),
+}
+
+/// A constraint on when sounds may be spawned from the sources of a target.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Rule {
+    /// Never spawn sounds from `target` while any sound from `other` is active.
+    NotWhile { target: Target, other: Target },
+    /// Only spawn sounds from `target` within `within` after a sound from `other` was spawned.
+    OnlyAfter { target: Target, other: Target, within: Ms },
+}
+
+/// A snapshot of the soundscape's activity against which rules are checked.
+#[derive(Clone, Debug, Default)]
+pub struct Activity {
+    /// All targets with at least one active sound.
+    pub active: FxHashSet<Target>,
+    /// The moment at which a sound was last spawned from each target.
+    pub last_spawned: FxHashMap<Target, time::Instant>,
+}
+
+/// A source that may not currently be used to spawn sounds due to some rule.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// The index of the violated rule within the project's rules.
+    pub rule: usize,
+    /// The source that is withheld.
+    pub source: audio::source::Id,
+    /// Why the rule withholds the source.
+    pub reason: Reason,
+}
+
+/// The reason for which a rule withholds a source.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// A sound from the rule's other target is active.
+    OtherActive,
+    /// No sound from the rule's other target has been spawned yet.
+    OtherNeverSpawned,
+    /// The last sound from the rule's other target was spawned too long ago.
+    OtherSpawnedTooLongAgo,
+}
+
+pub mod default {
+    use time_calc::Ms;
+    pub const WITHIN: Ms = Ms(5_000.0);
+}
+
+impl Rule {
+    /// A short, human-readable description of the kind of rule.
+    pub fn kind_name(&self) -> &'static str {
+        match *self {
+            Rule::NotWhile { .. } => "Not While",
+            Rule::OnlyAfter { .. } => "Only After",
+        }
+    }
+
+    /// The target whose sources are constrained by the rule.
+    pub fn target(&self) -> &Target {
+        match *self {
+            Rule::NotWhile { ref target, .. } | Rule::OnlyAfter { ref target, .. } => target,
+        }
+    }
+
+    /// The target whose activity determines whether or not the rule is satisfied.
+    pub fn other(&self) -> &Target {
+        match *self {
+            Rule::NotWhile { ref other, .. } | Rule::OnlyAfter { ref other, .. } => other,
+        }
+    }
+
+    /// Check whether a source belonging to the given targets may spawn a sound at `now`.
+    ///
+    /// Returns `None` if the rule does not apply to the source or if it is satisfied.
+    pub fn check(
+        &self,
+        targets: &Targets,
+        activity: &Activity,
+        now: time::Instant,
+    ) -> Option<Reason> {
+        if !targets.contains(self.target()) {
+            return None;
+        }
+        match *self {
+            Rule::NotWhile { ref other, .. } => match activity.active.contains(other) {
+                true => Some(Reason::OtherActive),
+                false => None,
+            },
+            Rule::OnlyAfter { ref other, within, .. } => match activity.last_spawned.get(other) {
+                None => Some(Reason::OtherNeverSpawned),
+                Some(&last) => {
+                    let since = now.duration_since(last);
+                    match Ms(duration_to_secs(&since) * 1_000.0) > within {
+                        true => Some(Reason::OtherSpawnedTooLongAgo),
+                        false => None,
+                    }
+                },
+            },
+        }
+    }
+}
+
+impl Activity {
+    /// Clear all activity, e.g. before collecting a new snapshot.
+    pub fn clear(&mut self) {
+        self.active.clear();
+        self.last_spawned.clear();
+    }
+
+    /// Record an active sound from a source belonging to the given targets.
+    pub fn insert_active(&mut self, targets: &Targets) {
+        self.active.extend(targets.iter().cloned());
+    }
+
+    /// Record a sound spawned at the given moment from a source belonging to the given targets.
+    pub fn insert_spawned(&mut self, targets: &Targets, instant: time::Instant) {
+        for &target in targets {
+            let last = self.last_spawned.entry(target).or_insert(instant);
+            if *last < instant {
+                *last = instant;
+            }
+        }
+    }
+}
+
+/// Check the source belonging to the given targets against all rules.
+///
+/// Returns the first violated rule, if any.
+pub fn check(
+    rules: &[Rule],
+    source: audio::source::Id,
+    targets: &Targets,
+    activity: &Activity,
+    now: time::Instant,
+) -> Option<Violation> {
+    rules
+        .iter()
+        .enumerate()
+        .filter_map(|(rule, r)| r.check(targets, activity, now).map(|reason| (rule, reason)))
+        .map(|(rule, reason)| Violation { rule, source, reason })
+        .next()
+}
+
+#[test]
+fn test_rule_check() {
+    let thunder = audio::source::Id(0);
+    let echo = audio::source::Id(1);
+    let shout = audio::source::Id(2);
+    let birdsong = group::Id(0);
+    let (within, other) = (Ms(5_000.0), Target::Source(shout));
+    let rules = vec![
+        Rule::NotWhile { target: Target::Source(thunder), other: Target::Group(birdsong) },
+        Rule::OnlyAfter { target: Target::Source(echo), other, within },
+    ];
+    let targets = |source, groups: &[group::Id]| -> Targets {
+        let mut targets = Targets::default();
+        targets.insert(Target::Source(source));
+        targets.extend(groups.iter().map(|&g| Target::Group(g)));
+        targets
+    };
+    let thunder_targets = targets(thunder, &[]);
+    let echo_targets = targets(echo, &[birdsong]);
+    let shout_targets = targets(shout, &[]);
+    let start = time::Instant::now();
+    let mut activity = Activity::default();
+
+    // Thunder is free to play while the birdsong group is silent. Echo has no shout to follow.
+    assert_eq!(check(&rules, thunder, &thunder_targets, &activity, start), None);
+    let violation = check(&rules, echo, &echo_targets, &activity, start).unwrap();
+    assert_eq!((violation.rule, violation.reason), (1, Reason::OtherNeverSpawned));
+
+    // Once shout has spawned, echo may play within the window but no later.
+    activity.insert_spawned(&shout_targets, start);
+    activity.insert_active(&shout_targets);
+    let soon = start + time::Duration::from_secs(4);
+    let later = start + time::Duration::from_secs(6);
+    assert_eq!(check(&rules, echo, &echo_targets, &activity, soon), None);
+    let violation = check(&rules, echo, &echo_targets, &activity, later).unwrap();
+    assert_eq!(violation.reason, Reason::OtherSpawnedTooLongAgo);
+
+    // While the echo sound from the birdsong group is active, thunder is withheld.
+    activity.insert_spawned(&echo_targets, soon);
+    activity.insert_active(&echo_targets);
+    let violation = check(&rules, thunder, &thunder_targets, &activity, soon).unwrap();
+    assert_eq!(violation, Violation { rule: 0, source: thunder, reason: Reason::OtherActive });
+    assert_eq!(check(&rules, shout, &shout_targets, &activity, soon), None);
+}
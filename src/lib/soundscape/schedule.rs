@@ -0,0 +1,481 @@
+//! A weekly and calendar schedule for the soundscape.
+//!
+//! A `Schedule` is a list of time `Window`s. Each window applies a set of `Action`s while it is
+//! active, e.g. pausing the soundscape outside of opening hours, muting a group during school-group
+//! mornings or reducing the number of simultaneous sounds during quiet evening events.
+//!
+//! The schedule is evaluated on the soundscape thread's `Tick`. When windows overlap, windows
+//! later in the list take precedence over earlier ones.
+//!
+//! Scenes recalled by a window are the exception: they are recalled on the main thread as the
+//! window begins, as the project's scenes are not known to the soundscape thread.
+
+use audio;
+use chrono::{self, Datelike, FixedOffset, Local, NaiveDate, TimeZone, Timelike};
+use fxhash::{FxHashMap, FxHashSet};
+use installation;
+use scene;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use time_calc::Ms;
+use utils::Range;
+use super::group::{self, Group};
+
+/// The number of minutes within a single day.
+pub const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// The names of the days of the week, starting from Monday.
+pub const DAY_NAMES: [&'static str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// A list of time windows along with the actions that apply within them.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Schedule {
+    /// An explicit offset of the exhibition's local time from UTC in minutes.
+    ///
+    /// If `None`, the offset of the OS's local timezone is used. This is looked up at each
+    /// evaluation and so follows daylight saving changes. An explicit offset is fixed and must be
+    /// updated by hand whenever the local clocks change.
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i32>,
+    /// All time windows in order of ascending precedence.
+    #[serde(default)]
+    pub windows: Vec<Window>,
+}
+
+/// A span of time during which a set of actions apply.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Window {
+    /// A human-friendly name for the window, e.g. "School Mornings".
+    #[serde(default)]
+    pub name: String,
+    /// The days of the week on which the window applies, starting from Monday.
+    ///
+    /// Ignored if a `date` is given.
+    #[serde(default = "default::days")]
+    pub days: [bool; 7],
+    /// If given, the window only applies on this date rather than weekly.
+    #[serde(default)]
+    pub date: Option<Date>,
+    /// The minute of the day at which the window starts.
+    #[serde(default)]
+    pub start_minute: u32,
+    /// The minute of the day at which the window ends.
+    ///
+    /// If this is less than or equal to `start_minute`, the window wraps past midnight into the
+    /// following day.
+    #[serde(default = "default::end_minute")]
+    pub end_minute: u32,
+    /// The actions that apply while the window is active.
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+/// A calendar date within the exhibition's local time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// Some change to the soundscape that applies while a window is active.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Action {
+    /// Play the soundscape.
+    PlaySoundscape,
+    /// Pause the soundscape.
+    PauseSoundscape,
+    /// Prevent the soundscape from spawning new sounds from the group.
+    DisableGroup(group::Id),
+    /// Silence all sounds from sources within the group.
+    MuteGroup(group::Id),
+    /// Prevent the soundscape from spawning new sounds from the source.
+    DisableSource(audio::source::Id),
+    /// Silence all sounds from the source.
+    MuteSource(audio::source::Id),
+    /// Replace the occurrence rate of the group.
+    GroupOccurrenceRate(group::Id, Range<Ms>),
+    /// Replace the range of simultaneous sounds for the group.
+    GroupSimultaneousSounds(group::Id, Range<usize>),
+    /// Replace the range of simultaneous sounds for the installation.
+    InstallationSimultaneousSounds(installation::Id, Range<usize>),
+    /// Recall the scene with its crossfade as the window begins.
+    RecallScene(scene::Id),
+}
+
+/// A moment in the exhibition's local time at the resolution of minutes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LocalTime {
+    /// The number of days since 1970-01-01.
+    pub day: i64,
+    /// The day of the week where `0` is Monday.
+    pub weekday: usize,
+    /// The minute within the day.
+    pub minute: u32,
+}
+
+/// The combined result of all windows that are active at some moment.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overrides {
+    /// Whether the soundscape should be playing or paused, if any window says so.
+    pub playing: Option<bool>,
+    pub disabled_groups: FxHashSet<group::Id>,
+    pub muted_groups: FxHashSet<group::Id>,
+    pub disabled_sources: FxHashSet<audio::source::Id>,
+    pub muted_sources: FxHashSet<audio::source::Id>,
+    pub group_occurrence_rates: FxHashMap<group::Id, Range<Ms>>,
+    pub group_simultaneous_sounds: FxHashMap<group::Id, Range<usize>>,
+    pub installation_simultaneous_sounds: FxHashMap<installation::Id, Range<usize>>,
+    /// The scene that should be recalled, if any window says so.
+    pub scene: Option<scene::Id>,
+}
+
+pub mod default {
+    use super::MINUTES_PER_DAY;
+    pub fn days() -> [bool; 7] {
+        [true; 7]
+    }
+    pub fn end_minute() -> u32 {
+        MINUTES_PER_DAY
+    }
+}
+
+impl Schedule {
+    /// Combine the actions of all windows that are active at the given time.
+    pub fn overrides(&self, now: &LocalTime) -> Overrides {
+        let mut overrides = Overrides::default();
+        for window in self.windows.iter().filter(|w| w.is_active(now)) {
+            for action in &window.actions {
+                overrides.apply(action);
+            }
+        }
+        overrides
+    }
+
+    /// The current local time according to the schedule's UTC offset.
+    pub fn now(&self) -> LocalTime {
+        LocalTime::now(self.utc_offset_minutes)
+    }
+
+    /// The offset from UTC in minutes that currently applies to the schedule.
+    pub fn current_utc_offset_minutes(&self) -> i32 {
+        utc_offset_minutes_at(unix_secs(SystemTime::now()), self.utc_offset_minutes)
+    }
+}
+
+impl Window {
+    /// Whether or not the window applies to the given day.
+    pub fn applies_on(&self, day: i64) -> bool {
+        match self.date {
+            Some(date) => date.days_since_epoch() == Some(day),
+            None => self.days[weekday(&naive_date(day))],
+        }
+    }
+
+    /// The spans of minutes `(start, end)` during which the window is active on the given day.
+    pub fn spans_on(&self, day: i64) -> Vec<(u32, u32)> {
+        let start = self.start_minute.min(MINUTES_PER_DAY);
+        let end = self.end_minute.min(MINUTES_PER_DAY);
+        let mut spans = vec![];
+        if start < end {
+            if self.applies_on(day) {
+                spans.push((start, end));
+            }
+            return spans;
+        }
+        // The window wraps past midnight, so the early hours belong to the previous day's window.
+        if self.applies_on(day - 1) && end > 0 {
+            spans.push((0, end));
+        }
+        if self.applies_on(day) && start < MINUTES_PER_DAY {
+            spans.push((start, MINUTES_PER_DAY));
+        }
+        spans
+    }
+
+    /// Whether or not the window is active at the given time.
+    pub fn is_active(&self, now: &LocalTime) -> bool {
+        self.spans_on(now.day)
+            .iter()
+            .any(|&(start, end)| start <= now.minute && now.minute < end)
+    }
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Window {
+            name: String::new(),
+            days: default::days(),
+            date: None,
+            start_minute: 0,
+            end_minute: default::end_minute(),
+            actions: vec![],
+        }
+    }
+}
+
+impl Action {
+    /// A short, human-readable description of the kind of action.
+    pub fn kind_name(&self) -> &'static str {
+        match *self {
+            Action::PlaySoundscape => "Play Soundscape",
+            Action::PauseSoundscape => "Pause Soundscape",
+            Action::DisableGroup(_) => "Disable Group",
+            Action::MuteGroup(_) => "Mute Group",
+            Action::DisableSource(_) => "Disable Source",
+            Action::MuteSource(_) => "Mute Source",
+            Action::GroupOccurrenceRate(..) => "Group Occurrence Rate",
+            Action::GroupSimultaneousSounds(..) => "Group Simultaneous Sounds",
+            Action::InstallationSimultaneousSounds(..) => "Installation Simultaneous Sounds",
+            Action::RecallScene(_) => "Recall Scene",
+        }
+    }
+
+    /// Whether or not the action replaces some constraint with a preset.
+    pub fn is_preset(&self) -> bool {
+        match *self {
+            Action::GroupOccurrenceRate(..)
+            | Action::GroupSimultaneousSounds(..)
+            | Action::InstallationSimultaneousSounds(..) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Date {
+    /// The `chrono` representation of the date, or `None` if no such date exists.
+    pub fn to_naive_date(&self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year, self.month, self.day)
+    }
+
+    /// The number of days between 1970-01-01 and this date, or `None` if no such date exists.
+    pub fn days_since_epoch(&self) -> Option<i64> {
+        self.to_naive_date().map(|date| days_since_epoch(&date))
+    }
+
+    /// The date of the given number of days since 1970-01-01.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        Date::from(naive_date(days))
+    }
+}
+
+impl From<NaiveDate> for Date {
+    fn from(date: NaiveDate) -> Self {
+        Date {
+            year: date.year(),
+            month: date.month(),
+            day: date.day(),
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// Dates are parsed from the `YYYY-MM-DD` form, e.g. as entered via the GUI.
+impl FromStr for Date {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split('-').collect();
+        if parts.len() != 3 {
+            return Err(());
+        }
+        let year = parts[0].parse().map_err(|_| ())?;
+        let month = parts[1].parse().map_err(|_| ())?;
+        let day = parts[2].parse().map_err(|_| ())?;
+        // Reject days that do not exist within the month, e.g. the 31st of April.
+        NaiveDate::from_ymd_opt(year, month, day).map(Date::from).ok_or(())
+    }
+}
+
+impl LocalTime {
+    /// The local time at the given number of seconds since the unix epoch.
+    ///
+    /// Offsets of a day or more from UTC are invalid and treated as `0`.
+    pub fn from_unix_secs(secs: i64, utc_offset_minutes: i32) -> Self {
+        let offset = FixedOffset::east_opt(utc_offset_minutes * 60)
+            .or_else(|| FixedOffset::east_opt(0))
+            .expect("a zero offset is always valid");
+        let time = offset.timestamp(secs, 0).naive_local();
+        let date = time.date();
+        LocalTime {
+            day: days_since_epoch(&date),
+            weekday: weekday(&date),
+            minute: time.hour() * 60 + time.minute(),
+        }
+    }
+
+    /// The local time at the given system time.
+    ///
+    /// If `utc_offset_minutes` is `None`, the OS's local timezone at that time is used.
+    pub fn from_system_time(time: SystemTime, utc_offset_minutes: Option<i32>) -> Self {
+        let secs = unix_secs(time);
+        LocalTime::from_unix_secs(secs, utc_offset_minutes_at(secs, utc_offset_minutes))
+    }
+
+    /// The current local time.
+    ///
+    /// If `utc_offset_minutes` is `None`, the OS's local timezone is used.
+    pub fn now(utc_offset_minutes: Option<i32>) -> Self {
+        LocalTime::from_system_time(SystemTime::now(), utc_offset_minutes)
+    }
+
+    /// The calendar date of the local time.
+    pub fn date(&self) -> Date {
+        Date::from_days_since_epoch(self.day)
+    }
+}
+
+impl Overrides {
+    // Apply the given action, replacing any previous actions that conflict with it.
+    fn apply(&mut self, action: &Action) {
+        match *action {
+            Action::PlaySoundscape => self.playing = Some(true),
+            Action::PauseSoundscape => self.playing = Some(false),
+            Action::DisableGroup(id) => {
+                self.disabled_groups.insert(id);
+            }
+            Action::MuteGroup(id) => {
+                self.muted_groups.insert(id);
+            }
+            Action::DisableSource(id) => {
+                self.disabled_sources.insert(id);
+            }
+            Action::MuteSource(id) => {
+                self.muted_sources.insert(id);
+            }
+            Action::GroupOccurrenceRate(id, range) => {
+                self.group_occurrence_rates.insert(id, range);
+            }
+            Action::GroupSimultaneousSounds(id, range) => {
+                self.group_simultaneous_sounds.insert(id, range);
+            }
+            Action::InstallationSimultaneousSounds(id, range) => {
+                self.installation_simultaneous_sounds.insert(id, range);
+            }
+            Action::RecallScene(id) => self.scene = Some(id),
+        }
+    }
+
+    /// The occurrence rate of the group, taking any scheduled preset into account.
+    pub fn group_occurrence_rate(&self, id: &group::Id, group: &Group) -> Range<Ms> {
+        self.group_occurrence_rates.get(id).cloned().unwrap_or(group.occurrence_rate)
+    }
+
+    /// The simultaneous sounds of the group, taking any scheduled preset into account.
+    pub fn group_simultaneous_sounds(&self, id: &group::Id, group: &Group) -> Range<usize> {
+        self.group_simultaneous_sounds.get(id).cloned().unwrap_or(group.simultaneous_sounds)
+    }
+
+    /// The simultaneous sounds of the installation, taking any scheduled preset into account.
+    pub fn installation_simultaneous_sounds(
+        &self,
+        id: &installation::Id,
+        installation: &installation::Soundscape,
+    ) -> Range<usize> {
+        self.installation_simultaneous_sounds
+            .get(id)
+            .cloned()
+            .unwrap_or(installation.simultaneous_sounds)
+    }
+}
+
+/// Format a minute of the day as `HH:MM`.
+pub fn format_minute(minute: u32) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+/// The offset from UTC in minutes at the given number of seconds since the unix epoch.
+///
+/// This is the given explicit offset if there is one, otherwise the offset of the OS's local
+/// timezone at that moment.
+pub fn utc_offset_minutes_at(secs: i64, utc_offset_minutes: Option<i32>) -> i32 {
+    match utc_offset_minutes {
+        Some(minutes) => minutes,
+        None => Local.timestamp(secs, 0).offset().local_minus_utc() / 60,
+    }
+}
+
+// The number of seconds since the unix epoch at the given system time.
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    }
+}
+
+// The date 1970-01-01, from which `LocalTime` days are counted.
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).expect("1970-01-01 is a valid date")
+}
+
+// The number of days between 1970-01-01 and the given date.
+fn days_since_epoch(date: &NaiveDate) -> i64 {
+    date.signed_duration_since(epoch()).num_days()
+}
+
+// The date of the given number of days since 1970-01-01.
+fn naive_date(days: i64) -> NaiveDate {
+    epoch() + chrono::Duration::days(days)
+}
+
+// The day of the week where `0` is Monday.
+fn weekday(date: &NaiveDate) -> usize {
+    date.weekday().num_days_from_monday() as usize
+}
+
+#[test]
+fn test_local_time_and_windows() {
+    // 2018-03-05 was a Monday.
+    let monday = Date { year: 2018, month: 3, day: 5 };
+    let day = monday.days_since_epoch().unwrap();
+    assert_eq!(Date::from_days_since_epoch(day), monday);
+    assert_eq!(Date::from_days_since_epoch(0), Date { year: 1970, month: 1, day: 1 });
+    assert_eq!("2018-03-05".parse(), Ok(monday));
+    assert_eq!(monday.to_string(), "2018-03-05");
+    assert_eq!("2018-04-31".parse::<Date>(), Err(()));
+    assert_eq!(Date { year: 2018, month: 4, day: 31 }.days_since_epoch(), None);
+
+    // 23:30 on Sunday in UTC is 00:30 on Monday one hour east.
+    let secs = day * 24 * 60 * 60 - 30 * 60;
+    let utc = LocalTime::from_unix_secs(secs, 0);
+    assert_eq!((utc.weekday, utc.minute), (6, 23 * 60 + 30));
+    let local = LocalTime::from_unix_secs(secs, 60);
+    assert_eq!((local.day, local.weekday, local.minute), (day, 0, 30));
+
+    // An explicit offset overrides the OS timezone.
+    let time = UNIX_EPOCH + ::std::time::Duration::from_secs(secs as u64);
+    assert_eq!(utc_offset_minutes_at(secs, Some(60)), 60);
+    assert_eq!(LocalTime::from_system_time(time, Some(60)), local);
+
+    // An evening window on Sundays only that wraps past midnight.
+    let mut days = [false; 7];
+    days[6] = true;
+    let window = Window {
+        days,
+        start_minute: 22 * 60,
+        end_minute: 60,
+        actions: vec![Action::PauseSoundscape, Action::MuteGroup(group::Id(1))],
+        ..Default::default()
+    };
+    assert!(window.is_active(&utc));
+    assert!(window.is_active(&local));
+    let later = LocalTime::from_unix_secs(secs + 2 * 60 * 60, 60);
+    assert!(!window.is_active(&later));
+    assert_eq!(window.spans_on(day), vec![(0, 60)]);
+    assert_eq!(window.spans_on(day - 1), vec![(22 * 60, MINUTES_PER_DAY)]);
+
+    // Later windows take precedence.
+    let open = Window { actions: vec![Action::PlaySoundscape], ..Default::default() };
+    let mut schedule = Schedule { utc_offset_minutes: Some(60), windows: vec![open, window] };
+    let overrides = schedule.overrides(&local);
+    assert_eq!(overrides.playing, Some(false));
+    assert!(overrides.muted_groups.contains(&group::Id(1)));
+    schedule.windows.reverse();
+    assert_eq!(schedule.overrides(&local).playing, Some(true));
+    assert_eq!(schedule.overrides(&later).playing, Some(true));
+}
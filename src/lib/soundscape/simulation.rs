@@ -0,0 +1,550 @@
+//! Run the soundscape on a virtual clock to collect statistics about its behaviour.
+//!
+//! A simulation steps a copy of the soundscape's project state through the same `tick` logic as
+//! the live soundscape. Sounds are never sent to the audio threads and the clock is stepped
+//! forward as fast as possible, so that hours of playback may be simulated within seconds. The
+//! resulting `Report` describes the play counts, simultaneous sounds, constraint violations and
+//! long gaps of every source, group and installation.
+
+use audio;
+use fxhash::{FxHashMap, FxHashSet};
+use installation;
+use serde_json;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{self, SystemTime};
+use utils::{self, duration_to_secs, Range};
+use super::{event_log, group, AudioStreams, Model, Tick};
+use super::schedule::LocalTime;
+
+/// The interval at which the virtual clock is stepped forward.
+pub const TICK_MS: u64 = 100;
+
+/// The names of the project's sources, groups and installations, used to label the report.
+#[derive(Clone, Debug, Default)]
+pub struct Names {
+    pub sources: FxHashMap<audio::source::Id, String>,
+    pub groups: FxHashMap<group::Id, String>,
+    pub installations: FxHashMap<installation::Id, String>,
+}
+
+/// The kind of item described by a row of the report.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Kind {
+    Source,
+    Group,
+    Installation,
+}
+
+/// Statistics collected for a single source, group or installation.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Stats {
+    pub kind: Kind,
+    /// The ID of the item as found within the project's `state.json`.
+    pub id: u64,
+    pub name: String,
+    /// The number of sounds spawned.
+    pub plays: usize,
+    /// The number of simultaneous sounds averaged over the playback duration.
+    pub mean_simultaneous_sounds: f64,
+    /// The greatest number of sounds that played at once.
+    pub max_simultaneous_sounds: usize,
+    /// The seconds of playback during which fewer sounds played than the configured minimum.
+    pub secs_below_min: f64,
+    /// The seconds of playback during which more sounds played than the configured maximum.
+    pub secs_above_max: f64,
+    /// The number of gaps between plays that exceeded the configured max occurrence interval.
+    ///
+    /// Always `0` for installations, as they have no occurrence rate.
+    pub long_gaps: usize,
+    /// The longest duration in seconds between plays, including before the first and after the
+    /// last.
+    pub longest_gap_secs: f64,
+}
+
+/// The results of a simulation.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Report {
+    /// The duration of simulated time in seconds.
+    pub simulated_secs: f64,
+    /// The duration over which the soundscape played, excluding time paused by the schedule.
+    pub playback_secs: f64,
+    /// Sources, followed by groups and then installations, each in alphabetical order.
+    pub stats: Vec<Stats>,
+}
+
+// Statistics accumulated for a single source, group or installation.
+#[derive(Clone, Debug, Default)]
+struct Tally {
+    plays: usize,
+    // The number of simultaneous sounds integrated over the seconds of playback.
+    sound_secs: f64,
+    max_sounds: usize,
+    secs_below_min: f64,
+    secs_above_max: f64,
+    // The playback seconds at which the last sound was spawned.
+    last_play_secs: Option<f64>,
+    long_gaps: usize,
+    longest_gap_secs: f64,
+}
+
+// The tallies of every source, group and installation.
+#[derive(Default)]
+struct Tallies {
+    sources: FxHashMap<audio::source::Id, Tally>,
+    groups: FxHashMap<group::Id, Tally>,
+    installations: FxHashMap<installation::Id, Tally>,
+}
+
+/// The path of the "assets/reports/" directory in which simulation reports are stored.
+pub fn directory<P>(assets: P) -> PathBuf
+where
+    P: AsRef<Path>,
+{
+    assets.as_ref().join("reports")
+}
+
+/// The name of the report files for a simulation run at the given local time, without extension.
+pub fn file_stem(time: &LocalTime) -> String {
+    format!("simulation-{}-{:02}{:02}", time.date(), time.minute / 60, time.minute % 60)
+}
+
+impl Tally {
+    // Account for the given seconds of playback during which the given number of sounds played.
+    fn sounds(&mut self, sounds: usize, secs: f64, range: &Range<usize>) {
+        self.sound_secs += sounds as f64 * secs;
+        self.max_sounds = ::std::cmp::max(self.max_sounds, sounds);
+        if sounds < range.min {
+            self.secs_below_min += secs;
+        } else if sounds > range.max {
+            self.secs_above_max += secs;
+        }
+    }
+
+    // Account for a sound spawned at the given playback seconds.
+    fn play(&mut self, secs: f64, max_interval_secs: Option<f64>) {
+        self.gap(secs, max_interval_secs);
+        self.plays += 1;
+        self.last_play_secs = Some(secs);
+    }
+
+    // Account for the gap since the last play, or since the beginning if there was none.
+    fn gap(&mut self, secs: f64, max_interval_secs: Option<f64>) {
+        let gap = secs - self.last_play_secs.unwrap_or(0.0);
+        if gap > self.longest_gap_secs {
+            self.longest_gap_secs = gap;
+        }
+        if max_interval_secs.map(|max| gap > max).unwrap_or(false) {
+            self.long_gaps += 1;
+        }
+    }
+
+    // Close the final gap and produce the statistics for the item.
+    fn finish(
+        mut self,
+        kind: Kind,
+        id: u64,
+        name: String,
+        playback_secs: f64,
+        max_interval_secs: Option<f64>,
+    ) -> Stats {
+        self.gap(playback_secs, max_interval_secs);
+        let mean_simultaneous_sounds = match playback_secs > 0.0 {
+            true => self.sound_secs / playback_secs,
+            false => 0.0,
+        };
+        Stats {
+            kind,
+            id,
+            name,
+            plays: self.plays,
+            mean_simultaneous_sounds,
+            max_simultaneous_sounds: self.max_sounds,
+            secs_below_min: self.secs_below_min,
+            secs_above_max: self.secs_above_max,
+            long_gaps: self.long_gaps,
+            longest_gap_secs: self.longest_gap_secs,
+        }
+    }
+}
+
+impl Report {
+    /// The report as comma-separated values with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        csv.push_str("kind,id,name,plays,mean_simultaneous_sounds,max_simultaneous_sounds,");
+        csv.push_str("secs_below_min,secs_above_max,long_gaps,longest_gap_secs\n");
+        for s in &self.stats {
+            writeln!(
+                csv,
+                "{:?},{},\"{}\",{},{:.3},{},{:.1},{:.1},{},{:.1}",
+                s.kind,
+                s.id,
+                s.name.replace('"', "\"\""),
+                s.plays,
+                s.mean_simultaneous_sounds,
+                s.max_simultaneous_sounds,
+                s.secs_below_min,
+                s.secs_above_max,
+                s.long_gaps,
+                s.longest_gap_secs,
+            ).expect("failed to write simulation report row");
+        }
+        csv
+    }
+
+    /// Save the report within the given directory as both a `.json` and a `.csv` file with the
+    /// given file stem.
+    ///
+    /// Returns the path to the JSON file.
+    pub fn save(
+        &self,
+        directory: &Path,
+        file_stem: &str,
+    ) -> Result<PathBuf, utils::FileError<serde_json::Error>> {
+        fs::create_dir_all(directory)?;
+        let json_path = directory.join(file_stem).with_extension("json");
+        utils::save_to_json(&json_path, self)?;
+        let csv_path = json_path.with_extension("csv");
+        utils::safe_file_save(&csv_path, self.to_csv().as_bytes())?;
+        Ok(json_path)
+    }
+}
+
+/// Simulate the soundscape's current project state for the given duration on a separate thread.
+///
+/// The report is saved within the given directory, and the path to its JSON file (or a
+/// description of the error) is sent via `result_tx` once complete.
+pub fn spawn(
+    model: &Model,
+    duration: time::Duration,
+    names: Names,
+    directory: PathBuf,
+    result_tx: mpsc::Sender<Result<PathBuf, String>>,
+) {
+    let mut simulated = snapshot(model);
+    thread::Builder::new()
+        .name("soundscape_simulation".into())
+        .spawn(move || {
+            let report = run(&mut simulated, duration, &names);
+            let now = simulated.schedule.now();
+            let result = report
+                .save(&directory, &file_stem(&now))
+                .map_err(|err| format!("failed to save simulation report: {}", err));
+            result_tx.send(result).ok();
+        })
+        .expect("failed to spawn soundscape simulation thread");
+}
+
+/// Step the given model forward over the given duration of simulated time, ending sounds as
+/// their durations pass.
+///
+/// Statistics are only accumulated while the soundscape plays, so time paused by the schedule
+/// does not count toward gaps or averages.
+pub fn run(model: &mut Model, duration: time::Duration, names: &Names) -> Report {
+    let tick_interval = time::Duration::from_millis(TICK_MS);
+    let tick_secs = duration_to_secs(&tick_interval);
+    let start = time::Instant::now();
+    let start_system_time = SystemTime::now();
+    let mut elapsed = time::Duration::from_secs(0);
+    let mut playback_duration = time::Duration::from_secs(0);
+    let mut tallies = Tallies::default();
+
+    // Buffers re-used between ticks.
+    let mut seen = FxHashSet::default();
+    let mut ended = vec![];
+    let mut source_sounds = FxHashMap::default();
+    let mut group_sounds = FxHashMap::default();
+    let mut installation_sounds = FxHashMap::default();
+
+    while elapsed < duration {
+        elapsed += tick_interval;
+        let playing = model.is_playing.load(atomic::Ordering::Relaxed);
+        if playing {
+            playback_duration += tick_interval;
+        }
+        let tick = Tick {
+            instant: start + elapsed,
+            system_time: start_system_time + elapsed,
+            playing,
+            since_last_tick: tick_interval,
+            playback_duration,
+        };
+        super::step(model, tick);
+        if !playing {
+            continue;
+        }
+
+        // Count the active sounds, accounting for those spawned during this tick.
+        let playback_secs = duration_to_secs(&playback_duration);
+        source_sounds.clear();
+        group_sounds.clear();
+        installation_sounds.clear();
+        for (&sound_id, sound) in model.active_sounds.iter() {
+            let is_new = seen.insert(sound_id);
+            let source_id = sound.handle.source_id();
+            *source_sounds.entry(source_id).or_insert(0) += 1;
+            *installation_sounds.entry(sound.initial_installation).or_insert(0) += 1;
+            let source = match model.sources.get(&source_id) {
+                None => continue,
+                Some(source) => source,
+            };
+            if is_new {
+                let max_interval_secs = Some(source.occurrence_rate.max.ms() / 1_000.0);
+                tallies.sources
+                    .entry(source_id)
+                    .or_insert_with(Tally::default)
+                    .play(playback_secs, max_interval_secs);
+                tallies.installations
+                    .entry(sound.initial_installation)
+                    .or_insert_with(Tally::default)
+                    .play(playback_secs, None);
+            }
+            for (group_id, group) in model.groups.iter() {
+                if !group.contains_source(group_id, &source.groups, &source.tags) {
+                    continue;
+                }
+                *group_sounds.entry(*group_id).or_insert(0) += 1;
+                if is_new {
+                    let max_interval_secs = Some(group.occurrence_rate.max.ms() / 1_000.0);
+                    tallies.groups
+                        .entry(*group_id)
+                        .or_insert_with(Tally::default)
+                        .play(playback_secs, max_interval_secs);
+                }
+            }
+        }
+
+        // Account for the number of sounds against each configured range.
+        for (id, source) in model.sources.iter() {
+            let sounds = source_sounds.get(id).cloned().unwrap_or(0);
+            let tally = tallies.sources.entry(*id).or_insert_with(Tally::default);
+            tally.sounds(sounds, tick_secs, &source.simultaneous_sounds);
+        }
+        for (id, group) in model.groups.iter() {
+            let sounds = group_sounds.get(id).cloned().unwrap_or(0);
+            let tally = tallies.groups.entry(*id).or_insert_with(Tally::default);
+            tally.sounds(sounds, tick_secs, &group.simultaneous_sounds);
+        }
+        for (id, installation) in model.installations.iter() {
+            let sounds = installation_sounds.get(id).cloned().unwrap_or(0);
+            let tally = tallies.installations.entry(*id).or_insert_with(Tally::default);
+            tally.sounds(sounds, tick_secs, &installation.simultaneous_sounds);
+        }
+
+        // End the sounds whose durations have passed, as the audio output thread would.
+        for (&sound_id, sound) in model.active_sounds.iter() {
+            let sound_duration = time::Duration::from_millis(sound.duration.ms() as u64);
+            if playback_duration >= sound.spawned_at + sound_duration {
+                ended.push(sound_id);
+            }
+        }
+        for sound_id in ended.drain(..) {
+            model.remove_active_sound(&sound_id);
+            seen.remove(&sound_id);
+        }
+    }
+
+    report(model, duration, playback_duration, tallies, names)
+}
+
+// Produce the report from the accumulated tallies of all items that still exist within the model.
+fn report(
+    model: &Model,
+    duration: time::Duration,
+    playback_duration: time::Duration,
+    mut tallies: Tallies,
+    names: &Names,
+) -> Report {
+    let playback_secs = duration_to_secs(&playback_duration);
+    let name_of = |name: Option<&String>| name.cloned().unwrap_or_else(String::new);
+
+    let mut sources = vec![];
+    for (id, source) in model.sources.iter() {
+        let tally = tallies.sources.remove(id).unwrap_or_default();
+        let max_interval_secs = Some(source.occurrence_rate.max.ms() / 1_000.0);
+        let name = name_of(names.sources.get(id));
+        sources.push(tally.finish(Kind::Source, id.0, name, playback_secs, max_interval_secs));
+    }
+
+    let mut groups = vec![];
+    for (id, group) in model.groups.iter() {
+        let tally = tallies.groups.remove(id).unwrap_or_default();
+        let max_interval_secs = Some(group.occurrence_rate.max.ms() / 1_000.0);
+        let name = name_of(names.groups.get(id));
+        let id = id.0 as u64;
+        groups.push(tally.finish(Kind::Group, id, name, playback_secs, max_interval_secs));
+    }
+
+    let mut installations = vec![];
+    for id in model.installations.keys() {
+        let tally = tallies.installations.remove(id).unwrap_or_default();
+        let name = name_of(names.installations.get(id));
+        let id = id.0 as u64;
+        installations.push(tally.finish(Kind::Installation, id, name, playback_secs, None));
+    }
+
+    let mut stats = vec![];
+    for mut kind_stats in vec![sources, groups, installations] {
+        kind_stats.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        stats.extend(kind_stats);
+    }
+
+    Report {
+        simulated_secs: duration_to_secs(&duration),
+        playback_secs,
+        stats,
+    }
+}
+
+// A copy of the given model's project state that plays no audio and writes no event log.
+//
+// Active sounds, occupancy and any replay in progress are not copied, so the simulation begins
+// from silence with every installation's occupancy unreported.
+fn snapshot(model: &Model) -> Model {
+    let frame_count = Arc::new(AtomicUsize::new(0));
+    let is_playing = Arc::new(AtomicBool::new(true));
+    let rule_violations = Arc::new(Mutex::new(Vec::new()));
+    let occupancies = Arc::new(Mutex::new(Default::default()));
+    let mut simulated = Model::new(
+        frame_count,
+        model.seed,
+        is_playing,
+        rule_violations,
+        occupancies,
+        event_log::Writer::disabled(),
+        AudioStreams::Simulated,
+        audio::sound::IdGenerator::new(),
+        None,
+    );
+    simulated.realtime_source_latency = model.realtime_source_latency;
+    simulated.installations = model.installations.clone();
+    simulated.groups = model.groups.clone();
+    simulated.sources = model.sources.clone();
+    simulated.speakers = model.speakers.clone();
+    simulated.schedule = model.schedule.clone();
+    simulated.rules = model.rules.clone();
+    simulated.chains = model.chains.clone();
+    simulated.installation_polygons = model.installation_polygons.clone();
+    simulated
+}
+
+#[test]
+fn test_tally() {
+    let range = Range { min: 1, max: 2 };
+    let mut tally = Tally::default();
+    tally.sounds(0, 1.0, &range);
+    tally.play(1.0, Some(5.0));
+    tally.sounds(1, 1.0, &range);
+    tally.sounds(3, 2.0, &range);
+    tally.play(10.0, Some(5.0));
+    tally.sounds(1, 6.0, &range);
+    let stats = tally.finish(Kind::Source, 0, "a".into(), 10.0, Some(5.0));
+    assert_eq!(stats.plays, 2);
+    assert_eq!(stats.mean_simultaneous_sounds, (1.0 + 6.0 + 6.0) / 10.0);
+    assert_eq!(stats.max_simultaneous_sounds, 3);
+    assert_eq!(stats.secs_below_min, 1.0);
+    assert_eq!(stats.secs_above_max, 2.0);
+    assert_eq!(stats.long_gaps, 1);
+    assert_eq!(stats.longest_gap_secs, 9.0);
+}
+
+#[test]
+fn test_run() {
+    use metres::Metres;
+    use nannou::prelude::pt2;
+    use std::io::Read;
+    use super::{Source, Speaker};
+    use time_calc::Ms;
+
+    // A single installation with one speaker, playing a single source within a single group.
+    let (installation, group, source) = (installation::Id(0), group::Id(0), audio::source::Id(0));
+    let mut model = Model::new(
+        Arc::new(AtomicUsize::new(0)),
+        [1, 2, 3, 4],
+        Arc::new(AtomicBool::new(true)),
+        Arc::new(Mutex::new(Vec::new())),
+        Arc::new(Mutex::new(Default::default())),
+        event_log::Writer::disabled(),
+        AudioStreams::Simulated,
+        audio::sound::IdGenerator::new(),
+        None,
+    );
+    let simultaneous_sounds = Range { min: 1, max: 1 };
+    let state = installation::Soundscape { simultaneous_sounds, ..Default::default() };
+    model.insert_installation(installation, state);
+    let point = pt2(Metres(0.0), Metres(0.0));
+    let installations: FxHashSet<_> = Some(installation).into_iter().collect();
+    let speaker = Speaker { point, installations: installations.clone() };
+    model.insert_speaker(audio::speaker::Id(0), speaker);
+    model.insert_group(group, Default::default());
+    let occurrence_rate = Range { min: Ms(5_000.0), max: Ms(10_000.0) };
+    let constraints = audio::source::Soundscape {
+        installations,
+        groups: Some(group).into_iter().collect(),
+        occurrence_rate,
+        simultaneous_sounds: Range { min: 0, max: 1 },
+        playback_duration: Range { min: Ms(1_000.0), max: Ms(2_000.0) },
+        ..Default::default()
+    };
+    let input = audio::source::realtime::Input { channel: 0, trim_db: 0.0 };
+    let realtime = audio::source::Realtime { duration: Ms(2_000.0), channels: vec![input] };
+    let source_state = Source {
+        constraints,
+        kind: audio::source::Kind::Realtime(realtime),
+        spread: Metres(1.0),
+        channel_radians: 0.0,
+        volume: 1.0,
+        muted: false,
+        effects: vec![],
+        tags: vec![],
+        last_sound_created: None,
+    };
+    model.insert_source(source, source_state);
+
+    let mut names = Names::default();
+    names.sources.insert(source, "Door".into());
+    names.groups.insert(group, "Arrivals".into());
+    names.installations.insert(installation, "Foyer".into());
+
+    // Every item plays and never waits longer than the source's max occurrence interval.
+    let report = run(&mut model, time::Duration::from_secs(5 * 60), &names);
+    assert_eq!(report.playback_secs, 5.0 * 60.0);
+    let kinds: Vec<_> = report.stats.iter().map(|s| (s.kind, s.id, s.name.as_str())).collect();
+    let expected = vec![
+        (Kind::Source, 0, "Door"),
+        (Kind::Group, 0, "Arrivals"),
+        (Kind::Installation, 0, "Foyer"),
+    ];
+    assert_eq!(kinds, expected);
+    let max_interval_secs = occurrence_rate.max.ms() / 1_000.0;
+    for stats in &report.stats {
+        assert!(stats.plays > 0);
+        assert_eq!(stats.long_gaps, 0);
+        assert!(stats.longest_gap_secs <= max_interval_secs);
+    }
+
+    // The saved JSON and CSV describe the same items.
+    let dir = ::std::env::temp_dir().join("audio_server_simulation_test");
+    let json_path = report.save(&dir, "simulation").unwrap();
+    let loaded: Report = utils::load_from_json(&json_path).unwrap();
+    assert_eq!(loaded.stats.len(), report.stats.len());
+    for (a, b) in loaded.stats.iter().zip(&report.stats) {
+        assert_eq!((a.kind, a.id, &a.name, a.plays), (b.kind, b.id, &b.name, b.plays));
+    }
+    let mut csv = String::new();
+    fs::File::open(json_path.with_extension("csv"))
+        .and_then(|mut file| file.read_to_string(&mut csv))
+        .unwrap();
+    let rows: Vec<_> = csv.lines().skip(1).collect();
+    assert_eq!(rows.len(), 3);
+    for (row, stats) in rows.iter().zip(&report.stats) {
+        let prefix = format!("{:?},{},\"{}\",{},", stats.kind, stats.id, stats.name, stats.plays);
+        assert!(row.starts_with(&prefix));
+    }
+    fs::remove_dir_all(&dir).ok();
+}
@@ -176,6 +176,26 @@ pub fn duration_to_secs(d: &time::Duration) -> f64 {
     d.as_secs() as f64 + d.subsec_nanos() as f64 * 1e-9
 }
 
+/// A simple linear interpolation function.
+///
+/// This is used to interpolate between previous and current DBAP speaker gains over the duration
+/// of a buffer and between levels during a scene crossfade.
+pub fn lerp(a: f32, b: f32, lerp: f32) -> f32 {
+    a + (b - a) * lerp
+}
+
+/// Convert the given decibels to an amplitude multiplier.
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+/// Convert the given amplitude multiplier to decibels.
+///
+/// The gain is clamped to a minimum of `-120` dB so that silence does not produce `-inf`.
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.max(1e-6).log10()
+}
+
 /// Errors that might occur when saving a file.
 #[derive(Debug)]
 pub enum FileError<E> {